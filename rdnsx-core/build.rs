@@ -0,0 +1,16 @@
+//! Compresses the built-in subdomain wordlist for embedding in the library
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const WORDLIST: &str = "assets/subdomains.txt";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", WORDLIST);
+
+    let words = fs::read(WORDLIST).expect("built-in wordlist is readable");
+    let compressed = zstd::encode_all(words.as_slice(), 19).expect("built-in wordlist compresses");
+    let out_dir = env::var("OUT_DIR").expect("cargo sets OUT_DIR for build scripts");
+    fs::write(Path::new(&out_dir).join("subdomains.txt.zst"), compressed).expect("compressed wordlist is writable");
+}
@@ -0,0 +1,168 @@
+//! Audit log of every DNS query, one JSON object per line
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::error::{DnsxError, Result};
+use crate::types::RecordType;
+
+/// Default size at which the audit log is rotated
+pub const DEFAULT_AUDIT_LOG_MAX_BYTES: u64 = 100 * 1024 * 1024;
+
+/// One audited query
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub domain: String,
+    pub record_type: RecordType,
+    /// Resolver that answered or failed; `None` when the error does not name one
+    pub resolver: Option<String>,
+    /// DNS response code, or `TIMEOUT`/`ERROR` when no response was received
+    pub response_code: String,
+    pub response_time_ms: f64,
+    pub records_returned: usize,
+}
+
+impl AuditEntry {
+    /// Describe a failed query from the error it returned
+    pub fn from_error(domain: &str, record_type: RecordType, error: &DnsxError, response_time_ms: f64) -> Self {
+        let (resolver, response_code) = match error {
+            DnsxError::Rcode { rcode, .. } => (None, rcode.to_string()),
+            DnsxError::Timeout { resolver, .. } => (Some(resolver.clone()), "TIMEOUT".to_string()),
+            DnsxError::Network { resolver, .. } => (Some(resolver.clone()), "ERROR".to_string()),
+            _ => (None, "ERROR".to_string()),
+        };
+
+        Self {
+            timestamp: Utc::now(),
+            domain: domain.to_string(),
+            record_type,
+            resolver,
+            response_code,
+            response_time_ms,
+            records_returned: 0,
+        }
+    }
+}
+
+/// Appends [`AuditEntry`] lines to a file, rotating it once it reaches a size limit
+///
+/// A full log is renamed to `<path>.<timestamp>` and a new file is started, so
+/// no entries are ever overwritten.
+#[derive(Debug)]
+pub struct AuditLogger {
+    path: PathBuf,
+    max_bytes: u64,
+    file: Mutex<AuditFile>,
+}
+
+#[derive(Debug)]
+struct AuditFile {
+    file: File,
+    size: u64,
+}
+
+impl AuditLogger {
+    /// Open (or create) the audit log at `path`, appending to existing entries
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64) -> Result<Self> {
+        let path = path.into();
+        let file = open_append(&path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        Ok(Self {
+            path,
+            max_bytes: max_bytes.max(1),
+            file: Mutex::new(AuditFile { file, size }),
+        })
+    }
+
+    /// Path of the active log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one entry, rotating first if it would push the file over the size limit
+    pub fn log(&self, entry: &AuditEntry) -> Result<()> {
+        let mut line = serde_json::to_vec(entry).map_err(|e| DnsxError::Serialization(e.to_string()))?;
+        line.push(b'\n');
+
+        let mut current = self.file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if current.size > 0 && current.size + line.len() as u64 > self.max_bytes {
+            self.rotate(&mut current)?;
+        }
+
+        // A single write per line keeps entries whole if the process dies mid-run
+        current
+            .file
+            .write_all(&line)
+            .map_err(|e| DnsxError::io(format!("Failed to write audit log {}", self.path.display()), e))?;
+        current.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&self, current: &mut AuditFile) -> Result<()> {
+        let rotated = PathBuf::from(format!("{}.{}", self.path.display(), Utc::now().format("%Y%m%dT%H%M%S%.3f")));
+        fs::rename(&self.path, &rotated).map_err(|e| {
+            DnsxError::io(format!("Failed to rotate audit log {} to {}", self.path.display(), rotated.display()), e)
+        })?;
+
+        *current = AuditFile { file: open_append(&self.path)?, size: 0 };
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| DnsxError::io(format!("Failed to open audit log {}", path.display()), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ResponseCode;
+
+    #[test]
+    fn test_audit_log_rotation() {
+        let dir = std::env::temp_dir().join(format!("rdnsx-audit-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
+
+        let error = DnsxError::Rcode {
+            resolver: "192.0.2.53:53".to_string(),
+            domain: "missing.example.com".to_string(),
+            rcode: ResponseCode::NxDomain,
+        };
+        let entry = AuditEntry::from_error("missing.example.com", RecordType::A, &error, 12.5);
+        assert_eq!(entry.response_code, "NXDOMAIN");
+
+        let line_len = serde_json::to_vec(&entry).unwrap().len() as u64 + 1;
+        let logger = AuditLogger::new(&path, line_len * 2).unwrap();
+        for _ in 0..3 {
+            logger.log(&entry).unwrap();
+        }
+
+        let current = fs::read_to_string(&path).unwrap();
+        assert_eq!(current.lines().count(), 1);
+        let parsed: serde_json::Value = serde_json::from_str(current.lines().next().unwrap()).unwrap();
+        assert_eq!(parsed["record_type"], "A");
+        assert_eq!(parsed["records_returned"], 0);
+
+        let rotated: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with("audit.jsonl."))
+            .collect();
+        assert_eq!(rotated.len(), 1);
+        assert_eq!(fs::read_to_string(rotated[0].path()).unwrap().lines().count(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,20 +1,259 @@
 //! Subdomain bruteforcing
 
-use std::collections::HashSet;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
 
+use futures::future::join_all;
+use ring::digest;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Semaphore;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use crate::client::DnsxClient;
-use crate::error::Result;
+use crate::error::{DnsxError, Result};
 use crate::input::read_wordlist;
-use crate::types::RecordType;
+use crate::utils::{canonicalize_domain, normalize_domain};
+use crate::types::{DnsRecord, RecordType, RecordValue};
+use crate::wildcard::WildcardFilter;
+
+/// Common subdomain labels from `assets/subdomains.txt`, zstd-compressed by `build.rs`
+const DEFAULT_WORDLIST_ZST: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/subdomains.txt.zst"));
+
+/// The built-in wordlist, decompressed on first use
+pub fn default_wordlist() -> &'static [String] {
+    static WORDS: OnceLock<Vec<String>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        let raw = zstd::decode_all(DEFAULT_WORDLIST_ZST).expect("embedded wordlist is valid zstd");
+        String::from_utf8_lossy(&raw)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect()
+    })
+}
+
+/// How often a label resolves relative to others, by label, sorted for binary search
+///
+/// Weights approximate label rankings from public subdomain datasets; higher
+/// means more likely to exist. Labels not listed weigh 0.
+pub(crate) static PRIORITY_MAP: &[(&str, u32)] = &[
+    ("account", 1300), ("accounts", 1300), ("ad", 1000), ("admin", 3800), ("ads", 1200), ("api", 4200),
+    ("app", 2800), ("apps", 2000), ("assets", 2000), ("auth", 2000), ("autoconfig", 5000), ("autodiscover", 5600),
+    ("backup", 1600), ("beta", 2800), ("billing", 1300), ("blog", 4000), ("calendar", 1800), ("careers", 1000),
+    ("cart", 900), ("cdn", 3000), ("chat", 1700), ("checkout", 1000), ("citrix", 1200), ("cloud", 1500),
+    ("cms", 1700), ("community", 1300), ("confluence", 1300), ("cp", 2000), ("cpanel", 5500), ("crm", 1600),
+    ("dashboard", 1600), ("db", 1500), ("dc", 1000), ("demo", 2600), ("dev", 4300), ("dev2", 1100), ("dns", 1900),
+    ("dns1", 2100), ("dns2", 2000), ("docs", 2800), ("download", 1700), ("email", 2800), ("en", 1500),
+    ("events", 1100), ("exchange", 1600), ("exchange2", 600), ("files", 1600), ("forum", 3000), ("forums", 1400),
+    ("ftp", 6000), ("gateway", 1400), ("git", 1800), ("gitlab", 1600), ("grafana", 1000), ("graphql", 1000),
+    ("help", 1600), ("host", 1300), ("images", 2600), ("imap", 4500), ("imap4", 700), ("img", 2500), ("info", 1500),
+    ("internal", 1400), ("intranet", 2000), ("jenkins", 1400), ("jira", 1500), ("kibana", 900), ("ldap", 1000),
+    ("legacy", 900), ("lists", 2000), ("live", 1600), ("login", 2000), ("lync", 1200), ("lyncdiscover", 2000),
+    ("m", 4500), ("mail", 9000), ("mail1", 2000), ("mail2", 3000), ("mail3", 900), ("media", 2500), ("meet", 1200),
+    ("mobile", 2600), ("monitor", 1000), ("mta", 1100), ("mx", 3500), ("mx1", 2600), ("mx2", 2200), ("mx3", 900),
+    ("my", 2200), ("mysql", 2000), ("new", 2500), ("news", 2800), ("newsletter", 1200), ("ns", 4000), ("ns1", 6000),
+    ("ns2", 5800), ("ns3", 3000), ("ns4", 1800), ("ns5", 800), ("ntp", 1000), ("office", 1400), ("old", 2500),
+    ("origin", 1300), ("owa", 2000), ("partners", 1100), ("pay", 1200), ("payments", 1200), ("pop", 5000),
+    ("pop2", 600), ("pop3", 3800), ("portal", 2600), ("preview", 1200), ("prod", 1400), ("proxy", 1500),
+    ("qa", 2200), ("rdp", 900), ("relay", 1400), ("remote", 2200), ("s3", 1000), ("sandbox", 1500), ("search", 1800),
+    ("secure", 2500), ("server", 1600), ("shop", 2600), ("sip", 2200), ("sip2", 600), ("sites", 1200), ("sms", 1000),
+    ("smtp", 6500), ("smtp1", 1200), ("sql", 1500), ("ssh", 900), ("sso", 1800), ("stage", 2400), ("staging", 3600),
+    ("static", 3200), ("stats", 1800), ("status", 1800), ("store", 2000), ("support", 3000), ("svn", 1400),
+    ("test", 4200), ("test2", 1200), ("time", 900), ("uat", 2000), ("video", 1800), ("vpn", 3600), ("vpn2", 900),
+    ("wap", 1500), ("web", 2600), ("web1", 1300), ("web2", 1100), ("webdisk", 5000), ("webmail", 6500),
+    ("wiki", 2400), ("ws", 1200), ("www", 10000), ("www1", 2400), ("www2", 3500), ("www3", 1200), ("zoom", 700),
+];
+
+/// Resolution likelihood weight of a wordlist entry (case-insensitive)
+pub fn subdomain_priority(word: &str) -> u32 {
+    let word = word.trim().to_lowercase();
+    PRIORITY_MAP
+        .binary_search_by(|(label, _)| (*label).cmp(word.as_str()))
+        .map(|i| PRIORITY_MAP[i].1)
+        .unwrap_or(0)
+}
+
+/// Answers quicker than this (in milliseconds) most likely came from a resolver's cache
+const FAST_RESPONSE_MS: f64 = 5.0;
+
+/// Confidence added when a subdomain resolves to an address no other result shares
+const UNIQUE_IP_BOOST: f64 = 0.2;
+
+/// Confidence removed for a fast, probably cached (wildcard) answer
+const FAST_RESPONSE_PENALTY: f64 = 0.2;
+
+const TAG_UNIQUE_IP: &str = "unique_ip";
+const TAG_WILDCARD_CANDIDATE: &str = "wildcard_candidate";
+const TAG_FAST_RESPONSE: &str = "fast_response";
+const TAG_DANGLING_CNAME: &str = "dangling_cname";
+
+/// Records found for one subdomain candidate, keyed by record type
+#[derive(Debug, Clone, Serialize)]
+pub struct BruteforceResult {
+    pub subdomain: String,
+    pub records: HashMap<RecordType, Vec<DnsRecord>>,
+    /// Fastest successful lookup among the queried record types
+    pub response_time_ms: f64,
+    /// How likely this is a real, distinct host rather than wildcard noise (0-1)
+    ///
+    /// Only reflects the answer itself until [`Bruteforcer::score_results`] has
+    /// compared it with the rest of the scan.
+    pub confidence: f64,
+    /// `unique_ip`, `wildcard_candidate`, `fast_response` and/or `dangling_cname`
+    pub tags: Vec<String>,
+}
+
+impl BruteforceResult {
+    /// Whether any record type returned a usable answer
+    pub fn is_found(&self) -> bool {
+        self.records.values().any(|records| !records.is_empty())
+    }
+
+    /// Distinct addresses among the records
+    pub fn ips(&self) -> Vec<IpAddr> {
+        let ips: HashSet<IpAddr> = self
+            .records
+            .values()
+            .flatten()
+            .filter_map(|record| match record.value {
+                RecordValue::Ip(ip) => Some(ip),
+                _ => None,
+            })
+            .collect();
+        ips.into_iter().collect()
+    }
+
+    fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    fn add_tag(&mut self, tag: &str) {
+        if !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    /// All records, in no particular order
+    pub fn into_records(self) -> Vec<DnsRecord> {
+        self.records.into_values().flatten().collect()
+    }
+}
+
+/// Candidates processed between checkpoint saves in [`Bruteforcer::enumerate`]
+pub const CHECKPOINT_INTERVAL: usize = 1000;
+
+/// Progress of a bruteforce run as stored on disk
+#[derive(Serialize, Deserialize)]
+struct CheckpointFile {
+    last_domain: String,
+    found: Vec<String>,
+}
+
+/// Saves and restores bruteforce progress so a long run can continue where it stopped
+///
+/// Candidates are generated in a fixed order from the wordlist, so the last
+/// one processed is enough to know where to pick up again.
+pub struct BruteforceCheckpoint;
+
+impl BruteforceCheckpoint {
+    /// Write the last processed candidate and the subdomains found so far to `path`
+    pub fn save(path: &Path, last_domain: &str, found_so_far: &[String]) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let checkpoint = CheckpointFile {
+            last_domain: last_domain.to_string(),
+            found: found_so_far.to_vec(),
+        };
+        let content = serde_json::to_string(&checkpoint).map_err(|e| DnsxError::Serialization(e.to_string()))?;
+        // Written aside and renamed so an interruption mid-write keeps the previous checkpoint
+        let partial = path.with_extension("checkpoint.partial");
+        std::fs::write(&partial, content)?;
+        std::fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    /// The last processed candidate and the subdomains found before it
+    pub fn load(path: &Path) -> Result<(String, Vec<String>)> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| DnsxError::io(format!("Failed to read checkpoint {}", path.display()), e))?;
+        let checkpoint: CheckpointFile = serde_json::from_str(&content)
+            .map_err(|e| DnsxError::Serialization(format!("Invalid checkpoint {}: {}", path.display(), e)))?;
+        Ok((checkpoint.last_domain, checkpoint.found))
+    }
+
+    /// `~/.rdnsx/{wordlist_hash}_{domain}.checkpoint`, where the hash covers the words in scan order
+    pub fn default_path(words: &[String], domain: &str) -> Result<PathBuf> {
+        let mut context = digest::Context::new(&digest::SHA256);
+        for word in words {
+            context.update(word.as_bytes());
+            context.update(b"\n");
+        }
+        let hash = hex::encode(&context.finish().as_ref()[..8]);
+        Ok(checkpoint_dir()?.join(format!("{}_{}.checkpoint", hash, checkpoint_domain(domain))))
+    }
+
+    /// The most recently written checkpoint for `domain`, whatever wordlist it was made with
+    pub fn latest_for_domain(domain: &str) -> Result<Option<PathBuf>> {
+        let dir = checkpoint_dir()?;
+        let suffix = format!("_{}.checkpoint", checkpoint_domain(domain));
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(DnsxError::io(format!("Failed to list checkpoints in {}", dir.display()), e)),
+        };
+
+        Ok(entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(&suffix))
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .max_by_key(|(modified, _)| *modified)
+            .map(|(_, path)| path))
+    }
+
+    /// The candidates after `last_domain`
+    ///
+    /// All of them are kept when `last_domain` is not among them, since the
+    /// wordlist then no longer matches the one the checkpoint was taken with.
+    pub fn remaining(candidates: Vec<String>, last_domain: &str) -> Vec<String> {
+        if !candidates.iter().any(|candidate| candidate == last_domain) {
+            warn!("Checkpoint position {} is not in the candidate list, starting over", last_domain);
+            return candidates;
+        }
+        candidates.into_iter().skip_while(|candidate| candidate != last_domain).skip(1).collect()
+    }
+}
+
+fn checkpoint_dir() -> Result<PathBuf> {
+    let home = std::env::var_os("HOME")
+        .ok_or_else(|| DnsxError::Other("HOME is not set, cannot locate checkpoints".to_string()))?;
+    Ok(PathBuf::from(home).join(".rdnsx"))
+}
+
+/// `domain` made safe for a file name
+fn checkpoint_domain(domain: &str) -> String {
+    domain
+        .trim()
+        .trim_end_matches('.')
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ',') { c } else { '_' })
+        .collect()
+}
 
 /// Subdomain bruteforcer
 pub struct Bruteforcer {
     client: Arc<DnsxClient>,
     concurrency: usize,
+    wildcard_filter: Option<Arc<WildcardFilter>>,
+    priority_ordering: bool,
+    checkpoints: bool,
+    resume: bool,
 }
 
 impl Bruteforcer {
@@ -23,21 +262,170 @@ impl Bruteforcer {
         Self {
             client,
             concurrency,
+            wildcard_filter: None,
+            priority_ordering: false,
+            checkpoints: false,
+            resume: false,
         }
     }
 
+    /// Drop answers that match the target's wildcard records
+    pub fn with_wildcard_filter(mut self, filter: Arc<WildcardFilter>) -> Self {
+        self.wildcard_filter = Some(filter);
+        self
+    }
+
+    /// Try the labels most likely to exist (www, mail, api, ...) before the rest of the wordlist
+    pub fn with_priority_ordering(mut self, enabled: bool) -> Self {
+        self.priority_ordering = enabled;
+        self
+    }
+
+    /// Save a [`BruteforceCheckpoint`] every [`CHECKPOINT_INTERVAL`] candidates in [`enumerate`](Self::enumerate)
+    pub fn with_checkpoints(mut self, enabled: bool) -> Self {
+        self.checkpoints = enabled;
+        self
+    }
+
+    /// Continue from the checkpoint of an earlier run with the same wordlist and domain, if there is one
+    pub fn with_resume(mut self, enabled: bool) -> Self {
+        self.resume = enabled;
+        self
+    }
+
+    /// Sort words by descending [`subdomain_priority`], keeping wordlist order among equals
+    pub fn prioritize_words(words: &mut [String]) {
+        words.sort_by_cached_key(|word| std::cmp::Reverse(subdomain_priority(word)));
+    }
+
+    /// Query every record type for one candidate concurrently and merge the answers
+    ///
+    /// Failed lookups, unparsed values and (when a filter is given) wildcard
+    /// answers are left out; record types with nothing left are omitted.
+    pub async fn resolve_candidate(
+        client: &DnsxClient,
+        subdomain: String,
+        record_types: &[RecordType],
+        wildcard_filter: Option<&WildcardFilter>,
+    ) -> BruteforceResult {
+        let lookups = join_all(record_types.iter().map(|record_type| {
+            let subdomain = &subdomain;
+            async move {
+                let started = Instant::now();
+                let result = client.query(subdomain, *record_type).await;
+                (*record_type, result, started.elapsed())
+            }
+        }))
+        .await;
+
+        let mut records = HashMap::new();
+        let mut fastest = None;
+        for (record_type, result, elapsed) in lookups {
+            let Ok(mut found) = result else {
+                continue;
+            };
+            fastest = Some(fastest.map_or(elapsed, |f: std::time::Duration| f.min(elapsed)));
+            found.retain(|r| !matches!(r.value, RecordValue::Other(_)));
+            if let Some(filter) = wildcard_filter {
+                found = filter.filter(found).await.unwrap_or_default();
+            }
+            if !found.is_empty() {
+                records.insert(record_type, found);
+            }
+        }
+
+        let mut result = BruteforceResult {
+            subdomain,
+            records,
+            response_time_ms: fastest.map_or(0.0, |f| f.as_secs_f64() * 1000.0),
+            confidence: 1.0,
+            tags: Vec::new(),
+        };
+        if result.is_found() && result.response_time_ms < FAST_RESPONSE_MS {
+            result.add_tag(TAG_FAST_RESPONSE);
+        }
+        if Self::has_dangling_cname(client, &result).await {
+            result.add_tag(TAG_DANGLING_CNAME);
+        }
+        result.confidence = confidence(0.0, &result);
+        result
+    }
+
+    /// Whether the candidate is a CNAME whose target does not resolve
+    async fn has_dangling_cname(client: &DnsxClient, result: &BruteforceResult) -> bool {
+        let records = || result.records.values().flatten();
+        if records().any(|r| matches!(r.value, RecordValue::Ip(_))) {
+            return false;
+        }
+        let Some(target) = records().find_map(|r| match &r.value {
+            RecordValue::Domain(target) if r.record_type == RecordType::Cname => Some(target.clone()),
+            _ => None,
+        }) else {
+            return false;
+        };
+
+        if client.lookup_ipv4(&target).await.is_ok_and(|ips| !ips.is_empty()) {
+            return false;
+        }
+        !client.query(&target, RecordType::Aaaa).await.is_ok_and(|records| !records.is_empty())
+    }
+
+    /// Score results against each other and the target's wildcard behaviour
+    ///
+    /// Tags `unique_ip` results whose address no other result resolves to and
+    /// `wildcard_candidate` results under a level with wildcard DNS, then sets
+    /// `confidence` to `1 - wildcard probability`, raised for unique addresses
+    /// and lowered for fast (cached) answers.
+    pub async fn score_results(results: &mut [BruteforceResult], wildcard_filter: Option<&WildcardFilter>) {
+        let mut ip_counts: HashMap<IpAddr, usize> = HashMap::new();
+        for result in results.iter() {
+            for ip in result.ips() {
+                *ip_counts.entry(ip).or_default() += 1;
+            }
+        }
+
+        for result in results.iter_mut() {
+            let ips = result.ips();
+            if ips.iter().any(|ip| ip_counts.get(ip) == Some(&1)) {
+                result.add_tag(TAG_UNIQUE_IP);
+            }
+
+            let wildcard_probability = match wildcard_filter {
+                Some(filter) => filter.wildcard_probability(&result.subdomain, &ips).await,
+                None => 0.0,
+            };
+            if wildcard_probability > 0.0 {
+                result.add_tag(TAG_WILDCARD_CANDIDATE);
+            }
+
+            result.confidence = confidence(wildcard_probability, result);
+        }
+    }
+
+    /// Most confident results first, by name among equals
+    pub fn sort_by_confidence(results: &mut [BruteforceResult]) {
+        results.sort_by(|a, b| b.confidence.total_cmp(&a.confidence).then_with(|| a.subdomain.cmp(&b.subdomain)));
+    }
+
     /// Generate subdomain candidates from wordlist and domain
-    fn generate_subdomains(domain: &str, words: Vec<String>, placeholder: &str) -> Vec<String> {
+    ///
+    /// Candidates are canonicalized; words that do not form a valid name are skipped.
+    pub fn generate_subdomains(domain: &str, words: Vec<String>, placeholder: &str) -> Vec<String> {
         let mut subdomains = Vec::new();
 
         for word in words {
             let subdomain = if domain.contains(placeholder) {
-                domain.replace(placeholder, &word)
+                domain.replace(placeholder, word.trim())
             } else {
-                format!("{}.{}", word.trim(), domain)
+                format!("{}.{}", word.trim(), normalize_domain(domain))
             };
-            debug!("Generated subdomain: {} -> {}", word.trim(), subdomain);
-            subdomains.push(subdomain);
+            match canonicalize_domain(&subdomain) {
+                Ok(subdomain) => {
+                    debug!("Generated subdomain: {} -> {}", word.trim(), subdomain);
+                    subdomains.push(subdomain);
+                }
+                Err(e) => debug!("Skipping candidate {}: {}", subdomain, e),
+            }
         }
 
         subdomains
@@ -54,21 +442,65 @@ impl Bruteforcer {
         let words = read_wordlist(wordlist_source)?;
         debug!("Loaded {} words from wordlist", words.len());
 
+        self.enumerate_words(domain, words, placeholder).await
+    }
+
+    /// Enumerate subdomains for a domain using the built-in wordlist
+    pub async fn enumerate_with_default_wordlist(&self, domain: &str) -> Result<Vec<String>> {
+        let words = default_wordlist().to_vec();
+        debug!("Using built-in wordlist of {} words", words.len());
+
+        self.enumerate_words(domain, words, "FUZZ").await
+    }
+
+    async fn enumerate_words(&self, domain: &str, mut words: Vec<String>, placeholder: &str) -> Result<Vec<String>> {
+        if self.priority_ordering {
+            Self::prioritize_words(&mut words);
+        }
+
+        let checkpoint = if self.checkpoints || self.resume {
+            Some(BruteforceCheckpoint::default_path(&words, domain)?)
+        } else {
+            None
+        };
+
         // Generate subdomain candidates
-        let subdomains = Self::generate_subdomains(domain, words, placeholder);
+        let mut subdomains = Self::generate_subdomains(domain, words, placeholder);
         debug!("Generated {} subdomain candidates", subdomains.len());
 
         // Query subdomains sequentially for now (to avoid complexity)
         let mut found = Vec::new();
 
-        for subdomain in subdomains {
-            match self.client.lookup_ipv4(&subdomain).await {
+        if let Some(path) = checkpoint.as_deref().filter(|path| self.resume && path.exists()) {
+            let (last_domain, found_before) = BruteforceCheckpoint::load(path)?;
+            debug!("Resuming after {} with {} subdomains found before", last_domain, found_before.len());
+            subdomains = BruteforceCheckpoint::remaining(subdomains, &last_domain);
+            found = found_before;
+        }
+
+        for (i, subdomain) in subdomains.iter().enumerate() {
+            match self.client.lookup_ipv4(subdomain).await {
                 Ok(ips) if !ips.is_empty() => {
                     debug!("Found subdomain: {}", subdomain);
-                    found.push(subdomain);
+                    found.push(subdomain.clone());
                 }
                 _ => {} // Subdomain doesn't exist or failed to resolve
             }
+
+            if let Some(path) = checkpoint.as_deref().filter(|_| self.checkpoints) {
+                if (i + 1) % CHECKPOINT_INTERVAL == 0 {
+                    BruteforceCheckpoint::save(path, subdomain, &found)?;
+                }
+            }
+        }
+
+        // A finished run has nothing left to resume
+        if let Some(path) = &checkpoint {
+            if path.exists() {
+                if let Err(e) = std::fs::remove_file(path) {
+                    warn!("Failed to remove checkpoint {}: {}", path.display(), e);
+                }
+            }
         }
 
         // Deduplicate
@@ -76,18 +508,36 @@ impl Bruteforcer {
         Ok(unique.into_iter().collect())
     }
 
-    /// Enumerate subdomains and return all DNS records found
+    /// Enumerate subdomains and return the records found for each
+    ///
+    /// All `record_types` are queried concurrently per candidate; a candidate
+    /// is reported when any of them returns a non-wildcard answer.
     pub async fn enumerate_with_records(
         &self,
         domain: &str,
         wordlist_source: &str,
         placeholder: &str,
-        record_type: RecordType,
-    ) -> Result<Vec<(String, Vec<crate::types::DnsRecord>)>> {
+        record_types: &[RecordType],
+    ) -> Result<Vec<BruteforceResult>> {
         // Read wordlist
         let words = read_wordlist(wordlist_source)?;
         debug!("Loaded {} words from wordlist", words.len());
 
+        self.enumerate_words_with_records(domain, words, placeholder, record_types).await
+    }
+
+    /// [`enumerate_with_records`](Self::enumerate_with_records) with the words already loaded
+    pub async fn enumerate_words_with_records(
+        &self,
+        domain: &str,
+        mut words: Vec<String>,
+        placeholder: &str,
+        record_types: &[RecordType],
+    ) -> Result<Vec<BruteforceResult>> {
+        if self.priority_ordering {
+            Self::prioritize_words(&mut words);
+        }
+
         // Generate subdomain candidates
         let subdomains = Self::generate_subdomains(domain, words, placeholder);
         debug!("Generated {} subdomain candidates", subdomains.len());
@@ -99,14 +549,13 @@ impl Bruteforcer {
         for subdomain in subdomains {
             let client = Arc::clone(&self.client);
             let permit = semaphore.clone();
-            let record_type = record_type;
+            let record_types = record_types.to_vec();
+            let wildcard_filter = self.wildcard_filter.clone();
 
             let handle = tokio::spawn(async move {
                 let _permit = permit.acquire().await.ok();
-                match client.query(&subdomain, record_type).await {
-                    Ok(records) if !records.is_empty() => Some((subdomain, records)),
-                    _ => None,
-                }
+                let result = Self::resolve_candidate(&client, subdomain, &record_types, wildcard_filter.as_deref()).await;
+                result.is_found().then_some(result)
             });
 
             handles.push(handle);
@@ -120,6 +569,19 @@ impl Bruteforcer {
             }
         }
 
+        Self::score_results(&mut found, self.wildcard_filter.as_deref()).await;
         Ok(found)
     }
 }
+
+/// Confidence for a result given its wildcard probability and tags
+fn confidence(wildcard_probability: f64, result: &BruteforceResult) -> f64 {
+    let mut confidence = 1.0 - wildcard_probability.clamp(0.0, 1.0);
+    if result.has_tag(TAG_UNIQUE_IP) {
+        confidence += UNIQUE_IP_BOOST;
+    }
+    if result.has_tag(TAG_FAST_RESPONSE) {
+        confidence -= FAST_RESPONSE_PENALTY;
+    }
+    confidence.clamp(0.0, 1.0)
+}
@@ -1,17 +1,29 @@
 //! DNS response caching to avoid redundant queries
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use parking_lot::RwLock;
-use tracing::debug;
+use chrono::{DateTime, Utc};
+use linked_hash_map::LinkedHashMap;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
 
-use crate::error::Result;
-use crate::types::{DnsRecord, RecordType};
+use crate::config::{DnsxOptions, DEFAULT_NEGATIVE_CACHE_TTL};
+use crate::error::{DnsxError, Result};
+use crate::types::{DnsRecord, RecordType, RecordValue, ResponseCode};
+use crate::utils::normalize_domain;
+
+/// Resolver named in the errors for negative answers served from the cache
+pub const CACHE_RESOLVER: &str = "cache";
 
 /// Cache key combining domain and record type
+///
+/// The domain is normalized, so `Example.COM.` and `example.com` share an entry.
 #[derive(Debug, Clone, Eq)]
 pub struct CacheKey {
     pub domain: String,
@@ -21,7 +33,7 @@ pub struct CacheKey {
 impl CacheKey {
     pub fn new(domain: impl Into<String>, record_type: RecordType) -> Self {
         Self {
-            domain: domain.into(),
+            domain: normalize_domain(&domain.into()),
             record_type,
         }
     }
@@ -72,28 +84,167 @@ impl CachedResponse {
     }
 }
 
+/// A remembered NXDOMAIN or SERVFAIL answer
+#[derive(Debug, Clone, Copy)]
+struct NegativeEntry {
+    expires_at: Instant,
+    response_code: ResponseCode,
+}
+
+/// On-disk form of a cache entry
+///
+/// `Instant`s mean nothing to another process, so entries carry the wall
+/// clock time they expire at instead.
+#[derive(Serialize, Deserialize)]
+struct PersistedEntry {
+    domain: String,
+    record_type: RecordType,
+    records: Vec<DnsRecord>,
+    expires_at: DateTime<Utc>,
+    /// Set for negative answers, which have no records
+    negative: Option<ResponseCode>,
+}
+
+impl PersistedEntry {
+    fn new(key: &CacheKey, records: Vec<DnsRecord>, remaining: Duration, negative: Option<ResponseCode>) -> Self {
+        Self {
+            domain: key.domain.clone(),
+            record_type: key.record_type,
+            records,
+            expires_at: Utc::now() + chrono::Duration::from_std(remaining).unwrap_or(chrono::Duration::zero()),
+            negative,
+        }
+    }
+
+    /// Time left before expiry, `None` once expired
+    fn remaining(&self) -> Option<Duration> {
+        (self.expires_at - Utc::now()).to_std().ok().filter(|remaining| !remaining.is_zero())
+    }
+}
+
 /// DNS response cache with TTL support
+///
+/// Once full, the least recently used entry makes room for a new one.
+/// Negative answers are kept apart, with their own TTL, so they never push
+/// out real records. Clones share the same entries.
+#[derive(Clone)]
 pub struct DnsCache {
-    cache: Arc<RwLock<HashMap<CacheKey, CachedResponse>>>,
+    /// Entries from least to most recently used
+    cache: Arc<RwLock<LinkedHashMap<CacheKey, CachedResponse>>>,
+    negative_cache: Arc<RwLock<HashMap<CacheKey, NegativeEntry>>>,
     max_size: usize,
     default_ttl: Duration,
+    negative_ttl: Duration,
+    evictions: Arc<AtomicU64>,
 }
 
 impl DnsCache {
     /// Create a new DNS cache
     pub fn new(max_size: usize, default_ttl: Duration) -> Self {
         Self {
-            cache: Arc::new(RwLock::new(HashMap::new())),
+            cache: Arc::new(RwLock::new(LinkedHashMap::new())),
+            negative_cache: Arc::new(RwLock::new(HashMap::new())),
             max_size,
             default_ttl,
+            negative_ttl: DEFAULT_NEGATIVE_CACHE_TTL,
+            evictions: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// How long NXDOMAIN and SERVFAIL answers are kept when no TTL is given (default 60s)
+    pub fn with_negative_ttl(mut self, negative_ttl: Duration) -> Self {
+        self.negative_ttl = negative_ttl;
+        self
+    }
+
+    /// Create a cache with the negative TTL of `options`, loaded from its `cache_file` if one is set
+    pub fn from_options(options: &DnsxOptions, max_size: usize, default_ttl: Duration) -> Result<Self> {
+        let cache = match &options.cache_file {
+            Some(path) => Self::open(path, max_size, default_ttl)?,
+            None => Self::new(max_size, default_ttl),
+        };
+        Ok(cache.with_negative_ttl(options.negative_cache_ttl))
+    }
+
+    /// Create a cache holding the unexpired entries saved to `path` by [`save`](Self::save)
+    ///
+    /// A missing file gives an empty cache. If the file holds more than
+    /// `max_size` entries, the least recently used ones are dropped.
+    pub fn open(path: &Path, max_size: usize, default_ttl: Duration) -> Result<Self> {
+        let cache = Self::new(max_size, default_ttl);
+        if !path.exists() {
+            return Ok(cache);
         }
+
+        let bytes = std::fs::read(path)
+            .map_err(|e| DnsxError::io(format!("Failed to read cache file {}", path.display()), e))?;
+        let mut deserializer = rmp_serde::Deserializer::new(bytes.as_slice()).with_human_readable();
+        let entries = Vec::<PersistedEntry>::deserialize(&mut deserializer)
+            .map_err(|e| DnsxError::Serialization(format!("Invalid cache file {}: {}", path.display(), e)))?;
+
+        let now = Instant::now();
+        let (negative, positive): (Vec<_>, Vec<_>) = entries.into_iter().partition(|entry| entry.negative.is_some());
+        {
+            let mut entries = cache.cache.write();
+            let skip = positive.len().saturating_sub(max_size);
+            for entry in positive.into_iter().skip(skip) {
+                if let Some(remaining) = entry.remaining() {
+                    let key = CacheKey::new(entry.domain, entry.record_type);
+                    entries.insert(key, CachedResponse::new(entry.records, remaining));
+                }
+            }
+        }
+        {
+            let mut entries = cache.negative_cache.write();
+            for entry in negative.into_iter().take(max_size) {
+                if let (Some(remaining), Some(response_code)) = (entry.remaining(), entry.negative) {
+                    let key = CacheKey::new(entry.domain, entry.record_type);
+                    entries.insert(key, NegativeEntry { expires_at: now + remaining, response_code });
+                }
+            }
+        }
+
+        debug!("Loaded cache from {}: {}", path.display(), cache.stats());
+        Ok(cache)
+    }
+
+    /// Write the unexpired entries to `path` in MessagePack, for [`open`](Self::open) to load later
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut entries = Vec::new();
+        for (key, cached) in self.cache.read().iter() {
+            let remaining = cached.remaining_ttl();
+            if !remaining.is_zero() {
+                entries.push(PersistedEntry::new(key, cached.records.clone(), remaining, None));
+            }
+        }
+        let now = Instant::now();
+        for (key, entry) in self.negative_cache.read().iter() {
+            if entry.expires_at > now {
+                let remaining = entry.expires_at - now;
+                entries.push(PersistedEntry::new(key, Vec::new(), remaining, Some(entry.response_code)));
+            }
+        }
+
+        // Human-readable mode keeps IP addresses as strings, which the untagged RecordValue needs
+        let mut bytes = Vec::new();
+        let mut serializer = rmp_serde::Serializer::new(&mut bytes).with_struct_map().with_human_readable();
+        entries.serialize(&mut serializer).map_err(|e| DnsxError::Serialization(e.to_string()))?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| DnsxError::io(format!("Failed to create {}", parent.display()), e))?;
+        }
+        std::fs::write(path, bytes)
+            .map_err(|e| DnsxError::io(format!("Failed to write cache file {}", path.display()), e))?;
+        debug!("Saved {} cache entries to {}", entries.len(), path.display());
+        Ok(())
     }
 
     /// Get a cached response if it exists and is still valid
     pub fn get(&self, key: &CacheKey) -> Option<Vec<DnsRecord>> {
-        let cache = self.cache.read();
+        let mut cache = self.cache.write();
 
-        if let Some(cached) = cache.get(key) {
+        if let Some(cached) = cache.get_refresh(key) {
             if cached.is_valid() {
                 debug!("Cache hit for {} {:?}", key.domain, key.record_type);
                 Some(cached.records.clone())
@@ -105,21 +256,77 @@ impl DnsCache {
         }
     }
 
+    /// Get the response code of a cached negative response, if one is still valid
+    pub fn get_negative(&self, key: &CacheKey) -> Option<ResponseCode> {
+        let negative_cache = self.negative_cache.read();
+        negative_cache
+            .get(key)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.response_code)
+    }
+
     /// Store a response in the cache
     pub fn put(&self, key: CacheKey, records: Vec<DnsRecord>, ttl: Option<Duration>) {
         let ttl = ttl.unwrap_or(self.default_ttl);
-        let cached_response = CachedResponse::new(records, ttl);
+        self.negative_cache.write().remove(&key);
+        self.insert(key, CachedResponse::new(records, ttl));
+    }
 
-        let mut cache = self.cache.write();
+    /// Store a negative response (e.g. NXDOMAIN), for the negative TTL unless `ttl` is given
+    pub fn put_negative(&self, key: CacheKey, response_code: ResponseCode, ttl: Option<Duration>) {
+        let ttl = ttl.unwrap_or(self.negative_ttl);
+        self.cache.write().remove(&key);
+
+        let mut negative_cache = self.negative_cache.write();
+        if negative_cache.len() >= self.max_size && !negative_cache.contains_key(&key) {
+            let now = Instant::now();
+            negative_cache.retain(|_, entry| entry.expires_at > now);
+            // Still full: drop the entry closest to expiring
+            if negative_cache.len() >= self.max_size {
+                let soonest = negative_cache.iter().min_by_key(|(_, entry)| entry.expires_at).map(|(k, _)| k.clone());
+                if let Some(soonest) = soonest {
+                    negative_cache.remove(&soonest);
+                }
+            }
+        }
+        negative_cache.insert(key, NegativeEntry { expires_at: Instant::now() + ttl, response_code });
+    }
 
-        // Clean up expired entries if we're at capacity
-        if cache.len() >= self.max_size {
-            self.cleanup_expired(&mut cache);
+    /// SOA minimum TTL of the closest enclosing zone with a cached SOA record
+    ///
+    /// Walks from `domain` towards the root, so `a.b.example.com` finds the
+    /// SOA cached for `example.com`.
+    pub fn get_soa_minimum(&self, domain: &str) -> Option<u32> {
+        let mut name = domain.trim_end_matches('.');
+        loop {
+            let key = CacheKey::new(name, RecordType::Soa);
+            if let Some(records) = self.get(&key) {
+                let minimum = records.iter().find_map(|r| match r.value {
+                    RecordValue::Soa { minimum, .. } => Some(minimum),
+                    _ => None,
+                });
+                if minimum.is_some() {
+                    return minimum;
+                }
+            }
+            name = name.split_once('.')?.1;
         }
+    }
+
+    fn insert(&self, key: CacheKey, cached_response: CachedResponse) {
+        let mut cache = self.cache.write();
 
-        // If still at capacity, remove oldest entries
-        if cache.len() >= self.max_size {
-            self.evict_oldest(&mut cache);
+        // Replacing an entry never needs room
+        if !cache.contains_key(&key) {
+            // Clean up expired entries if we're at capacity
+            if cache.len() >= self.max_size {
+                self.cleanup_expired(&mut cache);
+            }
+
+            // If still at capacity, remove the least recently used entries
+            while cache.len() >= self.max_size && !cache.is_empty() {
+                self.evict_oldest(&mut cache);
+            }
         }
 
         cache.insert(key, cached_response);
@@ -148,11 +355,16 @@ impl DnsCache {
             Duration::from_secs(0)
         };
 
+        let now = Instant::now();
+        let negative_entries = self.negative_cache.read().values().filter(|entry| entry.expires_at > now).count();
+
         CacheStats {
             total_entries: cache.len(),
             valid_entries,
             expired_entries,
             average_ttl: avg_ttl,
+            evictions: self.evictions.load(Ordering::Relaxed),
+            negative_entries,
         }
     }
 
@@ -160,21 +372,23 @@ impl DnsCache {
     pub fn clear(&self) {
         let mut cache = self.cache.write();
         cache.clear();
+        self.negative_cache.write().clear();
     }
 
     /// Clean up expired entries
-    fn cleanup_expired(&self, cache: &mut HashMap<CacheKey, CachedResponse>) {
-        cache.retain(|_, cached| cached.is_valid());
+    fn cleanup_expired(&self, cache: &mut LinkedHashMap<CacheKey, CachedResponse>) {
+        let expired: Vec<CacheKey> =
+            cache.iter().filter(|(_, cached)| !cached.is_valid()).map(|(key, _)| key.clone()).collect();
+        for key in expired {
+            cache.remove(&key);
+        }
     }
 
-    /// Evict oldest entries (simple LRU approximation)
-    fn evict_oldest(&self, cache: &mut HashMap<CacheKey, CachedResponse>) {
-        // For simplicity, just remove 10% of entries
-        let to_remove = (cache.len() / 10).max(1);
-        let keys_to_remove: Vec<CacheKey> = cache.keys().take(to_remove).cloned().collect();
-
-        for key in keys_to_remove {
-            cache.remove(&key);
+    /// Evict the least recently used entry
+    fn evict_oldest(&self, cache: &mut LinkedHashMap<CacheKey, CachedResponse>) {
+        if let Some((key, _)) = cache.pop_front() {
+            debug!("Evicted {} {:?} from cache", key.domain, key.record_type);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
         }
     }
 }
@@ -186,47 +400,99 @@ pub struct CacheStats {
     pub valid_entries: usize,
     pub expired_entries: usize,
     pub average_ttl: Duration,
+    /// Entries removed to make room since the cache was created
+    pub evictions: u64,
+    /// Unexpired NXDOMAIN and SERVFAIL answers, not counted in `total_entries`
+    pub negative_entries: usize,
 }
 
 impl std::fmt::Display for CacheStats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Cache: {} total ({} valid, {} expired), avg TTL: {:.1}s",
+            "Cache: {} total ({} valid, {} expired), {} negative, avg TTL: {:.1}s, {} evicted",
             self.total_entries,
             self.valid_entries,
             self.expired_entries,
-            self.average_ttl.as_secs_f64()
+            self.negative_entries,
+            self.average_ttl.as_secs_f64(),
+            self.evictions
         )
     }
 }
 
 /// Cached DNS client wrapper
+///
+/// NXDOMAIN and SERVFAIL answers are cached for the negative TTL of the
+/// cache. NXDOMAIN answers live no longer than the zone's SOA minimum TTL
+/// (RFC 2308), so SOA records for the zones being queried are fetched in
+/// the background.
 pub struct CachedDnsClient<C> {
-    client: C,
+    client: Arc<C>,
     cache: DnsCache,
+    /// Parent domains whose SOA has already been fetched or is being fetched
+    soa_prefetched: Arc<Mutex<HashSet<String>>>,
+    /// Where the cache is saved when the client is dropped
+    cache_file: Option<PathBuf>,
 }
 
 impl<C> CachedDnsClient<C>
 where
-    C: DnsQuery,
+    C: DnsQuery + Send + Sync + 'static,
 {
     /// Create a new cached DNS client
     pub fn new(client: C, cache: DnsCache) -> Self {
-        Self { client, cache }
+        Self {
+            client: Arc::new(client),
+            cache,
+            soa_prefetched: Arc::new(Mutex::new(HashSet::new())),
+            cache_file: None,
+        }
+    }
+
+    /// Save the cache to `path` when this client is dropped (open it with [`DnsCache::open`])
+    pub fn with_cache_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cache_file = Some(path.into());
+        self
     }
 
     /// Query with caching
     pub async fn query(&self, domain: &str, record_type: RecordType) -> Result<Vec<DnsRecord>> {
         let key = CacheKey::new(domain, record_type);
 
-        // Check cache first
+        // Check cache first, negative answers before records
+        if let Some(rcode) = self.cache.get_negative(&key) {
+            return Err(DnsxError::Rcode { resolver: CACHE_RESOLVER.to_string(), domain: domain.to_string(), rcode });
+        }
         if let Some(cached_records) = self.cache.get(&key) {
             return Ok(cached_records);
         }
 
+        if record_type != RecordType::Soa {
+            self.prefetch_soa(domain);
+        }
+
         // Query upstream
-        let records = self.client.query(domain, record_type).await?;
+        let records = match self.client.query(domain, record_type).await {
+            Ok(records) => records,
+            Err(DnsxError::Rcode {
+                resolver,
+                domain: failed,
+                rcode: rcode @ (ResponseCode::NxDomain | ResponseCode::ServFail),
+            }) => {
+                // RFC 2308: NXDOMAIN answers live no longer than the SOA minimum TTL
+                let ttl = match rcode {
+                    ResponseCode::NxDomain => self
+                        .cache
+                        .get_soa_minimum(domain)
+                        .map(|minimum| Duration::from_secs(u64::from(minimum.max(1))).min(self.cache.negative_ttl)),
+                    _ => None,
+                };
+                self.cache.put_negative(key, rcode, ttl);
+                return Err(DnsxError::Rcode { resolver, domain: failed, rcode });
+            }
+            Err(e) => return Err(e),
+        };
 
         // Cache the result (use minimum TTL from records or default)
         let min_ttl = records.iter()
@@ -239,6 +505,44 @@ where
         Ok(records)
     }
 
+    /// Fetch and cache the SOA of `domain`'s enclosing zone in the background
+    ///
+    /// Starts at the parent of `domain` and walks up until a name returns an
+    /// SOA record. Each parent is only fetched once, and top-level domains are
+    /// never queried.
+    fn prefetch_soa(&self, domain: &str) {
+        let Some((_, parent)) = domain.trim_end_matches('.').split_once('.') else {
+            return;
+        };
+        if !parent.contains('.') || self.cache.get_soa_minimum(domain).is_some() {
+            return;
+        }
+        if !self.soa_prefetched.lock().insert(parent.to_string()) {
+            return;
+        }
+
+        let client = Arc::clone(&self.client);
+        let cache = self.cache.clone();
+        let mut name = parent.to_string();
+        tokio::spawn(async move {
+            loop {
+                if let Ok(records) = client.query(&name, RecordType::Soa).await {
+                    if let Some(soa) = records.iter().find(|r| matches!(r.value, RecordValue::Soa { .. })) {
+                        debug!("Prefetched SOA for {}", name);
+                        let ttl = Duration::from_secs(u64::from(soa.ttl));
+                        cache.put(CacheKey::new(name, RecordType::Soa), records, Some(ttl));
+                        return;
+                    }
+                }
+
+                match name.split_once('.') {
+                    Some((_, next)) if next.contains('.') => name = next.to_string(),
+                    _ => return,
+                }
+            }
+        });
+    }
+
     /// Get cache statistics
     pub fn cache_stats(&self) -> CacheStats {
         self.cache.stats()
@@ -250,6 +554,16 @@ where
     }
 }
 
+impl<C> Drop for CachedDnsClient<C> {
+    fn drop(&mut self) {
+        if let Some(path) = &self.cache_file {
+            if let Err(e) = self.cache.save(path) {
+                warn!("Failed to save DNS cache: {}", e);
+            }
+        }
+    }
+}
+
 /// Trait for DNS query operations
 #[async_trait::async_trait]
 pub trait DnsQuery {
@@ -266,7 +580,7 @@ impl DnsQuery for crate::client::DnsxClient {
 #[async_trait::async_trait]
 impl<C> DnsQuery for CachedDnsClient<C>
 where
-    C: DnsQuery + Send + Sync,
+    C: DnsQuery + Send + Sync + 'static,
 {
     async fn query(&self, domain: &str, record_type: RecordType) -> Result<Vec<DnsRecord>> {
         self.query(domain, record_type).await
@@ -287,6 +601,7 @@ mod tests {
             response_code: ResponseCode::NoError,
             resolver: "127.0.0.1".to_string(),
             timestamp: chrono::Utc::now(),
+            query_time_ms: 0.0,
         }
     }
 
@@ -294,10 +609,11 @@ mod tests {
     fn test_cache_key() {
         let key1 = CacheKey::new("example.com", RecordType::A);
         let key2 = CacheKey::new("example.com", RecordType::A);
-        let key3 = CacheKey::new("example.com", RecordType::AAAA);
+        let key3 = CacheKey::new("example.com", RecordType::Aaaa);
 
         assert_eq!(key1, key2);
         assert_ne!(key1, key3);
+        assert_eq!(CacheKey::new("EXAMPLE.COM.", RecordType::A), CacheKey::new("example.com", RecordType::A));
     }
 
     #[test]
@@ -319,6 +635,125 @@ mod tests {
         assert_eq!(stats.valid_entries, 1);
     }
 
+    #[test]
+    fn test_lru_eviction() {
+        let cache = DnsCache::new(4, Duration::from_secs(60));
+        let key = |i: usize| CacheKey::new(format!("host{}.example.com", i), RecordType::A);
+        for i in 0..4 {
+            cache.put(key(i), vec![create_test_record("example.com", RecordType::A)], None);
+        }
+
+        // Touch host0 and host2 so host1 and host3 become the least recently used
+        assert!(cache.get(&key(0)).is_some());
+        assert!(cache.get(&key(2)).is_some());
+        cache.put(key(4), Vec::new(), None);
+        cache.put(key(5), Vec::new(), None);
+
+        assert!(cache.get(&key(1)).is_none());
+        assert!(cache.get(&key(3)).is_none());
+        for i in [0, 2, 4, 5] {
+            assert!(cache.get(&key(i)).is_some(), "host{} was evicted", i);
+        }
+        let stats = cache.stats();
+        assert_eq!(stats.total_entries, 4);
+        assert_eq!(stats.evictions, 2);
+
+        // Replacing an entry in a full cache evicts nothing
+        cache.put(key(0), Vec::new(), None);
+        assert_eq!(cache.stats().evictions, 2);
+    }
+
+    #[test]
+    fn test_soa_minimum_and_negative_entries() {
+        let cache = DnsCache::new(100, Duration::from_secs(60));
+        assert_eq!(cache.get_soa_minimum("www.example.com"), None);
+
+        let mut soa = create_test_record("example.com", RecordType::Soa);
+        soa.value = RecordValue::Soa {
+            mname: "ns1.example.com".to_string(),
+            rname: "hostmaster.example.com".to_string(),
+            serial: 1,
+            refresh: 7200,
+            retry: 3600,
+            expire: 1209600,
+            minimum: 900,
+        };
+        cache.put(CacheKey::new("example.com", RecordType::Soa), vec![soa], None);
+        assert_eq!(cache.get_soa_minimum("a.b.example.com"), Some(900));
+        assert_eq!(cache.get_soa_minimum("example.org"), None);
+
+        let key = CacheKey::new("missing.example.com", RecordType::A);
+        cache.put_negative(key.clone(), ResponseCode::NxDomain, Some(Duration::from_secs(900)));
+        assert_eq!(cache.get_negative(&key), Some(ResponseCode::NxDomain));
+        assert!(cache.get(&key).is_none());
+    }
+
+    /// Answers NXDOMAIN to everything, counting the non-SOA queries it gets
+    struct NxDomainResolver {
+        queries: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl DnsQuery for NxDomainResolver {
+        async fn query(&self, domain: &str, record_type: RecordType) -> Result<Vec<DnsRecord>> {
+            if record_type != RecordType::Soa {
+                self.queries.fetch_add(1, Ordering::SeqCst);
+            }
+            Err(DnsxError::Rcode {
+                resolver: "192.0.2.53:53".to_string(),
+                domain: domain.to_string(),
+                rcode: ResponseCode::NxDomain,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nxdomain_is_served_from_negative_cache() {
+        let resolver = NxDomainResolver { queries: Default::default() };
+        let cache = DnsCache::new(100, Duration::from_secs(300)).with_negative_ttl(Duration::from_secs(60));
+        let client = CachedDnsClient::new(resolver, cache);
+
+        let first = client.query("missing.example.com", RecordType::A).await;
+        assert!(matches!(first, Err(DnsxError::Rcode { rcode: ResponseCode::NxDomain, .. })));
+        let second = client.query("missing.example.com", RecordType::A).await;
+        assert!(matches!(
+            second,
+            Err(DnsxError::Rcode { ref domain, rcode: ResponseCode::NxDomain, .. }) if domain == "missing.example.com"
+        ));
+
+        assert_eq!(client.client.queries.load(Ordering::SeqCst), 1);
+        let stats = client.cache_stats();
+        assert_eq!(stats.negative_entries, 1);
+        assert_eq!(stats.total_entries, 0);
+    }
+
+    #[test]
+    fn test_cache_survives_restart() {
+        let path = std::env::temp_dir().join(format!("rdnsx-cache-{}.msgpack", std::process::id()));
+        let key = CacheKey::new("example.com", RecordType::A);
+        let mut record = create_test_record("example.com", RecordType::A);
+        record.value = RecordValue::Ip("93.184.216.34".parse().unwrap());
+        let missing = CacheKey::new("missing.example.com", RecordType::A);
+
+        let cache = DnsCache::new(100, Duration::from_secs(60));
+        cache.put(key.clone(), vec![record.clone()], Some(Duration::from_secs(600)));
+        cache.put(CacheKey::new("expired.example.com", RecordType::A), vec![record.clone()], Some(Duration::ZERO));
+        cache.put_negative(missing.clone(), ResponseCode::NxDomain, None);
+        let client = CachedDnsClient::new(NxDomainResolver { queries: Default::default() }, cache)
+            .with_cache_file(&path);
+        drop(client);
+
+        let reloaded = DnsCache::open(&path, 100, Duration::from_secs(60)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let records = reloaded.get(&key).expect("entry lost across restart");
+        assert_eq!(records[0].value, record.value);
+        assert_eq!(records[0].domain, "example.com");
+        assert_eq!(reloaded.get_negative(&missing), Some(ResponseCode::NxDomain));
+        let stats = reloaded.stats();
+        assert_eq!(stats.total_entries, 1);
+        assert!(stats.average_ttl > Duration::from_secs(590));
+    }
+
     #[tokio::test]
     async fn test_cached_client() {
         use crate::client::DnsxClient;
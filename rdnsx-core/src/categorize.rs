@@ -0,0 +1,199 @@
+//! Domain categorization from the records a scan collected
+
+use std::fmt;
+use std::net::IpAddr;
+
+use ipnetwork::IpNetwork;
+use serde::Serialize;
+
+use crate::cdn_detection::CdnDetector;
+use crate::types::{DnsRecord, RecordType, RecordValue};
+
+/// What a domain appears to be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DomainTag {
+    /// Has an address and an HTTPS record
+    Web,
+    /// Has MX records
+    Mail,
+    /// Aliased to a known CDN
+    Cdn,
+    /// Aliased to an object storage endpoint such as an S3 bucket
+    CloudStorage,
+    /// Resolves into a domain parking service
+    Parked,
+    /// Named like a development, staging or test environment
+    Development,
+}
+
+impl DomainTag {
+    /// Tag name as used in JSON output
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DomainTag::Web => "web",
+            DomainTag::Mail => "mail",
+            DomainTag::Cdn => "cdn",
+            DomainTag::CloudStorage => "cloud_storage",
+            DomainTag::Parked => "parked",
+            DomainTag::Development => "development",
+        }
+    }
+}
+
+impl fmt::Display for DomainTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Hostname suffixes of object storage services
+const CLOUD_STORAGE_SUFFIXES: &[&str] = &[
+    ".s3.amazonaws.com",
+    ".s3-website.amazonaws.com",
+    ".blob.core.windows.net",
+    ".web.core.windows.net",
+    ".storage.googleapis.com",
+    ".digitaloceanspaces.com",
+    ".r2.cloudflarestorage.com",
+    ".backblazeb2.com",
+    ".objectstorage.oraclecloud.com",
+];
+
+/// Ranges of the large parking services (Sedo, Bodis, ParkingCrew, Above.com, GoDaddy)
+const PARKING_RANGES: &[&str] = &[
+    "91.195.240.0/23",
+    "64.190.62.0/24",
+    "199.59.240.0/22",
+    "185.53.176.0/22",
+    "103.224.182.0/24",
+    "103.224.212.0/24",
+    "34.102.136.180/32",
+];
+
+/// Name tokens that mark non-production environments
+const DEVELOPMENT_TOKENS: &[&str] = &[
+    "dev", "develop", "development", "staging", "stage", "stg", "test", "testing", "qa", "uat",
+];
+
+/// Tags domains by the shape of their DNS records
+pub struct DomainCategorizer;
+
+impl DomainCategorizer {
+    /// Tags for `domain` given the records collected for it, in [`DomainTag`] order
+    ///
+    /// Records for other domains are ignored, so a whole scan's results can be passed.
+    pub fn categorize(domain: &str, records: &[DnsRecord]) -> Vec<DomainTag> {
+        let name = normalize(domain);
+        let records: Vec<&DnsRecord> = records.iter().filter(|r| normalize(&r.domain) == name).collect();
+        let has = |record_type: RecordType| records.iter().any(|r| r.record_type == record_type);
+
+        let mut tags = Vec::new();
+        if (has(RecordType::A) || has(RecordType::Aaaa)) && has(RecordType::Https) {
+            tags.push(DomainTag::Web);
+        }
+        if has(RecordType::Mx) {
+            tags.push(DomainTag::Mail);
+        }
+
+        let aliases: Vec<String> = records.iter().filter_map(|r| alias_target(r)).collect();
+        let storage = aliases.iter().any(|target| is_cloud_storage(target));
+        // S3 and friends also match the CDN providers' hostname patterns
+        if !storage && aliases.iter().any(|target| CdnDetector::builtin_cdn_provider(target).is_some()) {
+            tags.push(DomainTag::Cdn);
+        }
+        if storage {
+            tags.push(DomainTag::CloudStorage);
+        }
+
+        let parked = records.iter().any(|r| match r.value {
+            RecordValue::Ip(ip) => is_parking_ip(ip),
+            _ => false,
+        });
+        if parked {
+            tags.push(DomainTag::Parked);
+        }
+        if is_development_name(&name) {
+            tags.push(DomainTag::Development);
+        }
+
+        tags
+    }
+}
+
+fn normalize(domain: &str) -> String {
+    domain.trim().trim_end_matches('.').to_lowercase()
+}
+
+/// Target of a CNAME, including chain hops stored under the queried type
+fn alias_target(record: &DnsRecord) -> Option<String> {
+    match (&record.value, record.record_type) {
+        (_, RecordType::Ns | RecordType::Ptr | RecordType::Mx) => None,
+        (RecordValue::Domain(target), _) => Some(normalize(target)),
+        _ => None,
+    }
+}
+
+fn is_cloud_storage(target: &str) -> bool {
+    CLOUD_STORAGE_SUFFIXES.iter().any(|suffix| target.ends_with(suffix))
+        // Regional S3 endpoints: bucket.s3.eu-west-1.amazonaws.com, bucket.s3-us-west-2.amazonaws.com
+        || (target.ends_with(".amazonaws.com")
+            && target.split('.').any(|label| label == "s3" || label.starts_with("s3-")))
+}
+
+fn is_parking_ip(ip: IpAddr) -> bool {
+    PARKING_RANGES
+        .iter()
+        .filter_map(|range| range.parse::<IpNetwork>().ok())
+        .any(|network| network.contains(ip))
+}
+
+/// Whether a label below the TLD is made of, e.g., `dev`, `staging-api` or `qa2`
+fn is_development_name(name: &str) -> bool {
+    let labels: Vec<&str> = name.split('.').collect();
+    labels[..labels.len().saturating_sub(1)]
+        .iter()
+        .flat_map(|label| label.split(|c: char| !c.is_ascii_alphabetic()))
+        .any(|token| DEVELOPMENT_TOKENS.contains(&token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ResponseCode;
+
+    fn record(domain: &str, record_type: RecordType, value: RecordValue) -> DnsRecord {
+        DnsRecord::new(
+            domain.to_string(),
+            record_type,
+            value,
+            300,
+            ResponseCode::NoError,
+            "8.8.8.8:53".to_string(),
+            1.0,
+        )
+    }
+
+    #[test]
+    fn test_categorize() {
+        let mx = RecordValue::Mx { priority: 10, exchange: "mx.example.com".to_string() };
+        let s3 = RecordValue::Domain("assets.s3.eu-west-1.amazonaws.com".to_string());
+        let records = vec![
+            record("example.com", RecordType::A, RecordValue::Ip("192.0.2.1".parse().unwrap())),
+            record("example.com", RecordType::Https, RecordValue::Text("1 . alpn=h2".to_string())),
+            record("example.com", RecordType::Mx, mx),
+            record("static.example.com", RecordType::Cname, RecordValue::Domain("d111.cloudfront.net.".to_string())),
+            record("assets.example.com", RecordType::A, s3),
+            record("staging-api.example.com", RecordType::A, RecordValue::Ip("91.195.240.12".parse().unwrap())),
+        ];
+
+        assert_eq!(DomainCategorizer::categorize("example.com", &records), vec![DomainTag::Web, DomainTag::Mail]);
+        assert_eq!(DomainCategorizer::categorize("static.example.com", &records), vec![DomainTag::Cdn]);
+        assert_eq!(DomainCategorizer::categorize("assets.example.com", &records), vec![DomainTag::CloudStorage]);
+        assert_eq!(
+            DomainCategorizer::categorize("staging-api.example.com", &records),
+            vec![DomainTag::Parked, DomainTag::Development]
+        );
+        assert!(DomainCategorizer::categorize("devices.example.com", &records).is_empty());
+    }
+}
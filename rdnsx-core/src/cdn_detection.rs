@@ -1,10 +1,13 @@
 //! CDN detection and analysis
 
 use std::net::IpAddr;
+use std::path::Path;
 use std::sync::Arc;
+
+use serde::Deserialize;
 use tracing::info;
 
-use crate::error::Result;
+use crate::error::{DnsxError, Result};
 use crate::resolver::ResolverPool;
 use crate::types::RecordType;
 
@@ -85,15 +88,93 @@ pub struct AsnInfo {
     pub is_cdn_asn: bool,
 }
 
+/// A user-supplied CDN provider definition
+#[derive(Debug, Clone, Deserialize)]
+pub struct CdnPattern {
+    pub provider: String,
+    /// Hostname globs such as `*.examplecdn.net`
+    pub patterns: Vec<String>,
+    #[serde(default)]
+    pub asns: Vec<u32>,
+}
+
+/// CDN provider patterns loaded from a JSON file, consulted before the built-in list
+#[derive(Debug, Clone, Default)]
+pub struct CdnPatternDatabase {
+    entries: Vec<CdnPattern>,
+}
+
+impl CdnPatternDatabase {
+    /// Load a JSON array of `{ "provider", "patterns", "asns" }` entries
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| DnsxError::io(format!("Failed to read CDN patterns {}", path.display()), e))?;
+        let entries: Vec<CdnPattern> = serde_json::from_str(&content)
+            .map_err(|e| DnsxError::parse(path.display().to_string(), format!("Invalid CDN pattern file: {}", e)))?;
+
+        info!("Loaded {} custom CDN providers from {}", entries.len(), path.display());
+        Ok(Self { entries })
+    }
+
+    /// Provider whose hostname patterns match `domain`
+    pub fn identify(&self, domain: &str) -> Option<&str> {
+        let domain = domain.trim_end_matches('.').to_lowercase();
+        self.entries
+            .iter()
+            .find(|entry| entry.patterns.iter().any(|p| glob_match(&p.to_lowercase(), &domain)))
+            .map(|entry| entry.provider.as_str())
+    }
+
+    /// Provider that announces `asn`
+    pub fn provider_for_asn(&self, asn: u32) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.asns.contains(&asn))
+            .map(|entry| entry.provider.as_str())
+    }
+}
+
+/// Match `text` against a pattern where `*` stands for any sequence of characters
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || !text[first.len()..].ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
 /// CDN detection functionality
 pub struct CdnDetector {
     resolver_pool: Arc<ResolverPool>,
+    pattern_db: Option<CdnPatternDatabase>,
 }
 
 impl CdnDetector {
     /// Create a new CDN detector
     pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
-        Self { resolver_pool }
+        Self {
+            resolver_pool,
+            pattern_db: None,
+        }
+    }
+
+    /// Load additional provider patterns from a JSON file
+    pub fn with_pattern_db(mut self, path: &Path) -> Result<Self> {
+        self.pattern_db = Some(CdnPatternDatabase::load(path)?);
+        Ok(self)
     }
 
     /// Detect and analyze CDN usage
@@ -189,7 +270,7 @@ impl CdnDetector {
                         current_domain = cname_target;
 
                         // Check if this looks like a CDN
-                        if let Some(detected_provider) = Self::identify_cdn_provider(&current_domain) {
+                        if let Some(detected_provider) = self.identify_cdn_provider(&current_domain) {
                             provider = Some(detected_provider);
                         }
                     } else {
@@ -210,8 +291,17 @@ impl CdnDetector {
         })
     }
 
+    /// Identify CDN provider, preferring user-supplied patterns over the built-in list
+    fn identify_cdn_provider(&self, domain: &str) -> Option<String> {
+        self.pattern_db
+            .as_ref()
+            .and_then(|db| db.identify(domain))
+            .map(str::to_string)
+            .or_else(|| Self::builtin_cdn_provider(domain))
+    }
+
     /// Identify CDN provider from domain patterns with improved heuristics
-    fn identify_cdn_provider(domain: &str) -> Option<String> {
+    pub(crate) fn builtin_cdn_provider(domain: &str) -> Option<String> {
         let domain_lower = domain.to_lowercase();
 
         // Major CDN providers with comprehensive pattern matching
@@ -383,37 +473,18 @@ impl CdnDetector {
         // For now, we'll use a simplified ASN detection based on known CDN ASNs
         // In a real implementation, you'd query an ASN database or service
 
-        if let IpAddr::V4(ipv4) = ip {
-            let ip_u32 = u32::from_be_bytes(ipv4.octets());
-
-            // Check known CDN ASNs (simplified mapping)
-            let cdn_asns = [
-                (13335, "Cloudflare"),
-                (20940, "Akamai"),
-                (54113, "Fastly"),
-                (16509, "Amazon (CloudFront)"),
-                (16625, "Akamai"),
-                (209242, "Cloudflare"),
-                (396982, "Google Cloud"),
-                (15169, "Google"),
-                (8068, "Microsoft"),
-                (8075, "Microsoft"),
-                (12008, "CDN77"),
-                (197902, "StackPath"),
-                (60068, "CDN77"),
-                (62240, "Clouvider"),
-                (9009, "M247"),
-            ];
-
-            for (asn, org) in cdn_asns.iter() {
-                if Self::is_asn_range(ip_u32, *asn) {
-                    return Ok(AsnInfo {
-                        asn: *asn,
-                        organization: org.to_string(),
-                        is_cdn_asn: true,
-                    });
-                }
-            }
+        if let Some((asn, org)) = Self::builtin_asn(ip) {
+            // Custom patterns may claim an ASN for a more specific provider
+            let organization = self
+                .pattern_db
+                .as_ref()
+                .and_then(|db| db.provider_for_asn(asn))
+                .unwrap_or(org);
+            return Ok(AsnInfo {
+                asn,
+                organization: organization.to_string(),
+                is_cdn_asn: true,
+            });
         }
 
         // Default ASN info for unknown IPs
@@ -424,6 +495,35 @@ impl CdnDetector {
         })
     }
 
+    /// ASN and organization of `ip` when it falls in one of the built-in provider ranges
+    pub(crate) fn builtin_asn(ip: IpAddr) -> Option<(u32, &'static str)> {
+        let IpAddr::V4(ipv4) = ip else {
+            return None;
+        };
+        let ip_u32 = u32::from_be_bytes(ipv4.octets());
+
+        // Check known CDN ASNs (simplified mapping)
+        let cdn_asns = [
+            (13335, "Cloudflare"),
+            (20940, "Akamai"),
+            (54113, "Fastly"),
+            (16509, "Amazon (CloudFront)"),
+            (16625, "Akamai"),
+            (209242, "Cloudflare"),
+            (396982, "Google Cloud"),
+            (15169, "Google"),
+            (8068, "Microsoft"),
+            (8075, "Microsoft"),
+            (12008, "CDN77"),
+            (197902, "StackPath"),
+            (60068, "CDN77"),
+            (62240, "Clouvider"),
+            (9009, "M247"),
+        ];
+
+        cdn_asns.into_iter().find(|&(asn, _)| Self::is_asn_range(ip_u32, asn))
+    }
+
     /// Simple ASN range detection (simplified for demo)
     fn is_asn_range(ip: u32, asn: u32) -> bool {
         // This is a very simplified ASN detection
@@ -833,4 +933,32 @@ struct CnameChainResult {
     chain: Vec<CnameHop>,
     provider: Option<String>,
     final_domain: String,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.examplecdn.net", "edge1.examplecdn.net"));
+        assert!(!glob_match("*.examplecdn.net", "examplecdn.net.evil.com"));
+        assert!(glob_match("cdn-*.example.*", "cdn-eu.example.org"));
+        assert!(glob_match("static.example.com", "static.example.com"));
+        assert!(!glob_match("static.example.com", "www.example.com"));
+    }
+
+    #[test]
+    fn test_pattern_database_identify() {
+        let db = CdnPatternDatabase {
+            entries: vec![CdnPattern {
+                provider: "ExampleCDN".to_string(),
+                patterns: vec!["*.examplecdn.net".to_string()],
+                asns: vec![64500],
+            }],
+        };
+        assert_eq!(db.identify("Edge.ExampleCDN.net."), Some("ExampleCDN"));
+        assert_eq!(db.identify("www.example.com"), None);
+        assert_eq!(db.provider_for_asn(64500), Some("ExampleCDN"));
+    }
+}
@@ -1,15 +1,22 @@
 //! Main DNSx client
 
 use std::net::IpAddr;
+use std::sync::Arc;
+
+use futures::stream::{self, Stream, StreamExt};
+
+use crate::audit::AuditLogger;
 use crate::config::DnsxOptions;
-use crate::error::Result;
+use crate::error::{DnsxError, Result};
+use crate::monitor::serial_is_newer;
 use crate::query::QueryEngine;
 use crate::resolver::ResolverPool;
-use crate::types::{DnsRecord, RecordType};
+use crate::types::{DnsRecord, RecordType, RecordValue, ResponseCode};
 
 /// Main DNSx client
 pub struct DnsxClient {
     query_engine: QueryEngine,
+    concurrency: usize,
 }
 
 impl DnsxClient {
@@ -23,12 +30,107 @@ impl DnsxClient {
         let resolver_pool = ResolverPool::new(&options)?;
         let query_engine = QueryEngine::new(resolver_pool);
 
-        Ok(Self { query_engine })
+        Ok(Self {
+            query_engine,
+            concurrency: options.concurrency.max(1),
+        })
+    }
+
+    /// Bypass the recursive resolvers and ask each zone's authoritative nameservers
+    pub fn with_authoritative(mut self, authoritative: bool) -> Self {
+        self.query_engine = self.query_engine.with_authoritative(authoritative);
+        self
+    }
+
+    /// Write an audit log entry for every query, including failed ones
+    pub fn with_audit_logger(mut self, audit_logger: Option<Arc<AuditLogger>>) -> Self {
+        self.query_engine = self.query_engine.with_audit_logger(audit_logger);
+        self
+    }
+
+    /// Initialize all resolvers now instead of on their first query
+    pub async fn warm_up(&self) -> Result<()> {
+        self.query_engine.warm_up().await
     }
 
     /// Query a domain for a specific record type
+    #[cfg_attr(feature = "rdnsx-otel", tracing::instrument(
+        name = "dns.query",
+        skip(self),
+        fields(
+            dns.question.name = %domain,
+            dns.question.type = %record_type,
+            net.peer.ip = tracing::field::Empty,
+            dns.response_code = tracing::field::Empty,
+        )
+    ))]
     pub async fn query(&self, domain: &str, record_type: RecordType) -> Result<Vec<DnsRecord>> {
-        self.query_engine.query(domain, record_type).await
+        let result = self.query_engine.query(domain, record_type).await;
+
+        #[cfg(feature = "rdnsx-otel")]
+        if let Some(record) = result.as_ref().ok().and_then(|records| records.first()) {
+            let span = tracing::Span::current();
+            span.record("net.peer.ip", record.resolver.as_str());
+            span.record("dns.response_code", tracing::field::display(record.response_code));
+        }
+
+        result
+    }
+
+    /// Query many domains lazily, yielding records as individual queries complete
+    ///
+    /// At most `concurrency` queries are in flight and nothing is buffered beyond
+    /// the records of completed queries, so arbitrarily large inputs can be
+    /// processed without collecting results in memory.
+    pub fn query_stream<'a, I>(
+        &'a self,
+        domains: I,
+        record_types: &'a [RecordType],
+    ) -> impl Stream<Item = Result<DnsRecord>> + 'a
+    where
+        I: IntoIterator + 'a,
+        I::Item: Into<String>,
+    {
+        let queries = domains.into_iter().flat_map(move |domain| {
+            let domain: String = domain.into();
+            record_types.iter().map(move |record_type| (domain.clone(), *record_type))
+        });
+
+        stream::iter(queries)
+            .map(move |(domain, record_type)| async move { self.query(&domain, record_type).await })
+            .buffer_unordered(self.concurrency)
+            .flat_map(|result| match result {
+                Ok(records) => stream::iter(records.into_iter().map(Ok)).left_stream(),
+                Err(e) => stream::once(async move { Err(e) }).right_stream(),
+            })
+    }
+
+    /// Current SOA serial of the zone at `domain`
+    ///
+    /// `None` when the name does not exist or has no SOA record (i.e. is not a
+    /// zone apex); network and resolver failures are returned as errors.
+    pub async fn query_soa_serial(&self, domain: &str) -> Result<Option<u32>> {
+        match self.query(domain, RecordType::Soa).await {
+            Ok(records) => Ok(records.iter().find_map(|record| match record.value {
+                RecordValue::Soa { serial, .. } => Some(serial),
+                _ => None,
+            })),
+            Err(DnsxError::Rcode { rcode: ResponseCode::NxDomain | ResponseCode::NoError, .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Whether the zone at `domain` has a newer SOA serial than `last_serial`
+    ///
+    /// Serials are compared with RFC 1982 arithmetic, so a serial that wrapped
+    /// around past `u32::MAX` still counts as newer. A zone without an SOA
+    /// record reports `false`; use [`query_soa_serial`](Self::query_soa_serial)
+    /// to tell a vanished zone apart from an unchanged one.
+    pub async fn has_changed_since(&self, domain: &str, last_serial: u32) -> Result<bool> {
+        Ok(self
+            .query_soa_serial(domain)
+            .await?
+            .is_some_and(|serial| serial_is_newer(serial, last_serial)))
     }
 
     /// Lookup IPv4 addresses for a domain (A records)
@@ -41,6 +143,15 @@ impl DnsxClient {
         self.query_engine.lookup_ipv6(domain).await
     }
 
+    /// Final IP addresses of a domain, following its CNAME chain
+    ///
+    /// Up to [`MAX_CNAME_HOPS`](crate::resolver::MAX_CNAME_HOPS) aliases are
+    /// followed, so `api.example.com -> lb.example.com -> 1.2.3.4` gives
+    /// `1.2.3.4`. Loops and longer chains are errors.
+    pub async fn resolve_to_ips(&self, domain: &str) -> Result<Vec<IpAddr>> {
+        self.query_engine.resolve_to_ips(domain).await
+    }
+
     /// Lookup all IP addresses for a domain (A and AAAA records)
     pub async fn lookup(&self, domain: &str) -> Result<Vec<std::net::IpAddr>> {
         let mut ips = Vec::new();
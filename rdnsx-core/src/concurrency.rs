@@ -1,15 +1,22 @@
 //! Concurrent processing utilities for high-performance DNS scanning
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use futures::stream::{self, StreamExt};
-use tokio::sync::Semaphore;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, Semaphore};
 use tokio::time::timeout;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, warn, info};
 
+use crate::config::{DEFAULT_RATE_LIMIT_BURST_MULTIPLIER, MAX_RATE_LIMIT_BURST_MULTIPLIER};
 use crate::error::{DnsxError, Result};
-use crate::types::DnsRecord;
+use crate::utils::extract_domain_from_url;
+use crate::types::{DnsRecord, DnsRecordValidator, RecordType, ResponseCode, ValidationError};
 
 /// Configuration for concurrent processing
 #[derive(Debug, Clone)]
@@ -22,6 +29,8 @@ pub struct ConcurrencyConfig {
     pub timeout: Duration,
     /// Rate limit (requests per second, 0 = unlimited)
     pub rate_limit: u64,
+    /// Burst capacity as a multiple of `rate_limit`
+    pub rate_limit_burst_multiplier: f64,
 }
 
 impl Default for ConcurrencyConfig {
@@ -31,12 +40,37 @@ impl Default for ConcurrencyConfig {
             batch_size: 1000,
             timeout: Duration::from_secs(5),
             rate_limit: 0,
+            rate_limit_burst_multiplier: DEFAULT_RATE_LIMIT_BURST_MULTIPLIER,
         }
     }
 }
 
-/// Performance metrics for concurrent processing
+impl ConcurrencyConfig {
+    /// Limiter enforcing `rate_limit`, or `None` when unlimited
+    fn rate_limiter(&self) -> Option<RateLimiter> {
+        (self.rate_limit > 0).then(|| RateLimiter::with_burst(self.rate_limit, self.rate_limit_burst_multiplier))
+    }
+}
+
+/// What the query function produced for one item
+///
+/// Failed lookups yield no records but still count towards the response code
+/// distribution, so callers querying several record types per item report them here.
 #[derive(Debug, Default)]
+pub struct QueryOutcome {
+    pub records: Vec<DnsRecord>,
+    /// Lookups that failed, by record type
+    pub failures: Vec<(RecordType, DnsxError)>,
+}
+
+impl From<Vec<DnsRecord>> for QueryOutcome {
+    fn from(records: Vec<DnsRecord>) -> Self {
+        Self { records, failures: Vec::new() }
+    }
+}
+
+/// Performance metrics for concurrent processing
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct ProcessingMetrics {
     pub total_domains: usize,
     pub successful_queries: usize,
@@ -44,6 +78,78 @@ pub struct ProcessingMetrics {
     pub total_query_time: Duration,
     pub average_query_time: Duration,
     pub queries_per_second: f64,
+    /// Number of items actually processed (less than `total_domains` when cancelled)
+    pub processed_items: usize,
+    /// Whether processing stopped early because of cancellation
+    pub cancelled: bool,
+    /// Records that failed validation and were held back from the results
+    pub invalid_records: usize,
+    /// Breakdown keyed by resolver address
+    pub per_resolver: HashMap<String, ResolverStats>,
+    /// Answered queries per record type and response code (tuple keys have no JSON form)
+    #[serde(skip)]
+    pub rcode_distribution: HashMap<(RecordType, ResponseCode), u64>,
+}
+
+impl ProcessingMetrics {
+    /// Fold another set of per-resolver statistics into this one
+    pub fn merge_resolver_stats(&mut self, other: &HashMap<String, ResolverStats>) {
+        for (resolver, stats) in other {
+            self.per_resolver.entry(resolver.clone()).or_default().merge(stats);
+        }
+    }
+
+    /// Fold another response code distribution into this one
+    pub fn merge_rcode_distribution(&mut self, other: &HashMap<(RecordType, ResponseCode), u64>) {
+        for (key, count) in other {
+            *self.rcode_distribution.entry(*key).or_default() += count;
+        }
+    }
+
+    /// Answered queries with `rcode`, across all record types
+    pub fn rcode_count(&self, rcode: ResponseCode) -> u64 {
+        self.rcode_distribution
+            .iter()
+            .filter(|((_, code), _)| *code == rcode)
+            .map(|(_, count)| count)
+            .sum()
+    }
+}
+
+/// Statistics for a single resolver
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResolverStats {
+    pub queries: u64,
+    pub errors: u64,
+    pub nxdomain: u64,
+    pub avg_latency_ms: f64,
+}
+
+impl ResolverStats {
+    /// Account for one query answered by this resolver
+    pub fn record(&mut self, latency_ms: f64, error: bool, nxdomain: bool) {
+        self.queries += 1;
+        if error {
+            self.errors += 1;
+        }
+        if nxdomain {
+            self.nxdomain += 1;
+        }
+        self.avg_latency_ms += (latency_ms - self.avg_latency_ms) / self.queries as f64;
+    }
+
+    /// Combine with statistics gathered elsewhere
+    pub fn merge(&mut self, other: &ResolverStats) {
+        let total = self.queries + other.queries;
+        if total > 0 {
+            self.avg_latency_ms = (self.avg_latency_ms * self.queries as f64
+                + other.avg_latency_ms * other.queries as f64)
+                / total as f64;
+        }
+        self.queries = total;
+        self.errors += other.errors;
+        self.nxdomain += other.nxdomain;
+    }
 }
 
 /// Concurrent DNS query processor
@@ -51,6 +157,9 @@ pub struct ConcurrentProcessor<T, F> {
     config: ConcurrencyConfig,
     semaphore: Arc<Semaphore>,
     query_fn: Arc<F>,
+    cancellation: Option<CancellationToken>,
+    /// Receives records that fail validation, with what is wrong with them
+    invalid_records: Option<mpsc::UnboundedSender<(DnsRecord, Vec<ValidationError>)>>,
     _phantom: std::marker::PhantomData<T>,
 }
 
@@ -69,7 +178,7 @@ impl<T, F> ConcurrentProcessor<T, F> {
 impl<T, F> ConcurrentProcessor<T, F>
 where
     T: Send + Clone + 'static,
-    F: Fn(T) -> futures::future::BoxFuture<'static, Result<Vec<DnsRecord>>> + Send + Sync + 'static,
+    F: Fn(T) -> futures::future::BoxFuture<'static, Result<QueryOutcome>> + Send + Sync + 'static,
 {
     /// Create a new concurrent processor
     pub fn new(config: ConcurrencyConfig, query_fn: F) -> Self {
@@ -78,11 +187,32 @@ where
             config,
             semaphore,
             query_fn: Arc::new(query_fn),
+            cancellation: None,
+            invalid_records: None,
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// Stop processing at the next batch boundary once `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Send records that fail [`DnsRecordValidator`] checks to `sender` instead of only logging them
+    ///
+    /// Invalid records never appear in the results either way.
+    pub fn with_invalid_record_channel(mut self, sender: mpsc::UnboundedSender<(DnsRecord, Vec<ValidationError>)>) -> Self {
+        self.invalid_records = Some(sender);
+        self
+    }
+
     /// Process items concurrently with streaming
+    #[cfg_attr(feature = "rdnsx-otel", tracing::instrument(
+        name = "dns.batch",
+        skip_all,
+        fields(batch_size = tracing::field::Empty)
+    ))]
     pub async fn process_stream<I>(
         &self,
         items: I,
@@ -96,27 +226,34 @@ where
         let mut metrics = ProcessingMetrics::default();
 
         // Create rate limiter if needed
-        let rate_limiter = if self.config.rate_limit > 0 {
-            Some(RateLimiter::new(self.config.rate_limit))
-        } else {
-            None
-        };
+        let rate_limiter = self.config.rate_limiter();
 
         // Process items in batches to manage memory
         let items_vec: Vec<T> = items.collect();
         metrics.total_domains = items_vec.len();
 
+        #[cfg(feature = "rdnsx-otel")]
+        tracing::Span::current().record("batch_size", items_vec.len());
+
         let chunks = items_vec.chunks(self.config.batch_size);
 
         for chunk in chunks {
+            if self.cancellation.as_ref().is_some_and(|t| t.is_cancelled()) {
+                info!("Processing cancelled after {} items", metrics.processed_items);
+                metrics.cancelled = true;
+                break;
+            }
+
             debug!("Processing batch of {} items", chunk.len());
 
             let batch_start = Instant::now();
-            let batch_records = self.process_batch(chunk, &rate_limiter).await?;
+            let batch_records = self.process_batch(chunk, &rate_limiter, &mut metrics).await?;
+            let batch_records = self.validate_records(batch_records, &mut metrics);
             let batch_time = batch_start.elapsed();
 
             all_records.extend(batch_records);
             metrics.total_query_time += batch_time;
+            metrics.processed_items += chunk.len();
 
             debug!("Batch completed in {:.2}s", batch_time.as_secs_f64());
         }
@@ -141,11 +278,86 @@ where
         Ok((all_records, metrics))
     }
 
+    /// Process items in a background task, sending records through a bounded
+    /// channel as each query completes instead of accumulating them
+    ///
+    /// The channel applies backpressure: no new queries are started while the
+    /// receiver is `buffer` records behind. Dropping the receiver stops processing.
+    pub fn process_to_channel<I>(&self, items: I, buffer: usize) -> mpsc::Receiver<DnsRecord>
+    where
+        I: Iterator<Item = T> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(buffer.max(1));
+        let query_fn = Arc::clone(&self.query_fn);
+        let config = self.config.clone();
+        let cancellation = self.cancellation.clone();
+        let invalid_records = self.invalid_records.clone();
+        let rate_limiter = config.rate_limiter();
+
+        tokio::spawn(async move {
+            let query_timeout = config.timeout;
+            let mut results = stream::iter(items)
+                .map(|item| {
+                    let query_fn = Arc::clone(&query_fn);
+                    let rate_limiter = rate_limiter.clone();
+                    async move {
+                        if let Some(ref limiter) = rate_limiter {
+                            limiter.wait().await;
+                        }
+                        timeout(query_timeout, query_fn(item)).await
+                    }
+                })
+                .buffer_unordered(config.max_concurrent.max(1));
+
+            while let Some(result) = results.next().await {
+                if cancellation.as_ref().is_some_and(|t| t.is_cancelled()) {
+                    info!("Channel processing cancelled");
+                    break;
+                }
+
+                match result {
+                    Ok(Ok(outcome)) => {
+                        for record in outcome.records {
+                            if let Err(errors) = DnsRecordValidator::validate(&record) {
+                                reject_record(record, errors, invalid_records.as_ref());
+                                continue;
+                            }
+                            if tx.send(record).await.is_err() {
+                                debug!("Record receiver dropped, stopping");
+                                return;
+                            }
+                        }
+                    }
+                    Ok(Err(e)) => warn!("Query failed: {}", e),
+                    Err(_) => warn!("Query timed out"),
+                }
+            }
+        });
+
+        rx
+    }
+
+    /// Keep the valid records, logging and forwarding the rest
+    fn validate_records(&self, records: Vec<DnsRecord>, metrics: &mut ProcessingMetrics) -> Vec<DnsRecord> {
+        let mut valid = Vec::with_capacity(records.len());
+        for record in records {
+            match DnsRecordValidator::validate(&record) {
+                Ok(()) => valid.push(record),
+                Err(errors) => {
+                    metrics.invalid_records += 1;
+                    reject_record(record, errors, self.invalid_records.as_ref());
+                }
+            }
+        }
+        valid
+    }
+
     /// Process a batch of items concurrently
     async fn process_batch(
         &self,
         items: &[T],
         rate_limiter: &Option<RateLimiter>,
+        metrics: &mut ProcessingMetrics,
     ) -> Result<Vec<DnsRecord>>
     where
         T: Clone + Send + 'static,
@@ -165,33 +377,36 @@ where
                     }
 
                     // Execute query with timeout
+                    let started = Instant::now();
                     let result = timeout(self.config.timeout, query_fn(item)).await;
+                    let elapsed = started.elapsed();
 
                     match result {
-                        Ok(Ok(records)) => Ok(records),
-                        Ok(Err(e)) => {
-                            warn!("Query failed: {}", e);
-                            Ok(Vec::new()) // Return empty vec for failed queries
-                        }
-                        Err(_) => {
-                            warn!("Query timed out");
-                            Ok(Vec::new())
-                        }
+                        Ok(Ok(records)) => (Ok(records), elapsed),
+                        Ok(Err(e)) => (Err(e), elapsed),
+                        Err(_) => (Err(DnsxError::timeout("unknown", "unknown", self.config.timeout)), elapsed),
                     }
                 }
             })
             .buffer_unordered(self.config.max_concurrent);
 
-        let results: Vec<Result<Vec<DnsRecord>>> = futures.collect().await;
+        let results: Vec<(Result<QueryOutcome>, Duration)> = futures.collect().await;
         let mut all_records = Vec::new();
 
-        for result in results {
+        for (result, elapsed) in results {
+            let latency_ms = elapsed.as_secs_f64() * 1000.0;
             match result {
-                Ok(records) => {
+                Ok(QueryOutcome { records, failures }) => {
+                    metrics.successful_queries += 1;
+                    let errors = failures.iter().map(|(_, e)| e);
+                    record_resolver_stats(&mut metrics.per_resolver, &records, errors, latency_ms);
+                    record_rcodes(&mut metrics.rcode_distribution, &records, &failures);
                     all_records.extend(records);
                 }
                 Err(e) => {
-                    warn!("Batch processing error: {}", e);
+                    warn!("Query failed: {}", e);
+                    metrics.failed_queries += 1;
+                    record_resolver_stats(&mut metrics.per_resolver, &[], [&e], latency_ms);
                 }
             }
         }
@@ -200,25 +415,118 @@ where
     }
 }
 
-/// Rate limiter for controlling request frequency
+/// Attribute one processed item to every resolver that answered for it or failed it
+///
+/// Failures name their resolver when they got that far (timeouts, network errors
+/// and error responses); the rest are put down to `unknown`.
+fn record_resolver_stats<'a>(
+    per_resolver: &mut HashMap<String, ResolverStats>,
+    records: &'a [DnsRecord],
+    failures: impl IntoIterator<Item = &'a DnsxError>,
+    latency_ms: f64,
+) {
+    let mut outcomes: HashMap<&str, (bool, bool)> = HashMap::new();
+
+    let answered = records.iter().map(|record| (record.resolver.as_str(), Some(record.response_code)));
+    let failed = failures.into_iter().map(|e| (e.resolver().unwrap_or("unknown"), e.rcode()));
+    for (resolver, rcode) in answered.chain(failed) {
+        let outcome = outcomes.entry(resolver).or_default();
+        match rcode {
+            Some(ResponseCode::NoError) => {}
+            Some(ResponseCode::NxDomain) => outcome.1 = true,
+            _ => outcome.0 = true,
+        }
+    }
+
+    for (resolver, (error, nxdomain)) in outcomes {
+        per_resolver
+            .entry(resolver.to_string())
+            .or_default()
+            .record(latency_ms, error, nxdomain);
+    }
+}
+
+/// Count each (record type, response code) answer once per processed item,
+/// however many records the answer carried
+///
+/// Negative answers arrive as [`DnsxError::Rcode`] failures; other failures got no answer and are not counted.
+fn record_rcodes(
+    distribution: &mut HashMap<(RecordType, ResponseCode), u64>,
+    records: &[DnsRecord],
+    failures: &[(RecordType, DnsxError)],
+) {
+    let answered = records.iter().map(|record| (record.record_type, record.response_code));
+    let negative = failures.iter().filter_map(|(record_type, e)| Some((*record_type, e.rcode()?)));
+
+    let mut seen = Vec::new();
+    for key in answered.chain(negative) {
+        if !seen.contains(&key) {
+            seen.push(key);
+            *distribution.entry(key).or_default() += 1;
+        }
+    }
+}
+
+/// Log an invalid record and hand it to the validation channel, if any
+fn reject_record(
+    record: DnsRecord,
+    errors: Vec<ValidationError>,
+    channel: Option<&mpsc::UnboundedSender<(DnsRecord, Vec<ValidationError>)>>,
+) {
+    let reasons: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    warn!("Invalid {} record for {}: {}", record.record_type, record.domain, reasons.join("; "));
+    if let Some(channel) = channel {
+        // A dropped receiver only means nobody is collecting rejects any more
+        let _ = channel.send((record, errors));
+    }
+}
+
+/// Token bucket rate limiter for controlling request frequency
+///
+/// The bucket starts full, so with a burst capacity above one token the
+/// first requests go out immediately before the steady rate takes over.
 #[derive(Clone)]
 pub struct RateLimiter {
     interval: Duration,
-    last_request: std::sync::Arc<std::sync::Mutex<Instant>>,
+    /// How far ahead of the steady schedule requests may run (the bucket size)
+    burst_window: Duration,
+    /// Time at which the bucket would next be full again if no one waited
+    next_full: std::sync::Arc<std::sync::Mutex<Instant>>,
 }
 
 impl RateLimiter {
-    /// Create a new rate limiter
+    /// Create a new rate limiter without burst capacity
+    ///
+    /// The bucket holds a single token, so requests are spaced evenly from the first one on.
     pub fn new(requests_per_second: u64) -> Self {
+        Self::with_capacity(requests_per_second, 1.0)
+    }
+
+    /// Create a rate limiter holding up to `requests_per_second * burst_multiplier` tokens
+    ///
+    /// The multiplier is clamped to 1.0-10.0.
+    pub fn with_burst(requests_per_second: u64, burst_multiplier: f64) -> Self {
+        let burst_multiplier = if burst_multiplier.is_finite() {
+            burst_multiplier.clamp(DEFAULT_RATE_LIMIT_BURST_MULTIPLIER, MAX_RATE_LIMIT_BURST_MULTIPLIER)
+        } else {
+            DEFAULT_RATE_LIMIT_BURST_MULTIPLIER
+        };
+        Self::with_capacity(requests_per_second, requests_per_second as f64 * burst_multiplier)
+    }
+
+    /// Create a rate limiter whose bucket holds `capacity` tokens (at least one)
+    fn with_capacity(requests_per_second: u64, capacity: f64) -> Self {
         let interval = if requests_per_second > 0 {
             Duration::from_micros(1_000_000 / requests_per_second)
         } else {
             Duration::from_micros(0)
         };
+        let capacity = capacity.max(1.0);
 
         Self {
             interval,
-            last_request: std::sync::Arc::new(std::sync::Mutex::new(Instant::now())),
+            burst_window: interval.mul_f64(capacity),
+            next_full: std::sync::Arc::new(std::sync::Mutex::new(Instant::now())),
         }
     }
 
@@ -228,23 +536,141 @@ impl RateLimiter {
             return;
         }
 
-        let mut last_request = self.last_request.lock().unwrap();
-        let now = Instant::now();
-        let elapsed = now.duration_since(*last_request);
+        // Take a token and release the lock before sleeping so the returned
+        // future stays Send and concurrent callers queue up fairly. A token is
+        // available once the bucket is less than `burst_window` from full.
+        let sleep_duration = {
+            let mut next_full = self.next_full.lock().unwrap();
+            let now = Instant::now();
+            let after_take = (*next_full).max(now) + self.interval;
+            *next_full = after_take;
+            after_take.saturating_duration_since(now).saturating_sub(self.burst_window)
+        };
 
-        if elapsed < self.interval {
-            let sleep_duration = self.interval - elapsed;
+        if !sleep_duration.is_zero() {
             tokio::time::sleep(sleep_duration).await;
         }
+    }
+}
+
+/// Selects which input domains are processed
+#[derive(Debug, Clone)]
+pub enum DomainFilter {
+    /// Any of a set of glob patterns, e.g. `*.api.*` or `*staging*`
+    Glob(GlobSet),
+    /// Regular expression searched anywhere in the domain
+    Regex(Regex),
+    /// Domain starts with the given string
+    Prefix(String),
+    /// Domain ends with the given string
+    Suffix(String),
+    /// Domain contains the given string
+    Contains(String),
+}
+
+impl DomainFilter {
+    /// Build a case-insensitive glob filter matching any of `patterns`
+    pub fn glob<I, S>(patterns: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let pattern = pattern.as_ref();
+            let glob = GlobBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| DnsxError::invalid_input(format!("Invalid glob pattern '{}': {}", pattern, e)))?;
+            builder.add(glob);
+        }
+
+        let set = builder
+            .build()
+            .map_err(|e| DnsxError::invalid_input(format!("Invalid glob patterns: {}", e)))?;
+        Ok(Self::Glob(set))
+    }
+
+    /// Build a regular expression filter
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Regex::new(pattern)
+            .map(Self::Regex)
+            .map_err(|e| DnsxError::invalid_input(format!("Invalid regex '{}': {}", pattern, e)))
+    }
 
-        *last_request = Instant::now();
+    /// Whether `domain` passes this filter
+    pub fn matches(&self, domain: &str) -> bool {
+        match self {
+            Self::Glob(set) => set.is_match(domain),
+            Self::Regex(regex) => regex.is_match(domain),
+            Self::Prefix(prefix) => domain.starts_with(prefix.as_str()),
+            Self::Suffix(suffix) => domain.ends_with(suffix.as_str()),
+            Self::Contains(needle) => domain.contains(needle.as_str()),
+        }
     }
 }
 
+/// One line of JSONL input
+///
+/// Only `domain` is required and unknown fields are ignored, so the JSONL
+/// output of an earlier run can be fed back in as-is.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DomainEntry {
+    pub domain: String,
+    /// Record type the entry asks for, if any
+    #[serde(default)]
+    pub record_type: Option<RecordType>,
+    /// Free-form metadata carried over from an earlier stage
+    #[serde(default)]
+    pub context: Option<serde_json::Value>,
+}
+
+/// Reader of newline-delimited JSON objects, one [`DomainEntry`] per line
+pub struct JsonlReader<R> {
+    reader: R,
+}
+
 /// Stream-based domain reader for memory-efficient processing
 pub struct DomainStreamer<R> {
     reader: R,
     buffer_size: usize,
+    /// Domains are kept when any filter matches (all domains when empty)
+    filters: Vec<DomainFilter>,
+    /// Reduce URL input to its hostname before filtering
+    strip_urls: bool,
+    /// Also drop a leading `www.` when reducing URLs
+    strip_www: bool,
+}
+
+impl<R> DomainStreamer<R> {
+    /// Only yield domains matching `filter`; filters added by repeated calls are OR-combined
+    pub fn with_filter(mut self, filter: DomainFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = size;
+        self
+    }
+
+    /// Accept URLs (e.g. browser history or Burp exports) and query their hostnames
+    ///
+    /// Lines that are not URLs with a host are skipped and logged at debug level.
+    pub fn with_url_stripping(mut self, strip_www: bool) -> Self {
+        self.strip_urls = true;
+        self.strip_www = strip_www;
+        self
+    }
+}
+
+/// Hostname of a URL input line, `None` (after logging) when it has none
+fn strip_url(input: &str, strip_www: bool) -> Option<String> {
+    let domain = extract_domain_from_url(input, strip_www);
+    if domain.is_none() {
+        debug!("Skipping malformed URL input: {}", input);
+    }
+    domain
 }
 
 impl<R: std::io::BufRead> DomainStreamer<R> {
@@ -252,17 +678,17 @@ impl<R: std::io::BufRead> DomainStreamer<R> {
         Self {
             reader,
             buffer_size: 8192, // 8KB buffer
+            filters: Vec::new(),
+            strip_urls: false,
+            strip_www: false,
         }
     }
 
-    pub fn with_buffer_size(mut self, size: usize) -> Self {
-        self.buffer_size = size;
-        self
-    }
-
     /// Stream domains one by one without loading everything into memory
     pub fn stream_domains(self) -> impl Iterator<Item = Result<String>> {
         let mut lines = self.reader.lines();
+        let filters = self.filters;
+        let (strip_urls, strip_www) = (self.strip_urls, self.strip_www);
 
         std::iter::from_fn(move || {
             match lines.next() {
@@ -271,22 +697,76 @@ impl<R: std::io::BufRead> DomainStreamer<R> {
                     if trimmed.is_empty() || trimmed.starts_with('#') {
                         // Skip empty lines and comments, continue to next
                         Some(Ok(String::new())) // Will be filtered out
+                    } else if strip_urls {
+                        Some(Ok(strip_url(trimmed, strip_www).unwrap_or_default()))
                     } else {
                         Some(Ok(trimmed.to_string()))
                     }
                 }
-                Some(Err(e)) => Some(Err(DnsxError::Other(format!("IO error: {}", e)))),
+                Some(Err(e)) => Some(Err(DnsxError::Io(e))),
                 None => None,
             }
-        }).filter_map(|result| {
+        }).filter_map(move |result| {
             match result {
                 Ok(s) if s.is_empty() => None, // Filter out empty lines
+                Ok(s) if !filters.is_empty() && !filters.iter().any(|f| f.matches(&s)) => None,
                 other => Some(other),
             }
         })
     }
 }
 
+impl<R: std::io::BufRead> DomainStreamer<JsonlReader<R>> {
+    /// Stream newline-delimited JSON objects with a `domain` field instead of plain lines
+    pub fn from_jsonl(reader: R) -> Self {
+        Self {
+            reader: JsonlReader { reader },
+            buffer_size: 8192,
+            filters: Vec::new(),
+            strip_urls: false,
+            strip_www: false,
+        }
+    }
+
+    /// Stream entries one by one, with any `record_type` and `context` they carry
+    ///
+    /// Blank lines and `#` comments are skipped; a line that is not a JSON
+    /// object with a `domain` field yields a parse error and streaming continues.
+    pub fn stream_entries(self) -> impl Iterator<Item = Result<DomainEntry>> {
+        let filters = self.filters;
+        let (strip_urls, strip_www) = (self.strip_urls, self.strip_www);
+
+        self.reader.reader.lines().filter_map(move |line| {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(DnsxError::Io(e))),
+            };
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                return None;
+            }
+
+            match serde_json::from_str::<DomainEntry>(trimmed) {
+                Ok(mut entry) => {
+                    entry.domain = if strip_urls {
+                        strip_url(&entry.domain, strip_www)?
+                    } else {
+                        entry.domain.trim().to_string()
+                    };
+                    let kept = filters.is_empty() || filters.iter().any(|f| f.matches(&entry.domain));
+                    (!entry.domain.is_empty() && kept).then_some(Ok(entry))
+                }
+                Err(e) => Some(Err(DnsxError::parse(trimmed, format!("Invalid JSONL input: {}", e)))),
+            }
+        })
+    }
+
+    /// Stream only the domains of the entries
+    pub fn stream_domains(self) -> impl Iterator<Item = Result<String>> {
+        self.stream_entries().map(|entry| entry.map(|entry| entry.domain))
+    }
+}
+
 /// Adaptive batch sizer based on performance metrics
 pub struct AdaptiveBatchSizer {
     current_size: usize,
@@ -343,6 +823,23 @@ mod tests {
         assert!(elapsed >= Duration::from_millis(400));
     }
 
+    #[tokio::test]
+    async fn test_rate_limiter_burst() {
+        // 10/s with a 2x burst: 20 tokens up front, then one every 100ms
+        let limiter = RateLimiter::with_burst(10, 2.0);
+        let start = Instant::now();
+
+        for _ in 0..20 {
+            limiter.wait().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        for _ in 0..3 {
+            limiter.wait().await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+
     #[test]
     fn test_adaptive_batch_sizer() {
         let mut sizer = AdaptiveBatchSizer::new(100, 10, 1000);
@@ -352,7 +849,66 @@ mod tests {
         assert!(sizer.current_size() > 100);
 
         // Test decreasing batch size (low QPS)
+        let before = sizer.current_size();
         sizer.adjust(800.0);
-        assert!(sizer.current_size() < sizer.current_size);
+        assert!(sizer.current_size() < before);
+    }
+
+    #[test]
+    fn test_domain_streamer_filters() {
+        let input = "api.example.com\nwww.example.com\n# comment\nstaging-db.example.com\nv2.API.test.org\n";
+        let streamer = DomainStreamer::new(std::io::Cursor::new(input))
+            .with_filter(DomainFilter::glob(["*.api.*", "api.*"]).unwrap())
+            .with_filter(DomainFilter::regex("staging").unwrap());
+
+        let domains: Vec<String> = streamer.stream_domains().map(|d| d.unwrap()).collect();
+        assert_eq!(domains, vec!["api.example.com", "staging-db.example.com", "v2.API.test.org"]);
+
+        assert!(DomainFilter::Suffix(".org".to_string()).matches("v2.api.test.org"));
+        assert!(!DomainFilter::Prefix("www".to_string()).matches("api.example.com"));
+        assert!(DomainFilter::regex("(").is_err());
+    }
+
+    #[test]
+    fn test_domain_streamer_url_stripping() {
+        let input = "https://www.example.com/login?next=/\nhttp://api.example.com:8080/v1\nexample.org\nhttps://\n";
+        let domains: Vec<String> = DomainStreamer::new(std::io::Cursor::new(input))
+            .with_url_stripping(true)
+            .with_filter(DomainFilter::Suffix(".com".to_string()))
+            .stream_domains()
+            .map(|d| d.unwrap())
+            .collect();
+        assert_eq!(domains, vec!["example.com", "api.example.com"]);
+    }
+
+    #[test]
+    fn test_domain_streamer_jsonl() {
+        let input = concat!(
+            "{\"domain\":\"www.example.com\",\"record_type\":\"A\",\"value\":\"192.0.2.1\",\"ttl\":300}\n",
+            "\n",
+            "{\"domain\":\"api.example.com\",\"record_type\":\"CNAME\",\"context\":{\"stage\":1}}\n",
+            "not json\n",
+            "{\"domain\":\"mail.example.org\"}\n",
+        );
+        let entries: Vec<Result<DomainEntry>> = DomainStreamer::from_jsonl(std::io::Cursor::new(input))
+            .with_filter(DomainFilter::Suffix(".example.com".to_string()))
+            .stream_entries()
+            .collect();
+
+        assert_eq!(entries.len(), 3);
+        let first = entries[0].as_ref().unwrap();
+        assert_eq!(first.domain, "www.example.com");
+        assert_eq!(first.record_type, Some(RecordType::A));
+        assert_eq!(first.context, None);
+        let second = entries[1].as_ref().unwrap();
+        assert_eq!(second.record_type, Some(RecordType::Cname));
+        assert_eq!(second.context, Some(serde_json::json!({"stage": 1})));
+        assert!(matches!(entries[2], Err(DnsxError::Parse { .. })));
+
+        let domains: Vec<String> = DomainStreamer::from_jsonl(std::io::Cursor::new(input))
+            .stream_domains()
+            .filter_map(|d| d.ok())
+            .collect();
+        assert_eq!(domains, vec!["www.example.com", "api.example.com", "mail.example.org"]);
     }
 }
\ No newline at end of file
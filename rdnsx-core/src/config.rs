@@ -1,15 +1,26 @@
 //! Configuration types and file handling
 
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
 use crate::error::{DnsxError, Result};
+use crate::types::RecordType;
 
 /// Default resolvers (Google, Cloudflare, Quad9)
 pub const DEFAULT_RESOLVERS: &[&str] = &["8.8.8.8", "8.8.4.4", "1.1.1.1", "1.0.0.1", "9.9.9.9"];
 
+/// Cloudflare DNS-over-HTTPS endpoint
+pub const CLOUDFLARE_DOH: &str = "https://cloudflare-dns.com/dns-query";
+
+/// Google DNS-over-HTTPS endpoint
+pub const GOOGLE_DOH: &str = "https://dns.google/dns-query";
+
+/// NextDNS DNS-over-HTTPS endpoint (anonymous, without a profile)
+pub const NEXTDNS_DOH: &str = "https://dns.nextdns.io/dns-query";
+
 /// Default query timeout
 pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
 
@@ -22,9 +33,59 @@ pub const DEFAULT_CONCURRENCY: usize = 100;
 /// Default rate limit (queries per second, 0 = unlimited)
 pub const DEFAULT_RATE_LIMIT: u64 = 0;
 
+/// Default rate limiter burst capacity, as a multiple of the rate limit (no burst)
+pub const DEFAULT_RATE_LIMIT_BURST_MULTIPLIER: f64 = 1.0;
+
+/// Largest accepted rate limiter burst multiplier
+pub const MAX_RATE_LIMIT_BURST_MULTIPLIER: f64 = 10.0;
+
+/// Default number of pooled TCP connections per resolver
+pub const DEFAULT_TCP_POOL_SIZE: usize = 4;
+
+/// Default number of queries in flight on one pipelined TCP connection
+pub const DEFAULT_TCP_PIPELINE_DEPTH: usize = 10;
+
+/// Default EDNS0 UDP payload size advertised to resolvers
+pub const DEFAULT_EDNS_BUFFER_SIZE: u16 = 4096;
+
+/// Largest EDNS0 UDP payload size; hickory's UDP receive buffer holds 4096 bytes
+pub const MAX_EDNS_BUFFER_SIZE: u16 = 4096;
+
+/// Default largest accepted DNS response, the most a TCP message can carry
+pub const DEFAULT_MAX_RESPONSE_SIZE_BYTES: usize = 65535;
+
+/// Default response size above which a warning is logged
+pub const DEFAULT_WARN_ON_LARGE_RESPONSE_BYTES: usize = 4096;
+
+/// Default time NXDOMAIN and SERVFAIL answers stay in a [`DnsCache`](crate::DnsCache)
+pub const DEFAULT_NEGATIVE_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Default timeout of HTTP requests to passive sources such as crt.sh
+pub const DEFAULT_HTTP_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// Default export batch size
 pub const DEFAULT_EXPORT_BATCH_SIZE: usize = 1000;
 
+/// Default number of export backend calls in flight at once
+pub const DEFAULT_MAX_CONCURRENT_EXPORTS: usize = 3;
+
+/// Record type bundles available without a config file; `[record_type_aliases]` entries override them
+pub const BUILTIN_RECORD_TYPE_ALIASES: &[(&str, &[&str])] = &[
+    ("web", &["A", "AAAA", "CNAME"]),
+    ("mail", &["MX", "TXT", "NS"]),
+    ("dnssec", &["DNSKEY", "DS", "RRSIG", "NSEC", "NSEC3", "NSEC3PARAM"]),
+    ("full", &["A", "AAAA", "CNAME", "MX", "TXT", "NS", "SOA", "CAA"]),
+];
+
+/// How many levels of aliases referencing other aliases are expanded
+pub const MAX_ALIAS_DEPTH: usize = 3;
+
+/// Timeouts above this many seconds are reported as likely mistakes
+pub const MAX_SANE_TIMEOUT_SECS: u64 = 60;
+
+/// Concurrency above this is reported as likely to exhaust sockets or trip resolver rate limits
+pub const MAX_SANE_CONCURRENCY: usize = 10_000;
+
 /// DNSx client options (for internal use)
 #[derive(Debug, Clone)]
 pub struct DnsxOptions {
@@ -32,12 +93,42 @@ pub struct DnsxOptions {
     pub resolvers: Vec<String>,
     /// Query timeout
     pub timeout: std::time::Duration,
+    /// Timeouts overriding `timeout` for specific record types (e.g. slow DNSKEY lookups)
+    pub per_type_timeout: HashMap<RecordType, Duration>,
     /// Number of retries for failed queries
     pub retries: u32,
     /// Maximum concurrent queries
     pub concurrency: usize,
     /// Rate limit (queries per second, 0 = unlimited)
     pub rate_limit: u64,
+    /// Queries that may be sent at once before `rate_limit` applies, as a multiple of it (1.0-10.0)
+    pub rate_limit_burst_multiplier: f64,
+    /// EDNS0 UDP payload size in bytes (512 disables EDNS0)
+    pub edns_buffer_size: u16,
+    /// Retry truncated UDP answers over TCP
+    pub tcp_fallback: bool,
+    /// TCP connections kept open per resolver for fallback queries
+    pub tcp_pool_size: usize,
+    /// Send all queries over TCP, several at once per connection, instead of UDP
+    pub tcp_pipeline: bool,
+    /// Queries outstanding on one pipelined connection before further ones wait
+    pub tcp_pipeline_depth: usize,
+    /// Delay between retries of failed queries
+    pub retry_backoff: BackoffConfig,
+    /// Responses larger than this many bytes are discarded
+    pub max_response_size_bytes: usize,
+    /// Responses larger than this many bytes are logged (0 disables the warning)
+    pub warn_on_large_response_bytes: usize,
+    /// Certificate checks for DNS-over-TLS resolvers
+    pub dot: DoTConfig,
+    /// Re-send a query that failed over DNS-over-TLS in plain UDP to the same server
+    pub tls_fallback_to_udp: bool,
+    /// How long a cached client remembers NXDOMAIN and SERVFAIL answers
+    pub negative_cache_ttl: Duration,
+    /// File a cached client loads its entries from and saves them to on drop
+    pub cache_file: Option<PathBuf>,
+    /// Timeout of HTTP requests to passive sources such as certificate transparency logs
+    pub http_timeout: Duration,
 }
 
 impl Default for DnsxOptions {
@@ -45,13 +136,114 @@ impl Default for DnsxOptions {
         Self {
             resolvers: DEFAULT_RESOLVERS.iter().map(|s| s.to_string()).collect(),
             timeout: DEFAULT_TIMEOUT,
+            per_type_timeout: HashMap::new(),
             retries: DEFAULT_RETRIES,
             concurrency: DEFAULT_CONCURRENCY,
             rate_limit: DEFAULT_RATE_LIMIT,
+            rate_limit_burst_multiplier: DEFAULT_RATE_LIMIT_BURST_MULTIPLIER,
+            edns_buffer_size: DEFAULT_EDNS_BUFFER_SIZE,
+            tcp_fallback: true,
+            tcp_pool_size: DEFAULT_TCP_POOL_SIZE,
+            tcp_pipeline: false,
+            tcp_pipeline_depth: DEFAULT_TCP_PIPELINE_DEPTH,
+            retry_backoff: BackoffConfig::default(),
+            max_response_size_bytes: DEFAULT_MAX_RESPONSE_SIZE_BYTES,
+            warn_on_large_response_bytes: DEFAULT_WARN_ON_LARGE_RESPONSE_BYTES,
+            dot: DoTConfig::default(),
+            tls_fallback_to_udp: false,
+            negative_cache_ttl: DEFAULT_NEGATIVE_CACHE_TTL,
+            cache_file: None,
+            http_timeout: DEFAULT_HTTP_TIMEOUT,
         }
     }
 }
 
+/// Certificate checks for DNS-over-TLS (`tls://host[:port]`) resolvers
+///
+/// By default the server certificate must chain to the built-in web PKI roots
+/// and match the host in the resolver string.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoTConfig {
+    /// Accept any certificate; only for testing against local servers
+    #[serde(default)]
+    pub insecure_skip_verify: bool,
+
+    /// PEM file of root certificates to trust instead of the web PKI roots
+    #[serde(default)]
+    pub root_ca: Option<PathBuf>,
+}
+
+/// Exponential backoff between query retries
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BackoffConfig {
+    /// Delay before the first retry in milliseconds
+    #[serde(default = "default_backoff_initial_ms")]
+    pub initial_ms: u64,
+
+    /// Multiplier applied to the delay after each retry
+    #[serde(default = "default_backoff_factor")]
+    pub factor: f64,
+
+    /// Upper bound on the delay in milliseconds
+    #[serde(default = "default_backoff_max_ms")]
+    pub max_ms: u64,
+
+    /// Randomize each delay by up to 10% so clients don't retry in lockstep
+    #[serde(default = "default_backoff_jitter")]
+    pub jitter: bool,
+}
+
+impl BackoffConfig {
+    /// Delay before retry number `attempt` (starting at 1)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let base = (self.initial_ms as f64 * self.factor.powi(exponent)).min(self.max_ms as f64);
+        let millis = if self.jitter {
+            base * rand::Rng::gen_range(&mut rand::thread_rng(), 0.9..=1.1)
+        } else {
+            base
+        };
+        Duration::from_millis(millis.max(0.0) as u64)
+    }
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial_ms: default_backoff_initial_ms(),
+            factor: default_backoff_factor(),
+            max_ms: default_backoff_max_ms(),
+            jitter: default_backoff_jitter(),
+        }
+    }
+}
+
+/// A problem found by [`Config::validate`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigError {
+    /// Dotted path of the setting, e.g. `resolvers.servers[1]`
+    pub field: String,
+    /// The offending value as written
+    pub value: String,
+    pub reason: String,
+}
+
+impl ConfigError {
+    pub fn new(field: impl Into<String>, value: impl ToString, reason: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            value: value.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {:?}: {}", self.field, self.value, self.reason)
+    }
+}
+
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -66,6 +258,18 @@ pub struct Config {
     /// Export configuration
     #[serde(default)]
     pub export: ExportConfig,
+
+    /// Named bundles of record types, e.g. `web = ["A", "AAAA", "CNAME"]`
+    #[serde(default)]
+    pub record_type_aliases: HashMap<String, Vec<String>>,
+
+    /// Where record changes are reported
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+
+    /// Stages run by `rdnsx pipeline`
+    #[serde(default)]
+    pub pipeline: PipelineConfig,
 }
 
 impl Default for Config {
@@ -74,6 +278,9 @@ impl Default for Config {
             resolvers: ResolverConfig::default(),
             performance: PerformanceConfig::default(),
             export: ExportConfig::default(),
+            record_type_aliases: HashMap::new(),
+            notifications: NotificationsConfig::default(),
+            pipeline: PipelineConfig::default(),
         }
     }
 }
@@ -91,6 +298,22 @@ pub struct ResolverConfig {
     /// Number of retries for failed queries
     #[serde(default = "default_retries")]
     pub retries: u32,
+
+    /// Backoff between retries
+    #[serde(default)]
+    pub retry_backoff: BackoffConfig,
+
+    /// TCP connections kept open per resolver for truncated-answer fallback
+    #[serde(default = "default_tcp_pool_size")]
+    pub tcp_pool_size: usize,
+
+    /// Certificate checks for DNS-over-TLS resolvers
+    #[serde(default)]
+    pub dot: DoTConfig,
+
+    /// Retry failed DNS-over-TLS queries in plain UDP to the same server
+    #[serde(default)]
+    pub tls_fallback_to_udp: bool,
 }
 
 impl Default for ResolverConfig {
@@ -99,6 +322,10 @@ impl Default for ResolverConfig {
             servers: default_resolvers(),
             timeout: default_timeout_secs(),
             retries: default_retries(),
+            retry_backoff: BackoffConfig::default(),
+            tcp_pool_size: default_tcp_pool_size(),
+            dot: DoTConfig::default(),
+            tls_fallback_to_udp: false,
         }
     }
 }
@@ -129,6 +356,10 @@ pub struct ExportConfig {
     #[serde(default = "default_batch_size")]
     pub batch_size: usize,
 
+    /// Export calls allowed in flight at once across all enabled backends
+    #[serde(default = "default_max_concurrent_exports")]
+    pub max_concurrent_exports: usize,
+
     /// Elasticsearch export settings
     #[serde(default)]
     pub elasticsearch: ElasticsearchConfig,
@@ -146,6 +377,7 @@ impl Default for ExportConfig {
     fn default() -> Self {
         Self {
             batch_size: default_batch_size(),
+            max_concurrent_exports: default_max_concurrent_exports(),
             elasticsearch: ElasticsearchConfig::default(),
             mongodb: MongodbConfig::default(),
             cassandra: CassandraConfig::default(),
@@ -166,6 +398,10 @@ pub struct ElasticsearchConfig {
     /// Index name
     #[serde(default = "default_es_index")]
     pub index: String,
+
+    /// Index lifecycle policy attached to the index (none by default)
+    #[serde(default)]
+    pub ilm_policy: Option<IlmConfig>,
 }
 
 impl Default for ElasticsearchConfig {
@@ -174,6 +410,33 @@ impl Default for ElasticsearchConfig {
             enabled: false,
             url: default_es_url(),
             index: default_es_index(),
+            ilm_policy: None,
+        }
+    }
+}
+
+/// Elasticsearch index lifecycle (ILM) phases
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IlmConfig {
+    /// Days the index stays in the hot phase
+    #[serde(default = "default_ilm_hot_days")]
+    pub hot_max_age_days: u32,
+
+    /// Days the index spends in the warm phase after leaving hot
+    #[serde(default = "default_ilm_warm_days")]
+    pub warm_max_age_days: u32,
+
+    /// Index age in days at which it is deleted
+    #[serde(default = "default_ilm_delete_days")]
+    pub delete_after_days: u32,
+}
+
+impl Default for IlmConfig {
+    fn default() -> Self {
+        Self {
+            hot_max_age_days: default_ilm_hot_days(),
+            warm_max_age_days: default_ilm_warm_days(),
+            delete_after_days: default_ilm_delete_days(),
         }
     }
 }
@@ -249,6 +512,88 @@ impl Default for CassandraConfig {
 }
 
 // Default value functions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// Webhook that receives each record change as JSON
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// URL to POST changes to (empty disables the webhook)
+    #[serde(default)]
+    pub url: String,
+
+    /// Key for the X-RDNSx-Signature HMAC-SHA256 header (empty sends no signature)
+    #[serde(default)]
+    pub secret: String,
+
+    /// Retries after a 5xx response or a failed connection
+    #[serde(default = "default_webhook_retry_count")]
+    pub retry_count: u32,
+
+    /// Timeout per request in seconds
+    #[serde(default = "default_webhook_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            secret: String::new(),
+            retry_count: default_webhook_retry_count(),
+            timeout_secs: default_webhook_timeout_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PipelineConfig {
+    /// Stages in the order they run; the first must be `bruteforce` or `domains`
+    #[serde(default)]
+    pub stages: Vec<PipelineStageConfig>,
+}
+
+/// One `[[pipeline.stages]]` entry, selected by its `stage` key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum PipelineStageConfig {
+    /// Find subdomains of `domain` with a wordlist file or comma-separated words (default: built-in list)
+    Bruteforce {
+        domain: String,
+        #[serde(default)]
+        wordlist: Option<String>,
+    },
+    /// Start from a fixed list of domains
+    Domains { domains: Vec<String> },
+    /// Detect the CDN in front of each domain
+    CdnDetect,
+    /// Check SPF, DMARC and DKIM of each domain
+    EmailSecurity,
+    /// Send the records found so far to the backends enabled under `[export]`
+    Export,
+}
+
+impl PipelineStageConfig {
+    /// The `stage` key of this entry
+    pub fn name(&self) -> &'static str {
+        match self {
+            PipelineStageConfig::Bruteforce { .. } => "bruteforce",
+            PipelineStageConfig::Domains { .. } => "domains",
+            PipelineStageConfig::CdnDetect => "cdn_detect",
+            PipelineStageConfig::EmailSecurity => "email_security",
+            PipelineStageConfig::Export => "export",
+        }
+    }
+
+    /// Whether the stage produces domains rather than only working on them
+    pub fn is_source(&self) -> bool {
+        matches!(self, PipelineStageConfig::Bruteforce { .. } | PipelineStageConfig::Domains { .. })
+    }
+}
+
 fn default_resolvers() -> Vec<String> {
     DEFAULT_RESOLVERS.iter().map(|s| s.to_string()).collect()
 }
@@ -261,6 +606,26 @@ fn default_retries() -> u32 {
     DEFAULT_RETRIES
 }
 
+fn default_tcp_pool_size() -> usize {
+    DEFAULT_TCP_POOL_SIZE
+}
+
+fn default_backoff_initial_ms() -> u64 {
+    100
+}
+
+fn default_backoff_factor() -> f64 {
+    2.0
+}
+
+fn default_backoff_max_ms() -> u64 {
+    5000
+}
+
+fn default_backoff_jitter() -> bool {
+    true
+}
+
 fn default_concurrency() -> usize {
     DEFAULT_CONCURRENCY
 }
@@ -273,6 +638,10 @@ fn default_batch_size() -> usize {
     DEFAULT_EXPORT_BATCH_SIZE
 }
 
+fn default_max_concurrent_exports() -> usize {
+    DEFAULT_MAX_CONCURRENT_EXPORTS
+}
+
 fn default_es_url() -> String {
     "http://localhost:9200".to_string()
 }
@@ -281,6 +650,18 @@ fn default_es_index() -> String {
     "dnsx-records".to_string()
 }
 
+fn default_ilm_hot_days() -> u32 {
+    7
+}
+
+fn default_ilm_warm_days() -> u32 {
+    23
+}
+
+fn default_ilm_delete_days() -> u32 {
+    30
+}
+
 fn default_mongo_url() -> String {
     "mongodb://localhost:27017".to_string()
 }
@@ -305,14 +686,29 @@ fn default_cassandra_table() -> String {
     "records".to_string()
 }
 
+fn default_webhook_retry_count() -> u32 {
+    3
+}
+
+fn default_webhook_timeout_secs() -> u64 {
+    10
+}
+
+/// Whether `name` is an unquoted CQL keyspace or table name
+fn is_cql_identifier(name: &str) -> bool {
+    name.len() <= 48
+        && name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 impl Config {
     /// Load configuration from a TOML file
     pub fn from_file(path: &Path) -> Result<Self> {
         let contents = fs::read_to_string(path)
-            .map_err(|e| DnsxError::Other(format!("Failed to read config file {}: {}", path.display(), e)))?;
+            .map_err(|e| DnsxError::io(format!("Failed to read config file {}", path.display()), e))?;
 
         let config: Config = toml::from_str(&contents)
-            .map_err(|e| DnsxError::Other(format!("Failed to parse config file {}: {}", path.display(), e)))?;
+            .map_err(|e| DnsxError::parse(path.display().to_string(), format!("Invalid config file: {}", e)))?;
 
         Ok(config)
     }
@@ -334,18 +730,287 @@ impl Config {
         }
     }
 
+    /// Check every setting without contacting resolvers or export backends
+    ///
+    /// Returns all problems found rather than stopping at the first, so a
+    /// config file can be fixed in one pass. Backends are only checked when
+    /// enabled.
+    pub fn validate(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+
+        let resolvers = &self.resolvers;
+        if resolvers.servers.is_empty() {
+            errors.push(ConfigError::new("resolvers.servers", "[]", "at least one resolver is required"));
+        }
+        for (i, server) in resolvers.servers.iter().enumerate() {
+            let parsed = if crate::utils::is_doh_url(server) {
+                crate::utils::parse_doh_url(server).map(|_| ())
+            } else if crate::utils::is_dot_url(server) {
+                crate::utils::parse_dot_url(server).map(|_| ())
+            } else {
+                crate::utils::parse_resolver(server).map(|_| ())
+            };
+            if let Err(e) = parsed {
+                errors.push(ConfigError::new(format!("resolvers.servers[{}]", i), server, e.to_string()));
+            }
+        }
+        if resolvers.timeout == 0 {
+            errors.push(ConfigError::new("resolvers.timeout", resolvers.timeout, "must be at least 1 second"));
+        } else if resolvers.timeout > MAX_SANE_TIMEOUT_SECS {
+            errors.push(ConfigError::new(
+                "resolvers.timeout",
+                resolvers.timeout,
+                format!("more than {} seconds per query stalls scans on dead resolvers", MAX_SANE_TIMEOUT_SECS),
+            ));
+        }
+        if resolvers.tcp_pool_size == 0 {
+            errors.push(ConfigError::new("resolvers.tcp_pool_size", 0, "must be at least 1"));
+        }
+        if let Some(root_ca) = &resolvers.dot.root_ca {
+            if !root_ca.is_file() {
+                errors.push(ConfigError::new("resolvers.dot.root_ca", root_ca.display(), "file not found"));
+            }
+        }
+
+        let backoff = &resolvers.retry_backoff;
+        if backoff.factor.is_nan() || backoff.factor < 1.0 {
+            errors.push(ConfigError::new(
+                "resolvers.retry_backoff.factor",
+                backoff.factor,
+                "must be at least 1.0 so delays do not shrink",
+            ));
+        }
+        if backoff.initial_ms > backoff.max_ms {
+            errors.push(ConfigError::new(
+                "resolvers.retry_backoff.initial_ms",
+                backoff.initial_ms,
+                format!("exceeds max_ms ({})", backoff.max_ms),
+            ));
+        }
+
+        let threads = self.performance.threads;
+        if threads == 0 {
+            errors.push(ConfigError::new("performance.threads", threads, "must be at least 1"));
+        } else if threads > MAX_SANE_CONCURRENCY {
+            errors.push(ConfigError::new(
+                "performance.threads",
+                threads,
+                format!("more than {} concurrent queries exhausts sockets", MAX_SANE_CONCURRENCY),
+            ));
+        }
+
+        let export = &self.export;
+        if export.batch_size == 0 {
+            errors.push(ConfigError::new("export.batch_size", 0, "must be at least 1"));
+        }
+        if export.max_concurrent_exports == 0 {
+            errors.push(ConfigError::new("export.max_concurrent_exports", 0, "must be at least 1"));
+        }
+
+        let es = &export.elasticsearch;
+        if es.enabled {
+            match reqwest::Url::parse(&es.url) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                Ok(url) => errors.push(ConfigError::new(
+                    "export.elasticsearch.url",
+                    &es.url,
+                    format!("unsupported scheme '{}' (expected http or https)", url.scheme()),
+                )),
+                Err(e) => errors.push(ConfigError::new("export.elasticsearch.url", &es.url, e.to_string())),
+            }
+            // Elasticsearch index names must be lowercase and cannot start with these characters
+            if es.index.is_empty() || es.index != es.index.to_lowercase() || es.index.starts_with(['-', '_', '+']) {
+                errors.push(ConfigError::new(
+                    "export.elasticsearch.index",
+                    &es.index,
+                    "must be lowercase and not start with '-', '_' or '+'",
+                ));
+            }
+            if let Some(ilm) = &es.ilm_policy {
+                let rollover = ilm.hot_max_age_days + ilm.warm_max_age_days;
+                if ilm.delete_after_days < rollover {
+                    errors.push(ConfigError::new(
+                        "export.elasticsearch.ilm_policy.delete_after_days",
+                        ilm.delete_after_days,
+                        format!("indices would be deleted before leaving the warm phase ({} days)", rollover),
+                    ));
+                }
+            }
+        }
+
+        let mongo = &export.mongodb;
+        if mongo.enabled {
+            if !(mongo.url.starts_with("mongodb://") || mongo.url.starts_with("mongodb+srv://")) {
+                errors.push(ConfigError::new(
+                    "export.mongodb.url",
+                    &mongo.url,
+                    "must start with mongodb:// or mongodb+srv://",
+                ));
+            }
+            if mongo.database.is_empty() {
+                errors.push(ConfigError::new("export.mongodb.database", "", "must not be empty"));
+            }
+            if mongo.collection.is_empty() {
+                errors.push(ConfigError::new("export.mongodb.collection", "", "must not be empty"));
+            }
+        }
+
+        let cassandra = &export.cassandra;
+        if cassandra.enabled {
+            if cassandra.contact_points.is_empty() {
+                errors.push(ConfigError::new("export.cassandra.contact_points", "[]", "at least one is required"));
+            }
+            for (i, point) in cassandra.contact_points.iter().enumerate() {
+                let valid = point
+                    .rsplit_once(':')
+                    .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+                if !valid {
+                    errors.push(ConfigError::new(
+                        format!("export.cassandra.contact_points[{}]", i),
+                        point,
+                        "expected host:port",
+                    ));
+                }
+            }
+            if cassandra.username.is_empty() != cassandra.password.is_empty() {
+                errors.push(ConfigError::new(
+                    "export.cassandra.username",
+                    &cassandra.username,
+                    "username and password must be set together",
+                ));
+            }
+            for (field, name) in [("keyspace", &cassandra.keyspace), ("table", &cassandra.table)] {
+                if !is_cql_identifier(name) {
+                    errors.push(ConfigError::new(
+                        format!("export.cassandra.{}", field),
+                        name,
+                        "must be a letter followed by up to 47 letters, digits or underscores",
+                    ));
+                }
+            }
+        }
+
+        let webhook = &self.notifications.webhooks;
+        if !webhook.url.is_empty() {
+            match reqwest::Url::parse(&webhook.url) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                Ok(url) => errors.push(ConfigError::new(
+                    "notifications.webhooks.url",
+                    &webhook.url,
+                    format!("unsupported scheme '{}' (expected http or https)", url.scheme()),
+                )),
+                Err(e) => errors.push(ConfigError::new("notifications.webhooks.url", &webhook.url, e.to_string())),
+            }
+            if webhook.timeout_secs == 0 {
+                errors.push(ConfigError::new("notifications.webhooks.timeout_secs", 0, "must be at least 1 second"));
+            }
+        }
+
+        if let Some(first) = self.pipeline.stages.first().filter(|stage| !stage.is_source()) {
+            errors.push(ConfigError::new(
+                "pipeline.stages[0].stage",
+                first.name(),
+                "the first stage must be bruteforce or domains",
+            ));
+        }
+        let any_backend = export.elasticsearch.enabled || export.mongodb.enabled || export.cassandra.enabled;
+        if let Some(i) = self.pipeline.stages.iter().position(|stage| *stage == PipelineStageConfig::Export) {
+            if !any_backend {
+                errors.push(ConfigError::new(
+                    format!("pipeline.stages[{}].stage", i),
+                    "export",
+                    "no export backend is enabled under [export]",
+                ));
+            }
+        }
+
+        let mut aliases: Vec<&String> = self.record_type_aliases.keys().collect();
+        aliases.sort();
+        for alias in aliases {
+            if let Err(e) = self.expand_record_type_aliases(std::slice::from_ref(alias)) {
+                errors.push(ConfigError::new(format!("record_type_aliases.{}", alias), alias, e.to_string()));
+            }
+        }
+
+        errors
+    }
+
+    /// Replace record type aliases in `names` with the types they stand for
+    ///
+    /// Matching is case-insensitive and aliases may reference other aliases up
+    /// to [`MAX_ALIAS_DEPTH`] levels deep. Names that are not aliases are kept
+    /// as given; duplicates are dropped, keeping the first occurrence.
+    pub fn expand_record_type_aliases(&self, names: &[String]) -> Result<Vec<String>> {
+        let mut expanded = Vec::new();
+        for name in names {
+            self.expand_alias(name, 0, &mut expanded)?;
+        }
+        Ok(expanded)
+    }
+
+    fn expand_alias(&self, name: &str, depth: usize, expanded: &mut Vec<String>) -> Result<()> {
+        let Some(targets) = self.alias_targets(name) else {
+            let name = name.to_uppercase();
+            if !expanded.contains(&name) {
+                expanded.push(name);
+            }
+            return Ok(());
+        };
+
+        if depth >= MAX_ALIAS_DEPTH {
+            return Err(DnsxError::validation(format!(
+                "Record type alias '{}' nests more than {} levels deep (is it part of a cycle?)",
+                name, MAX_ALIAS_DEPTH
+            )));
+        }
+        for target in targets {
+            self.expand_alias(&target, depth + 1, expanded)?;
+        }
+        Ok(())
+    }
+
+    fn alias_targets(&self, name: &str) -> Option<Vec<String>> {
+        if let Some((_, targets)) = self.record_type_aliases.iter().find(|(alias, _)| alias.eq_ignore_ascii_case(name)) {
+            return Some(targets.clone());
+        }
+        BUILTIN_RECORD_TYPE_ALIASES
+            .iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+            .map(|(_, targets)| targets.iter().map(|t| t.to_string()).collect())
+    }
+
     /// Create example configuration file
     pub fn create_example_config(path: &Path) -> Result<()> {
         let example = r#"# RDNSx Configuration File
 # Place this file in your working directory or specify with --config
 
 [resolvers]
-# DNS servers to use for queries
+# DNS servers to use for queries: IP[:port], DoH URLs such as "https://cloudflare-dns.com/dns-query",
+# or DoT servers such as "tls://9.9.9.9:853"
 servers = ["8.8.8.8", "8.8.4.4", "1.1.1.1", "1.0.0.1"]
 # Query timeout in seconds
 timeout = 5
 # Number of retries for failed queries
 retries = 3
+# TCP connections kept open per resolver for truncated answers
+tcp_pool_size = 4
+# Retry failed DoT queries in plain UDP to the same server (gives up the encryption)
+tls_fallback_to_udp = false
+
+[resolvers.retry_backoff]
+# Delay before the first retry in milliseconds, multiplied by factor each time
+initial_ms = 100
+factor = 2.0
+# Longest delay between retries in milliseconds
+max_ms = 5000
+# Randomize delays by up to 10%
+jitter = true
+
+[resolvers.dot]
+# Accept any DoT server certificate (testing only)
+insecure_skip_verify = false
+# PEM file of root certificates to trust instead of the built-in web PKI roots
+# root_ca = "/etc/rdnsx/dot-ca.pem"
 
 [performance]
 # Maximum concurrent queries
@@ -356,6 +1021,8 @@ rate_limit = 0
 [export]
 # Batch size for database exports
 batch_size = 1000
+# Export calls in flight at once across all enabled backends
+max_concurrent_exports = 3
 
 [export.elasticsearch]
 # Enable Elasticsearch export
@@ -365,6 +1032,12 @@ url = "http://localhost:9200"
 # Index name for DNS records
 index = "dnsx-records"
 
+# Optional index lifecycle policy (hot -> warm -> delete)
+# [export.elasticsearch.ilm_policy]
+# hot_max_age_days = 7
+# warm_max_age_days = 23
+# delete_after_days = 30
+
 [export.mongodb]
 # Enable MongoDB export
 enabled = false
@@ -387,10 +1060,42 @@ password = ""
 keyspace = "dnsx"
 # Table name
 table = "records"
+
+[record_type_aliases]
+# Bundles usable with --record-type; web, mail, dnssec and full are built in
+# and can be redefined here. Aliases may reference other aliases.
+web = ["A", "AAAA", "CNAME"]
+mail = ["MX", "TXT", "NS"]
+full = ["web", "mail", "SOA", "CAA"]
+
+# Stages run by `rdnsx pipeline`, in order; each gets the domains and records of the one before
+# [[pipeline.stages]]
+# stage = "bruteforce"
+# domain = "example.com"
+# wordlist = "wordlist.txt"
+#
+# [[pipeline.stages]]
+# stage = "cdn_detect"
+#
+# [[pipeline.stages]]
+# stage = "email_security"
+#
+# [[pipeline.stages]]
+# stage = "export"
+
+[notifications.webhooks]
+# URL that receives each record change found by `rdnsx diff --notify` (empty = off)
+url = ""
+# Signs the JSON body with HMAC-SHA256 in the X-RDNSx-Signature header (empty = unsigned)
+secret = ""
+# Retries after a 5xx response or a failed connection, with exponential backoff
+retry_count = 3
+# Timeout per request in seconds
+timeout_secs = 10
 "#;
 
         fs::write(path, example)
-            .map_err(|e| DnsxError::Other(format!("Failed to write example config file: {}", e)))?;
+            .map_err(|e| DnsxError::io("Failed to write example config file", e))?;
 
         println!("Created example configuration file: {}", path.display());
         println!("Edit this file to customize RDNSx settings");
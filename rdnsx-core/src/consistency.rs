@@ -0,0 +1,206 @@
+//! Cross-resolver answer comparison for spotting cache poisoning
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use futures::future::join_all;
+use hickory_resolver::proto::rr::RData;
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use crate::config::DnsxOptions;
+use crate::error::{DnsxError, Result};
+use crate::input::subnet_of;
+use crate::resolver::ResolverPool;
+use crate::types::{RecordType, ResponseCode};
+
+/// Public resolvers run by independent operators on separate anycast networks
+pub const POISON_CHECK_RESOLVERS: &[(&str, &str)] = &[
+    ("Cloudflare", "1.1.1.1"),
+    ("Google", "8.8.8.8"),
+    ("Quad9", "9.9.9.9"),
+    ("OpenDNS", "208.67.222.222"),
+    ("Level3", "4.2.2.1"),
+    ("Hurricane Electric", "74.82.42.42"),
+];
+
+/// Addresses returned by fewer than this share of the answering resolvers are outliers
+pub const OUTLIER_SHARE: f64 = 0.2;
+
+/// An address only a few resolvers answered with
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PoisoningOutlier {
+    pub ip: IpAddr,
+    /// Names of the resolvers that returned it
+    pub resolvers: Vec<String>,
+    /// Fraction of answering resolvers that returned it
+    pub share: f64,
+}
+
+/// How consistently independent resolvers answer for a domain
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PoisoningReport {
+    pub domain: String,
+    /// Addresses returned by at least half of the answering resolvers, sorted
+    pub majority_answer: Vec<IpAddr>,
+    pub outliers: Vec<PoisoningOutlier>,
+    /// Fraction of answering resolvers whose answer contains an outlier (0-1)
+    pub poisoning_likelihood: f64,
+    /// Resolvers that answered; those that failed are left out of every share
+    pub resolvers_answered: usize,
+}
+
+/// Compares the answers of several independent resolvers for the same name
+///
+/// A poisoned cache serves its forged answer only to its own clients, so an
+/// address that only one or two resolver networks return stands out against
+/// the rest. Addresses in the same /24 (/48 for IPv6) as a majority address
+/// are not flagged, since CDNs rotate between neighbouring edge addresses.
+pub struct ConsistencyChecker {
+    resolvers: Vec<(String, Arc<ResolverPool>)>,
+}
+
+impl ConsistencyChecker {
+    /// Create a checker that asks the [`POISON_CHECK_RESOLVERS`]
+    ///
+    /// Timeouts, retries and transport settings come from `options`; its resolver list is ignored.
+    pub fn new(options: &DnsxOptions) -> Result<Self> {
+        Self::with_resolvers(options, POISON_CHECK_RESOLVERS)
+    }
+
+    /// Create a checker that asks the given `(name, address)` resolvers
+    pub fn with_resolvers(options: &DnsxOptions, resolvers: &[(&str, &str)]) -> Result<Self> {
+        if resolvers.len() < 2 {
+            return Err(DnsxError::validation("At least two resolvers are needed to compare answers"));
+        }
+
+        let resolvers = resolvers
+            .iter()
+            .map(|(name, addr)| {
+                let options = DnsxOptions { resolvers: vec![addr.to_string()], ..options.clone() };
+                Ok((name.to_string(), Arc::new(ResolverPool::new(&options)?)))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { resolvers })
+    }
+
+    /// Ask every resolver for the A or AAAA records of `domain` and flag minority answers
+    ///
+    /// Fails when `record_type` is not an address type or no resolver answered.
+    pub async fn detect_poisoning(&self, domain: &str, record_type: RecordType) -> Result<PoisoningReport> {
+        if !matches!(record_type, RecordType::A | RecordType::Aaaa) {
+            return Err(DnsxError::validation(format!(
+                "Poisoning checks compare addresses, {} records are not supported",
+                record_type
+            )));
+        }
+
+        let queries = self.resolvers.iter().map(|(name, pool)| async move {
+            let ips: Result<Vec<IpAddr>> = match pool.query(domain, record_type).await {
+                Ok((lookup, _)) => Ok(lookup
+                    .iter()
+                    .filter_map(|rdata| match rdata {
+                        RData::A(a) => Some(IpAddr::V4(**a)),
+                        RData::AAAA(aaaa) => Some(IpAddr::V6(**aaaa)),
+                        _ => None,
+                    })
+                    .collect()),
+                Err(DnsxError::Rcode { rcode: ResponseCode::NxDomain | ResponseCode::NoError, .. }) => Ok(Vec::new()),
+                Err(e) => Err(e),
+            };
+            (name.clone(), ips)
+        });
+
+        let mut answers = Vec::new();
+        let mut last_error = None;
+        for (name, result) in join_all(queries).await {
+            match result {
+                Ok(ips) => {
+                    debug!("{} answered {} ({}) with {:?}", name, domain, record_type, ips);
+                    answers.push((name, ips));
+                }
+                Err(e) => {
+                    warn!("{} could not be asked for {} ({}): {}", name, domain, record_type, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if answers.is_empty() => Err(e),
+            _ => Ok(compare_answers(domain, &answers)),
+        }
+    }
+}
+
+/// Majority answer and outliers among each resolver's `(name, addresses)`
+pub(crate) fn compare_answers(domain: &str, answers: &[(String, Vec<IpAddr>)]) -> PoisoningReport {
+    let answered = answers.len();
+    let mut seen_by: BTreeMap<IpAddr, BTreeSet<&str>> = BTreeMap::new();
+    for (name, ips) in answers {
+        for ip in ips {
+            seen_by.entry(*ip).or_default().insert(name.as_str());
+        }
+    }
+
+    let share = |count: usize| if answered == 0 { 0.0 } else { count as f64 / answered as f64 };
+    let majority_answer: Vec<IpAddr> =
+        seen_by.iter().filter(|(_, names)| share(names.len()) >= 0.5).map(|(ip, _)| *ip).collect();
+    let majority_networks: BTreeSet<_> = majority_answer.iter().map(|ip| cdn_network(*ip)).collect();
+
+    let outliers: Vec<PoisoningOutlier> = seen_by
+        .iter()
+        .filter(|(ip, names)| share(names.len()) < OUTLIER_SHARE && !majority_networks.contains(&cdn_network(**ip)))
+        .map(|(ip, names)| PoisoningOutlier {
+            ip: *ip,
+            resolvers: names.iter().map(|name| name.to_string()).collect(),
+            share: share(names.len()),
+        })
+        .collect();
+
+    let resolvers_with_outliers =
+        answers.iter().filter(|(_, ips)| ips.iter().any(|ip| outliers.iter().any(|o| o.ip == *ip))).count();
+
+    PoisoningReport {
+        domain: domain.to_string(),
+        majority_answer,
+        outliers,
+        poisoning_likelihood: share(resolvers_with_outliers),
+        resolvers_answered: answered,
+    }
+}
+
+/// Block a CDN typically rotates edge addresses within
+fn cdn_network(ip: IpAddr) -> ipnetwork::IpNetwork {
+    subnet_of(ip, if ip.is_ipv4() { 24 } else { 48 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_answers() {
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        let honest = vec![ip("93.184.216.34")];
+        let mut answers: Vec<(String, Vec<IpAddr>)> = ["Cloudflare", "Google", "Quad9", "OpenDNS", "Level3"]
+            .iter()
+            .map(|name| (name.to_string(), honest.clone()))
+            .collect();
+        answers.push(("Rogue".to_string(), vec![ip("203.0.113.66")]));
+
+        let report = compare_answers("example.com", &answers);
+        assert_eq!(report.majority_answer, honest);
+        assert_eq!(report.outliers.len(), 1);
+        assert_eq!(report.outliers[0].ip, ip("203.0.113.66"));
+        assert_eq!(report.outliers[0].resolvers, vec!["Rogue"]);
+        assert!((report.poisoning_likelihood - 1.0 / 6.0).abs() < 1e-9);
+
+        // A neighbouring CDN edge address is not an outlier
+        answers[5].1 = vec![ip("93.184.216.35")];
+        let report = compare_answers("example.com", &answers);
+        assert!(report.outliers.is_empty());
+        assert_eq!(report.poisoning_likelihood, 0.0);
+    }
+}
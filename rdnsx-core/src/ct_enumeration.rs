@@ -0,0 +1,219 @@
+//! Passive subdomain discovery from certificate transparency logs
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures::StreamExt;
+use serde::Serialize;
+use tracing::{debug, info};
+
+use crate::concurrency::RateLimiter;
+use crate::config::{DnsxOptions, DEFAULT_CONCURRENCY, DEFAULT_HTTP_TIMEOUT};
+use crate::error::{DnsxError, Result};
+use crate::resolver::ResolverPool;
+use crate::utils::normalize_domain;
+use crate::vhost::{normalize_name, CrtShEntry, CRT_SH_URL};
+
+/// A certificate logged for the searched domain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CtCertificate {
+    /// Distinguished name of the issuing CA
+    pub issuer: String,
+    pub not_before: Option<DateTime<Utc>>,
+    pub not_after: Option<DateTime<Utc>>,
+    /// Hex serial number as logged
+    pub serial: String,
+}
+
+/// Subdomains and certificates found in certificate transparency logs
+#[derive(Debug, Clone, Serialize)]
+pub struct CtEnumerationResult {
+    pub domain: String,
+    /// Unique names under the domain, sorted, with `*.` wildcards reduced to their base name
+    pub subdomains: Vec<String>,
+    /// Unique certificates, by issuer and serial (precertificates and their final certificates count once)
+    pub certificates: Vec<CtCertificate>,
+    pub total_discovered: usize,
+    /// Addresses of the subdomains that resolve; empty unless resolution is enabled
+    pub resolved: BTreeMap<String, Vec<IpAddr>>,
+}
+
+/// Searches crt.sh for certificates issued under a domain
+pub struct CtEnumerator {
+    resolver_pool: Arc<ResolverPool>,
+    ct_url: String,
+    http_timeout: Duration,
+    rate_limiter: Option<RateLimiter>,
+    concurrency: usize,
+    resolve: bool,
+}
+
+impl CtEnumerator {
+    /// Create an enumerator that searches crt.sh without resolving the names found
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        Self {
+            resolver_pool,
+            ct_url: CRT_SH_URL.to_string(),
+            http_timeout: DEFAULT_HTTP_TIMEOUT,
+            rate_limiter: None,
+            concurrency: DEFAULT_CONCURRENCY,
+            resolve: false,
+        }
+    }
+
+    /// Take the HTTP timeout, rate limit and concurrency from `options`
+    pub fn with_options(mut self, options: &DnsxOptions) -> Self {
+        self.http_timeout = options.http_timeout;
+        self.rate_limiter = (options.rate_limit > 0)
+            .then(|| RateLimiter::with_burst(options.rate_limit, options.rate_limit_burst_multiplier));
+        self.concurrency = options.concurrency.max(1);
+        self
+    }
+
+    /// Search a crt.sh-compatible mirror instead of crt.sh itself
+    pub fn with_ct_url(mut self, url: impl Into<String>) -> Self {
+        self.ct_url = url.into();
+        self
+    }
+
+    /// Resolve every discovered name and report the addresses of those that exist
+    pub fn with_resolution(mut self, resolve: bool) -> Self {
+        self.resolve = resolve;
+        self
+    }
+
+    /// Names and certificates logged for `domain` and its subdomains
+    pub async fn enumerate(&self, domain: &str) -> Result<CtEnumerationResult> {
+        let domain = normalize_domain(domain);
+        info!("Searching certificate transparency logs for {}", domain);
+
+        let body = self.search(&domain).await?;
+        let mut result = parse_crt_sh(&domain, &body)?;
+        debug!("{} names on {} certificates for {}", result.total_discovered, result.certificates.len(), domain);
+
+        if self.resolve {
+            result.resolved = futures::stream::iter(&result.subdomains)
+                .map(|name| async move {
+                    if let Some(limiter) = &self.rate_limiter {
+                        limiter.wait().await;
+                    }
+                    match self.resolver_pool.resolve_to_ips(name).await {
+                        Ok(ips) if !ips.is_empty() => Some((name.clone(), ips)),
+                        Ok(_) => None,
+                        Err(e) => {
+                            debug!("Failed to resolve {}: {}", name, e);
+                            None
+                        }
+                    }
+                })
+                .buffer_unordered(self.concurrency)
+                .filter_map(|resolved| async move { resolved })
+                .collect()
+                .await;
+            info!("{} of {} CT names for {} resolve", result.resolved.len(), result.total_discovered, domain);
+        }
+        Ok(result)
+    }
+
+    async fn search(&self, domain: &str) -> Result<String> {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.wait().await;
+        }
+
+        let http = reqwest::Client::builder()
+            .timeout(self.http_timeout)
+            .user_agent(concat!("rdnsx/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| DnsxError::Other(format!("Failed to create HTTP client: {}", e)))?;
+        let response = http
+            .get(&self.ct_url)
+            .query(&[("q", format!("%.{}", domain).as_str()), ("output", "json")])
+            .send()
+            .await
+            .map_err(|e| DnsxError::network(&self.ct_url, format!("Certificate transparency search failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(DnsxError::network(
+                &self.ct_url,
+                format!("Certificate transparency search returned HTTP {}", response.status()),
+            ));
+        }
+
+        response
+            .text()
+            .await
+            .map_err(|e| DnsxError::network(&self.ct_url, format!("Failed to read search results: {}", e)))
+    }
+}
+
+/// Names under `domain` and the distinct certificates in a crt.sh JSON response
+fn parse_crt_sh(domain: &str, body: &str) -> Result<CtEnumerationResult> {
+    let entries: Vec<CrtShEntry> = serde_json::from_str(body)
+        .map_err(|e| DnsxError::Serialization(format!("Invalid certificate transparency response: {}", e)))?;
+
+    let suffix = format!(".{}", domain);
+    let subdomains: BTreeSet<String> = entries
+        .iter()
+        .flat_map(|entry| entry.name_value.lines().chain(entry.common_name.as_deref()))
+        .filter_map(normalize_name)
+        .filter(|name| name == domain || name.ends_with(&suffix))
+        .collect();
+
+    let mut seen = HashSet::new();
+    let certificates = entries
+        .iter()
+        .map(|entry| CtCertificate {
+            issuer: entry.issuer_name.clone().unwrap_or_default(),
+            not_before: entry.not_before.as_deref().and_then(parse_ct_time),
+            not_after: entry.not_after.as_deref().and_then(parse_ct_time),
+            serial: entry.serial_number.clone().unwrap_or_default(),
+        })
+        .filter(|certificate| seen.insert((certificate.issuer.clone(), certificate.serial.clone())))
+        .collect();
+
+    let subdomains: Vec<String> = subdomains.into_iter().collect();
+    Ok(CtEnumerationResult {
+        domain: domain.to_string(),
+        total_discovered: subdomains.len(),
+        subdomains,
+        certificates,
+        resolved: BTreeMap::new(),
+    })
+}
+
+/// crt.sh validity timestamps are UTC without a zone suffix
+fn parse_ct_time(value: &str) -> Option<DateTime<Utc>> {
+    value.parse::<NaiveDateTime>().ok().map(|time| time.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_crt_sh() {
+        let body = r#"[
+            {"issuer_name": "C=US, O=Let's Encrypt, CN=R3", "common_name": "www.example.com",
+             "name_value": "www.example.com\n*.API.example.com\nexample.com", "serial_number": "03a1",
+             "not_before": "2024-01-15T00:00:00", "not_after": "2024-04-14T23:59:59"},
+            {"issuer_name": "C=US, O=Let's Encrypt, CN=R3", "common_name": "www.example.com",
+             "name_value": "www.example.com", "serial_number": "03a1",
+             "not_before": "2024-01-15T00:00:00", "not_after": "2024-04-14T23:59:59"},
+            {"issuer_name": "C=US, O=DigiCert Inc, CN=DigiCert TLS RSA SHA256 2020 CA1",
+             "name_value": "mail.example.com\nexample.net\nnotexample.com", "serial_number": "0f22"}
+        ]"#;
+
+        let result = parse_crt_sh("example.com", body).unwrap();
+        assert_eq!(result.subdomains, vec!["api.example.com", "example.com", "mail.example.com", "www.example.com"]);
+        assert_eq!(result.total_discovered, 4);
+        assert_eq!(result.certificates.len(), 2);
+        assert_eq!(result.certificates[0].serial, "03a1");
+        assert_eq!(result.certificates[0].not_after.unwrap().to_rfc3339(), "2024-04-14T23:59:59+00:00");
+        assert!(result.certificates[1].not_before.is_none());
+        assert!(result.resolved.is_empty());
+
+        assert!(parse_crt_sh("example.com", "<html>rate limited</html>").is_err());
+    }
+}
@@ -0,0 +1,445 @@
+//! DANE (RFC 6698) validation of TLSA records against live TLS certificates
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ring::digest;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, RootCertStore, SignatureScheme};
+use serde::Serialize;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tracing::{debug, info};
+
+use crate::email_security::der_element;
+use crate::error::{DnsxError, Result};
+use crate::query::parse_rdata;
+use crate::resolver::ResolverPool;
+use crate::types::{RecordType, RecordValue};
+
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const OID: u8 = 0x06;
+const CONTEXT_0: u8 = 0xa0;
+const CONTEXT_3: u8 = 0xa3;
+/// id-at-commonName (2.5.4.3)
+const COMMON_NAME_OID: &[u8] = &[0x55, 0x04, 0x03];
+
+/// A TLSA record as published in DNS
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TlsaRecord {
+    /// 0 PKIX-TA, 1 PKIX-EE, 2 DANE-TA, 3 DANE-EE
+    pub cert_usage: u8,
+    /// 0 full certificate, 1 SubjectPublicKeyInfo
+    pub selector: u8,
+    /// 0 exact match, 1 SHA-256, 2 SHA-512
+    pub matching_type: u8,
+    /// Association data, hex encoded
+    pub cert_data: String,
+}
+
+/// A certificate presented by the server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CertInfo {
+    /// Position in the chain, 0 being the end-entity certificate
+    pub depth: usize,
+    /// Subject common name, empty when the certificate has none
+    pub subject: String,
+    /// Issuer common name, empty when the issuer has none
+    pub issuer: String,
+    /// SHA-256 of the whole DER certificate, hex encoded
+    pub sha256: String,
+    /// SHA-256 of the SubjectPublicKeyInfo, hex encoded
+    pub spki_sha256: String,
+}
+
+/// Outcome of checking one TLSA record
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TlsaValidation {
+    pub record: TlsaRecord,
+    /// Chain depth of the certificate the record matched
+    pub matched_depth: Option<usize>,
+    pub valid: bool,
+    /// Why the record does or does not validate
+    pub reason: String,
+}
+
+/// TLSA records of an HTTPS endpoint checked against the certificates it serves
+#[derive(Debug, Clone, Serialize)]
+pub struct DaneHttpsResult {
+    pub domain: String,
+    pub tlsa_records: Vec<TlsaRecord>,
+    pub certificate_chain: Vec<CertInfo>,
+    /// Whether the chain validates against the web PKI roots (required by usages 0 and 1)
+    pub pkix_valid: bool,
+    pub validation_results: Vec<TlsaValidation>,
+}
+
+impl DaneHttpsResult {
+    /// At least one TLSA record matches the served certificates
+    pub fn is_valid(&self) -> bool {
+        self.validation_results.iter().any(|v| v.valid)
+    }
+}
+
+/// Validates TLSA records against the certificates a server presents
+///
+/// The TLSA lookup goes through the configured resolvers and is not DNSSEC
+/// validated, so a match shows the records and certificates agree, not that
+/// the records are authentic.
+pub struct DaneTlsaValidator {
+    resolver_pool: Arc<ResolverPool>,
+    timeout: Duration,
+}
+
+impl DaneTlsaValidator {
+    /// Create a new validator
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        Self {
+            resolver_pool,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Validate the `_443._tcp` TLSA records of `domain` against its HTTPS certificate chain
+    pub async fn validate_https(&self, domain: &str) -> Result<DaneHttpsResult> {
+        let domain = domain.trim().trim_end_matches('.').to_lowercase();
+        info!("Validating DANE TLSA records for: {}", domain);
+
+        let tlsa_name = format!("_443._tcp.{}", domain);
+        let tlsa_records: Vec<TlsaRecord> = match self.resolver_pool.query(&tlsa_name, RecordType::Tlsa).await {
+            Ok((lookup, _)) => lookup
+                .iter()
+                .filter_map(|rdata| match parse_rdata(rdata).ok()? {
+                    RecordValue::Tlsa { cert_usage, selector, matching_type, cert_data } => Some(TlsaRecord {
+                        cert_usage,
+                        selector,
+                        matching_type,
+                        cert_data: hex::encode(cert_data),
+                    }),
+                    _ => None,
+                })
+                .collect(),
+            Err(e) => {
+                debug!("TLSA lookup for {} failed: {}", tlsa_name, e);
+                Vec::new()
+            }
+        };
+
+        let (chain, pkix_valid) = self.fetch_chain(&domain).await?;
+        let certificate_chain: Vec<CertInfo> =
+            chain.iter().enumerate().map(|(depth, cert)| cert_info(depth, cert)).collect();
+        let validation_results =
+            tlsa_records.iter().map(|record| validate_tlsa(record, &chain, pkix_valid)).collect();
+
+        Ok(DaneHttpsResult {
+            domain,
+            tlsa_records,
+            certificate_chain,
+            pkix_valid,
+            validation_results,
+        })
+    }
+
+    /// Certificates served on port 443, and whether they pass web PKI validation
+    async fn fetch_chain(&self, domain: &str) -> Result<(Vec<CertificateDer<'static>>, bool)> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let roots = Arc::new(webpki_roots::TLS_SERVER_ROOTS.iter().cloned().collect::<RootCertStore>());
+        let pkix = WebPkiServerVerifier::builder_with_provider(roots, provider.clone())
+            .build()
+            .map_err(|e| DnsxError::Other(format!("Failed to build certificate verifier: {}", e)))?;
+        let verifier = Arc::new(RecordingVerifier {
+            pkix,
+            provider: provider.clone(),
+            pkix_valid: Mutex::new(false),
+        });
+
+        let config = rustls::ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|e| DnsxError::Other(format!("Failed to configure TLS: {}", e)))?
+            .dangerous()
+            .with_custom_certificate_verifier(verifier.clone())
+            .with_no_client_auth();
+
+        let server_name = ServerName::try_from(domain.to_string())
+            .map_err(|e| DnsxError::invalid_input(format!("Invalid TLS server name {}: {}", domain, e)))?;
+        let endpoint = format!("{}:443", domain);
+
+        let handshake = async {
+            let tcp = TcpStream::connect(&endpoint).await?;
+            TlsConnector::from(Arc::new(config)).connect(server_name, tcp).await
+        };
+        let stream = tokio::time::timeout(self.timeout, handshake)
+            .await
+            .map_err(|_| DnsxError::network(&endpoint, format!("TLS handshake timed out after {:?}", self.timeout)))?
+            .map_err(|e| DnsxError::network(&endpoint, format!("TLS handshake failed: {}", e)))?;
+
+        let chain = stream.get_ref().1.peer_certificates().map(<[_]>::to_vec).unwrap_or_default();
+        let pkix_valid = *verifier.pkix_valid.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok((chain, pkix_valid))
+    }
+}
+
+/// Accepts any certificate so the chain can be inspected, noting whether web PKI validation passed
+#[derive(Debug)]
+struct RecordingVerifier {
+    pkix: Arc<WebPkiServerVerifier>,
+    provider: Arc<CryptoProvider>,
+    pkix_valid: Mutex<bool>,
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        let pkix = self.pkix.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now);
+        if let Err(e) = &pkix {
+            debug!("Web PKI validation failed for {:?}: {}", server_name, e);
+        }
+        *self.pkix_valid.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = pkix.is_ok();
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Check one TLSA record against a certificate chain (end-entity first)
+pub(crate) fn validate_tlsa(record: &TlsaRecord, chain: &[CertificateDer<'_>], pkix_valid: bool) -> TlsaValidation {
+    let result = |matched_depth, valid, reason: &str| TlsaValidation {
+        record: record.clone(),
+        matched_depth,
+        valid,
+        reason: reason.to_string(),
+    };
+
+    if record.cert_usage > 3 || record.selector > 1 || record.matching_type > 2 {
+        return result(None, false, "unknown usage, selector or matching type");
+    }
+    let Ok(expected) = hex::decode(&record.cert_data) else {
+        return result(None, false, "association data is not valid hex");
+    };
+
+    // End-entity usages match the leaf only; trust anchor usages match a CA above it
+    let mut candidates = match record.cert_usage {
+        1 | 3 => 0..chain.len().min(1),
+        _ => chain.len().min(1)..chain.len(),
+    };
+    let matched_depth = candidates.find(|&depth| {
+        association_data(&chain[depth], record.selector, record.matching_type).is_some_and(|data| data == expected)
+    });
+
+    match (matched_depth, record.cert_usage) {
+        (None, 1 | 3) => result(None, false, "does not match the end-entity certificate"),
+        (None, _) => result(None, false, "does not match any CA certificate in the chain"),
+        (Some(depth), 0 | 1) if !pkix_valid => {
+            result(Some(depth), false, "matches, but the chain fails web PKI validation required by PKIX usages")
+        }
+        (Some(depth), _) => result(Some(depth), true, "matches"),
+    }
+}
+
+/// Data a TLSA record with `selector` and `matching_type` would carry for `cert`
+fn association_data(cert: &[u8], selector: u8, matching_type: u8) -> Option<Vec<u8>> {
+    let selected = match selector {
+        0 => cert,
+        1 => subject_public_key_info(cert)?,
+        _ => return None,
+    };
+    match matching_type {
+        0 => Some(selected.to_vec()),
+        1 => Some(digest::digest(&digest::SHA256, selected).as_ref().to_vec()),
+        2 => Some(digest::digest(&digest::SHA512, selected).as_ref().to_vec()),
+        _ => None,
+    }
+}
+
+fn cert_info(depth: usize, cert: &[u8]) -> CertInfo {
+    let fields = tbs_fields(cert);
+    CertInfo {
+        depth,
+        subject: fields.and_then(|f| common_name(f.subject)).unwrap_or_default(),
+        issuer: fields.and_then(|f| common_name(f.issuer)).unwrap_or_default(),
+        sha256: hex::encode(digest::digest(&digest::SHA256, cert)),
+        spki_sha256: subject_public_key_info(cert)
+            .map(|spki| hex::encode(digest::digest(&digest::SHA256, spki)))
+            .unwrap_or_default(),
+    }
+}
+
+/// Fields of a TBSCertificate needed here, each as contents of the DER element
+#[derive(Clone, Copy)]
+pub(crate) struct TbsFields<'a> {
+    pub(crate) issuer: &'a [u8],
+    pub(crate) subject: &'a [u8],
+    /// Whole SubjectPublicKeyInfo element, tag and length included
+    pub(crate) spki: &'a [u8],
+    /// The `Extensions` SEQUENCE of a v3 certificate
+    pub(crate) extensions: Option<&'a [u8]>,
+}
+
+pub(crate) fn tbs_fields(cert: &[u8]) -> Option<TbsFields<'_>> {
+    let (tag, certificate, _) = der_element(cert)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+    let (tag, tbs, _) = der_element(certificate)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+
+    let mut rest = tbs;
+    if rest.first() == Some(&CONTEXT_0) {
+        rest = der_element(rest)?.2; // version
+    }
+    let (_, _, rest) = der_element(rest)?; // serialNumber
+    let (_, _, rest) = der_element(rest)?; // signature
+    let (_, issuer, rest) = der_element(rest)?;
+    let (_, _, rest) = der_element(rest)?; // validity
+    let (_, subject, spki_start) = der_element(rest)?;
+    let (tag, _, after) = der_element(spki_start)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+
+    // issuerUniqueID [1] and subjectUniqueID [2] may come before extensions [3]
+    let mut extensions = None;
+    let mut rest = after;
+    while let Some((tag, contents, next)) = der_element(rest) {
+        if tag == CONTEXT_3 {
+            extensions = der_element(contents).filter(|(tag, _, _)| *tag == SEQUENCE).map(|(_, seq, _)| seq);
+            break;
+        }
+        rest = next;
+    }
+
+    Some(TbsFields {
+        issuer,
+        subject,
+        spki: &spki_start[..spki_start.len() - after.len()],
+        extensions,
+    })
+}
+
+fn subject_public_key_info(cert: &[u8]) -> Option<&[u8]> {
+    tbs_fields(cert).map(|fields| fields.spki)
+}
+
+/// First commonName attribute of an X.501 Name
+fn common_name(name: &[u8]) -> Option<String> {
+    let mut rdns = name;
+    while !rdns.is_empty() {
+        let (tag, rdn, rest) = der_element(rdns)?;
+        rdns = rest;
+        if tag != SET {
+            continue;
+        }
+        let (_, attribute, _) = der_element(rdn)?;
+        let (tag, oid, value) = der_element(attribute)?;
+        if tag == OID && oid == COMMON_NAME_OID {
+            let (_, text, _) = der_element(value)?;
+            return Some(String::from_utf8_lossy(text).into_owned());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// DER element with a short-form length
+    fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag, contents.len() as u8];
+        out.extend_from_slice(contents);
+        out
+    }
+
+    fn name(cn: &str) -> Vec<u8> {
+        let attribute = [der(OID, COMMON_NAME_OID), der(0x0c, cn.as_bytes())].concat();
+        der(SEQUENCE, &der(SET, &der(SEQUENCE, &attribute)))
+    }
+
+    /// Structurally valid (unsigned) certificate with the given names and key bytes
+    fn certificate(subject: &str, issuer: &str, key: &[u8]) -> Vec<u8> {
+        let spki = der(SEQUENCE, &[der(SEQUENCE, &der(OID, &[0x2b, 0x65, 0x70])), der(0x03, key)].concat());
+        let tbs = [
+            der(CONTEXT_0, &der(0x02, &[2])),
+            der(0x02, &[1]),
+            der(SEQUENCE, &der(OID, &[0x2b, 0x65, 0x70])),
+            name(issuer),
+            der(SEQUENCE, &[]),
+            name(subject),
+            spki,
+        ]
+        .concat();
+        der(SEQUENCE, &[der(SEQUENCE, &tbs), der(SEQUENCE, &der(OID, &[0x2b, 0x65, 0x70])), der(0x03, &[0])].concat())
+    }
+
+    fn tlsa(cert_usage: u8, selector: u8, matching_type: u8, data: &[u8]) -> TlsaRecord {
+        TlsaRecord { cert_usage, selector, matching_type, cert_data: hex::encode(data) }
+    }
+
+    #[test]
+    fn test_cert_info() {
+        let leaf = certificate("www.example.com", "Example CA", &[1, 2, 3]);
+        let info = cert_info(0, &leaf);
+        assert_eq!(info.subject, "www.example.com");
+        assert_eq!(info.issuer, "Example CA");
+        assert_eq!(info.sha256, hex::encode(digest::digest(&digest::SHA256, &leaf)));
+        assert_eq!(subject_public_key_info(&leaf).unwrap()[0], SEQUENCE);
+    }
+
+    #[test]
+    fn test_validate_tlsa() {
+        let leaf = certificate("www.example.com", "Example CA", &[1, 2, 3]);
+        let ca = certificate("Example CA", "Example Root", &[4, 5, 6]);
+        let chain = vec![CertificateDer::from(leaf.clone()), CertificateDer::from(ca.clone())];
+
+        let leaf_spki_sha256 = digest::digest(&digest::SHA256, subject_public_key_info(&leaf).unwrap());
+        let dane_ee = validate_tlsa(&tlsa(3, 1, 1, leaf_spki_sha256.as_ref()), &chain, false);
+        assert!(dane_ee.valid);
+        assert_eq!(dane_ee.matched_depth, Some(0));
+
+        let ca_sha512 = digest::digest(&digest::SHA512, &ca);
+        let dane_ta = validate_tlsa(&tlsa(2, 0, 2, ca_sha512.as_ref()), &chain, false);
+        assert_eq!(dane_ta.matched_depth, Some(1));
+        assert!(dane_ta.valid);
+
+        // PKIX usages also need a publicly trusted chain
+        let pkix_ee = tlsa(1, 0, 0, &leaf);
+        assert!(!validate_tlsa(&pkix_ee, &chain, false).valid);
+        assert!(validate_tlsa(&pkix_ee, &chain, true).valid);
+
+        // A leaf hash under a trust anchor usage does not match
+        assert!(!validate_tlsa(&tlsa(2, 1, 1, leaf_spki_sha256.as_ref()), &chain, true).valid);
+        assert!(!validate_tlsa(&tlsa(3, 1, 1, &[0; 32]), &chain, true).valid);
+    }
+}
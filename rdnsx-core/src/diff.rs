@@ -0,0 +1,152 @@
+//! Comparison of DNS record snapshots
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::types::{DnsRecord, RecordType};
+
+/// A record whose value changed between snapshots
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordChange {
+    pub before: DnsRecord,
+    pub after: DnsRecord,
+}
+
+/// A single change between snapshots, e.g. for sending to a webhook
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum DnsChange {
+    Added { record: DnsRecord },
+    Removed { record: DnsRecord },
+    Modified { before: DnsRecord, after: DnsRecord },
+}
+
+impl DnsChange {
+    /// Domain of the changed record
+    pub fn domain(&self) -> &str {
+        match self {
+            DnsChange::Added { record } | DnsChange::Removed { record } => &record.domain,
+            DnsChange::Modified { after, .. } => &after.domain,
+        }
+    }
+}
+
+/// Differences between two sets of DNS records
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RecordSetDiff {
+    /// Records for a (domain, type) that did not exist before or gained extra values
+    pub added: Vec<DnsRecord>,
+    /// Records for a (domain, type) that no longer exist or lost values
+    pub removed: Vec<DnsRecord>,
+    /// Same domain and type with a different value
+    pub modified: Vec<RecordChange>,
+}
+
+impl RecordSetDiff {
+    /// Compare two snapshots keyed on domain and record type
+    ///
+    /// When a (domain, type) pair changes values, old and new values are paired up
+    /// as modifications; any surplus on either side is reported as added or removed.
+    /// TTL-only changes are ignored.
+    pub fn compute(before: &[DnsRecord], after: &[DnsRecord]) -> Self {
+        let before_groups = group(before);
+        let after_groups = group(after);
+        let mut diff = Self::default();
+
+        for (key, old_records) in &before_groups {
+            let Some(new_records) = after_groups.get(key) else {
+                diff.removed.extend(old_records.iter().map(|r| (*r).clone()));
+                continue;
+            };
+
+            let old_values: BTreeSet<String> = old_records.iter().map(|r| r.value.to_string()).collect();
+            let new_values: BTreeSet<String> = new_records.iter().map(|r| r.value.to_string()).collect();
+
+            let mut gone = old_records.iter().filter(|r| !new_values.contains(&r.value.to_string()));
+            let mut fresh = new_records.iter().filter(|r| !old_values.contains(&r.value.to_string()));
+
+            loop {
+                match (gone.next(), fresh.next()) {
+                    (Some(old), Some(new)) => diff.modified.push(RecordChange {
+                        before: (*old).clone(),
+                        after: (*new).clone(),
+                    }),
+                    (Some(old), None) => diff.removed.push((*old).clone()),
+                    (None, Some(new)) => diff.added.push((*new).clone()),
+                    (None, None) => break,
+                }
+            }
+        }
+
+        for (key, new_records) in &after_groups {
+            if !before_groups.contains_key(key) {
+                diff.added.extend(new_records.iter().map(|r| (*r).clone()));
+            }
+        }
+
+        diff
+    }
+
+    /// Every change, additions first, then removals, then modifications
+    pub fn changes(&self) -> Vec<DnsChange> {
+        let added = self.added.iter().map(|record| DnsChange::Added { record: record.clone() });
+        let removed = self.removed.iter().map(|record| DnsChange::Removed { record: record.clone() });
+        let modified = self.modified.iter().map(|change| DnsChange::Modified {
+            before: change.before.clone(),
+            after: change.after.clone(),
+        });
+        added.chain(removed).chain(modified).collect()
+    }
+
+    /// Whether the snapshots match
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Group records by normalized domain and type, dropping duplicate values
+fn group(records: &[DnsRecord]) -> BTreeMap<(String, RecordType), Vec<&DnsRecord>> {
+    let mut groups: BTreeMap<(String, RecordType), Vec<&DnsRecord>> = BTreeMap::new();
+    for record in records {
+        let entry = groups
+            .entry((record.domain.trim_end_matches('.').to_lowercase(), record.record_type))
+            .or_default();
+        if !entry.iter().any(|r| r.value.to_string() == record.value.to_string()) {
+            entry.push(record);
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{RecordValue, ResponseCode};
+
+    fn a(domain: &str, ip: &str) -> DnsRecord {
+        DnsRecord::new(
+            domain.to_string(),
+            RecordType::A,
+            RecordValue::Ip(ip.parse().unwrap()),
+            300,
+            ResponseCode::NoError,
+            "8.8.8.8:53".to_string(),
+            1.0,
+        )
+    }
+
+    #[test]
+    fn test_record_set_diff() {
+        let before = vec![a("www.example.com", "192.0.2.1"), a("old.example.com", "192.0.2.2"), a("same.example.com", "192.0.2.3")];
+        let after = vec![a("www.example.com", "192.0.2.10"), a("new.example.com", "192.0.2.4"), a("same.example.com", "192.0.2.3")];
+
+        let diff = RecordSetDiff::compute(&before, &after);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].domain, "new.example.com");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].domain, "old.example.com");
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].after.value.to_string(), "192.0.2.10");
+    }
+}
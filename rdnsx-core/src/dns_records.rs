@@ -1,12 +1,16 @@
 //! DNS record structures and implementations
 
-use std::time::SystemTime;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
+use crate::utils::normalize_domain;
 use crate::{RecordType, RecordValue, ResponseCode};
 
 /// DNS record
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct DnsRecord {
     /// Domain name queried
     pub domain: String,
@@ -20,14 +24,14 @@ pub struct DnsRecord {
     pub response_code: ResponseCode,
     /// Resolver used
     pub resolver: String,
-    /// Query timestamp
-    pub timestamp: SystemTime,
+    /// Query timestamp (serialized as RFC 3339)
+    pub timestamp: DateTime<Utc>,
     /// Query time in milliseconds
     pub query_time_ms: f64,
 }
 
 impl DnsRecord {
-    /// Create a new DNS record
+    /// Create a new DNS record, normalizing `domain` (lowercase, no trailing dot)
     pub fn new(
         domain: String,
         record_type: RecordType,
@@ -38,16 +42,191 @@ impl DnsRecord {
         query_time_ms: f64,
     ) -> Self {
         Self {
-            domain,
+            domain: normalize_domain(&domain),
             record_type,
             value,
             ttl,
             response_code,
             resolver,
-            timestamp: SystemTime::now(),
+            timestamp: Utc::now(),
             query_time_ms,
         }
     }
+
+    /// Time elapsed since the record was queried
+    pub fn age(&self) -> chrono::Duration {
+        Utc::now() - self.timestamp
+    }
+
+    /// JSON Schema (draft 2020-12) of the serialized record
+    pub fn json_schema() -> Value {
+        schemars::schema_for!(DnsRecord).to_value()
+    }
+
+    /// Avro schema for schema-enforced pipelines (e.g. Kafka Schema Registry)
+    ///
+    /// Derived from the JSON Schema. `value` is carried as its rendered string,
+    /// the same form the exporters store, since Avro has no untagged unions.
+    pub fn avro_schema() -> Value {
+        let fields: Vec<Value> = schema_fields(&Self::json_schema())
+            .into_iter()
+            .map(|field| {
+                let avro_type = match field.kind {
+                    FieldKind::String | FieldKind::DateTime | FieldKind::Mixed => json!("string"),
+                    FieldKind::Integer => json!("long"),
+                    FieldKind::Number => json!("double"),
+                    FieldKind::Enum { name, symbols } => json!({ "type": "enum", "name": name, "symbols": symbols }),
+                };
+                let mut avro_field = json!({ "name": field.name, "type": avro_type });
+                if let Some(doc) = field.description {
+                    avro_field["doc"] = json!(doc);
+                }
+                avro_field
+            })
+            .collect();
+
+        json!({
+            "type": "record",
+            "name": "DnsRecord",
+            "namespace": "io.rdnsx",
+            "fields": fields,
+        })
+    }
+
+    /// The record as a master-format zone file line (`name. TTL IN TYPE RDATA`)
+    pub fn to_zone_file_line(&self) -> String {
+        let rdata = zone_rdata(self.record_type, &self.value);
+        format!("{} {} IN {} {}", fqdn(&self.domain), self.ttl, self.record_type, rdata)
+    }
+}
+
+/// Name with exactly one trailing dot
+fn fqdn(name: &str) -> String {
+    format!("{}.", name.trim_end_matches('.'))
+}
+
+/// Presentation-format RDATA, with names made absolute and binary fields encoded
+fn zone_rdata(record_type: RecordType, value: &RecordValue) -> String {
+    match value {
+        RecordValue::Domain(name) => fqdn(name),
+        RecordValue::Text(text) if record_type == RecordType::Txt => quote_txt(text),
+        RecordValue::Mx { priority, exchange } => format!("{} {}", priority, fqdn(exchange)),
+        RecordValue::Srv { priority, weight, port, target } => {
+            format!("{} {} {} {}", priority, weight, port, fqdn(target))
+        }
+        RecordValue::Soa { mname, rname, serial, refresh, retry, expire, minimum } => format!(
+            "{} {} {} {} {} {} {}",
+            fqdn(mname),
+            fqdn(rname),
+            serial,
+            refresh,
+            retry,
+            expire,
+            minimum
+        ),
+        RecordValue::Caa { flags, tag, value } => format!("{} {} \"{}\"", flags, tag, escape_quoted(value)),
+        RecordValue::Dnskey { flags, protocol, algorithm, public_key } => {
+            let public_key = base64::engine::general_purpose::STANDARD.encode(public_key);
+            format!("{} {} {} {}", flags, protocol, algorithm, public_key)
+        }
+        RecordValue::Ds { key_tag, algorithm, digest_type, digest } => {
+            format!("{} {} {} {}", key_tag, algorithm, digest_type, hex::encode_upper(digest))
+        }
+        RecordValue::Tlsa { cert_usage, selector, matching_type, cert_data } => {
+            format!("{} {} {} {}", cert_usage, selector, matching_type, hex::encode_upper(cert_data))
+        }
+        RecordValue::Sshfp { algorithm, fingerprint_type, fingerprint } => {
+            format!("{} {} {}", algorithm, fingerprint_type, hex::encode_upper(fingerprint))
+        }
+        RecordValue::Hinfo { cpu, os } => format!("\"{}\" \"{}\"", escape_quoted(cpu), escape_quoted(os)),
+        other => other.to_string(),
+    }
+}
+
+/// TXT data as quoted character-strings of at most 255 bytes each
+fn quote_txt(text: &str) -> String {
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+    for c in text.chars() {
+        if chunk.len() + c.len_utf8() > 255 {
+            chunks.push(std::mem::take(&mut chunk));
+        }
+        chunk.push(c);
+    }
+    chunks.push(chunk);
+
+    chunks
+        .iter()
+        .map(|chunk| format!("\"{}\"", escape_quoted(chunk)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape_quoted(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Shape of one top-level field in the record's JSON Schema
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FieldKind {
+    String,
+    /// RFC 3339 string
+    DateTime,
+    Integer,
+    Number,
+    Enum { name: String, symbols: Vec<String> },
+    /// Union of several types (the untagged record value)
+    Mixed,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SchemaField {
+    pub name: String,
+    pub kind: FieldKind,
+    pub description: Option<String>,
+}
+
+/// Top-level fields of a struct schema in declaration order
+pub(crate) fn schema_fields(schema: &Value) -> Vec<SchemaField> {
+    let defs = &schema["$defs"];
+    let required = schema["required"].as_array().cloned().unwrap_or_default();
+
+    required
+        .iter()
+        .filter_map(Value::as_str)
+        .map(|name| {
+            let property = &schema["properties"][name];
+            SchemaField {
+                name: name.to_string(),
+                kind: field_kind(property, defs),
+                description: property["description"].as_str().map(str::to_string),
+            }
+        })
+        .collect()
+}
+
+fn field_kind(property: &Value, defs: &Value) -> FieldKind {
+    if let Some(name) = property["$ref"].as_str().and_then(|r| r.strip_prefix("#/$defs/")) {
+        let def = &defs[name];
+        // Unit enums render as `enum`, or as `oneOf` consts when variants are documented
+        let symbols: Option<Vec<String>> = match (def["enum"].as_array(), def["oneOf"].as_array()) {
+            (Some(values), _) => values.iter().map(|v| v.as_str().map(str::to_string)).collect(),
+            (None, Some(variants)) => variants.iter().map(|v| v["const"].as_str().map(str::to_string)).collect(),
+            _ => None,
+        };
+        return match symbols {
+            Some(symbols) => FieldKind::Enum { name: name.to_string(), symbols },
+            None => field_kind(def, defs),
+        };
+    }
+
+    match (property["type"].as_str(), property["format"].as_str()) {
+        (Some("string"), Some("date-time")) => FieldKind::DateTime,
+        (Some("string"), _) => FieldKind::String,
+        (Some("integer"), _) => FieldKind::Integer,
+        (Some("number"), _) => FieldKind::Number,
+        _ => FieldKind::Mixed,
+    }
 }
 
 impl std::fmt::Display for DnsRecord {
@@ -59,4 +238,118 @@ impl std::fmt::Display for DnsRecord {
             self.value.to_string()
         )
     }
-}
\ No newline at end of file
+}
+
+/// Longest presentation-format domain name, without the trailing dot (RFC 1035)
+pub const MAX_DOMAIN_LENGTH: usize = 253;
+
+/// Largest valid TTL; values with the top bit set are treated as zero (RFC 2181 section 8)
+pub const MAX_TTL: u32 = i32::MAX as u32;
+
+/// One problem found in a record by [`DnsRecordValidator`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationError {
+    /// Record field that failed the check
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl ValidationError {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self { field, message: message.into() }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Checks records are well formed before they are exported
+pub struct DnsRecordValidator;
+
+impl DnsRecordValidator {
+    /// Every problem with `record`, or `Ok` if there are none
+    pub fn validate(record: &DnsRecord) -> std::result::Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        let domain = record.domain.trim_end_matches('.');
+        if domain.is_empty() {
+            errors.push(ValidationError::new("domain", "domain is empty"));
+        } else if domain.len() > MAX_DOMAIN_LENGTH {
+            errors.push(ValidationError::new(
+                "domain",
+                format!("{} characters exceeds the {} character limit", domain.len(), MAX_DOMAIN_LENGTH),
+            ));
+        }
+
+        if !RecordType::all().contains(&record.record_type) {
+            errors.push(ValidationError::new("record_type", format!("unknown record type {}", record.record_type)));
+        }
+
+        if !value_matches_type(&record.value, record.record_type) {
+            errors.push(ValidationError::new(
+                "value",
+                format!("{} is not a valid {} value", record.value.to_string(), record.record_type),
+            ));
+        }
+
+        if record.ttl > MAX_TTL {
+            errors.push(ValidationError::new("ttl", format!("{} is above the maximum of {}", record.ttl, MAX_TTL)));
+        }
+
+        if !record.query_time_ms.is_finite() || record.query_time_ms < 0.0 {
+            errors.push(ValidationError::new(
+                "query_time_ms",
+                format!("{} is not a non-negative finite duration", record.query_time_ms),
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Whether `value` is a shape a `record_type` answer can have
+///
+/// Names are accepted for every type since CNAME/DNAME chains in an answer are
+/// stored under the queried type, as are unparsed (`Other`) values.
+fn value_matches_type(value: &RecordValue, record_type: RecordType) -> bool {
+    match (record_type, value) {
+        (_, RecordValue::Other(_) | RecordValue::Domain(_)) => true,
+        (RecordType::A, RecordValue::Ip(ip)) => ip.is_ipv4(),
+        (RecordType::Aaaa, RecordValue::Ip(ip)) => ip.is_ipv6(),
+        (RecordType::Mx, RecordValue::Mx { .. })
+        | (RecordType::Txt, RecordValue::Text(_))
+        | (RecordType::Srv, RecordValue::Srv { .. })
+        | (RecordType::Soa, RecordValue::Soa { .. })
+        | (RecordType::Caa, RecordValue::Caa { .. } | RecordValue::Text(_))
+        | (RecordType::Cert, RecordValue::Cert { .. })
+        | (RecordType::Dnskey, RecordValue::Dnskey { .. })
+        | (RecordType::Ds, RecordValue::Ds { .. })
+        | (RecordType::Hinfo, RecordValue::Hinfo { .. })
+        | (RecordType::Https, RecordValue::Https { .. })
+        | (RecordType::Key, RecordValue::Key { .. })
+        | (RecordType::Loc, RecordValue::Loc { .. })
+        | (RecordType::Naptr, RecordValue::Naptr { .. })
+        | (RecordType::Sshfp, RecordValue::Sshfp { .. })
+        | (RecordType::Svcb, RecordValue::Svcb { .. })
+        | (RecordType::Tlsa, RecordValue::Tlsa { .. })
+        | (RecordType::Uri, RecordValue::Uri { .. }) => true,
+        // No structured value exists for these yet, so they are carried as text
+        (
+            RecordType::Afsdb
+            | RecordType::Nsec
+            | RecordType::Nsec3
+            | RecordType::Nsec3param
+            | RecordType::Opt
+            | RecordType::Rrsig,
+            RecordValue::Text(_),
+        ) => true,
+        _ => false,
+    }
+}
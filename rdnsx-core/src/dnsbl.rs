@@ -0,0 +1,214 @@
+//! DNS-based blocklist (DNSBL) lookups
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+
+use futures::future::join_all;
+use hickory_resolver::proto::rr::RData;
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use crate::error::{DnsxError, Result};
+use crate::input::reverse_ip;
+use crate::resolver::ResolverPool;
+use crate::types::{RecordType, ResponseCode};
+
+/// Widely used public blocklists, checked when no lists are given
+pub const DEFAULT_DNSBLS: &[&str] = &[
+    "zen.spamhaus.org",
+    "bl.spamcop.net",
+    "b.barracudacentral.org",
+    "dnsbl.sorbs.net",
+    "psbl.surriel.com",
+    "dnsbl-1.uceprotect.net",
+    "bl.mailspike.net",
+    "ix.dnsbl.manitu.net",
+    "dnsbl.dronebl.org",
+    "all.s5h.net",
+];
+
+/// One blocklist that lists the address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DnsblListing {
+    pub list_name: String,
+    /// The A record the list answered with; its last octet usually encodes the reason
+    pub return_code: Ipv4Addr,
+    pub meaning: String,
+}
+
+/// Where an address is and is not listed
+///
+/// Lists that could not be asked (timeouts, refused queries) appear in neither field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DnsblResult {
+    pub ip: IpAddr,
+    pub listed_on: Vec<DnsblListing>,
+    pub clear_on: Vec<String>,
+}
+
+impl DnsblResult {
+    pub fn is_listed(&self) -> bool {
+        !self.listed_on.is_empty()
+    }
+}
+
+/// Looks addresses up in DNS-based blocklists (RFC 5782)
+pub struct DnsblChecker {
+    resolver_pool: Arc<ResolverPool>,
+}
+
+/// What a single list said about an address
+enum ListAnswer {
+    Listed(Vec<DnsblListing>),
+    Clear,
+    Unknown,
+}
+
+impl DnsblChecker {
+    /// Create a new checker
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        Self { resolver_pool }
+    }
+
+    /// The built-in lists as owned names, for passing to [`DnsblChecker::check_ip`]
+    pub fn default_lists() -> Vec<String> {
+        DEFAULT_DNSBLS.iter().map(|list| list.to_string()).collect()
+    }
+
+    /// Look `ip` up in every list in `lists` at once
+    ///
+    /// A list that cannot be reached is skipped with a warning; an error is
+    /// only returned when none of them could be asked.
+    pub async fn check_ip(&self, ip: IpAddr, lists: &[String]) -> Result<DnsblResult> {
+        if lists.is_empty() {
+            return Err(DnsxError::validation("At least one DNSBL is required"));
+        }
+
+        let answers = join_all(lists.iter().map(|list| self.check_list(ip, list))).await;
+
+        let mut result = DnsblResult { ip, listed_on: Vec::new(), clear_on: Vec::new() };
+        let mut last_error = None;
+        for (list, answer) in lists.iter().zip(answers) {
+            match answer {
+                Ok(ListAnswer::Listed(listings)) => result.listed_on.extend(listings),
+                Ok(ListAnswer::Clear) => result.clear_on.push(list.clone()),
+                Ok(ListAnswer::Unknown) => {}
+                Err(e) => {
+                    warn!("DNSBL {} could not be queried for {}: {}", list, ip, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if result.listed_on.is_empty() && result.clear_on.is_empty() => Err(e),
+            _ => Ok(result),
+        }
+    }
+
+    async fn check_list(&self, ip: IpAddr, list: &str) -> Result<ListAnswer> {
+        let name = dnsbl_query_name(ip, list);
+        let lookup = match self.resolver_pool.query(&name, RecordType::A).await {
+            Ok((lookup, _)) => lookup,
+            // Not listed: the list has no entry for the address
+            Err(DnsxError::Rcode { rcode: ResponseCode::NxDomain | ResponseCode::NoError, .. }) => {
+                return Ok(ListAnswer::Clear)
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut listings = Vec::new();
+        for rdata in lookup.iter() {
+            let RData::A(a) = rdata else { continue };
+            let code = a.0;
+            if is_refusal(list, code) {
+                // Spamhaus answers public resolvers and over-quota users this way
+                warn!("DNSBL {} refused the query for {} ({})", list, ip, code);
+                return Ok(ListAnswer::Unknown);
+            }
+            // Anything outside 127/8 is a hijacked NXDOMAIN or a dead list's wildcard, not a listing
+            if code.octets()[0] != 127 {
+                debug!("Ignoring answer {} from DNSBL {} for {}", code, list, ip);
+                continue;
+            }
+            listings.push(DnsblListing {
+                list_name: list.to_string(),
+                return_code: code,
+                meaning: meaning(list, code),
+            });
+        }
+
+        Ok(if listings.is_empty() { ListAnswer::Clear } else { ListAnswer::Listed(listings) })
+    }
+}
+
+/// `4.3.2.1.zen.spamhaus.org` for 1.2.3.4, and the nibble form for IPv6
+pub fn dnsbl_query_name(ip: IpAddr, list: &str) -> String {
+    let reversed = reverse_ip(&ip);
+    let labels = reversed
+        .strip_suffix(".in-addr.arpa")
+        .or_else(|| reversed.strip_suffix(".ip6.arpa"))
+        .unwrap_or(&reversed);
+    format!("{}.{}", labels, list.trim().trim_end_matches('.'))
+}
+
+fn is_spamhaus(list: &str) -> bool {
+    list.trim_end_matches('.').ends_with("spamhaus.org")
+}
+
+fn is_refusal(list: &str, code: Ipv4Addr) -> bool {
+    is_spamhaus(list) && code.octets()[..3] == [127, 255, 255]
+}
+
+/// Human-readable reason for a listing, from the list's documented return codes
+fn meaning(list: &str, code: Ipv4Addr) -> String {
+    let last = code.octets()[3];
+    let meaning = if is_spamhaus(list) {
+        match last {
+            2 => "Spamhaus SBL: known spam source",
+            3 => "Spamhaus CSS: snowshoe spam",
+            4..=7 => "Spamhaus XBL: exploited or infected host",
+            9 => "Spamhaus DROP: hijacked or criminal netblock",
+            10 | 11 => "Spamhaus PBL: end-user range that should not send mail directly",
+            _ => "listed by Spamhaus",
+        }
+    } else if list.trim_end_matches('.') == "dnsbl.sorbs.net" {
+        match last {
+            2 => "SORBS: open HTTP proxy",
+            3 => "SORBS: open SOCKS proxy",
+            4 => "SORBS: misc open proxy",
+            5 => "SORBS: open SMTP relay",
+            6 => "SORBS: spam source",
+            7 => "SORBS: vulnerable web server",
+            9 => "SORBS: zombie or hijacked network",
+            10 => "SORBS: dynamic IP range",
+            _ => "listed by SORBS",
+        }
+    } else {
+        // Other lists use their own codes; 127.0.0.2 is the only one they agree on
+        "listed"
+    };
+    meaning.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dnsbl_query_name() {
+        let v4: IpAddr = "1.2.3.4".parse().unwrap();
+        assert_eq!(dnsbl_query_name(v4, "zen.spamhaus.org."), "4.3.2.1.zen.spamhaus.org");
+
+        let v6: IpAddr = "2001:db8::1".parse().unwrap();
+        let name = dnsbl_query_name(v6, "bl.example.net");
+        assert!(name.starts_with("1.0.0.0."));
+        assert!(name.ends_with(".8.b.d.0.1.0.0.2.bl.example.net"));
+
+        let code = |last| Ipv4Addr::new(127, 0, 0, last);
+        assert_eq!(meaning("zen.spamhaus.org", code(4)), "Spamhaus XBL: exploited or infected host");
+        assert_eq!(meaning("bl.spamcop.net", code(2)), "listed");
+        assert!(is_refusal("zen.spamhaus.org", Ipv4Addr::new(127, 255, 255, 254)));
+        assert!(!is_refusal("bl.spamcop.net", Ipv4Addr::new(127, 255, 255, 254)));
+    }
+}
@@ -1,40 +1,615 @@
-//! Email security record enumeration (SPF, DMARC, DKIM)
+//! Email security record enumeration (SPF, DMARC, DKIM, BIMI)
 
 use std::sync::Arc;
-use tracing::info;
 
-use crate::error::Result;
+use base64::Engine;
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+use serde::Serialize;
+use tracing::{debug, info};
+
+use crate::error::{DnsxError, Result};
 use crate::resolver::ResolverPool;
-use crate::types::RecordType;
+use crate::types::{RecordType, ResponseCode};
 
 /// Results from email security enumeration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EmailSecurityResult {
     pub domain: String,
     pub spf_records: Vec<SpfRecord>,
     pub dmarc_record: Option<DmarcRecord>,
     pub dkim_selectors: Vec<DkimSelector>,
+    /// Where the effective DMARC policy comes from (the domain itself or a parent)
+    pub dmarc_inheritance: Option<DmarcInheritanceResult>,
+    /// BIMI assertion published at `default._bimi.<domain>`
+    pub bimi_record: Option<String>,
+    /// Whether each `rua` destination of the effective DMARC record accepts the reports
+    pub rua_authorizations: Vec<DmarcReportAuthorization>,
+}
+
+impl EmailSecurityResult {
+    /// Effective DMARC policy (`none`, `quarantine`, `reject`), including one inherited from a parent
+    pub fn dmarc_policy(&self) -> Option<String> {
+        self.dmarc_inheritance
+            .as_ref()
+            .and_then(|inheritance| inheritance.policy.clone())
+            .or_else(|| dmarc_tag(&self.dmarc_record.as_ref()?.content, "p"))
+    }
+
+    /// DMARC record in effect, the domain's own or one inherited from a parent
+    pub fn effective_dmarc_record(&self) -> Option<&str> {
+        self.dmarc_record
+            .as_ref()
+            .map(|record| record.content.as_str())
+            .or_else(|| self.dmarc_inheritance.as_ref()?.record.as_deref())
+    }
 }
 
 /// SPF record information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SpfRecord {
     pub content: String,
     pub resolver: String,
 }
 
 /// DMARC record information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DmarcRecord {
     pub content: String,
     pub resolver: String,
 }
 
 /// DKIM selector information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DkimSelector {
     pub selector: String,
     pub record: String,
+    /// Strength of the published public key
+    pub key_strength: DkimKeyStrength,
+}
+
+/// Public key algorithm and size published in a DKIM record
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DkimKeyStrength {
+    /// `rsa`, `ed25519`, or the raw `k=` value for unknown types
+    pub algorithm: String,
+    /// Key size in bits (RSA modulus length, 256 for Ed25519)
+    pub key_bits: Option<u32>,
+    pub strength: KeyStrength,
+}
+
+/// Verdict on a DKIM public key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum KeyStrength {
+    /// RSA of at least 2048 bits, or Ed25519
+    Strong,
+    /// RSA between 1024 and 2047 bits
+    Weak,
+    /// RSA below 1024 bits
+    Insecure,
+    /// Empty `p=` tag: the key has been revoked
+    Revoked,
+    /// Missing or undecodable key material
+    Invalid,
+}
+
+impl std::fmt::Display for KeyStrength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyStrength::Strong => write!(f, "strong"),
+            KeyStrength::Weak => write!(f, "weak"),
+            KeyStrength::Insecure => write!(f, "insecure"),
+            KeyStrength::Revoked => write!(f, "revoked"),
+            KeyStrength::Invalid => write!(f, "invalid"),
+        }
+    }
+}
+
+impl DkimSelector {
+    /// Create a selector entry, assessing the key in `record`
+    pub fn new(selector: impl Into<String>, record: impl Into<String>) -> Self {
+        let mut dkim = Self {
+            selector: selector.into(),
+            record: record.into(),
+            key_strength: DkimKeyStrength {
+                algorithm: String::new(),
+                key_bits: None,
+                strength: KeyStrength::Invalid,
+            },
+        };
+        dkim.key_strength = dkim.validate_key_strength();
+        dkim
+    }
+
+    /// Decode the `p=` tag and classify the key by algorithm and size
+    pub fn validate_key_strength(&self) -> DkimKeyStrength {
+        let algorithm = dmarc_tag(&self.record, "k")
+            .map(|k| k.to_ascii_lowercase())
+            .unwrap_or_else(|| "rsa".to_string());
+        let verdict = |key_bits, strength| DkimKeyStrength {
+            algorithm: algorithm.clone(),
+            key_bits,
+            strength,
+        };
+
+        // Base64 in TXT records is often split with whitespace
+        let Some(encoded) = self
+            .record
+            .split(';')
+            .find_map(|tag| tag.trim().strip_prefix("p="))
+            .map(|p| p.split_whitespace().collect::<String>())
+        else {
+            return verdict(None, KeyStrength::Invalid);
+        };
+        if encoded.is_empty() {
+            return verdict(None, KeyStrength::Revoked);
+        }
+        let Ok(key) = base64::engine::general_purpose::STANDARD.decode(&encoded) else {
+            return verdict(None, KeyStrength::Invalid);
+        };
+
+        match algorithm.as_str() {
+            // RFC 8463: the raw 32-byte public key
+            "ed25519" if key.len() == 32 => verdict(Some(256), KeyStrength::Strong),
+            "rsa" => match rsa_modulus_bits(&key) {
+                Some(bits) if bits >= 2048 => verdict(Some(bits), KeyStrength::Strong),
+                Some(bits) if bits >= 1024 => verdict(Some(bits), KeyStrength::Weak),
+                Some(bits) => verdict(Some(bits), KeyStrength::Insecure),
+                None => verdict(None, KeyStrength::Invalid),
+            },
+            _ => verdict(None, KeyStrength::Invalid),
+        }
+    }
+}
+
+/// Split one DER element into (tag, contents, remaining input)
+pub(crate) fn der_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+
+    let (len, rest) = if first & 0x80 == 0 {
+        (first as usize, rest)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+        let len = rest[..count].iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        (len, &rest[count..])
+    };
+
+    if rest.len() < len {
+        return None;
+    }
+    Some((tag, &rest[..len], &rest[len..]))
+}
+
+/// Modulus length of a DER RSA key, either SubjectPublicKeyInfo or bare PKCS#1
+fn rsa_modulus_bits(der: &[u8]) -> Option<u32> {
+    const SEQUENCE: u8 = 0x30;
+    const INTEGER: u8 = 0x02;
+    const BIT_STRING: u8 = 0x03;
+
+    let (tag, outer, _) = der_element(der)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+
+    let (tag, _, rest) = der_element(outer)?;
+    let rsa_key = match tag {
+        // PKCS#1 RSAPublicKey: the modulus comes first
+        INTEGER => outer,
+        // SubjectPublicKeyInfo: skip the algorithm identifier, unwrap the bit string
+        SEQUENCE => {
+            let (tag, bits, _) = der_element(rest)?;
+            if tag != BIT_STRING {
+                return None;
+            }
+            let (_, key) = bits.split_first()?;
+            let (tag, key, _) = der_element(key)?;
+            if tag != SEQUENCE {
+                return None;
+            }
+            key
+        }
+        _ => return None,
+    };
+
+    let (tag, modulus, _) = der_element(rsa_key)?;
+    if tag != INTEGER {
+        return None;
+    }
+
+    let modulus = match modulus.iter().position(|&b| b != 0) {
+        Some(start) => &modulus[start..],
+        None => return None,
+    };
+    Some((modulus.len() as u32 - 1) * 8 + (8 - modulus[0].leading_zeros()))
+}
+
+/// Effective DMARC policy for a domain after organizational domain fallback
+#[derive(Debug, Clone, Serialize)]
+pub struct DmarcInheritanceResult {
+    pub domain: String,
+    /// Domain whose `_dmarc` record applies, if any was found
+    pub applicable_domain: Option<String>,
+    /// Effective policy (`sp=` when inherited and present, otherwise `p=`)
+    pub policy: Option<String>,
+    /// Raw DMARC record that applies
+    pub record: Option<String>,
+    /// The record is published on the queried domain itself
+    pub is_direct: bool,
+    /// Domains checked, starting with the queried domain
+    pub inheritance_chain: Vec<String>,
+}
+
+/// Second-level public suffixes that must not be treated as organizational domains
+const MULTI_LABEL_SUFFIXES: &[&str] = &[
+    "co.uk", "org.uk", "ac.uk", "gov.uk", "me.uk", "com.au", "net.au", "org.au", "edu.au",
+    "co.nz", "org.nz", "co.jp", "ne.jp", "or.jp", "com.br", "com.cn", "com.mx", "co.in",
+    "co.za", "com.tr", "com.sg", "com.hk", "co.kr",
+];
+
+/// Walks up the domain tree to find the DMARC record that governs a domain
+pub struct DmarcInheritanceChecker {
+    resolver_pool: Arc<ResolverPool>,
+}
+
+impl DmarcInheritanceChecker {
+    /// Create a new DMARC inheritance checker
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        Self { resolver_pool }
+    }
+
+    /// Query `_dmarc` at each level above `domain` until a record is found or
+    /// the public suffix is reached
+    pub async fn check(&self, domain: &str) -> Result<DmarcInheritanceResult> {
+        info!("Checking DMARC inheritance for: {}", domain);
+
+        let domain = domain.trim().trim_end_matches('.').to_lowercase();
+        let mut result = DmarcInheritanceResult {
+            domain: domain.clone(),
+            applicable_domain: None,
+            policy: None,
+            record: None,
+            is_direct: false,
+            inheritance_chain: Vec::new(),
+        };
+
+        for level in dmarc_lookup_levels(&domain) {
+            result.inheritance_chain.push(level.clone());
+
+            let Some(record) = self.fetch_dmarc(&level).await else {
+                continue;
+            };
+
+            result.is_direct = level == domain;
+            result.policy = if result.is_direct {
+                dmarc_tag(&record, "p")
+            } else {
+                dmarc_tag(&record, "sp").or_else(|| dmarc_tag(&record, "p"))
+            };
+            result.applicable_domain = Some(level);
+            result.record = Some(record);
+            break;
+        }
+
+        Ok(result)
+    }
+
+    async fn fetch_dmarc(&self, level: &str) -> Option<String> {
+        let (lookup, _) = self.resolver_pool.query(&format!("_dmarc.{}", level), RecordType::Txt).await.ok()?;
+        lookup.iter().find_map(|rdata| match rdata {
+            hickory_resolver::proto::rr::RData::TXT(txt) => {
+                let content = txt.iter()
+                    .map(|bytes| String::from_utf8_lossy(bytes))
+                    .collect::<Vec<_>>()
+                    .join("");
+                content.starts_with("v=DMARC1").then_some(content)
+            }
+            _ => None,
+        })
+    }
+}
+
+/// `domain` and each parent above the public suffix, most specific first
+fn dmarc_lookup_levels(domain: &str) -> Vec<String> {
+    let labels: Vec<&str> = domain.split('.').filter(|l| !l.is_empty()).collect();
+    let suffix_labels = if labels.len() >= 2 && MULTI_LABEL_SUFFIXES.contains(&labels[labels.len() - 2..].join(".").as_str()) {
+        2
+    } else {
+        1
+    };
+
+    (0..labels.len().saturating_sub(suffix_labels))
+        .map(|i| labels[i..].join("."))
+        .collect()
+}
+
+/// Value of a `key=value` tag in a DMARC record
+fn dmarc_tag(record: &str, key: &str) -> Option<String> {
+    record
+        .split(';')
+        .filter_map(|tag| tag.trim().split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, v)| v.trim().to_string())
+}
+
+/// Whether an external domain has agreed to receive a domain's DMARC reports (RFC 7489 §7.1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DmarcAuthStatus {
+    /// The reporting domain publishes `<target>._report._dmarc.<reporting>`
+    Authorized,
+    /// No authorization record; receivers will not send reports there
+    Unauthorized,
+    /// Reports go to the domain's own organizational domain
+    NotRequired,
+}
+
+/// Authorization status of one `rua` destination
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DmarcReportAuthorization {
+    pub uri: String,
+    pub reporting_domain: String,
+    pub status: DmarcAuthStatus,
+}
+
+/// Checks that external `rua` destinations have opted in to receiving reports
+///
+/// Without the opt-in, receivers drop the reports, so a DMARC record pointing
+/// at a third party it never arranged with produces no reports at all.
+pub struct DmarcReportAuthChecker {
+    resolver_pool: Arc<ResolverPool>,
+}
+
+impl DmarcReportAuthChecker {
+    /// Create a new report authorization checker
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        Self { resolver_pool }
+    }
+
+    /// Whether `reporting_domain` accepts DMARC reports about `target_domain`
+    ///
+    /// Queries `<target_domain>._report._dmarc.<reporting_domain>`, which
+    /// report processors usually cover with a `*._report._dmarc` wildcard.
+    pub async fn check_authorization(&self, target_domain: &str, reporting_domain: &str) -> Result<DmarcAuthStatus> {
+        let target = target_domain.trim().trim_end_matches('.').to_lowercase();
+        let reporting = reporting_domain.trim().trim_end_matches('.').to_lowercase();
+        if organizational_domain(&target) == organizational_domain(&reporting) {
+            return Ok(DmarcAuthStatus::NotRequired);
+        }
+
+        let name = format!("{}._report._dmarc.{}", target, reporting);
+        let lookup = match self.resolver_pool.query(&name, RecordType::Txt).await {
+            Ok((lookup, _)) => lookup,
+            Err(DnsxError::Rcode { rcode: ResponseCode::NxDomain | ResponseCode::NoError, .. }) => {
+                return Ok(DmarcAuthStatus::Unauthorized)
+            }
+            Err(e) => return Err(e),
+        };
+
+        let authorized = lookup.iter().any(|rdata| match rdata {
+            hickory_resolver::proto::rr::RData::TXT(txt) => {
+                let content = txt.iter().map(|bytes| String::from_utf8_lossy(bytes)).collect::<Vec<_>>().join("");
+                content.starts_with("v=DMARC1")
+            }
+            _ => false,
+        });
+        Ok(if authorized { DmarcAuthStatus::Authorized } else { DmarcAuthStatus::Unauthorized })
+    }
+
+    /// Check every `mailto:` destination in a DMARC record's `rua` tag
+    ///
+    /// Destinations whose check fails (timeouts, SERVFAIL) are left out.
+    pub async fn check_record(&self, target_domain: &str, dmarc_record: &str) -> Vec<DmarcReportAuthorization> {
+        let Some(rua) = dmarc_tag(dmarc_record, "rua") else {
+            return Vec::new();
+        };
+
+        let mut authorizations = Vec::new();
+        for uri in rua.split(',').map(str::trim).filter(|uri| !uri.is_empty()) {
+            let Some(reporting_domain) = report_uri_domain(uri) else {
+                continue;
+            };
+            match self.check_authorization(target_domain, &reporting_domain).await {
+                Ok(status) => authorizations.push(DmarcReportAuthorization {
+                    uri: uri.to_string(),
+                    reporting_domain,
+                    status,
+                }),
+                Err(e) => debug!("Could not check DMARC report authorization for {}: {}", uri, e),
+            }
+        }
+        authorizations
+    }
+}
+
+/// Domain of a `mailto:` report URI, e.g. `example.net` for `mailto:dmarc@example.net!10m`
+fn report_uri_domain(uri: &str) -> Option<String> {
+    let address = uri.trim().strip_prefix("mailto:")?;
+    let address = address.split('!').next()?;
+    let (_, domain) = address.rsplit_once('@')?;
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    (!domain.is_empty()).then_some(domain)
+}
+
+/// Registrable domain of `domain`, the last level DMARC inheritance walks up to
+fn organizational_domain(domain: &str) -> String {
+    dmarc_lookup_levels(domain).pop().unwrap_or_else(|| domain.to_string())
+}
+
+/// Domains that publish the same SPF policy once mechanisms are normalized
+#[derive(Debug, Clone)]
+pub struct SpfGroup {
+    /// SPF record as published by the first domain in the group
+    pub representative_record: String,
+    /// Normalized form shared by every domain in the group
+    pub normalized: String,
+    pub domains: Vec<String>,
+}
+
+/// Why a domain stands out from the rest of the set
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpfOutlierReason {
+    /// No `v=spf1` record was published
+    MissingSpf,
+    /// More than one `v=spf1` record, which receivers treat as a permerror
+    MultipleRecords,
+    /// Shares less than half of its mechanisms with the most common policy
+    Divergent { similarity: f64 },
+}
+
+impl std::fmt::Display for SpfOutlierReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpfOutlierReason::MissingSpf => write!(f, "no SPF record"),
+            SpfOutlierReason::MultipleRecords => write!(f, "multiple SPF records"),
+            SpfOutlierReason::Divergent { similarity } => {
+                write!(f, "differs from the common policy ({:.0}% overlap)", similarity * 100.0)
+            }
+        }
+    }
+}
+
+/// A domain whose SPF configuration does not fit in with the others
+#[derive(Debug, Clone)]
+pub struct SpfOutlier {
+    pub domain: String,
+    pub record: Option<String>,
+    pub reason: SpfOutlierReason,
+}
+
+/// SPF configuration compared across a set of domains
+#[derive(Debug, Clone)]
+pub struct SpfConsistencyReport {
+    /// Groups of domains with identical normalized SPF, largest first
+    pub groups: Vec<SpfGroup>,
+    pub outliers: Vec<SpfOutlier>,
+}
+
+impl SpfConsistencyReport {
+    /// Whether every domain publishes the same SPF policy
+    pub fn is_consistent(&self) -> bool {
+        self.groups.len() <= 1 && self.outliers.is_empty()
+    }
+}
+
+/// Compares SPF records across domains that are expected to share a policy
+pub struct SpfConsistencyChecker {
+    resolver_pool: Arc<ResolverPool>,
+}
+
+impl SpfConsistencyChecker {
+    /// Create a new SPF consistency checker
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        Self { resolver_pool }
+    }
+
+    /// Query SPF for every domain in parallel and group them by normalized policy
+    pub async fn compare(&self, domains: &[String]) -> Result<SpfConsistencyReport> {
+        info!("Comparing SPF across {} domains", domains.len());
+
+        let lookups = futures::future::join_all(domains.iter().map(|domain| async move {
+            let domain = domain.trim().trim_end_matches('.').to_lowercase();
+            let records = self.fetch_spf(&domain).await;
+            (domain, records)
+        }))
+        .await;
+
+        let mut outliers = Vec::new();
+        let mut by_policy: Vec<SpfGroup> = Vec::new();
+        for (domain, mut records) in lookups {
+            match records.len() {
+                0 => outliers.push(SpfOutlier { domain, record: None, reason: SpfOutlierReason::MissingSpf }),
+                1 => {
+                    let record = records.remove(0);
+                    let normalized = normalize_spf(&record);
+                    match by_policy.iter_mut().find(|g| g.normalized == normalized) {
+                        Some(group) => group.domains.push(domain),
+                        None => by_policy.push(SpfGroup {
+                            representative_record: record,
+                            normalized,
+                            domains: vec![domain],
+                        }),
+                    }
+                }
+                _ => outliers.push(SpfOutlier {
+                    domain,
+                    record: Some(records.join(" | ")),
+                    reason: SpfOutlierReason::MultipleRecords,
+                }),
+            }
+        }
+
+        // Stable sort keeps first-seen order among groups of equal size
+        by_policy.sort_by_key(|group| std::cmp::Reverse(group.domains.len()));
+
+        // Lone domains are only divergent relative to a policy others agree on
+        let mut groups = Vec::with_capacity(by_policy.len());
+        let baseline = by_policy.first().filter(|g| g.domains.len() > 1).map(|g| g.normalized.clone());
+        for group in by_policy {
+            let similarity = baseline
+                .as_deref()
+                .filter(|_| group.domains.len() == 1)
+                .map(|baseline| spf_similarity(baseline, &group.normalized));
+            match similarity {
+                Some(similarity) if similarity < 0.5 => outliers.push(SpfOutlier {
+                    domain: group.domains[0].clone(),
+                    record: Some(group.representative_record),
+                    reason: SpfOutlierReason::Divergent { similarity },
+                }),
+                _ => groups.push(group),
+            }
+        }
+
+        Ok(SpfConsistencyReport { groups, outliers })
+    }
+
+    async fn fetch_spf(&self, domain: &str) -> Vec<String> {
+        let Ok((lookup, _)) = self.resolver_pool.query(domain, RecordType::Txt).await else {
+            return Vec::new();
+        };
+        lookup.iter().filter_map(|rdata| match rdata {
+            hickory_resolver::proto::rr::RData::TXT(txt) => {
+                let content = txt.iter()
+                    .map(|bytes| String::from_utf8_lossy(bytes))
+                    .collect::<Vec<_>>()
+                    .join("");
+                content.to_lowercase().starts_with("v=spf1").then_some(content)
+            }
+            _ => None,
+        }).collect()
+    }
+}
+
+/// Canonical form of an SPF record: lowercased, with `include:`, `ip4:` and
+/// `ip6:` mechanisms sorted since their order does not change the result
+fn normalize_spf(record: &str) -> String {
+    let terms: Vec<String> = record.split_whitespace().skip(1).map(|t| t.to_lowercase()).collect();
+    let is_sortable = |term: &str| {
+        let mechanism = term.trim_start_matches(['+', '-', '~', '?']);
+        ["include:", "ip4:", "ip6:"].iter().any(|prefix| mechanism.starts_with(prefix))
+    };
+
+    let mut sortable: Vec<&String> = terms.iter().filter(|t| is_sortable(t)).collect();
+    sortable.sort();
+    let mut sorted = sortable.into_iter();
+
+    std::iter::once("v=spf1")
+        .chain(terms.iter().map(|t| if is_sortable(t) { sorted.next().unwrap() } else { t }).map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Jaccard similarity between the mechanism sets of two normalized records
+fn spf_similarity(a: &str, b: &str) -> f64 {
+    let a: std::collections::HashSet<&str> = a.split_whitespace().skip(1).collect();
+    let b: std::collections::HashSet<&str> = b.split_whitespace().skip(1).collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
 }
 
 /// Email security enumeration functionality
@@ -57,6 +632,9 @@ impl EmailSecurityEnumerator {
             spf_records: Vec::new(),
             dmarc_record: None,
             dkim_selectors: Vec::new(),
+            dmarc_inheritance: None,
+            bimi_record: None,
+            rua_authorizations: Vec::new(),
         };
 
         // Get SPF record
@@ -99,10 +677,28 @@ impl EmailSecurityEnumerator {
             }
         }
 
+        // Resolve the effective policy, falling back to the organizational domain
+        result.dmarc_inheritance = DmarcInheritanceChecker::new(self.resolver_pool.clone())
+            .check(domain)
+            .await
+            .ok();
+
+        // Reports sent outside the organization need the receiver's consent
+        if let Some(record) = result.effective_dmarc_record().map(str::to_string) {
+            let policy_domain = result
+                .dmarc_inheritance
+                .as_ref()
+                .and_then(|inheritance| inheritance.applicable_domain.clone())
+                .unwrap_or_else(|| domain.to_string());
+            result.rua_authorizations = DmarcReportAuthChecker::new(self.resolver_pool.clone())
+                .check_record(&policy_domain, &record)
+                .await;
+        }
+
         // Try common DKIM selectors
         let common_selectors = vec!["default", "google", "mail", "smtp", "dkim"];
         for selector in common_selectors {
-            let dkim_domain = format!("{}.domainkey.{}", selector, domain);
+            let dkim_domain = format!("{}._domainkey.{}", selector, domain);
             if let Ok((lookup, _)) = self.resolver_pool.query(&dkim_domain, RecordType::Txt).await {
                 for rdata in lookup.iter() {
                     if let hickory_resolver::proto::rr::RData::TXT(txt) = rdata {
@@ -112,10 +708,7 @@ impl EmailSecurityEnumerator {
                             .join("");
 
                         if txt_content.starts_with("v=DKIM1") {
-                            result.dkim_selectors.push(DkimSelector {
-                                selector: selector.to_string(),
-                                record: txt_content,
-                            });
+                            result.dkim_selectors.push(DkimSelector::new(selector, txt_content));
                             break;
                         }
                     }
@@ -123,9 +716,37 @@ impl EmailSecurityEnumerator {
             }
         }
 
+        // BIMI logo assertion for the default selector
+        let bimi_domain = format!("default._bimi.{}", domain);
+        if let Ok((lookup, _)) = self.resolver_pool.query(&bimi_domain, RecordType::Txt).await {
+            result.bimi_record = lookup.iter().find_map(|rdata| match rdata {
+                hickory_resolver::proto::rr::RData::TXT(txt) => {
+                    let txt_content = txt.iter().map(|bytes| String::from_utf8_lossy(bytes)).collect::<Vec<_>>().join("");
+                    txt_content.starts_with("v=BIMI1").then_some(txt_content)
+                }
+                _ => None,
+            });
+        }
+
         Ok(result)
     }
 
+    /// Enumerate several domains, yielding results as they complete
+    pub fn enumerate_stream<'a>(
+        &'a self,
+        domains: &'a [String],
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<EmailSecurityResult>> + 'a {
+        stream::iter(domains)
+            .map(move |domain| self.enumerate(domain))
+            .buffer_unordered(concurrency.max(1))
+    }
+
+    /// Enumerate several domains with up to `concurrency` in flight, in completion order
+    pub async fn enumerate_bulk(&self, domains: &[String], concurrency: usize) -> Result<Vec<EmailSecurityResult>> {
+        self.enumerate_stream(domains, concurrency).try_collect().await
+    }
+
     /// Analyze SPF record for security issues
     pub fn analyze_spf(&self, spf_record: &str) -> SpfAnalysis {
         let mut analysis = SpfAnalysis {
@@ -228,6 +849,85 @@ impl EmailSecurityEnumerator {
     }
 }
 
+/// Overall email security posture of a domain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EmailSecurityScore {
+    /// 0-100, higher is better
+    pub score: u8,
+    /// `A` (90-100), `B` (70-89), `C` (50-69), `D` (30-49) or `F` (below 30)
+    pub grade: char,
+}
+
+/// Rates SPF, DMARC, DKIM and BIMI configuration on a 0-100 scale
+///
+/// | Check | Points |
+/// |-------|--------|
+/// | Single valid SPF record | 20 |
+/// | SPF ends in `-all` | 10 |
+/// | DMARC record (own or inherited) | 20 |
+/// | DMARC `p=reject` | 10 |
+/// | DMARC applies to 100% of mail | 10 |
+/// | DKIM key published and usable | 20 |
+/// | BIMI record | 10 |
+#[derive(Debug, Default)]
+pub struct EmailSecurityScorer;
+
+impl EmailSecurityScorer {
+    /// Create a new scorer
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Score an enumeration result
+    pub fn score(&self, result: &EmailSecurityResult) -> EmailSecurityScore {
+        let mut score = 0;
+
+        // More than one SPF record is a permanent error (RFC 7208 section 4.5)
+        if let [spf] = result.spf_records.as_slice() {
+            score += 20;
+            if spf.content.split_whitespace().last() == Some("-all") {
+                score += 10;
+            }
+        }
+
+        if let Some(dmarc) = result.effective_dmarc_record() {
+            score += 20;
+            if result.dmarc_policy().is_some_and(|p| p.eq_ignore_ascii_case("reject")) {
+                score += 10;
+            }
+            // pct defaults to 100 when absent
+            if dmarc_tag(dmarc, "pct").is_none_or(|pct| pct == "100") {
+                score += 10;
+            }
+        }
+
+        let usable_dkim = result
+            .dkim_selectors
+            .iter()
+            .any(|dkim| !matches!(dkim.key_strength.strength, KeyStrength::Revoked | KeyStrength::Invalid));
+        if usable_dkim {
+            score += 20;
+        }
+
+        if result.bimi_record.is_some() {
+            score += 10;
+        }
+
+        EmailSecurityScore { score, grade: Self::grade(score) }
+    }
+
+    /// Letter grade for a 0-100 score
+    pub fn grade(score: u8) -> char {
+        match score {
+            90.. => 'A',
+            70..=89 => 'B',
+            50..=69 => 'C',
+            30..=49 => 'D',
+            _ => 'F',
+        }
+    }
+}
+
 /// SPF record analysis results
 #[derive(Debug, Clone)]
 pub struct SpfAnalysis {
@@ -250,4 +950,161 @@ pub struct DmarcAnalysis {
     pub ruf: Option<String>,
     pub warnings: Vec<String>,
     pub recommendations: Vec<String>,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dmarc_lookup_levels() {
+        assert_eq!(
+            dmarc_lookup_levels("a.mail.example.com"),
+            vec!["a.mail.example.com", "mail.example.com", "example.com"]
+        );
+        assert_eq!(dmarc_lookup_levels("mail.example.co.uk"), vec!["mail.example.co.uk", "example.co.uk"]);
+        assert!(dmarc_lookup_levels("com").is_empty());
+    }
+
+    /// DER element with a definite length
+    fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        match contents.len() {
+            len if len < 0x80 => out.push(len as u8),
+            len if len <= 0xff => out.extend([0x81, len as u8]),
+            len => out.extend([0x82, (len >> 8) as u8, len as u8]),
+        }
+        out.extend_from_slice(contents);
+        out
+    }
+
+    /// Base64 SubjectPublicKeyInfo for an RSA key with a `bits`-bit modulus
+    fn rsa_spki(bits: usize) -> String {
+        let mut modulus = vec![0u8; bits / 8 + 1];
+        modulus[1] = 0x80;
+        let rsa_key = der(0x30, &[der(0x02, &modulus), der(0x02, &[0x01, 0x00, 0x01])].concat());
+        let algorithm = der(0x30, &[der(0x06, &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01]), vec![0x05, 0x00]].concat());
+        let bit_string = der(0x03, &[&[0x00][..], &rsa_key].concat());
+        let spki = der(0x30, &[algorithm, bit_string].concat());
+        base64::engine::general_purpose::STANDARD.encode(spki)
+    }
+
+    #[test]
+    fn test_dkim_key_strength() {
+        let strength = |record: String| DkimSelector::new("default", record).key_strength;
+
+        let strong = strength(format!("v=DKIM1; k=rsa; p={}", rsa_spki(2048)));
+        assert_eq!(strong.key_bits, Some(2048));
+        assert_eq!(strong.strength, KeyStrength::Strong);
+
+        let weak = strength(format!("v=DKIM1; p={}", rsa_spki(1024)));
+        assert_eq!((weak.algorithm.as_str(), weak.key_bits, weak.strength), ("rsa", Some(1024), KeyStrength::Weak));
+
+        assert_eq!(strength(format!("v=DKIM1; p={}", rsa_spki(512))).strength, KeyStrength::Insecure);
+
+        let ed25519 = base64::engine::general_purpose::STANDARD.encode([7u8; 32]);
+        let ed = strength(format!("v=DKIM1; k=ed25519; p={}", ed25519));
+        assert_eq!((ed.key_bits, ed.strength), (Some(256), KeyStrength::Strong));
+
+        assert_eq!(strength("v=DKIM1; p=".to_string()).strength, KeyStrength::Revoked);
+        assert_eq!(strength("v=DKIM1; p=!!!".to_string()).strength, KeyStrength::Invalid);
+    }
+
+    #[test]
+    fn test_normalize_spf() {
+        assert_eq!(
+            normalize_spf("v=spf1 mx include:_spf.google.com ip4:192.0.2.0/24 include:Amazonses.com ~all"),
+            "v=spf1 mx include:_spf.google.com include:amazonses.com ip4:192.0.2.0/24 ~all"
+        );
+        assert_eq!(
+            normalize_spf("v=spf1 include:b.example include:a.example -all"),
+            normalize_spf("v=spf1  include:a.example include:b.example -all")
+        );
+        assert_ne!(normalize_spf("v=spf1 mx -all"), normalize_spf("v=spf1 mx ~all"));
+    }
+
+    #[test]
+    fn test_spf_similarity() {
+        let a = normalize_spf("v=spf1 include:a.example include:b.example -all");
+        let b = normalize_spf("v=spf1 include:a.example include:c.example -all");
+        assert!((spf_similarity(&a, &b) - 0.5).abs() < f64::EPSILON);
+        assert_eq!(spf_similarity(&a, &a), 1.0);
+        assert_eq!(spf_similarity(&a, "v=spf1 ip4:198.51.100.1 ?all"), 0.0);
+    }
+
+    #[test]
+    fn test_dmarc_tag() {
+        let record = "v=DMARC1; p=reject; sp=none; rua=mailto:d@example.com";
+        assert_eq!(dmarc_tag(record, "p").as_deref(), Some("reject"));
+        assert_eq!(dmarc_tag(record, "sp").as_deref(), Some("none"));
+        assert_eq!(dmarc_tag(record, "pct"), None);
+    }
+
+    #[test]
+    fn test_report_uri_domain() {
+        assert_eq!(report_uri_domain("mailto:dmarc@Reports.Example.NET!10m").as_deref(), Some("reports.example.net"));
+        assert_eq!(report_uri_domain(" mailto:rua@example.com"), Some("example.com".to_string()));
+        assert_eq!(report_uri_domain("https://example.com/dmarc"), None);
+        assert_eq!(report_uri_domain("mailto:nobody"), None);
+
+        assert_eq!(organizational_domain("mail.example.co.uk"), "example.co.uk");
+        assert_eq!(organizational_domain("example.com"), "example.com");
+    }
+
+    #[test]
+    fn test_dmarc_policy() {
+        let mut result = EmailSecurityResult {
+            domain: "mail.example.com".to_string(),
+            spf_records: Vec::new(),
+            dmarc_record: None,
+            dkim_selectors: Vec::new(),
+            dmarc_inheritance: None,
+            bimi_record: None,
+            rua_authorizations: Vec::new(),
+        };
+        assert_eq!(result.dmarc_policy(), None);
+
+        result.dmarc_record = Some(DmarcRecord {
+            content: "v=DMARC1; p=quarantine".to_string(),
+            resolver: "8.8.8.8:53".to_string(),
+        });
+        assert_eq!(result.dmarc_policy().as_deref(), Some("quarantine"));
+
+        result.dmarc_inheritance = Some(DmarcInheritanceResult {
+            domain: "mail.example.com".to_string(),
+            applicable_domain: Some("example.com".to_string()),
+            policy: Some("reject".to_string()),
+            record: Some("v=DMARC1; p=none; sp=reject".to_string()),
+            is_direct: false,
+            inheritance_chain: vec!["mail.example.com".to_string(), "example.com".to_string()],
+        });
+        assert_eq!(result.dmarc_policy().as_deref(), Some("reject"));
+    }
+
+    #[test]
+    fn test_email_security_score() {
+        let scorer = EmailSecurityScorer::new();
+        let mut result = EmailSecurityResult {
+            domain: "example.com".to_string(),
+            spf_records: vec![SpfRecord { content: "v=spf1 include:_spf.example.com ~all".to_string(), resolver: "8.8.8.8:53".to_string() }],
+            dmarc_record: Some(DmarcRecord { content: "v=DMARC1; p=quarantine; pct=50".to_string(), resolver: "8.8.8.8:53".to_string() }),
+            dkim_selectors: Vec::new(),
+            dmarc_inheritance: None,
+            bimi_record: None,
+            rua_authorizations: Vec::new(),
+        };
+        assert_eq!(scorer.score(&result), EmailSecurityScore { score: 40, grade: 'D' });
+
+        result.spf_records[0].content = "v=spf1 include:_spf.example.com -all".to_string();
+        result.dmarc_record.as_mut().unwrap().content = "v=DMARC1; p=reject".to_string();
+        result.bimi_record = Some("v=BIMI1; l=https://example.com/logo.svg".to_string());
+        assert_eq!(scorer.score(&result), EmailSecurityScore { score: 80, grade: 'B' });
+
+        // Conflicting SPF records invalidate SPF entirely
+        result.spf_records.push(result.spf_records[0].clone());
+        assert_eq!(scorer.score(&result).score, 50);
+
+        assert_eq!(EmailSecurityScorer::grade(100), 'A');
+        assert_eq!(EmailSecurityScorer::grade(29), 'F');
+    }
+}
@@ -1,24 +1,37 @@
 //! Advanced DNS enumeration capabilities
 
-use std::sync::Arc;
-
-use tracing::info;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, OnceLock};
+
+use futures::StreamExt;
+use hickory_resolver::proto::op::{Message, Query, ResponseCode as HickoryResponseCode};
+use hickory_resolver::proto::rr::{Name, RData, RecordType as HickoryRecordType};
+use regex::Regex;
+use serde::Serialize;
+use tokio::net::UdpSocket;
+use tracing::{debug, info};
 use reqwest;
 use ureq;
 
 use crate::cdn_detection::{CdnDetectionResult, CdnDetector};
+use crate::config::DnsxOptions;
+use crate::ct_enumeration::{CtEnumerationResult, CtEnumerator};
+use crate::dane::{DaneHttpsResult, DaneTlsaValidator};
 use crate::dnssec_analysis::{DnssecEnumerationResult, ZoneWalkingResult, DnssecAnalyzer};
-use crate::email_security::{EmailSecurityResult, EmailSecurityEnumerator};
+use crate::email_security::{EmailSecurityResult, EmailSecurityEnumerator, SpfConsistencyChecker, SpfConsistencyReport};
 use crate::error::{DnsxError, Result};
+use crate::geo_resolver::GeographicResolverSelector;
 use crate::resolver::ResolverPool;
-use crate::types::RecordType;
+use crate::takeover::{SubdomainTakeoverDetector, TakeoverFingerprint, TakeoverFinding};
+use crate::types::{DnsRecord, RecordType, RecordValue};
 use crate::wildcard::{WildcardFilter, WildcardAnalysis};
 use crate::zone_transfer::{ZoneTransferResult, ZoneTransferEnumerator};
 
 // Re-export types for backward compatibility
 pub use crate::cdn_detection::{CnameHop, OriginServerInfo, CdnAnalysis};
-pub use crate::dnssec_analysis::{DnskeyInfo, DsInfo, NsecRecord, ChainValidationResult};
-pub use crate::email_security::{SpfRecord, DmarcRecord, DkimSelector, SpfAnalysis, DmarcAnalysis};
+pub use crate::dnssec_analysis::{DnskeyInfo, DsInfo, NsecRecord, ChainValidationResult, Nsec3SecurityReport, Nsec3Recommendation, KeySizeIssue, KeyProblem};
+pub use crate::email_security::{SpfRecord, DmarcRecord, DkimSelector, DkimKeyStrength, KeyStrength, SpfAnalysis, DmarcAnalysis, DmarcInheritanceResult, SpfGroup, SpfOutlier, SpfOutlierReason};
 pub use crate::enumeration_types::*;
 
 // Module is declared in lib.rs
@@ -30,6 +43,12 @@ pub struct DnsEnumerator {
     email_security: EmailSecurityEnumerator,
     cdn_detector: CdnDetector,
     dnssec_analyzer: DnssecAnalyzer,
+    hinfo_enumerator: HinfoEnumerator,
+    service_discovery: ServiceDiscovery,
+    cloud_assets: CloudAssetDiscoverer,
+    dane_validator: DaneTlsaValidator,
+    takeover_detector: SubdomainTakeoverDetector,
+    ct_enumerator: CtEnumerator,
 }
 
 impl DnsEnumerator {
@@ -40,11 +59,43 @@ impl DnsEnumerator {
             zone_transfer: ZoneTransferEnumerator::new(resolver_pool.clone()),
             email_security: EmailSecurityEnumerator::new(resolver_pool.clone()),
             cdn_detector: CdnDetector::new(resolver_pool.clone()),
-            dnssec_analyzer: DnssecAnalyzer::new(resolver_pool),
+            dnssec_analyzer: DnssecAnalyzer::new(resolver_pool.clone()),
+            hinfo_enumerator: HinfoEnumerator::new(resolver_pool.clone()),
+            service_discovery: ServiceDiscovery::new(resolver_pool.clone()),
+            cloud_assets: CloudAssetDiscoverer::new(resolver_pool.clone()),
+            dane_validator: DaneTlsaValidator::new(resolver_pool.clone()),
+            takeover_detector: SubdomainTakeoverDetector::new(),
+            ct_enumerator: CtEnumerator::new(resolver_pool),
         }
     }
 
-    /// Attempt DNS zone transfer (AXFR) against specified servers
+    /// Use custom CDN provider patterns from a JSON file during CDN detection
+    pub fn with_cdn_patterns(mut self, path: &std::path::Path) -> Result<Self> {
+        self.cdn_detector = CdnDetector::new(self.resolver_pool.clone()).with_pattern_db(path)?;
+        Ok(self)
+    }
+
+    /// Apply the HTTP timeout, rate limit and concurrency of `options` to passive sources
+    pub fn with_options(mut self, options: &DnsxOptions) -> Self {
+        self.ct_enumerator = self.ct_enumerator.with_options(options);
+        self
+    }
+
+    /// Resolve the names found in certificate transparency logs
+    pub fn with_ct_resolution(mut self, resolve: bool) -> Self {
+        self.ct_enumerator = self.ct_enumerator.with_resolution(resolve);
+        self
+    }
+
+    /// Check takeover fingerprints from a JSON file before the built-in ones
+    pub fn with_takeover_fingerprints(mut self, path: &std::path::Path) -> Result<Self> {
+        let mut fingerprints = TakeoverFingerprint::load(path)?;
+        fingerprints.extend(SubdomainTakeoverDetector::builtin_fingerprints());
+        self.takeover_detector = SubdomainTakeoverDetector::new().with_fingerprints(fingerprints);
+        Ok(self)
+    }
+
+    /// Attempt DNS zone transfer (AXFR) against specified servers, or the domain's own NS records if none
     pub async fn zone_transfer(
         &self,
         domain: &str,
@@ -61,11 +112,36 @@ impl DnsEnumerator {
         self.email_security.enumerate(domain).await
     }
 
+    /// Email security enumeration over many domains, yielding results as they complete
+    pub fn email_security_stream<'a>(
+        &'a self,
+        domains: &'a [String],
+        concurrency: usize,
+    ) -> impl futures::Stream<Item = Result<EmailSecurityResult>> + 'a {
+        self.email_security.enumerate_stream(domains, concurrency)
+    }
+
+    /// Compare SPF policies across domains that should be configured alike
+    pub async fn spf_consistency(&self, domains: &[String]) -> Result<SpfConsistencyReport> {
+        SpfConsistencyChecker::new(self.resolver_pool.clone()).compare(domains).await
+    }
+
     /// Detect and analyze CDN usage
     pub async fn cdn_detection(&self, domain: &str) -> Result<CdnDetectionResult> {
         self.cdn_detector.detect(domain).await
     }
 
+    /// Subdomains and certificates from certificate transparency logs (crt.sh)
+    pub async fn ct_enumeration(&self, domain: &str) -> Result<CtEnumerationResult> {
+        self.ct_enumerator.enumerate(domain).await
+    }
+
+    /// Names among `records` whose CNAME chain ends at an unclaimed service, verified live
+    pub async fn check_takeover(&self, records: &[DnsRecord]) -> Result<Vec<TakeoverFinding>> {
+        let candidates = self.takeover_detector.detect(records);
+        self.takeover_detector.verify(&self.resolver_pool, candidates).await
+    }
+
     /// Enumerate IPv6 deployment and addresses
     pub async fn ipv6_enumeration(&self, domain: &str) -> Result<Ipv6EnumerationResult> {
         use crate::enumeration_types::Ipv6EnumerationResult;
@@ -115,6 +191,26 @@ impl DnsEnumerator {
         self.dnssec_analyzer.zone_walking(domain).await
     }
 
+    /// Look for HINFO records advertising host hardware and OS details
+    pub async fn hinfo_scan(&self, domains: &[String]) -> Result<Vec<HinfoFinding>> {
+        self.hinfo_enumerator.scan(domains).await
+    }
+
+    /// Discover services advertised via HTTPS/TLSA records and cloud verification TXT tokens
+    pub async fn service_discovery(&self, domain: &str) -> Result<ServiceMap> {
+        self.service_discovery.scan(domain).await
+    }
+
+    /// Look for storage buckets and hosted apps named after the domain on common cloud providers
+    pub async fn cloud_asset_discovery(&self, domain: &str) -> Result<Vec<CloudAsset>> {
+        self.cloud_assets.discover(domain).await
+    }
+
+    /// Check the HTTPS endpoint's TLSA records against the certificates it serves
+    pub async fn dane_tls_validation(&self, domain: &str) -> Result<DaneHttpsResult> {
+        self.dane_validator.validate_https(domain).await
+    }
+
     /// Perform passive DNS enumeration using historical data
     pub async fn passive_dns_enumeration(&self, domain: &str) -> Result<crate::enumeration_types::PassiveDnsResult> {
         use crate::enumeration_types::{PassiveDnsResult, PassiveSubdomain, HistoricalIp};
@@ -195,7 +291,7 @@ impl DnsEnumerator {
                 fingerprint.response_time_ms = start_time.elapsed().as_millis() as u64;
             }
             Err(e) => {
-                return Err(crate::error::DnsxError::Other(format!("Server fingerprinting failed: {}", e)));
+                return Err(DnsxError::network(nameserver, format!("Server fingerprinting failed: {}", e)));
             }
         }
 
@@ -223,6 +319,7 @@ impl DnsEnumerator {
             ipv6_prefixes: vec!["2001:db8::/32".to_string()], // RFC 3849 test address
             total_ipv4_addresses: 256,
             total_ipv6_addresses: 18446744073709551615, // Max u64 value as approximation for IPv6
+            suggested_resolvers: Vec::new(),
         };
 
         // Try to get basic info from known ASNs
@@ -275,6 +372,11 @@ impl DnsEnumerator {
             }
         }
 
+        result.suggested_resolvers = match &result.country {
+            Some(country) => GeographicResolverSelector::select_for_country(country),
+            None => GeographicResolverSelector::new().select_for_asn(&result.asn),
+        };
+
         Ok(result)
     }
 
@@ -288,7 +390,7 @@ impl DnsEnumerator {
                 info!("reqwest failed for {} API: {}, trying ureq fallback", api_name, reqwest_error);
                 // Fallback to ureq
                 Self::try_ureq_api(url, api_name, asn).map_err(|ureq_error| {
-                    DnsxError::Other(format!("Both reqwest and ureq failed. reqwest: {}, ureq: {}", reqwest_error, ureq_error))
+                    ureq_error.with_context(format!("Both reqwest and ureq failed; reqwest: {}", reqwest_error.report()))
                 })
             }
         }
@@ -303,7 +405,7 @@ impl DnsEnumerator {
             .send()
             .await
             .map_err(|e| {
-                let error_msg = format!("Failed to query {} API: {} (URL: {})", api_name, e, url);
+                let error_msg = format!("Failed to query {} API (URL: {})", api_name, url);
                 let final_msg = if e.is_timeout() {
                     format!("{} - Request timed out. Check internet connection.", error_msg)
                 } else if e.is_connect() {
@@ -311,15 +413,15 @@ impl DnsEnumerator {
                 } else {
                     error_msg
                 };
-                DnsxError::Other(final_msg)
+                DnsxError::network_source(api_name, final_msg, e)
             })?;
 
         if !response.status().is_success() {
-            return Err(DnsxError::Other(format!("{} API returned status: {} for URL: {}", api_name, response.status(), url)));
+            return Err(DnsxError::network(api_name, format!("API returned status: {} for URL: {}", response.status(), url)));
         }
 
         let json: serde_json::Value = response.json().await
-            .map_err(|e| DnsxError::Other(format!("Failed to parse {} API response: {}", api_name, e)))?;
+            .map_err(|e| DnsxError::parse_source(url, format!("Invalid {} API response", api_name), e))?;
 
         Self::parse_asn_response(json, api_name, asn)
     }
@@ -330,14 +432,14 @@ impl DnsEnumerator {
         let response = ureq::get(url)
             .timeout(std::time::Duration::from_secs(30))
             .call()
-            .map_err(|e| DnsxError::Other(format!("ureq failed for {} API: {} (URL: {})", api_name, e, url)))?;
+            .map_err(|e| DnsxError::network_source(api_name, format!("ureq request failed (URL: {})", url), e))?;
 
         if response.status() != 200 {
-            return Err(DnsxError::Other(format!("{} API returned status: {} for URL: {}", api_name, response.status(), url)));
+            return Err(DnsxError::network(api_name, format!("API returned status: {} for URL: {}", response.status(), url)));
         }
 
         let json: serde_json::Value = response.into_json()
-            .map_err(|e| DnsxError::Other(format!("Failed to parse {} API response: {}", api_name, e)))?;
+            .map_err(|e| DnsxError::parse_source(url, format!("Invalid {} API response", api_name), e))?;
 
         Self::parse_asn_response(json, api_name, asn)
     }
@@ -354,6 +456,7 @@ impl DnsEnumerator {
             ipv6_prefixes: Vec::new(),
             total_ipv4_addresses: 0,
             total_ipv6_addresses: 0,
+            suggested_resolvers: Vec::new(),
         };
 
         match api_name {
@@ -424,7 +527,7 @@ impl DnsEnumerator {
                 }
             }
             _ => {
-                return Err(DnsxError::Other(format!("Unknown API: {}", api_name)));
+                return Err(DnsxError::invalid_input(format!("Unknown API: {}", api_name)));
             }
         }
 
@@ -439,6 +542,686 @@ impl DnsEnumerator {
 
 
 
+/// HINFO record found on a host
+#[derive(Debug, Clone)]
+pub struct HinfoFinding {
+    pub domain: String,
+    pub cpu_string: String,
+    pub os_string: String,
+    /// The OS string discloses a specific version, which helps target known vulnerabilities
+    pub security_risk: bool,
+}
+
+/// Enumerates HINFO records, which are rarely needed and often left configured by mistake
+pub struct HinfoEnumerator {
+    resolver_pool: Arc<ResolverPool>,
+}
+
+impl HinfoEnumerator {
+    /// Create a new HINFO enumerator
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        Self { resolver_pool }
+    }
+
+    /// Query HINFO for each domain and return the non-empty answers
+    pub async fn scan(&self, domains: &[String]) -> Result<Vec<HinfoFinding>> {
+        let mut findings = Vec::new();
+
+        for domain in domains {
+            info!("Querying HINFO for: {}", domain);
+
+            let Ok((lookup, _)) = self.resolver_pool.query(domain, RecordType::Hinfo).await else {
+                continue;
+            };
+
+            for rdata in lookup.iter() {
+                let Ok(RecordValue::Hinfo { cpu, os }) = crate::query::parse_rdata(rdata) else {
+                    continue;
+                };
+                if cpu.trim().is_empty() && os.trim().is_empty() {
+                    continue;
+                }
+
+                findings.push(HinfoFinding {
+                    domain: domain.clone(),
+                    security_risk: os.chars().any(|c| c.is_ascii_digit()),
+                    cpu_string: cpu,
+                    os_string: os,
+                });
+            }
+        }
+
+        Ok(findings)
+    }
+}
+
+/// Credential-like string found in a TXT record
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SecretFinding {
+    /// Which pattern matched, e.g. `aws_access_key` or `high_entropy_string`
+    pub pattern_name: String,
+    pub matched_value: String,
+    /// 0.0-1.0; provider key formats score high, bare entropy hits low
+    pub confidence: f64,
+}
+
+/// Credential formats with a recognizable shape, with the confidence a match carries
+const SECRET_PATTERNS: &[(&str, &str, f64)] = &[
+    ("aws_access_key", r"\bAKIA[0-9A-Z]{16}\b", 0.9),
+    ("google_api_key", r"\bAIza[0-9A-Za-z\-_]{35}", 0.9),
+    ("jwt", r"\beyJ[A-Za-z0-9_-]+\.eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+", 0.8),
+    ("private_key", r"-----BEGIN ([A-Z0-9]+ )*PRIVATE KEY-----", 0.95),
+];
+
+/// Tokens need to be longer than this to be judged on entropy alone
+const SECRET_MIN_ENTROPY_LENGTH: usize = 20;
+/// Bits per character above which a token looks random rather than written
+const SECRET_ENTROPY_THRESHOLD: f64 = 4.5;
+
+/// Looks for credentials accidentally published in TXT records
+///
+/// TXT is where people paste whatever a provider asks them to, and now and
+/// then that is a secret rather than a verification token.
+pub struct TxtSecretScanner {
+    resolver_pool: Arc<ResolverPool>,
+}
+
+impl TxtSecretScanner {
+    /// Create a new scanner
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        Self { resolver_pool }
+    }
+
+    /// Query TXT for `domain` and report anything that looks like a credential
+    pub async fn scan(&self, domain: &str) -> Result<Vec<SecretFinding>> {
+        info!("Scanning TXT records of {} for secrets", domain);
+        let (lookup, _) = self.resolver_pool.query(domain, RecordType::Txt).await?;
+
+        let mut findings = Vec::new();
+        for rdata in lookup.iter() {
+            if let Ok(RecordValue::Text(txt)) = crate::query::parse_rdata(rdata) {
+                findings.extend(Self::scan_txt(domain, &txt));
+            }
+        }
+        Ok(findings)
+    }
+
+    /// Findings in the text of one TXT record published on `domain`
+    ///
+    /// Known formats are matched first; leftover tokens are then judged on
+    /// entropy, except in SPF, DKIM and DMARC records and provider
+    /// verification tokens, which are random by design and meant to be public.
+    pub fn scan_txt(domain: &str, txt: &str) -> Vec<SecretFinding> {
+        static PATTERNS: OnceLock<Vec<(&str, Regex, f64)>> = OnceLock::new();
+        let patterns = PATTERNS.get_or_init(|| {
+            SECRET_PATTERNS
+                .iter()
+                .map(|&(name, pattern, confidence)| (name, Regex::new(pattern).expect("valid secret pattern"), confidence))
+                .collect()
+        });
+
+        let mut findings = Vec::new();
+        for (name, regex, confidence) in patterns {
+            findings.extend(regex.find_iter(txt).map(|m| SecretFinding {
+                pattern_name: name.to_string(),
+                matched_value: m.as_str().to_string(),
+                confidence: *confidence,
+            }));
+        }
+
+        let trimmed = txt.trim().trim_matches('"');
+        let lower = trimmed.to_ascii_lowercase();
+        let expected_random = ["v=spf1", "v=dkim1", "v=dmarc1", "k=rsa"].iter().any(|p| lower.starts_with(p))
+            || CloudVerification::from_txt(domain, trimmed).is_some();
+        if expected_random {
+            return findings;
+        }
+
+        for token in trimmed.split(|c: char| c.is_whitespace() || matches!(c, ';' | ',' | '"')) {
+            // `key=value` and `key:value` pairs: only the value can be secret
+            let value = token.split_once(['=', ':']).map_or(token, |(_, value)| value).trim_end_matches('=');
+            if value.len() <= SECRET_MIN_ENTROPY_LENGTH {
+                continue;
+            }
+            let already_found = |f: &SecretFinding| f.matched_value.contains(value) || value.contains(&f.matched_value);
+            if findings.iter().any(already_found) {
+                continue;
+            }
+            let entropy = crate::tunneling::shannon_entropy(value);
+            if entropy > SECRET_ENTROPY_THRESHOLD {
+                findings.push(SecretFinding {
+                    pattern_name: "high_entropy_string".to_string(),
+                    matched_value: value.to_string(),
+                    confidence: (0.3 + (entropy - SECRET_ENTROPY_THRESHOLD) * 0.2).min(0.6),
+                });
+            }
+        }
+
+        findings
+    }
+}
+
+/// A service advertised through DNS (HTTPS/SVCB bindings or TLSA certificates)
+#[derive(Debug, Clone)]
+pub struct ServiceEntry {
+    /// Name that was queried, e.g. `_443._tcp.example.com`
+    pub name: String,
+    /// Protocol the record describes (HTTPS, SMTP, HTTP, ...)
+    pub service: String,
+    pub port: u16,
+    pub record_type: RecordType,
+    pub value: String,
+}
+
+/// Domain ownership token left in TXT for a cloud or SaaS provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloudVerification {
+    pub provider: String,
+    pub token: String,
+    /// Name the TXT record was found on
+    pub name: String,
+}
+
+/// TXT prefixes used by providers to verify domain ownership
+const CLOUD_VERIFICATION_PREFIXES: &[(&str, &str)] = &[
+    ("google-site-verification=", "Google"),
+    ("MS=", "Microsoft Azure"),
+    ("amazonses:", "AWS SES"),
+    ("facebook-domain-verification=", "Facebook"),
+    ("apple-domain-verification=", "Apple"),
+    ("atlassian-domain-verification=", "Atlassian"),
+    ("docusign=", "DocuSign"),
+    ("globalsign-domain-verification=", "GlobalSign"),
+    ("adobe-idp-site-verification=", "Adobe"),
+    ("stripe-verification=", "Stripe"),
+];
+
+impl CloudVerification {
+    /// Recognize a verification token in a TXT record found on `name`
+    pub fn from_txt(name: &str, txt: &str) -> Option<Self> {
+        let txt = txt.trim().trim_matches('"');
+        CLOUD_VERIFICATION_PREFIXES.iter().find_map(|(prefix, provider)| {
+            let token = txt.strip_prefix(prefix)?;
+            (!token.is_empty()).then(|| Self {
+                provider: provider.to_string(),
+                token: token.to_string(),
+                name: name.to_string(),
+            })
+        })
+    }
+}
+
+/// Services and provider verifications discovered for a domain
+#[derive(Debug, Clone, Default)]
+pub struct ServiceMap {
+    pub detected_services: Vec<ServiceEntry>,
+    pub cloud_verifications: Vec<CloudVerification>,
+}
+
+/// Ports checked for TLSA records, with the service usually found there
+const TLSA_PORTS: &[(u16, &str)] = &[(25, "SMTP"), (443, "HTTPS"), (80, "HTTP"), (8080, "HTTP-alt"), (8443, "HTTPS-alt")];
+
+/// Discovers services from DNS naming conventions rather than SRV records
+pub struct ServiceDiscovery {
+    resolver_pool: Arc<ResolverPool>,
+}
+
+impl ServiceDiscovery {
+    /// Create a new service discovery scanner
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        Self { resolver_pool }
+    }
+
+    /// Check HTTPS bindings, TLSA records on common ports and TXT verification tokens
+    pub async fn scan(&self, domain: &str) -> Result<ServiceMap> {
+        info!("Discovering DNS-advertised services for: {}", domain);
+
+        // HTTPS records for the default port live on the name itself (RFC 9460)
+        let mut probes = vec![(domain.to_string(), "HTTPS", 443, RecordType::Https)];
+        for (port, service) in TLSA_PORTS {
+            probes.push((format!("_{}._tcp.{}", port, domain), *service, *port, RecordType::Tlsa));
+        }
+
+        let lookups = futures::future::join_all(probes.into_iter().map(|(name, service, port, record_type)| async move {
+            let values = self.lookup_values(&name, record_type).await;
+            (name, service, port, record_type, values)
+        }));
+        let txt_names = [domain.to_string(), format!("_amazonses.{}", domain)];
+        let txt_lookups = futures::future::join_all(txt_names.iter().map(|name| async move {
+            (name, self.lookup_values(name, RecordType::Txt).await)
+        }));
+        let (lookups, txt_lookups) = tokio::join!(lookups, txt_lookups);
+
+        let mut map = ServiceMap::default();
+        for (name, service, port, record_type, values) in lookups {
+            for value in values {
+                map.detected_services.push(ServiceEntry {
+                    name: name.clone(),
+                    service: service.to_string(),
+                    port,
+                    record_type,
+                    value,
+                });
+            }
+        }
+
+        for (name, values) in txt_lookups {
+            for txt in values {
+                let verification = if name.starts_with("_amazonses.") {
+                    // SES puts the bare token on its own name
+                    Some(CloudVerification { provider: "AWS SES".to_string(), token: txt, name: name.clone() })
+                } else {
+                    CloudVerification::from_txt(name, &txt)
+                };
+                map.cloud_verifications.extend(verification);
+            }
+        }
+
+        Ok(map)
+    }
+
+    async fn lookup_values(&self, name: &str, record_type: RecordType) -> Vec<String> {
+        let Ok((lookup, _)) = self.resolver_pool.query(name, record_type).await else {
+            return Vec::new();
+        };
+
+        lookup
+            .iter()
+            .filter(|rdata| rdata.record_type() == record_type.to_hickory())
+            .filter_map(|rdata| crate::query::parse_rdata(rdata).ok())
+            .map(|value| value.to_string())
+            .collect()
+    }
+}
+
+/// Storage bucket or hosted app on a cloud provider that appears to belong to a domain
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CloudAsset {
+    pub domain: String,
+    pub cloud_provider: String,
+    pub service_type: String,
+    /// The asset answered an unauthenticated HTTP HEAD request with a 2xx status
+    pub is_accessible: bool,
+}
+
+/// Cloud service hostname pattern; `{}` is replaced with a candidate name
+struct CloudServicePattern {
+    template: &'static str,
+    provider: &'static str,
+    service_type: &'static str,
+    /// The provider resolves every name under the suffix, so only an HTTP
+    /// answer other than 404 shows that the asset exists
+    wildcard_dns: bool,
+}
+
+const CLOUD_SERVICE_PATTERNS: &[CloudServicePattern] = &[
+    CloudServicePattern { template: "{}.s3.amazonaws.com", provider: "AWS", service_type: "S3 bucket", wildcard_dns: true },
+    CloudServicePattern { template: "{}.blob.core.windows.net", provider: "Azure", service_type: "Blob storage", wildcard_dns: false },
+    CloudServicePattern { template: "{}.azurewebsites.net", provider: "Azure", service_type: "App Service", wildcard_dns: false },
+    CloudServicePattern { template: "{}.cloudapp.net", provider: "Azure", service_type: "Cloud Service", wildcard_dns: false },
+    CloudServicePattern { template: "{}.azureedge.net", provider: "Azure", service_type: "CDN endpoint", wildcard_dns: false },
+    CloudServicePattern { template: "{}.storage.googleapis.com", provider: "GCP", service_type: "Cloud Storage bucket", wildcard_dns: true },
+    CloudServicePattern { template: "{}.appspot.com", provider: "GCP", service_type: "App Engine", wildcard_dns: true },
+    CloudServicePattern { template: "{}.firebaseio.com", provider: "GCP", service_type: "Firebase database", wildcard_dns: true },
+    CloudServicePattern { template: "{}.web.app", provider: "GCP", service_type: "Firebase Hosting", wildcard_dns: false },
+    CloudServicePattern { template: "{}.nyc3.digitaloceanspaces.com", provider: "DigitalOcean", service_type: "Spaces bucket", wildcard_dns: true },
+    CloudServicePattern { template: "{}.herokuapp.com", provider: "Heroku", service_type: "App", wildcard_dns: true },
+];
+
+/// Cloud hostnames probed at once during discovery
+const CLOUD_PROBE_CONCURRENCY: usize = 20;
+
+/// Suffixes commonly appended to an organisation name for bucket and app names
+const CLOUD_NAME_SUFFIXES: &[&str] = &["", "-assets", "-static", "-media", "-backup", "-dev", "-prod"];
+
+/// Finds cloud assets named after a domain (`example.com` -> `example-assets.s3.amazonaws.com`)
+pub struct CloudAssetDiscoverer {
+    resolver_pool: Arc<ResolverPool>,
+    client: reqwest::Client,
+}
+
+impl CloudAssetDiscoverer {
+    /// Create a new cloud asset discoverer
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+
+        Self { resolver_pool, client }
+    }
+
+    /// Resolve cloud service names derived from `domain` and probe the ones that exist
+    pub async fn discover(&self, domain: &str) -> Result<Vec<CloudAsset>> {
+        info!("Discovering cloud assets for: {}", domain);
+
+        let probes = cloud_asset_candidates(domain)
+            .into_iter()
+            .flat_map(|name| CLOUD_SERVICE_PATTERNS.iter().map(move |pattern| (pattern.template.replace("{}", &name), pattern)));
+
+        let mut assets: Vec<CloudAsset> = futures::stream::iter(probes)
+            .map(|(host, pattern)| self.probe(host, pattern))
+            .buffer_unordered(CLOUD_PROBE_CONCURRENCY)
+            .filter_map(|asset| async move { asset })
+            .collect()
+            .await;
+
+        assets.sort_by(|a, b| a.domain.cmp(&b.domain));
+        Ok(assets)
+    }
+
+    async fn probe(&self, host: String, pattern: &CloudServicePattern) -> Option<CloudAsset> {
+        self.resolver_pool.query(&host, RecordType::A).await.ok()?;
+
+        let status = self
+            .client
+            .head(format!("https://{}/", host))
+            .send()
+            .await
+            .map(|response| response.status())
+            .ok();
+
+        if pattern.wildcard_dns && !matches!(status, Some(s) if s != reqwest::StatusCode::NOT_FOUND) {
+            return None;
+        }
+
+        Some(CloudAsset {
+            domain: host,
+            cloud_provider: pattern.provider.to_string(),
+            service_type: pattern.service_type.to_string(),
+            is_accessible: status.is_some_and(|s| s.is_success()),
+        })
+    }
+}
+
+/// Bucket and app names an organisation is likely to use for `domain`
+///
+/// Built from the label left of the public suffix, with and without the TLD
+/// (`example`, `example-com`, `examplecom`), each combined with common suffixes.
+pub(crate) fn cloud_asset_candidates(domain: &str) -> Vec<String> {
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    let labels: Vec<&str> = domain.split('.').filter(|l| !l.is_empty()).collect();
+    let Some((tld, rest)) = labels.split_last() else {
+        return Vec::new();
+    };
+
+    let mut bases = Vec::new();
+    if let Some(name) = rest.last() {
+        bases.push(name.to_string());
+        bases.push(format!("{}-{}", name, tld));
+        bases.push(format!("{}{}", name, tld));
+        if rest.len() > 1 {
+            // Keep the subdomain, e.g. `cdn.example.com` -> `cdn-example`
+            bases.push(rest.join("-"));
+        }
+    } else {
+        bases.push(tld.to_string());
+    }
+
+    let mut candidates = Vec::new();
+    for base in &bases {
+        for suffix in CLOUD_NAME_SUFFIXES {
+            let candidate = format!("{}{}", base, suffix);
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+    }
+    candidates
+}
+
+/// One MX exchange and its preference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MxEntry {
+    pub priority: u16,
+    pub exchange: String,
+}
+
+/// MX configuration of a single domain
+#[derive(Debug, Clone, Default)]
+pub struct MxSummary {
+    /// Unique exchanges, most preferred (lowest priority value) first
+    pub records_sorted_by_priority: Vec<MxEntry>,
+    /// Hosted mail provider of the most preferred exchange, when recognized
+    pub mail_provider: Option<String>,
+    /// More than one exchange host, so mail is still accepted when one is down
+    pub is_backup_configured: bool,
+}
+
+/// Exchange hostname suffixes of well-known mail providers
+const MAIL_PROVIDER_SUFFIXES: &[(&str, &str)] = &[
+    ("google.com", "Google Workspace"),
+    ("googlemail.com", "Google Workspace"),
+    ("outlook.com", "Microsoft 365"),
+    ("mxroute.com", "MXroute"),
+    ("pphosted.com", "Proofpoint"),
+    ("ppe-hosted.com", "Proofpoint Essentials"),
+    ("mimecast.com", "Mimecast"),
+    ("zoho.com", "Zoho Mail"),
+    ("zoho.eu", "Zoho Mail"),
+    ("messagingengine.com", "Fastmail"),
+    ("protonmail.ch", "Proton Mail"),
+    ("icloud.com", "iCloud Mail"),
+    ("yahoodns.net", "Yahoo Mail"),
+    ("secureserver.net", "GoDaddy"),
+    ("amazonaws.com", "Amazon SES"),
+    ("mailgun.org", "Mailgun"),
+    ("sendgrid.net", "SendGrid"),
+    ("barracudanetworks.com", "Barracuda"),
+    ("iphmx.com", "Cisco Secure Email"),
+    ("mail.ovh.net", "OVHcloud"),
+];
+
+/// Groups MX answers per domain and identifies the mail provider
+pub struct MxAnalyzer;
+
+impl MxAnalyzer {
+    /// Collect the MX records of each domain into a priority-ordered summary
+    ///
+    /// Non-MX records are ignored and duplicate answers (e.g. from several
+    /// resolvers) are merged.
+    pub fn group_by_domain(records: &[DnsRecord]) -> HashMap<String, MxSummary> {
+        let mut grouped: HashMap<String, MxSummary> = HashMap::new();
+
+        for record in records {
+            let RecordValue::Mx { priority, exchange } = &record.value else {
+                continue;
+            };
+            let entry = MxEntry {
+                priority: *priority,
+                exchange: exchange.trim_end_matches('.').to_lowercase(),
+            };
+            let summary = grouped.entry(record.domain.trim_end_matches('.').to_lowercase()).or_default();
+            if !summary.records_sorted_by_priority.contains(&entry) {
+                summary.records_sorted_by_priority.push(entry);
+            }
+        }
+
+        for summary in grouped.values_mut() {
+            let entries = &mut summary.records_sorted_by_priority;
+            entries.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.exchange.cmp(&b.exchange)));
+
+            let hosts: std::collections::HashSet<&str> = entries.iter().map(|e| e.exchange.as_str()).collect();
+            summary.is_backup_configured = hosts.len() > 1;
+            summary.mail_provider = entries.first().and_then(|e| Self::identify_provider(&e.exchange));
+        }
+
+        grouped
+    }
+
+    /// Mail provider operating an exchange host, if it is a known one
+    pub fn identify_provider(exchange: &str) -> Option<String> {
+        let exchange = exchange.trim_end_matches('.').to_lowercase();
+        MAIL_PROVIDER_SUFFIXES
+            .iter()
+            .find(|(suffix, _)| exchange == *suffix || exchange.ends_with(&format!(".{}", suffix)))
+            .map(|(_, provider)| provider.to_string())
+    }
+}
+
+/// Delegated nameservers of a zone split by whether they actually serve it
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LameDelegationResult {
+    /// Nameservers listed by the parent that gave no authoritative SOA answer
+    pub lame_ns: Vec<String>,
+    pub authoritative_ns: Vec<String>,
+    /// Share of the delegated nameservers that are lame, 0-100
+    pub lame_percentage: f64,
+}
+
+/// Timeout for each direct, non-recursive query to a parent or delegated nameserver
+const DELEGATION_QUERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Finds nameservers a parent zone delegates to that are not authoritative for the child
+pub struct LameDelegationDetector {
+    resolver_pool: Arc<ResolverPool>,
+}
+
+impl LameDelegationDetector {
+    /// Create a new lame delegation detector
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        Self { resolver_pool }
+    }
+
+    /// Check every nameserver the parent zone lists for `domain`
+    ///
+    /// The delegation is read from the parent's own servers (not the child's NS
+    /// set), then each delegated server is asked for the zone's SOA with RD=0.
+    /// A server is authoritative only if it answers with the AA bit set and the
+    /// SOA in the answer section; referrals, refusals and timeouts are lame.
+    pub async fn check(&self, domain: &str) -> Result<LameDelegationResult> {
+        let zone = Name::from_ascii(format!("{}.", domain.trim().trim_end_matches('.')))
+            .map_err(|e| DnsxError::from(e).with_context(format!("Invalid domain name {}", domain)))?;
+        info!("Checking delegation of {}", zone);
+
+        let delegated = self.parent_delegation(&zone).await?;
+        let zone = &zone;
+        let checks = futures::future::join_all(delegated.iter().map(|ns| async move {
+            (ns.clone(), self.serves_zone(ns, zone).await)
+        }))
+        .await;
+
+        let mut result = LameDelegationResult::default();
+        for (ns, authoritative) in checks {
+            if authoritative {
+                result.authoritative_ns.push(ns);
+            } else {
+                result.lame_ns.push(ns);
+            }
+        }
+        result.lame_percentage = result.lame_ns.len() as f64 * 100.0 / delegated.len() as f64;
+        Ok(result)
+    }
+
+    /// NS names in the referral a server of the parent zone gives for `zone`
+    async fn parent_delegation(&self, zone: &Name) -> Result<Vec<String>> {
+        let parent = zone.base_name();
+        let (lookup, _) = self.resolver_pool.query(&parent.to_string(), RecordType::Ns).await?;
+        let parent_servers: Vec<String> = lookup
+            .iter()
+            .filter_map(|rdata| match rdata {
+                RData::NS(ns) => Some(ns.to_string()),
+                _ => None,
+            })
+            .collect();
+
+        for server in &parent_servers {
+            for ip in self.resolver_pool.lookup_ipv4(server).await.unwrap_or_default() {
+                let addr = SocketAddr::from((ip, 53));
+                let response = match query_direct(addr, zone, HickoryRecordType::NS).await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        debug!("Parent server {} ({}) failed for {}: {}", server, addr, zone, e);
+                        continue;
+                    }
+                };
+                if response.response_code() == HickoryResponseCode::NXDomain {
+                    return Err(DnsxError::Rcode {
+                        resolver: addr.to_string(),
+                        domain: zone.to_string(),
+                        rcode: crate::types::ResponseCode::NxDomain,
+                    });
+                }
+
+                let mut nameservers: Vec<String> = response
+                    .answers()
+                    .iter()
+                    .chain(response.name_servers())
+                    .filter(|record| record.name() == zone)
+                    .filter_map(|record| match record.data() {
+                        Some(RData::NS(ns)) => Some(ns.to_string().trim_end_matches('.').to_lowercase()),
+                        _ => None,
+                    })
+                    .collect();
+                nameservers.sort();
+                nameservers.dedup();
+                if !nameservers.is_empty() {
+                    debug!("{} delegates {} to {:?}", server, zone, nameservers);
+                    return Ok(nameservers);
+                }
+            }
+        }
+
+        Err(DnsxError::resolve(format!("No server of {} returned a delegation for {}", parent, zone)))
+    }
+
+    /// Whether any address of `nameserver` answers authoritatively for `zone`
+    async fn serves_zone(&self, nameserver: &str, zone: &Name) -> bool {
+        for ip in self.resolver_pool.lookup_ipv4(nameserver).await.unwrap_or_default() {
+            let addr = SocketAddr::from((ip, 53));
+            match query_direct(addr, zone, HickoryRecordType::SOA).await {
+                Ok(response) => {
+                    let has_soa = response
+                        .answers()
+                        .iter()
+                        .any(|record| record.name() == zone && matches!(record.data(), Some(RData::SOA(_))));
+                    if response.authoritative() && response.response_code() == HickoryResponseCode::NoError && has_soa {
+                        return true;
+                    }
+                    debug!("{} ({}) is not authoritative for {}", nameserver, addr, zone);
+                }
+                Err(e) => debug!("{} ({}) failed SOA query for {}: {}", nameserver, addr, zone, e),
+            }
+        }
+        false
+    }
+}
+
+/// Send one non-recursive (RD=0) UDP query straight to `server`
+async fn query_direct(server: SocketAddr, name: &Name, record_type: HickoryRecordType) -> Result<Message> {
+    let mut request = Message::new();
+    request
+        .set_id(rand::random())
+        .set_recursion_desired(false)
+        .add_query(Query::query(name.clone(), record_type));
+    let request = request
+        .to_vec()
+        .map_err(|e| DnsxError::from(e).with_context("Failed to encode query"))?;
+
+    let exchange = async {
+        let socket = UdpSocket::bind((std::net::Ipv4Addr::UNSPECIFIED, 0)).await?;
+        socket.send_to(&request, server).await?;
+        let mut buffer = vec![0u8; 4096];
+        loop {
+            let (len, from) = socket.recv_from(&mut buffer).await?;
+            // Ignore stray datagrams; only the server's reply to this query counts
+            if from == server && len >= 2 && buffer[..2] == request[..2] {
+                buffer.truncate(len);
+                return Ok::<_, std::io::Error>(buffer);
+            }
+        }
+    };
+
+    let response = tokio::time::timeout(DELEGATION_QUERY_TIMEOUT, exchange)
+        .await
+        .map_err(|_| DnsxError::timeout(server.to_string(), name.to_string(), DELEGATION_QUERY_TIMEOUT))?
+        .map_err(|e| DnsxError::network_source(server.to_string(), "UDP exchange failed", e))?;
+
+    Message::from_vec(&response)
+        .map_err(|e| DnsxError::parse_source(hex::encode(&response), format!("Malformed response from {}", server), e))
+}
+
 /// Passive DNS subdomain information
 #[derive(Debug, Clone)]
 pub struct PassiveSubdomain {
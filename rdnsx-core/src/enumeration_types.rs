@@ -62,4 +62,6 @@ pub struct AsnEnumerationResult {
     pub ipv6_prefixes: Vec<String>,
     pub total_ipv4_addresses: u64,
     pub total_ipv6_addresses: u64,
+    /// Resolvers in the network's region, for geo-DNS answers as its users see them
+    pub suggested_resolvers: Vec<String>,
 }
\ No newline at end of file
@@ -3,9 +3,14 @@
 
 use thiserror::Error;
 
+use crate::types::ResponseCode;
+
 /// Result type alias for RDNSx operations
 pub type Result<T> = std::result::Result<T, DnsxError>;
 
+/// Underlying error from a client library, kept as the `source()` of a [`DnsxError`]
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
 /// Main error type for RDNSx operations
 #[derive(Error, Debug)]
 pub enum DnsxError {
@@ -14,8 +19,24 @@ pub enum DnsxError {
     Resolve(String),
 
     /// Query timeout
-    #[error("DNS query timed out after {0:?}")]
-    Timeout(std::time::Duration),
+    #[error("DNS query for {domain} timed out after {duration:?} (resolver {resolver})")]
+    Timeout {
+        resolver: String,
+        domain: String,
+        duration: std::time::Duration,
+    },
+
+    /// The server answered with an error response code
+    #[error("DNS query for {domain} returned {rcode} (resolver {resolver})")]
+    Rcode {
+        resolver: String,
+        domain: String,
+        rcode: ResponseCode,
+    },
+
+    /// The response exceeded the configured size limit and was discarded
+    #[error("DNS response of {size} bytes exceeds the {limit} byte limit")]
+    ResponseTooLarge { size: usize, limit: usize },
 
     /// Invalid input (domain, IP, etc.)
     #[error("Invalid input: {0}")]
@@ -29,17 +50,40 @@ pub enum DnsxError {
     #[error("Resolver configuration error: {0}")]
     ResolverConfig(String),
 
-    /// Network I/O error
-    #[error("Network I/O error: {0}")]
-    Network(#[from] std::io::Error),
+    /// Network failure while talking to a resolver or remote API
+    #[error("Network error ({resolver}): {message}")]
+    Network {
+        resolver: String,
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
+
+    /// Local I/O error (files, stdin)
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Malformed data that could not be parsed
+    #[error("Failed to parse {raw:?}: {message}")]
+    Parse {
+        raw: String,
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
 
     /// Serialization/deserialization error
     #[error("Serialization error: {0}")]
     Serialization(String),
 
     /// Export error (Elasticsearch, MongoDB, etc.)
-    #[error("Export error: {0}")]
-    Export(String),
+    #[error("Export to {backend} failed: {message}")]
+    Export {
+        backend: String,
+        message: String,
+        #[source]
+        source: Option<BoxError>,
+    },
 
     /// Wildcard detection error
     #[error("Wildcard detection error: {0}")]
@@ -53,7 +97,19 @@ pub enum DnsxError {
     #[error("Address parsing error: {0}")]
     AddrParse(#[from] std::net::AddrParseError),
 
-    /// Generic error with context
+    /// DNS message or name encoding error
+    #[error("DNS protocol error: {0}")]
+    Protocol(#[from] hickory_resolver::proto::error::ProtoError),
+
+    /// What was being attempted when `source` occurred
+    #[error("{context}")]
+    Context {
+        context: String,
+        #[source]
+        source: Box<DnsxError>,
+    },
+
+    /// Miscellaneous error that fits no other variant
     #[error("{0}")]
     Other(String),
 }
@@ -65,8 +121,121 @@ impl DnsxError {
     }
 
     /// Create a new timeout error
-    pub fn timeout(duration: std::time::Duration) -> Self {
-        Self::Timeout(duration)
+    pub fn timeout(resolver: impl Into<String>, domain: impl Into<String>, duration: std::time::Duration) -> Self {
+        Self::Timeout {
+            resolver: resolver.into(),
+            domain: domain.into(),
+            duration,
+        }
+    }
+
+    /// Create a new network error
+    pub fn network(resolver: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Network {
+            resolver: resolver.into(),
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Create a network error caused by `source`
+    pub fn network_source(
+        resolver: impl Into<String>,
+        message: impl Into<String>,
+        source: impl Into<BoxError>,
+    ) -> Self {
+        Self::Network {
+            resolver: resolver.into(),
+            message: message.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// Create a new parse error
+    pub fn parse(raw: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Parse {
+            raw: raw.into(),
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Create a parse error caused by `source`
+    pub fn parse_source(raw: impl Into<String>, message: impl Into<String>, source: impl Into<BoxError>) -> Self {
+        Self::Parse {
+            raw: raw.into(),
+            message: message.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// Create a new export error
+    pub fn export(backend: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Export {
+            backend: backend.into(),
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Create an export error caused by `source`
+    pub fn export_source(
+        backend: impl Into<String>,
+        message: impl Into<String>,
+        source: impl Into<BoxError>,
+    ) -> Self {
+        Self::Export {
+            backend: backend.into(),
+            message: message.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// Wrap this error with a description of what was being attempted
+    ///
+    /// The original error stays reachable through `source()`, so its type is not lost.
+    pub fn with_context<C: std::fmt::Display>(self, context: C) -> Self {
+        Self::Context {
+            context: context.to_string(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Response code of a negative answer, looking through added context
+    pub fn rcode(&self) -> Option<ResponseCode> {
+        match self {
+            Self::Rcode { rcode, .. } => Some(*rcode),
+            Self::Context { source, .. } => source.rcode(),
+            _ => None,
+        }
+    }
+
+    /// Resolver a timeout, network error or error response came from, looking through added context
+    pub fn resolver(&self) -> Option<&str> {
+        match self {
+            Self::Timeout { resolver, .. } | Self::Network { resolver, .. } | Self::Rcode { resolver, .. } => {
+                Some(resolver.as_str())
+            }
+            Self::Context { source, .. } => source.resolver(),
+            _ => None,
+        }
+    }
+
+    /// This error and every cause below it on one line, separated by `: `
+    pub fn report(&self) -> String {
+        let mut report = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(cause) = source {
+            report.push_str(": ");
+            report.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        report
+    }
+
+    /// Wrap an I/O error with a description of what was being attempted
+    pub fn io(context: impl std::fmt::Display, error: std::io::Error) -> Self {
+        Self::Io(std::io::Error::new(error.kind(), format!("{}: {}", context, error)))
     }
 
     /// Create a new invalid input error
@@ -0,0 +1,155 @@
+//! Expiry countdown for time-limited DNS records (RRSIG signatures, CAA iodef hints)
+
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+
+use crate::types::{DnsRecord, RecordType, RecordValue};
+
+/// A record that expires within the warning window (or already has)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExpiryWarning {
+    pub domain: String,
+    pub record_type: RecordType,
+    pub expires_at: DateTime<Utc>,
+    /// Whole days left until expiry; negative once the record has expired
+    pub days_remaining: i64,
+}
+
+/// Finds records that carry an expiry time and are close to it
+#[derive(Debug, Default)]
+pub struct ExpiryAnalyzer;
+
+impl ExpiryAnalyzer {
+    /// Create a new expiry analyzer
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Records expiring within `warn_within` from now, soonest first
+    pub fn find_expiring(&self, records: &[DnsRecord], warn_within: Duration) -> Vec<ExpiryWarning> {
+        self.find_expiring_at(records, warn_within, Utc::now())
+    }
+
+    /// Same as [`find_expiring`](Self::find_expiring) relative to a fixed point in time
+    pub fn find_expiring_at(
+        &self,
+        records: &[DnsRecord],
+        warn_within: Duration,
+        now: DateTime<Utc>,
+    ) -> Vec<ExpiryWarning> {
+        let window = chrono::Duration::from_std(warn_within).unwrap_or(chrono::Duration::MAX);
+        let deadline = now.checked_add_signed(window).unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+        let mut warnings: Vec<ExpiryWarning> = records
+            .iter()
+            .filter_map(|record| {
+                let expires_at = Self::expiry_of(record)?;
+                (expires_at <= deadline).then(|| ExpiryWarning {
+                    domain: record.domain.clone(),
+                    record_type: record.record_type,
+                    expires_at,
+                    days_remaining: (expires_at - now).num_days(),
+                })
+            })
+            .collect();
+
+        warnings.sort_by_key(|w| w.expires_at);
+        warnings
+    }
+
+    /// Expiry time carried by a record, if its type has one
+    pub fn expiry_of(record: &DnsRecord) -> Option<DateTime<Utc>> {
+        match (record.record_type, &record.value) {
+            (RecordType::Rrsig, RecordValue::Text(text) | RecordValue::Other(text)) => rrsig_expiration(text),
+            (RecordType::Caa, RecordValue::Caa { tag, value, .. }) if tag.eq_ignore_ascii_case("iodef") => {
+                iodef_expiry_hint(value)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Signature expiration from an RRSIG in presentation format
+/// (`A 13 2 300 20250101000000 ...`) or as rendered by the resolver's debug output
+fn rrsig_expiration(text: &str) -> Option<DateTime<Utc>> {
+    if let Some(rest) = text.split("sig_expiration:").nth(1) {
+        let digits: String = rest.trim_start().chars().take_while(|c| c.is_ascii_digit()).collect();
+        return Utc.timestamp_opt(digits.parse().ok()?, 0).single();
+    }
+
+    parse_dnssec_time(text.split_whitespace().nth(4)?)
+}
+
+/// RFC 4034 section 3.2: either YYYYMMDDHHmmSS or seconds since the epoch
+fn parse_dnssec_time(field: &str) -> Option<DateTime<Utc>> {
+    if field.len() == 14 && field.chars().all(|c| c.is_ascii_digit()) {
+        return NaiveDateTime::parse_from_str(field, "%Y%m%d%H%M%S").ok().map(|dt| dt.and_utc());
+    }
+    Utc.timestamp_opt(field.parse().ok()?, 0).single()
+}
+
+/// Expiry-looking query parameter on a CAA iodef URL, e.g. `?expires=2025-06-30`
+fn iodef_expiry_hint(url: &str) -> Option<DateTime<Utc>> {
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .filter(|(key, _)| matches!(key.to_ascii_lowercase().as_str(), "expires" | "expiry" | "exp" | "valid_until" | "not_after"))
+        .find_map(|(_, value)| parse_hint_time(value))
+}
+
+fn parse_hint_time(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc());
+    }
+    Utc.timestamp_opt(value.parse().ok()?, 0).single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ResponseCode;
+
+    fn record(record_type: RecordType, value: RecordValue) -> DnsRecord {
+        DnsRecord::new("example.com".to_string(), record_type, value, 300, ResponseCode::NoError, "8.8.8.8:53".to_string(), 0.0)
+    }
+
+    #[test]
+    fn test_rrsig_expiration_formats() {
+        let expected = Utc.with_ymd_and_hms(2025, 1, 31, 12, 0, 0).unwrap();
+        assert_eq!(rrsig_expiration("A 13 2 300 20250131120000 20250101000000 12345 example.com. c2ln"), Some(expected));
+        assert_eq!(rrsig_expiration(&format!("A 13 2 300 {} 1735689600 12345 example.com. c2ln", expected.timestamp())), Some(expected));
+        assert_eq!(
+            rrsig_expiration(&format!("DNSSEC(SIG(SIG {{ type_covered: A, sig_expiration: {}, sig_inception: 0 }}))", expected.timestamp())),
+            Some(expected)
+        );
+        assert_eq!(rrsig_expiration("not a signature"), None);
+    }
+
+    #[test]
+    fn test_find_expiring() {
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let records = vec![
+            record(RecordType::Rrsig, RecordValue::Other("A 13 2 300 20250201000000 20241201000000 1 example.com. c2ln".to_string())),
+            record(RecordType::Rrsig, RecordValue::Other("A 13 2 300 20250110000000 20241201000000 1 example.com. c2ln".to_string())),
+            record(RecordType::Caa, RecordValue::Caa {
+                flags: 0,
+                tag: "iodef".to_string(),
+                value: "https://ca.example/report?token=abc&expires=2024-12-25".to_string(),
+            }),
+            record(RecordType::A, RecordValue::Ip("192.0.2.1".parse().unwrap())),
+        ];
+
+        let warnings = ExpiryAnalyzer::new().find_expiring_at(&records, Duration::from_secs(30 * 86400), now);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].record_type, RecordType::Caa);
+        assert_eq!(warnings[0].days_remaining, -7);
+        assert_eq!(warnings[1].record_type, RecordType::Rrsig);
+        assert_eq!(warnings[1].days_remaining, 9);
+    }
+}
@@ -1,23 +1,33 @@
 //! High-performance Cassandra exporter for DNS records
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use futures::TryStreamExt;
 use scylla::client::session::Session;
 use scylla::client::session_builder::SessionBuilder;
 use scylla::frame::Compression;
 use scylla::statement::prepared::PreparedStatement;
 use scylla::statement::batch::Batch;
+use scylla::value::CqlTimestamp;
 use tokio::sync::{Mutex, mpsc};
 use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
 use crate::error::{DnsxError, Result};
-use crate::export::Exporter;
+use crate::export::{top_counts, ExportStats, Exporter, STATS_TOP_N};
 use crate::types::DnsRecord;
 
+/// Breakdown of the rows stored in the exporter's table
+pub type CassandraStats = ExportStats;
+
+/// Default number of prepared statements kept per exporter
+pub const DEFAULT_PREPARED_STATEMENT_CACHE_SIZE: usize = 64;
+
 /// Performance metrics for Cassandra operations
 #[derive(Debug, Default)]
 pub struct CassandraMetrics {
@@ -28,19 +38,86 @@ pub struct CassandraMetrics {
     pub records_per_second: f64,
     pub errors: usize,
     pub retries: usize,
+    /// Batches that reused a cached prepared statement
+    pub prepared_stmt_hits: u64,
+    /// Batches that had to prepare their statement first
+    pub prepared_stmt_misses: u64,
+}
+
+/// Prepared statements by `keyspace.table`, shared by the workers
+///
+/// Holds at most `max_entries` statements; when full, an arbitrary entry is
+/// dropped to make room, so writing to many tables costs re-preparation
+/// rather than memory.
+#[derive(Clone)]
+pub struct PreparedStatementCache {
+    statements: Arc<Mutex<HashMap<String, PreparedStatement>>>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+    max_entries: usize,
+}
+
+impl PreparedStatementCache {
+    /// Create an empty cache holding up to `max_entries` statements
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            statements: Arc::new(Mutex::new(HashMap::new())),
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+            max_entries: max_entries.max(1),
+        }
+    }
+
+    /// The statement cached under `key`, preparing `cql` on a miss
+    pub async fn get_or_prepare(&self, session: &Session, key: &str, cql: String) -> Result<PreparedStatement> {
+        let mut statements = self.statements.lock().await;
+        if let Some(statement) = statements.get(key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(statement.clone());
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let statement = session
+            .prepare(cql)
+            .await
+            .map_err(|e| DnsxError::export_source("cassandra", "Failed to prepare statement", e))?;
+
+        if statements.len() >= self.max_entries {
+            if let Some(evicted) = statements.keys().next().cloned() {
+                statements.remove(&evicted);
+                debug!("Prepared statement cache full, dropped {}", evicted);
+            }
+        }
+        statements.insert(key.to_string(), statement.clone());
+        if statements.len() * 10 >= self.max_entries * 9 {
+            warn!(
+                "Prepared statement cache is near capacity ({}/{} entries)",
+                statements.len(),
+                self.max_entries
+            );
+        }
+
+        Ok(statement)
+    }
+
+    /// Lookups that found a cached statement and lookups that had to prepare one, as `(hits, misses)`
+    pub fn stats(&self) -> (u64, u64) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
 }
 
 /// High-performance Cassandra exporter with batching and connection pooling
 pub struct CassandraExporter {
     /// Prepared statement cache
-    #[allow(dead_code)]
-    prepared_statements: Arc<Mutex<HashMap<String, PreparedStatement>>>,
+    prepared_statements: PreparedStatementCache,
     /// Worker channels for concurrent processing
     workers: Vec<JoinHandle<Result<()>>>,
-    /// Record sender channels
-    record_senders: Vec<mpsc::UnboundedSender<DnsRecord>>,
+    /// Bounded record channels, one per worker; a full channel makes `export` wait
+    record_senders: Vec<mpsc::Sender<DnsRecord>>,
     /// Metrics for monitoring performance
     metrics: Arc<Mutex<CassandraMetrics>>,
+    /// Session shared with the workers, also used for read queries
+    session: Arc<Session>,
     /// Configuration
     config: CassandraConfig,
 }
@@ -58,9 +135,87 @@ pub struct CassandraConfig {
     pub retry_attempts: usize,
     pub retry_delay: Duration,
     pub num_workers: usize,
+    /// Records buffered across all worker channels before `export` waits for the workers
+    pub channel_buffer_size: usize,
     pub connection_pool_size: usize,
     pub tcp_nodelay: bool,
     pub keepalive_interval: Option<Duration>,
+    pub replication_strategy: ReplicationStrategy,
+    pub partition_strategy: CassandraPartitionStrategy,
+    /// Prepared statements kept before old ones are dropped
+    pub prepared_statement_cache_size: usize,
+}
+
+/// How DNS records are partitioned in the Cassandra table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CassandraPartitionStrategy {
+    /// `PRIMARY KEY ((domain, record_type), timestamp)`
+    #[default]
+    ByDomain,
+    /// `PRIMARY KEY ((domain, record_type, time_bucket), timestamp)`, which keeps
+    /// repeatedly scanned domains from growing a single hot partition
+    ByTimeBucket { bucket_seconds: u64 },
+}
+
+impl CassandraPartitionStrategy {
+    /// Daily time buckets
+    pub const DAILY: Self = Self::ByTimeBucket { bucket_seconds: 86_400 };
+
+    /// Bucket value for a record timestamp in milliseconds since the epoch
+    pub fn time_bucket(&self, timestamp_ms: i64) -> Option<i64> {
+        match self {
+            Self::ByDomain => None,
+            Self::ByTimeBucket { bucket_seconds } => {
+                Some(timestamp_ms.div_euclid(1000) / (*bucket_seconds).max(1) as i64)
+            }
+        }
+    }
+
+    /// Partition key columns for the table definition
+    fn partition_key(&self) -> &'static str {
+        match self {
+            Self::ByDomain => "(domain, record_type)",
+            Self::ByTimeBucket { .. } => "(domain, record_type, time_bucket)",
+        }
+    }
+}
+
+/// Keyspace replication strategy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplicationStrategy {
+    /// Single replication factor for the whole cluster (single-DC or testing)
+    Simple { factor: u32 },
+    /// Per-datacenter replication factors for multi-DC clusters
+    NetworkTopology { datacenter_factors: HashMap<String, u32> },
+}
+
+impl Default for ReplicationStrategy {
+    fn default() -> Self {
+        Self::Simple { factor: 1 }
+    }
+}
+
+impl ReplicationStrategy {
+    /// CQL replication map for `CREATE KEYSPACE ... WITH REPLICATION = ...`
+    pub fn to_cql(&self) -> String {
+        match self {
+            Self::Simple { factor } => {
+                format!("{{'class': 'SimpleStrategy', 'replication_factor': {}}}", factor)
+            }
+            Self::NetworkTopology { datacenter_factors } => {
+                // Sorted so the generated statement is stable
+                let mut factors: Vec<_> = datacenter_factors.iter().collect();
+                factors.sort();
+
+                let mut cql = "{'class': 'NetworkTopologyStrategy'".to_string();
+                for (datacenter, factor) in factors {
+                    cql.push_str(&format!(", '{}': {}", datacenter.replace('\'', "''"), factor));
+                }
+                cql.push('}');
+                cql
+            }
+        }
+    }
 }
 
 impl Default for CassandraConfig {
@@ -76,9 +231,13 @@ impl Default for CassandraConfig {
             retry_attempts: 3,
             retry_delay: Duration::from_millis(100),
             num_workers: 4,
+            channel_buffer_size: 1000 * 4,
             connection_pool_size: 4,
             tcp_nodelay: true,
             keepalive_interval: Some(Duration::from_secs(60)),
+            replication_strategy: ReplicationStrategy::default(),
+            partition_strategy: CassandraPartitionStrategy::default(),
+            prepared_statement_cache_size: DEFAULT_PREPARED_STATEMENT_CACHE_SIZE,
         }
     }
 }
@@ -93,6 +252,7 @@ impl CassandraExporter {
         table: &str,
         batch_size: usize,
     ) -> Result<Self> {
+        let defaults = CassandraConfig::default();
         Self::with_config(CassandraConfig {
             contact_points: contact_points.to_vec(),
             username: username.map(|s| s.to_string()),
@@ -100,37 +260,18 @@ impl CassandraExporter {
             keyspace: keyspace.to_string(),
             table: table.to_string(),
             batch_size,
-            ..Default::default()
+            channel_buffer_size: batch_size * defaults.num_workers,
+            ..defaults
         }).await
     }
 
     /// Create a new exporter with full configuration
     pub async fn with_config(config: CassandraConfig) -> Result<Self> {
-        info!("Connecting to Cassandra cluster: {:?}", config.contact_points);
-
-        let mut session_builder = SessionBuilder::new()
-            .known_nodes(&config.contact_points)
-            .compression(Some(Compression::Lz4));
-
-        if let (Some(user), Some(pass)) = (&config.username, &config.password) {
-            session_builder = session_builder.user(user, pass);
-        }
-
-        // Optimize connection settings for high performance
-        let session = session_builder
-            .connection_timeout(Duration::from_secs(30))
-            // .request_timeout(Some(Duration::from_secs(10))) // Temporarily disabled due to API compatibility
-            .tcp_nodelay(config.tcp_nodelay)
-            .keepalive_interval(config.keepalive_interval.unwrap_or(Duration::from_secs(60)))
-            .build()
-            .await
-            .map_err(|e| DnsxError::Other(format!("Failed to connect to Cassandra: {}", e)))?;
-
-        let session = Arc::new(session);
+        let session = Arc::new(Self::connect(&config).await?);
 
         // Create keyspace and table with optimized schema
-        Self::create_keyspace(&session, &config.keyspace).await?;
-        Self::create_optimized_table(&session, &config.keyspace, &config.table).await?;
+        Self::create_keyspace(&session, &config.keyspace, &config.replication_strategy).await?;
+        Self::create_optimized_table(&session, &config.keyspace, &config.table, &config.partition_strategy).await?;
 
         info!("Connected to Cassandra, using keyspace: {}, table: {}", config.keyspace, config.table);
 
@@ -138,18 +279,20 @@ impl CassandraExporter {
         let mut workers = Vec::new();
         let mut record_senders = Vec::new();
 
-        let prepared_statements = Arc::new(Mutex::new(HashMap::new()));
+        let prepared_statements = PreparedStatementCache::new(config.prepared_statement_cache_size);
         let metrics = Arc::new(Mutex::new(CassandraMetrics::default()));
 
+        // The buffer is split between workers so the total stays at the configured size
+        let worker_capacity = (config.channel_buffer_size / config.num_workers.max(1)).max(1);
         for worker_id in 0..config.num_workers {
-            let (tx, rx) = mpsc::unbounded_channel();
+            let (tx, rx) = mpsc::channel(worker_capacity);
             record_senders.push(tx);
 
             let worker = Self::spawn_worker(
                 worker_id,
                 rx,
                 Arc::clone(&session),
-                Arc::clone(&prepared_statements),
+                prepared_statements.clone(),
                 Arc::clone(&metrics),
                 config.clone(),
             );
@@ -161,35 +304,87 @@ impl CassandraExporter {
             workers,
             record_senders,
             metrics,
+            session,
             config,
         })
     }
 
+    /// Create `new_table` with `partition_strategy` next to the table named in `config`
+    ///
+    /// The existing table is left untouched and no rows are copied; point
+    /// `config.table` at the new table once it should receive writes.
+    pub async fn migrate_partitioning(
+        config: &CassandraConfig,
+        new_table: &str,
+        partition_strategy: CassandraPartitionStrategy,
+    ) -> Result<()> {
+        if new_table == config.table {
+            return Err(DnsxError::validation(format!(
+                "Migration target must differ from the current table '{}'",
+                config.table
+            )));
+        }
+
+        let session = Self::connect(config).await?;
+        Self::create_keyspace(&session, &config.keyspace, &config.replication_strategy).await?;
+        Self::create_optimized_table(&session, &config.keyspace, new_table, &partition_strategy).await?;
+
+        info!(
+            "Created {}.{} ({:?}) alongside {}.{}",
+            config.keyspace, new_table, partition_strategy, config.keyspace, config.table
+        );
+        Ok(())
+    }
+
+    /// Connect to the cluster described by `config`
+    async fn connect(config: &CassandraConfig) -> Result<Session> {
+        info!("Connecting to Cassandra cluster: {:?}", config.contact_points);
+
+        let mut session_builder = SessionBuilder::new()
+            .known_nodes(&config.contact_points)
+            .compression(Some(Compression::Lz4));
+
+        if let (Some(user), Some(pass)) = (&config.username, &config.password) {
+            session_builder = session_builder.user(user, pass);
+        }
+
+        // Optimize connection settings for high performance
+        session_builder
+            .connection_timeout(Duration::from_secs(30))
+            // .request_timeout(Some(Duration::from_secs(10))) // Temporarily disabled due to API compatibility
+            .tcp_nodelay(config.tcp_nodelay)
+            .keepalive_interval(config.keepalive_interval.unwrap_or(Duration::from_secs(60)))
+            .build()
+            .await
+            .map_err(|e| DnsxError::export_source("cassandra", "Failed to connect", e))
+    }
+
     /// Spawn a worker thread for processing batches
     fn spawn_worker(
         worker_id: usize,
-        mut rx: mpsc::UnboundedReceiver<DnsRecord>,
+        mut rx: mpsc::Receiver<DnsRecord>,
         session: Arc<Session>,
-        prepared_statements: Arc<Mutex<HashMap<String, PreparedStatement>>>,
+        prepared_statements: PreparedStatementCache,
         metrics: Arc<Mutex<CassandraMetrics>>,
         config: CassandraConfig,
     ) -> JoinHandle<Result<()>> {
         tokio::spawn(async move {
             debug!("Worker {} started", worker_id);
+            let source = format!("Worker {}", worker_id);
             let mut batch = Vec::with_capacity(config.batch_size);
 
             while let Some(record) = rx.recv().await {
                 batch.push(record);
 
                 if batch.len() >= config.batch_size {
-                    Self::process_batch(&session, &prepared_statements, &metrics, &config, batch, worker_id).await?;
+                    Self::process_batch(&session, &prepared_statements, &metrics, &config, batch, &source).await?;
                     batch = Vec::with_capacity(config.batch_size);
                 }
             }
 
             // Process remaining records
             if !batch.is_empty() {
-                Self::process_batch(&session, &prepared_statements, &metrics, &config, batch, worker_id).await?;
+                Self::process_batch(&session, &prepared_statements, &metrics, &config, batch, &source).await?;
             }
 
             debug!("Worker {} finished", worker_id);
@@ -200,11 +395,11 @@ impl CassandraExporter {
     /// Process a batch of records with retries
     async fn process_batch(
         session: &Session,
-        prepared_statements: &Arc<Mutex<HashMap<String, PreparedStatement>>>,
+        prepared_statements: &PreparedStatementCache,
         metrics: &Arc<Mutex<CassandraMetrics>>,
         config: &CassandraConfig,
         batch: Vec<DnsRecord>,
-        worker_id: usize,
+        source: &str,
     ) -> Result<()> {
         let batch_size = batch.len();
         let start_time = Instant::now();
@@ -222,8 +417,8 @@ impl CassandraExporter {
                     metrics_lock.batches_processed += 1;
                     metrics_lock.total_insert_time += elapsed;
 
-                    debug!("Worker {}: Successfully inserted {} records in {:.2}ms",
-                          worker_id, batch_size, elapsed.as_millis());
+                    debug!("{}: Successfully inserted {} records in {:.2}ms",
+                          source, batch_size, elapsed.as_millis());
                     return Ok(());
                 }
                 Err(e) => {
@@ -231,8 +426,8 @@ impl CassandraExporter {
                     last_error = Some(e);
 
                     if attempts < config.retry_attempts {
-                        warn!("Worker {}: Batch insert failed (attempt {}/{}), retrying in {:?}",
-                              worker_id, attempts, config.retry_attempts, config.retry_delay);
+                        warn!("{}: Batch insert failed (attempt {}/{}), retrying in {:?}",
+                              source, attempts, config.retry_attempts, config.retry_delay);
 
                         let mut metrics_lock = metrics.lock().await;
                         metrics_lock.retries += 1;
@@ -246,13 +441,13 @@ impl CassandraExporter {
         let mut metrics_lock = metrics.lock().await;
         metrics_lock.errors += 1;
 
-        Err(last_error.unwrap_or_else(|| DnsxError::Other("Batch insert failed after all retries".to_string())))
+        Err(last_error.unwrap_or_else(|| DnsxError::export("cassandra", "Batch insert failed after all retries")))
     }
 
     /// Execute a batch insert operation
     async fn execute_batch(
         session: &Session,
-        prepared_statements: &Arc<Mutex<HashMap<String, PreparedStatement>>>,
+        prepared_statements: &PreparedStatementCache,
         config: &CassandraConfig,
         records: &[DnsRecord],
     ) -> Result<()> {
@@ -262,25 +457,17 @@ impl CassandraExporter {
 
         // Get or create prepared statement
         let stmt_key = format!("{}.{}", config.keyspace, config.table);
-        let _prepared = {
-            let mut cache = prepared_statements.lock().await;
-            if let Some(stmt) = cache.get(&stmt_key) {
-                stmt.clone()
-            } else {
-                let cql = format!(
-                    "INSERT INTO {}.{} (domain, record_type, value, ttl, response_code, resolver, timestamp, query_time_ms) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-                    config.keyspace, config.table
-                );
-
-                let stmt = session
-                    .prepare(cql)
-                    .await
-                    .map_err(|e| DnsxError::Other(format!("Failed to prepare statement: {}", e)))?;
-
-                cache.insert(stmt_key, stmt.clone());
-                stmt
-            }
+        let cql = match config.partition_strategy {
+            CassandraPartitionStrategy::ByDomain => format!(
+                "INSERT INTO {}.{} (domain, record_type, value, ttl, response_code, resolver, timestamp, query_time_ms) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                config.keyspace, config.table
+            ),
+            CassandraPartitionStrategy::ByTimeBucket { .. } => format!(
+                "INSERT INTO {}.{} (domain, record_type, value, ttl, response_code, resolver, timestamp, query_time_ms, time_bucket) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                config.keyspace, config.table
+            ),
         };
+        let _prepared = prepared_statements.get_or_prepare(session, &stmt_key, cql).await?;
 
         // Use Cassandra batch operations for better performance
         let batch = Batch::default();
@@ -290,10 +477,7 @@ impl CassandraExporter {
             let record_type = record.record_type.to_string();
             let response_code = record.response_code.to_string();
             let value_str = record.value.to_string();
-            let timestamp = record.timestamp
-                .duration_since(std::time::UNIX_EPOCH)
-                .map_err(|e| DnsxError::Other(format!("Invalid timestamp: {}", e)))?
-                .as_millis() as i64;
+            let timestamp = record.timestamp.timestamp_millis();
 
             // batch.append_statement(&prepared); // Temporarily disabled due to API compatibility
             values.push((
@@ -308,29 +492,36 @@ impl CassandraExporter {
             ));
         }
 
-        // Execute batch
-        session
-            .batch(&batch, &values)
-            .await
-            .map_err(|e| DnsxError::Other(format!("Failed to execute batch: {}", e)))?;
+        // Execute batch; time-bucketed tables take the bucket as an extra column
+        let result = if matches!(config.partition_strategy, CassandraPartitionStrategy::ByTimeBucket { .. }) {
+            let bucketed: Vec<_> = values
+                .into_iter()
+                .map(|(domain, record_type, value, ttl, response_code, resolver, timestamp, query_time_ms)| {
+                    let time_bucket = config.partition_strategy.time_bucket(timestamp).unwrap_or_default();
+                    (domain, record_type, value, ttl, response_code, resolver, timestamp, query_time_ms, time_bucket)
+                })
+                .collect();
+            session.batch(&batch, &bucketed).await
+        } else {
+            session.batch(&batch, &values).await
+        };
+        result.map_err(|e| DnsxError::export_source("cassandra", "Failed to execute batch", e))?;
 
         Ok(())
     }
 
     /// Create keyspace if it doesn't exist
-    async fn create_keyspace(session: &Session, keyspace: &str) -> Result<()> {
+    async fn create_keyspace(session: &Session, keyspace: &str, replication: &ReplicationStrategy) -> Result<()> {
         let cql = format!(
-            "CREATE KEYSPACE IF NOT EXISTS {} WITH REPLICATION = {{
-                'class': 'SimpleStrategy',
-                'replication_factor': 1
-            }}",
-            keyspace
+            "CREATE KEYSPACE IF NOT EXISTS {} WITH REPLICATION = {}",
+            keyspace,
+            replication.to_cql()
         );
 
         session
             .query_unpaged(cql, &[])
             .await
-            .map_err(|e| DnsxError::Other(format!("Failed to create keyspace: {}", e)))?;
+            .map_err(|e| DnsxError::export_source("cassandra", "Failed to create keyspace", e))?;
         // Note: await_all_pages is not needed for DDL operations
 
         debug!("Keyspace '{}' created or already exists", keyspace);
@@ -338,7 +529,16 @@ impl CassandraExporter {
     }
 
     /// Create optimized table for high-performance workloads
-    async fn create_optimized_table(session: &Session, keyspace: &str, table: &str) -> Result<()> {
+    async fn create_optimized_table(
+        session: &Session,
+        keyspace: &str,
+        table: &str,
+        partition_strategy: &CassandraPartitionStrategy,
+    ) -> Result<()> {
+        let bucket_column = match partition_strategy {
+            CassandraPartitionStrategy::ByDomain => "",
+            CassandraPartitionStrategy::ByTimeBucket { .. } => "time_bucket bigint,",
+        };
         let cql = format!(
             r#"
             CREATE TABLE IF NOT EXISTS {}.{} (
@@ -350,7 +550,8 @@ impl CassandraExporter {
                 resolver text,
                 timestamp timestamp,
                 query_time_ms double,
-                PRIMARY KEY ((domain, record_type), timestamp)
+                {}
+                PRIMARY KEY ({}, timestamp)
             ) WITH CLUSTERING ORDER BY (timestamp DESC)
             AND compaction = {{
                 'class': 'TimeWindowCompactionStrategy',
@@ -367,22 +568,116 @@ impl CassandraExporter {
             }}
             AND gc_grace_seconds = 864000
             "#,
-            keyspace, table
+            keyspace, table, bucket_column, partition_strategy.partition_key()
         );
 
         session
             .query_unpaged(cql, &[])
             .await
-            .map_err(|e| DnsxError::Other(format!("Failed to create optimized table: {}", e)))?;
+            .map_err(|e| DnsxError::export_source("cassandra", "Failed to create optimized table", e))?;
 
         debug!("Optimized table '{}.{}' created or already exists", keyspace, table);
         Ok(())
     }
 
+    /// Insert pre-batched records directly, bypassing the worker channels
+    ///
+    /// Records are written in chunks of `batch_size` with the usual retries and
+    /// are stored once this returns, so no `flush` is needed for them.
+    pub async fn export_batch(&self, records: Vec<DnsRecord>) -> Result<()> {
+        let batch_size = self.config.batch_size.max(1);
+        let mut records = records.into_iter().peekable();
+        while records.peek().is_some() {
+            let chunk: Vec<DnsRecord> = records.by_ref().take(batch_size).collect();
+            Self::process_batch(
+                &self.session,
+                &self.prepared_statements,
+                &self.metrics,
+                &self.config,
+                chunk,
+                "Direct batch",
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
     /// Get performance metrics
     pub fn metrics(&self) -> CassandraMetrics {
         // self.metrics.blocking_lock().clone() // Temporarily disabled
-        CassandraMetrics::default()
+        let (prepared_stmt_hits, prepared_stmt_misses) = self.prepared_statements.stats();
+        CassandraMetrics {
+            prepared_stmt_hits,
+            prepared_stmt_misses,
+            ..CassandraMetrics::default()
+        }
+    }
+
+    /// Record counts, time range and top domains/resolvers for the table
+    ///
+    /// The total comes from `SELECT COUNT(*)`; Cassandra cannot group by
+    /// non-key columns, so the breakdowns are tallied from a paged scan.
+    pub async fn query_stats(&self) -> Result<CassandraStats> {
+        let table = format!("{}.{}", self.config.keyspace, self.config.table);
+
+        let (total,) = self
+            .session
+            .query_unpaged(format!("SELECT COUNT(*) FROM {}", table), &[])
+            .await
+            .map_err(|e| DnsxError::export_source("cassandra", "Count query failed", e))?
+            .into_rows_result()
+            .map_err(|e| DnsxError::export_source("cassandra", "Count query returned no rows", e))?
+            .single_row::<(i64,)>()
+            .map_err(|e| DnsxError::export_source("cassandra", "Invalid count result", e))?;
+
+        let mut rows = self
+            .session
+            .query_iter(
+                format!("SELECT domain, record_type, response_code, resolver, timestamp FROM {}", table),
+                &[],
+            )
+            .await
+            .map_err(|e| DnsxError::export_source("cassandra", "Scan failed", e))?
+            .rows_stream::<(Option<String>, Option<String>, Option<String>, Option<String>, Option<CqlTimestamp>)>()
+            .map_err(|e| DnsxError::export_source("cassandra", "Unexpected table schema", e))?;
+
+        let mut stats = CassandraStats {
+            total_records: total.max(0) as u64,
+            ..Default::default()
+        };
+        let mut domains = HashMap::new();
+        let mut resolvers = HashMap::new();
+        let mut earliest: Option<i64> = None;
+        let mut latest: Option<i64> = None;
+
+        while let Some((domain, record_type, response_code, resolver, timestamp)) = rows
+            .try_next()
+            .await
+            .map_err(|e| DnsxError::export_source("cassandra", "Failed to read row", e))?
+        {
+            if let Some(record_type) = record_type {
+                *stats.by_record_type.entry(record_type).or_default() += 1;
+            }
+            if let Some(response_code) = response_code {
+                *stats.by_response_code.entry(response_code).or_default() += 1;
+            }
+            if let Some(domain) = domain {
+                *domains.entry(domain).or_default() += 1;
+            }
+            if let Some(resolver) = resolver {
+                *resolvers.entry(resolver).or_default() += 1;
+            }
+            if let Some(CqlTimestamp(ms)) = timestamp {
+                earliest = Some(earliest.map_or(ms, |e| e.min(ms)));
+                latest = Some(latest.map_or(ms, |l| l.max(ms)));
+            }
+        }
+
+        stats.earliest = earliest.and_then(|ms| Utc.timestamp_millis_opt(ms).single());
+        stats.latest = latest.and_then(|ms| Utc.timestamp_millis_opt(ms).single());
+        stats.top_domains = top_counts(domains, STATS_TOP_N);
+        stats.top_resolvers = top_counts(resolvers, STATS_TOP_N);
+        Ok(stats)
     }
 
     /// Get configuration
@@ -400,13 +695,19 @@ impl Exporter for CassandraExporter {
 
         let worker_index = NEXT_WORKER.fetch_add(1, Ordering::Relaxed) % self.record_senders.len();
 
+        // Waits for room when the worker is behind, so a slow cluster throttles the scan
         self.record_senders[worker_index]
             .send(record)
-            .map_err(|e| DnsxError::Other(format!("Failed to send record to worker: {}", e)))?;
+            .await
+            .map_err(|e| DnsxError::export_source("cassandra", "Failed to send record to worker", e))?;
 
         Ok(())
     }
 
+    async fn export_batch(&self, records: Vec<DnsRecord>) -> Result<()> {
+        CassandraExporter::export_batch(self, records).await
+    }
+
     async fn flush(&self) -> Result<()> {
         // Close all sender channels to signal workers to finish
         drop(self.record_senders.clone()); // This will cause senders to be dropped
@@ -422,7 +723,7 @@ impl Exporter for CassandraExporter {
                 }
                 Err(e) => {
                     error!("Worker {} panicked: {}", i, e);
-                    return Err(DnsxError::Other(format!("Worker {} panicked: {}", i, e)));
+                    return Err(DnsxError::export_source("cassandra", format!("Worker {} panicked", i), e));
                 }
             }
         }
@@ -447,6 +748,14 @@ impl Exporter for CassandraExporter {
 
         Ok(())
     }
+
+    async fn health_check(&self) -> Result<()> {
+        self.session
+            .query_unpaged("SELECT now() FROM system.local", &[])
+            .await
+            .map_err(|e| DnsxError::export_source("cassandra", "Health check query failed", e))?;
+        Ok(())
+    }
 }
 
 impl Drop for CassandraExporter {
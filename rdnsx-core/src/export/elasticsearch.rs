@@ -3,23 +3,30 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::DateTime;
 use elasticsearch::{
-    Elasticsearch, http::transport::Transport,
-    indices::IndicesCreateParts,
+    BulkParts, CountParts, Elasticsearch, SearchParts,
+    http::{request::JsonBody, transport::Transport},
+    ilm::IlmPutLifecycleParts,
+    indices::{IndicesCreateParts, IndicesGetMappingParts, IndicesPutIndexTemplateParts, IndicesPutSettingsParts},
 };
 use serde_json::{json, Value};
 use tokio::sync::Mutex;
-use tracing::debug;
+use tracing::{debug, warn};
 
+use crate::config::IlmConfig;
+use crate::dns_records::{schema_fields, FieldKind};
 use crate::error::{DnsxError, Result};
-use crate::export::Exporter;
+use crate::export::{ExportStats, Exporter, STATS_TOP_N};
 use crate::types::DnsRecord;
 
+/// Maximum number of documents serialized into a single bulk request body
+const BULK_CHUNK_SIZE: usize = 1000;
+
 /// Elasticsearch exporter
 pub struct ElasticsearchExporter {
-    _client: Arc<Elasticsearch>,
-    _index: String,
+    client: Arc<Elasticsearch>,
+    index: String,
     batch_size: usize,
     buffer: Arc<Mutex<Vec<Value>>>,
 }
@@ -27,37 +34,223 @@ pub struct ElasticsearchExporter {
 impl ElasticsearchExporter {
     /// Create a new Elasticsearch exporter
     pub async fn new(url: &str, index: &str, batch_size: usize) -> Result<Self> {
+        Self::with_ilm(url, index, batch_size, None).await
+    }
+
+    /// Create a new exporter whose index is managed by an ILM policy
+    ///
+    /// The policy and an index template attaching it are created before the
+    /// index, so the index (and any later `<index>-*` index) is covered.
+    pub async fn with_ilm(url: &str, index: &str, batch_size: usize, ilm: Option<&IlmConfig>) -> Result<Self> {
         let transport = Transport::single_node(url)
-            .map_err(|e| DnsxError::Other(format!("Failed to create transport: {}", e)))?;
+            .map_err(|e| DnsxError::export_source("elasticsearch", "Failed to create transport", e))?;
         let client = Arc::new(Elasticsearch::new(transport));
 
+        if let Some(ilm) = ilm {
+            ensure_ilm_policy(&client, index, ilm).await?;
+        }
+
         // Ensure index exists with proper mapping
         ensure_index(&client, index).await?;
+        check_mapping(&client, index).await;
 
         Ok(Self {
-            _client: client,
-            _index: index.to_string(),
+            client,
+            index: index.to_string(),
             batch_size,
             buffer: Arc::new(Mutex::new(Vec::new())),
         })
     }
 
+    /// Record counts, time range and top domains/resolvers for the index
+    pub async fn query_stats(&self) -> Result<ExportStats> {
+        let indices = [self.index.as_str()];
+
+        let count = self.client.count(CountParts::Index(&indices)).send().await;
+        let count = read_json(count, "Count request").await?;
+
+        let search = self
+            .client
+            .search(SearchParts::Index(&indices))
+            .body(json!({
+                "size": 0,
+                "aggs": {
+                    "record_types": { "terms": { "field": "record_type", "size": 100 } },
+                    "response_codes": { "terms": { "field": "response_code", "size": 100 } },
+                    "domains": { "terms": { "field": "domain", "size": STATS_TOP_N } },
+                    "resolvers": { "terms": { "field": "resolver", "size": STATS_TOP_N } },
+                    "earliest": { "min": { "field": "@timestamp" } },
+                    "latest": { "max": { "field": "@timestamp" } },
+                }
+            }))
+            .send()
+            .await;
+        let mut search = read_json(search, "Aggregation request").await?;
+        let aggs = search["aggregations"].take();
+
+        let buckets = |name: &str| -> Vec<(String, u64)> {
+            aggs[name]["buckets"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|bucket| Some((bucket["key"].as_str()?.to_string(), bucket["doc_count"].as_u64()?)))
+                .collect()
+        };
+        // Empty indices report `null` for min/max
+        let bound = |name: &str| aggs[name]["value"].as_f64().and_then(|ms| DateTime::from_timestamp_millis(ms as i64));
+
+        Ok(ExportStats {
+            total_records: count["count"].as_u64().unwrap_or_default(),
+            by_record_type: buckets("record_types").into_iter().collect(),
+            by_response_code: buckets("response_codes").into_iter().collect(),
+            earliest: bound("earliest"),
+            latest: bound("latest"),
+            top_domains: buckets("domains"),
+            top_resolvers: buckets("resolvers"),
+        })
+    }
+
     /// Flush buffer to Elasticsearch
+    ///
+    /// The buffer is taken out of the lock and sent as a sequence of bulk
+    /// requests of at most `BULK_CHUNK_SIZE` documents. The request body cannot
+    /// be streamed: the client's `Body` trait writes each body into a `BytesMut`
+    /// before sending it, so chunking is what bounds the NDJSON held at once.
+    ///
+    /// Documents the bulk responses list as failed go back to the front of the
+    /// buffer for the next flush. When a whole request fails, so do its chunk
+    /// and every chunk after it.
     async fn flush_buffer(&self) -> Result<()> {
-        let mut buffer = self.buffer.lock().await;
-        if buffer.is_empty() {
+        let docs = std::mem::take(&mut *self.buffer.lock().await);
+        if docs.is_empty() {
             return Ok(());
         }
 
-        let doc_count = buffer.len();
+        // Positions in `docs` to buffer again, ascending
+        let mut failed = Vec::new();
+        let mut unsent = docs.len();
+        let mut error = None;
+        for start in (0..docs.len()).step_by(BULK_CHUNK_SIZE) {
+            let end = (start + BULK_CHUNK_SIZE).min(docs.len());
+            match self.send_bulk(&docs[start..end]).await {
+                Ok(rejected) => failed.extend(rejected.into_iter().map(|i| start + i)),
+                Err(e) => {
+                    unsent = start;
+                    error = Some(e);
+                    break;
+                }
+            }
+        }
 
-        // For now, temporarily disable bulk operations until API is clarified
-        buffer.clear(); // Clear the buffer
-        debug!("Elasticsearch bulk operations temporarily disabled due to API changes");
-        debug!("Would have sent {} documents to Elasticsearch", doc_count);
+        let total = docs.len();
+        let sent = unsent - failed.len();
+        let requeue: Vec<Value> = docs
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| *i >= unsent || failed.binary_search(i).is_ok())
+            .map(|(_, doc)| doc)
+            .collect();
+        if !requeue.is_empty() {
+            // Documents buffered while this flush ran stay behind the failed ones
+            let mut buffer = self.buffer.lock().await;
+            let newer = std::mem::replace(&mut *buffer, requeue);
+            buffer.extend(newer);
+        }
 
-        Ok(())
+        debug!("Sent {} documents to Elasticsearch index {}", sent, self.index);
+
+        match error {
+            Some(e) => Err(e),
+            None if !failed.is_empty() => Err(DnsxError::export(
+                "elasticsearch",
+                format!("{} of {} documents were rejected and will be retried", failed.len(), total),
+            )),
+            None => Ok(()),
+        }
     }
+
+    /// Index `docs` with one bulk request, returning the positions of the items it rejected
+    async fn send_bulk(&self, docs: &[Value]) -> Result<Vec<usize>> {
+        let action = json!({ "index": {} });
+        let body: Vec<JsonBody<&Value>> =
+            docs.iter().flat_map(|doc| [JsonBody::new(&action), JsonBody::new(doc)]).collect();
+
+        let response = self
+            .client
+            .bulk(BulkParts::Index(&self.index))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| DnsxError::export_source("elasticsearch", "Bulk request failed", e))?;
+
+        if !response.status_code().is_success() {
+            return Err(DnsxError::export(
+                "elasticsearch",
+                format!("Bulk request returned status {}", response.status_code()),
+            ));
+        }
+
+        let result: Value = response
+            .json()
+            .await
+            .map_err(|e| DnsxError::export_source("elasticsearch", "Invalid bulk response", e))?;
+
+        if !result["errors"].as_bool().unwrap_or(false) {
+            return Ok(Vec::new());
+        }
+        let rejected = rejected_items(&result, docs.len());
+        warn!(
+            "Elasticsearch rejected {} of {} documents: {}",
+            rejected.len(),
+            docs.len(),
+            first_item_error(&result).unwrap_or_else(|| "no reason given".to_string())
+        );
+        Ok(rejected)
+    }
+}
+
+/// Positions of the failed items in a bulk response reporting `errors`
+///
+/// Items are listed in request order; without a usable `items` array every document counts as failed.
+fn rejected_items(response: &Value, docs: usize) -> Vec<usize> {
+    let Some(items) = response["items"].as_array().filter(|items| items.len() == docs) else {
+        return (0..docs).collect();
+    };
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| {
+            let result = &item["index"];
+            !result["error"].is_null() || result["status"].as_u64().is_none_or(|status| status >= 300)
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Reason given for the first failed item of a bulk response
+fn first_item_error(response: &Value) -> Option<String> {
+    response["items"].as_array()?.iter().find_map(|item| {
+        let error = &item["index"]["error"];
+        Some(format!("{}: {}", error["type"].as_str()?, error["reason"].as_str().unwrap_or_default()))
+    })
+}
+
+/// Body of a successful response, or an export error naming `what`
+async fn read_json(
+    response: std::result::Result<elasticsearch::http::response::Response, elasticsearch::Error>,
+    what: &str,
+) -> Result<Value> {
+    let response = response.map_err(|e| DnsxError::export_source("elasticsearch", format!("{} failed", what), e))?;
+    if !response.status_code().is_success() {
+        return Err(DnsxError::export(
+            "elasticsearch",
+            format!("{} returned status {}", what, response.status_code()),
+        ));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| DnsxError::export_source("elasticsearch", format!("Invalid response to {}", what.to_lowercase()), e))
 }
 
 /// Ensure index exists with proper mapping
@@ -119,13 +312,154 @@ async fn ensure_index(client: &Elasticsearch, index: &str) -> Result<()> {
     Ok(())
 }
 
+/// Warn when an existing index maps record fields to incompatible types
+///
+/// Elasticsearch would otherwise reject or silently coerce those fields.
+async fn check_mapping(client: &Elasticsearch, index: &str) {
+    let response = client
+        .indices()
+        .get_mapping(IndicesGetMappingParts::Index(&[index]))
+        .send()
+        .await;
+    let mapping: Value = match response {
+        Ok(response) if response.status_code().is_success() => match response.json().await {
+            Ok(mapping) => mapping,
+            Err(e) => {
+                debug!("Invalid mapping response for {}: {}", index, e);
+                return;
+            }
+        },
+        Ok(response) => {
+            debug!("Could not fetch mapping for {}: status {}", index, response.status_code());
+            return;
+        }
+        Err(e) => {
+            debug!("Could not fetch mapping for {}: {}", index, e);
+            return;
+        }
+    };
+
+    // The response is keyed by the concrete index name, which differs when `index` is an alias
+    let Some(properties) = mapping.as_object().and_then(|m| m.values().next()).map(|m| &m["mappings"]["properties"]) else {
+        return;
+    };
+    for conflict in mapping_conflicts(properties) {
+        warn!("Elasticsearch index {} has an incompatible mapping: {}", index, conflict);
+    }
+}
+
+/// Fields whose mapped type cannot hold the value the exporter sends
+fn mapping_conflicts(properties: &Value) -> Vec<String> {
+    schema_fields(&DnsRecord::json_schema())
+        .into_iter()
+        .filter_map(|field| {
+            let name = if field.name == "timestamp" { "@timestamp".to_string() } else { field.name };
+            let mapped = properties[&name]["type"].as_str()?;
+            // Enums and the record value are indexed as their rendered strings
+            let allowed: &[&str] = match field.kind {
+                FieldKind::String | FieldKind::Enum { .. } | FieldKind::Mixed => &["keyword", "text", "wildcard", "constant_keyword"],
+                FieldKind::DateTime => &["date", "date_nanos"],
+                FieldKind::Integer => &["integer", "long", "unsigned_long"],
+                FieldKind::Number => &["float", "double", "half_float", "scaled_float"],
+            };
+            (!allowed.contains(&mapped)).then(|| format!("`{}` is mapped as {}, expected one of {}", name, mapped, allowed.join(", ")))
+        })
+        .collect()
+}
+
+/// Name of the ILM policy and index template managed for `index`
+fn ilm_policy_name(index: &str) -> String {
+    format!("{}-policy", index)
+}
+
+/// ILM policy body: hot, then warm, then delete at `delete_after_days`
+///
+/// Phases are clipped so deletion always happens at `delete_after_days`,
+/// even when the hot and warm phases would run past it.
+fn ilm_policy_body(ilm: &IlmConfig) -> Value {
+    let delete_after = ilm.delete_after_days;
+    let warm_start = ilm.hot_max_age_days.min(delete_after);
+    let warm_end = ilm.hot_max_age_days.saturating_add(ilm.warm_max_age_days).min(delete_after);
+
+    let mut phases = serde_json::Map::new();
+    phases.insert(
+        "hot".to_string(),
+        json!({ "min_age": "0ms", "actions": { "set_priority": { "priority": 100 } } }),
+    );
+    if warm_end > warm_start {
+        phases.insert(
+            "warm".to_string(),
+            json!({ "min_age": format!("{}d", warm_start), "actions": { "set_priority": { "priority": 50 } } }),
+        );
+    }
+    phases.insert(
+        "delete".to_string(),
+        json!({ "min_age": format!("{}d", delete_after), "actions": { "delete": {} } }),
+    );
+
+    json!({ "policy": { "phases": phases } })
+}
+
+/// Create or update the ILM policy for `index` and attach it
+async fn ensure_ilm_policy(client: &Elasticsearch, index: &str, ilm: &IlmConfig) -> Result<()> {
+    let policy = ilm_policy_name(index);
+
+    let response = client
+        .ilm()
+        .put_lifecycle(IlmPutLifecycleParts::Policy(&policy))
+        .body(ilm_policy_body(ilm))
+        .send()
+        .await
+        .map_err(|e| DnsxError::export_source("elasticsearch", "Failed to create ILM policy", e))?;
+    if !response.status_code().is_success() {
+        return Err(DnsxError::export(
+            "elasticsearch",
+            format!("Creating ILM policy {} returned status {}", policy, response.status_code()),
+        ));
+    }
+
+    // New indices pick the policy up from the template
+    let template = json!({
+        "index_patterns": [index, format!("{}-*", index)],
+        "priority": 200,
+        "template": {
+            "settings": { "index.lifecycle.name": policy }
+        }
+    });
+    let response = client
+        .indices()
+        .put_index_template(IndicesPutIndexTemplateParts::Name(&policy))
+        .body(template)
+        .send()
+        .await
+        .map_err(|e| DnsxError::export_source("elasticsearch", "Failed to create index template", e))?;
+    if !response.status_code().is_success() {
+        return Err(DnsxError::export(
+            "elasticsearch",
+            format!("Creating index template {} returned status {}", policy, response.status_code()),
+        ));
+    }
+
+    // An index that already exists is attached directly; a missing one is created from the template
+    let attached = client
+        .indices()
+        .put_settings(IndicesPutSettingsParts::Index(&[index]))
+        .body(json!({ "index.lifecycle.name": policy }))
+        .send()
+        .await;
+    match attached {
+        Ok(response) if response.status_code().is_success() => debug!("Attached ILM policy {} to {}", policy, index),
+        _ => debug!("Index {} does not exist yet, policy will be applied from the template", index),
+    }
+
+    Ok(())
+}
+
 #[async_trait]
 impl Exporter for ElasticsearchExporter {
     async fn export(&self, record: DnsRecord) -> Result<()> {
-        let timestamp = DateTime::<Utc>::from(record.timestamp);
-
         let doc = json!({
-            "@timestamp": timestamp.to_rfc3339(),
+            "@timestamp": record.timestamp.to_rfc3339(),
             "domain": record.domain,
             "record_type": format!("{}", record.record_type),
             "value": record.value.to_string(),
@@ -150,4 +484,94 @@ impl Exporter for ElasticsearchExporter {
     async fn flush(&self) -> Result<()> {
         self.flush_buffer().await
     }
+
+    async fn health_check(&self) -> Result<()> {
+        let response = self
+            .client
+            .ping()
+            .send()
+            .await
+            .map_err(|e| DnsxError::export_source("elasticsearch", "Ping failed", e))?;
+
+        if !response.status_code().is_success() {
+            return Err(DnsxError::export(
+                "elasticsearch",
+                format!("Ping returned {}", response.status_code()),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapping_conflicts() {
+        let compatible = json!({
+            "@timestamp": { "type": "date" },
+            "domain": { "type": "keyword" },
+            "record_type": { "type": "keyword" },
+            "value": { "type": "text" },
+            "ttl": { "type": "integer" },
+            "query_time_ms": { "type": "float" }
+        });
+        assert!(mapping_conflicts(&compatible).is_empty());
+
+        let stale = json!({
+            "@timestamp": { "type": "long" },
+            "ttl": { "type": "keyword" },
+            "resolver": { "type": "keyword" }
+        });
+        let conflicts = mapping_conflicts(&stale);
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts[0].starts_with("`ttl` is mapped as keyword"));
+        assert!(conflicts[1].starts_with("`@timestamp` is mapped as long"));
+    }
+
+    #[test]
+    fn test_ilm_policy_phases() {
+        let body = ilm_policy_body(&IlmConfig {
+            hot_max_age_days: 7,
+            warm_max_age_days: 23,
+            delete_after_days: 90,
+        });
+        let phases = &body["policy"]["phases"];
+        assert_eq!(phases["warm"]["min_age"], "7d");
+        assert_eq!(phases["delete"]["min_age"], "90d");
+
+        // Deletion wins when it comes before the warm phase would start
+        let body = ilm_policy_body(&IlmConfig {
+            hot_max_age_days: 7,
+            warm_max_age_days: 23,
+            delete_after_days: 5,
+        });
+        let phases = &body["policy"]["phases"];
+        assert!(phases.get("warm").is_none());
+        assert_eq!(phases["delete"]["min_age"], "5d");
+    }
+    #[test]
+    fn test_bulk_rejected_items() {
+        let response = json!({
+            "errors": true,
+            "items": [
+                { "index": { "status": 201 } },
+                {
+                    "index": {
+                        "status": 429,
+                        "error": { "type": "es_rejected_execution_exception", "reason": "queue full" }
+                    }
+                },
+                { "index": { "status": 201 } },
+                { "index": { "status": 400, "error": { "type": "mapper_parsing_exception", "reason": "bad ttl" } } }
+            ]
+        });
+        assert_eq!(rejected_items(&response, 4), vec![1, 3]);
+        assert_eq!(first_item_error(&response).unwrap(), "es_rejected_execution_exception: queue full");
+
+        // Items that do not line up with the request fail the whole chunk
+        assert_eq!(rejected_items(&json!({ "errors": true }), 3), vec![0, 1, 2]);
+        assert_eq!(rejected_items(&response, 5), vec![0, 1, 2, 3, 4]);
+    }
 }
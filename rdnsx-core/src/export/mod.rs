@@ -4,20 +4,148 @@ pub mod cassandra;
 pub mod elasticsearch;
 pub mod mongodb;
 
-pub use cassandra::CassandraExporter;
+pub use cassandra::{CassandraExporter, CassandraStats};
 pub use elasticsearch::ElasticsearchExporter;
-pub use mongodb::MongodbExporter;
+pub use mongodb::{DomainCount, MongodbAnalyzer, MongodbExporter};
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 
 use async_trait::async_trait;
-use crate::error::Result;
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use serde::Serialize;
+use tokio::sync::Semaphore;
+use tracing::warn;
+
+use crate::error::{DnsxError, Result};
 use crate::types::DnsRecord;
 
+/// Number of entries kept in the top-domain and top-resolver breakdowns
+pub const STATS_TOP_N: usize = 10;
+
+/// Summary of the records stored in an export backend
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExportStats {
+    pub total_records: u64,
+    pub by_record_type: BTreeMap<String, u64>,
+    pub by_response_code: BTreeMap<String, u64>,
+    pub earliest: Option<DateTime<Utc>>,
+    pub latest: Option<DateTime<Utc>>,
+    /// At most [`STATS_TOP_N`] domains, most records first
+    pub top_domains: Vec<(String, u64)>,
+    /// At most [`STATS_TOP_N`] resolvers, most records first
+    pub top_resolvers: Vec<(String, u64)>,
+}
+
+/// Largest `n` counts, ties broken alphabetically
+pub(crate) fn top_counts(counts: HashMap<String, u64>, n: usize) -> Vec<(String, u64)> {
+    let mut counts: Vec<(String, u64)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(n);
+    counts
+}
+
 /// Common export trait
 #[async_trait]
 pub trait Exporter: Send + Sync {
     /// Export a DNS record
     async fn export(&self, record: DnsRecord) -> Result<()>;
 
+    /// Export several records; backends with a bulk write path override this
+    async fn export_batch(&self, records: Vec<DnsRecord>) -> Result<()> {
+        for record in records {
+            self.export(record).await?;
+        }
+        Ok(())
+    }
+
     /// Flush any pending writes
     async fn flush(&self) -> Result<()>;
+
+    /// Check the backend is reachable with a lightweight request
+    async fn health_check(&self) -> Result<()>;
+}
+
+/// Fans records out to every configured exporter, with a shared cap on
+/// backend calls in flight
+///
+/// Each call to an inner exporter holds one of `max_concurrent_exports`
+/// permits, so several active backends cannot together swamp the network or
+/// the databases while a fast scan is producing records.
+pub struct ExportOrchestrator {
+    exporters: Vec<(String, Arc<dyn Exporter>)>,
+    permits: Arc<Semaphore>,
+}
+
+impl ExportOrchestrator {
+    /// Create an orchestrator with no exporters; `max_concurrent_exports` is raised to at least 1
+    pub fn new(max_concurrent_exports: usize) -> Self {
+        Self {
+            exporters: Vec::new(),
+            permits: Arc::new(Semaphore::new(max_concurrent_exports.max(1))),
+        }
+    }
+
+    /// Add a backend, named in error messages
+    pub fn with_exporter(mut self, name: impl Into<String>, exporter: Arc<dyn Exporter>) -> Self {
+        self.exporters.push((name.into(), exporter));
+        self
+    }
+
+    /// The managed exporters with their names, in the order they were added
+    pub fn exporters(&self) -> impl Iterator<Item = (&str, &dyn Exporter)> {
+        self.exporters.iter().map(|(name, exporter)| (name.as_str(), exporter.as_ref()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exporters.is_empty()
+    }
+
+    /// Run `call` against every exporter, each under a permit
+    ///
+    /// All backends are tried even when some fail; every failure is logged and
+    /// the first one is returned.
+    async fn for_each<'a, F, Fut>(&'a self, call: F) -> Result<()>
+    where
+        F: Fn(&'a dyn Exporter) -> Fut,
+        Fut: std::future::Future<Output = Result<()>> + 'a,
+    {
+        let calls = self.exporters.iter().map(|(name, exporter)| {
+            let call = call(exporter.as_ref());
+            async move {
+                let _permit = self
+                    .permits
+                    .acquire()
+                    .await
+                    .map_err(|e| DnsxError::export_source(name.as_str(), "Export semaphore closed", e))?;
+                let result = call.await;
+                if let Err(e) = &result {
+                    warn!("{} export failed: {}", name, e.report());
+                }
+                result
+            }
+        });
+
+        join_all(calls).await.into_iter().find(Result::is_err).unwrap_or(Ok(()))
+    }
+}
+
+#[async_trait]
+impl Exporter for ExportOrchestrator {
+    async fn export(&self, record: DnsRecord) -> Result<()> {
+        self.for_each(|exporter| exporter.export(record.clone())).await
+    }
+
+    async fn export_batch(&self, records: Vec<DnsRecord>) -> Result<()> {
+        self.for_each(|exporter| exporter.export_batch(records.clone())).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.for_each(|exporter| exporter.flush()).await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        self.for_each(|exporter| exporter.health_check()).await
+    }
 }
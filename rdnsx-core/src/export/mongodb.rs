@@ -1,17 +1,22 @@
 //! MongoDB exporter
 
+use std::collections::HashMap;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
 use mongodb::{
-    bson::{doc, Document},
+    bson::{doc, Bson, DateTime as BsonDateTime, Document},
     Client, Collection, Database, IndexModel,
 };
+use serde::Serialize;
 use tokio::sync::Mutex;
 use tracing::debug;
 
 use crate::error::{DnsxError, Result};
-use crate::export::Exporter;
+use crate::export::{ExportStats, Exporter, STATS_TOP_N};
 use crate::types::DnsRecord;
 
 /// MongoDB exporter
@@ -26,7 +31,7 @@ impl MongodbExporter {
     pub async fn new(url: &str, database: &str, collection: &str, batch_size: usize) -> Result<Self> {
         let client = Client::with_uri_str(url)
             .await
-            .map_err(|e| DnsxError::Export(format!("Failed to connect to MongoDB: {}", e)))?;
+            .map_err(|e| DnsxError::export_source("mongodb", "Failed to connect", e))?;
 
         let db: Database = client.database(database);
         let coll: Collection<Document> = db.collection(collection);
@@ -53,7 +58,7 @@ impl MongodbExporter {
         self.collection
             .insert_many(documents.clone())
             .await
-            .map_err(|e| DnsxError::Export(format!("MongoDB insert error: {}", e)))?;
+            .map_err(|e| DnsxError::export_source("mongodb", "Insert error", e))?;
 
         debug!("Flushed {} documents to MongoDB", documents.len());
         Ok(())
@@ -80,7 +85,7 @@ async fn ensure_indexes(collection: &Collection<Document>) -> Result<()> {
     collection
         .create_indexes(indexes)
         .await
-        .map_err(|e| DnsxError::Export(format!("Failed to create indexes: {}", e)))?;
+        .map_err(|e| DnsxError::export_source("mongodb", "Failed to create indexes", e))?;
 
     Ok(())
 }
@@ -88,13 +93,8 @@ async fn ensure_indexes(collection: &Collection<Document>) -> Result<()> {
 #[async_trait]
 impl Exporter for MongodbExporter {
     async fn export(&self, record: DnsRecord) -> Result<()> {
-        let timestamp = record.timestamp
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs() as i64;
-
         let doc = doc! {
-            "timestamp": timestamp,
+            "timestamp": BsonDateTime::from_millis(record.timestamp.timestamp_millis()),
             "domain": record.domain,
             "record_type": format!("{}", record.record_type),
             "value": record.value.to_string(),
@@ -119,4 +119,178 @@ impl Exporter for MongodbExporter {
     async fn flush(&self) -> Result<()> {
         self.flush_buffer().await
     }
+
+    async fn health_check(&self) -> Result<()> {
+        self.collection
+            .client()
+            .database("admin")
+            .run_command(doc! { "ping": 1 })
+            .await
+            .map_err(|e| DnsxError::export_source("mongodb", "Ping failed", e))?;
+        Ok(())
+    }
+}
+
+/// Number of stored records for a domain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DomainCount {
+    pub domain: String,
+    pub count: u64,
+}
+
+/// Aggregation queries over records written by [`MongodbExporter`]
+pub struct MongodbAnalyzer {
+    collection: Collection<Document>,
+}
+
+impl MongodbAnalyzer {
+    /// Connect to the collection holding exported records
+    pub async fn new(url: &str, database: &str, collection: &str) -> Result<Self> {
+        let client = Client::with_uri_str(url)
+            .await
+            .map_err(|e| DnsxError::export_source("mongodb", "Failed to connect", e))?;
+
+        Ok(Self {
+            collection: client.database(database).collection(collection),
+        })
+    }
+
+    /// Domains with the most stored records, most first
+    pub async fn top_domains_by_record_count(&self, limit: u32) -> Result<Vec<DomainCount>> {
+        let pipeline = vec![
+            doc! { "$group": { "_id": "$domain", "count": { "$sum": 1 } } },
+            doc! { "$sort": { "count": -1, "_id": 1 } },
+            doc! { "$limit": i64::from(limit) },
+        ];
+
+        Ok(self
+            .aggregate(pipeline)
+            .await?
+            .iter()
+            .filter_map(|doc| {
+                Some(DomainCount {
+                    domain: doc.get_str("_id").ok()?.to_string(),
+                    count: count_field(doc),
+                })
+            })
+            .collect())
+    }
+
+    /// Distinct A/AAAA addresses stored for `domain`
+    pub async fn unique_ips_per_domain(&self, domain: &str) -> Result<Vec<IpAddr>> {
+        let pipeline = vec![
+            doc! { "$match": { "domain": domain, "record_type": { "$in": ["A", "AAAA"] } } },
+            doc! { "$group": { "_id": "$value" } },
+            doc! { "$sort": { "_id": 1 } },
+        ];
+
+        Ok(self
+            .aggregate(pipeline)
+            .await?
+            .iter()
+            .filter_map(|doc| doc.get_str("_id").ok()?.parse().ok())
+            .collect())
+    }
+
+    /// Domains with an A/AAAA record pointing at `ip`
+    pub async fn domains_resolving_to_ip(&self, ip: IpAddr) -> Result<Vec<String>> {
+        let pipeline = vec![
+            doc! { "$match": { "value": ip.to_string(), "record_type": { "$in": ["A", "AAAA"] } } },
+            doc! { "$group": { "_id": "$domain" } },
+            doc! { "$sort": { "_id": 1 } },
+        ];
+
+        Ok(self
+            .aggregate(pipeline)
+            .await?
+            .iter()
+            .filter_map(|doc| doc.get_str("_id").ok().map(str::to_string))
+            .collect())
+    }
+
+    /// Number of stored records per record type
+    pub async fn record_type_distribution(&self) -> Result<HashMap<String, u64>> {
+        let pipeline = vec![
+            doc! { "$group": { "_id": "$record_type", "count": { "$sum": 1 } } },
+            doc! { "$sort": { "count": -1 } },
+        ];
+
+        Ok(self
+            .aggregate(pipeline)
+            .await?
+            .iter()
+            .filter_map(|doc| Some((doc.get_str("_id").ok()?.to_string(), count_field(doc))))
+            .collect())
+    }
+
+    /// Record counts, time range and top domains/resolvers for the collection
+    pub async fn query_stats(&self) -> Result<ExportStats> {
+        let total_records = self
+            .collection
+            .count_documents(doc! {})
+            .await
+            .map_err(|e| DnsxError::export_source("mongodb", "Count failed", e))?;
+
+        let range = self
+            .aggregate(vec![doc! {
+                "$group": { "_id": Bson::Null, "earliest": { "$min": "$timestamp" }, "latest": { "$max": "$timestamp" } }
+            }])
+            .await?;
+        let bound = |field: &str| -> Option<DateTime<Utc>> {
+            DateTime::from_timestamp_millis(range.first()?.get_datetime(field).ok()?.timestamp_millis())
+        };
+
+        Ok(ExportStats {
+            total_records,
+            by_record_type: self.record_type_distribution().await?.into_iter().collect(),
+            by_response_code: self.count_by("$response_code", None).await?.into_iter().collect(),
+            earliest: bound("earliest"),
+            latest: bound("latest"),
+            top_domains: self
+                .top_domains_by_record_count(STATS_TOP_N as u32)
+                .await?
+                .into_iter()
+                .map(|entry| (entry.domain, entry.count))
+                .collect(),
+            top_resolvers: self.count_by("$resolver", Some(STATS_TOP_N)).await?,
+        })
+    }
+
+    /// Record counts grouped by `field`, most first
+    async fn count_by(&self, field: &str, limit: Option<usize>) -> Result<Vec<(String, u64)>> {
+        let mut pipeline = vec![
+            doc! { "$group": { "_id": field, "count": { "$sum": 1 } } },
+            doc! { "$sort": { "count": -1, "_id": 1 } },
+        ];
+        if let Some(limit) = limit {
+            pipeline.push(doc! { "$limit": limit as i64 });
+        }
+
+        Ok(self
+            .aggregate(pipeline)
+            .await?
+            .iter()
+            .filter_map(|doc| Some((doc.get_str("_id").ok()?.to_string(), count_field(doc))))
+            .collect())
+    }
+
+    async fn aggregate(&self, pipeline: Vec<Document>) -> Result<Vec<Document>> {
+        self.collection
+            .aggregate(pipeline)
+            .await
+            .map_err(|e| DnsxError::export_source("mongodb", "Aggregation failed", e))?
+            .try_collect()
+            .await
+            .map_err(|e| DnsxError::export_source("mongodb", "Failed to read aggregation results", e))
+    }
+}
+
+/// `$sum` yields an int32 or int64 depending on magnitude
+fn count_field(doc: &Document) -> u64 {
+    match doc.get("count") {
+        Some(Bson::Int32(n)) => *n as u64,
+        Some(Bson::Int64(n)) => *n as u64,
+        Some(Bson::Double(n)) => *n as u64,
+        _ => 0,
+    }
 }
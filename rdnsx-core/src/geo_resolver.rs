@@ -0,0 +1,148 @@
+//! Regional resolver selection for geo-DNS targets
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::input::parse_asn;
+
+/// Region whose public resolvers see the same geo-DNS answers as its users
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum ResolverRegion {
+    /// United States and Canada
+    Us,
+    /// Europe
+    Eu,
+    /// Asia-Pacific
+    Apac,
+}
+
+impl ResolverRegion {
+    /// Public resolvers with good anycast coverage in this region
+    pub fn resolvers(self) -> &'static [&'static str] {
+        match self {
+            ResolverRegion::Us => &["8.8.8.8", "1.1.1.1"],
+            ResolverRegion::Eu => &["9.9.9.9", "8.8.8.8"],
+            ResolverRegion::Apac => &["1.1.1.1", "114.114.114.114"],
+        }
+    }
+
+    /// Region of an ISO 3166 country code, if it is one we have resolvers for
+    pub fn for_country(country: &str) -> Option<Self> {
+        let country = country.trim().to_uppercase();
+        if ["US", "CA"].contains(&country.as_str()) {
+            Some(ResolverRegion::Us)
+        } else if EU_COUNTRIES.contains(&country.as_str()) {
+            Some(ResolverRegion::Eu)
+        } else if APAC_COUNTRIES.contains(&country.as_str()) {
+            Some(ResolverRegion::Apac)
+        } else {
+            None
+        }
+    }
+}
+
+const EU_COUNTRIES: &[&str] = &[
+    "AT", "BE", "BG", "CH", "CY", "CZ", "DE", "DK", "EE", "ES", "FI", "FR", "GB", "GR", "HR", "HU", "IE", "IS",
+    "IT", "LI", "LT", "LU", "LV", "MT", "NL", "NO", "PL", "PT", "RO", "SE", "SI", "SK", "UA",
+];
+
+const APAC_COUNTRIES: &[&str] = &[
+    "AU", "BD", "CN", "HK", "ID", "IN", "JP", "KR", "MY", "NZ", "PH", "PK", "SG", "TH", "TW", "VN",
+];
+
+/// Registration country of large networks, so common targets work without a lookup
+const KNOWN_ASN_COUNTRIES: &[(u32, &str)] = &[
+    (2914, "US"),
+    (3215, "FR"),
+    (3320, "DE"),
+    (4134, "CN"),
+    (4713, "JP"),
+    (4766, "KR"),
+    (4837, "CN"),
+    (5089, "GB"),
+    (7545, "AU"),
+    (8075, "US"),
+    (9808, "CN"),
+    (13335, "US"),
+    (14061, "US"),
+    (15169, "US"),
+    (16276, "FR"),
+    (16509, "US"),
+    (17676, "JP"),
+    (24940, "DE"),
+    (37963, "CN"),
+    (45090, "CN"),
+];
+
+/// Picks resolvers in the same region as a target network
+///
+/// Geo-DNS answers depend on where the query comes from, so asking resolvers
+/// near the target's users shows the addresses those users actually get.
+#[derive(Debug, Clone)]
+pub struct GeographicResolverSelector {
+    /// Country code by ASN number
+    asn_countries: HashMap<u32, String>,
+}
+
+impl Default for GeographicResolverSelector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GeographicResolverSelector {
+    /// Create a selector that knows the countries of a set of large networks
+    pub fn new() -> Self {
+        Self {
+            asn_countries: KNOWN_ASN_COUNTRIES
+                .iter()
+                .map(|(asn, country)| (*asn, country.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Record the country of an ASN, e.g. from an ASN enumeration result
+    pub fn with_asn_country(mut self, asn: &str, country: &str) -> Self {
+        if let Ok(asn) = parse_asn(asn) {
+            self.asn_countries.insert(asn, country.trim().to_uppercase());
+        }
+        self
+    }
+
+    /// Regional resolvers for `asn` (`AS15169` or `15169`)
+    ///
+    /// Empty when the ASN's country is unknown or outside the covered regions,
+    /// in which case the configured resolvers should be kept.
+    pub fn select_for_asn(&self, asn: &str) -> Vec<String> {
+        parse_asn(asn)
+            .ok()
+            .and_then(|asn| self.asn_countries.get(&asn))
+            .map(|country| Self::select_for_country(country))
+            .unwrap_or_default()
+    }
+
+    /// Regional resolvers for an ISO 3166 country code, empty if the region is not covered
+    pub fn select_for_country(country: &str) -> Vec<String> {
+        ResolverRegion::for_country(country)
+            .map(|region| region.resolvers().iter().map(|r| r.to_string()).collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_for_asn() {
+        let selector = GeographicResolverSelector::new().with_asn_country("AS64500", "jp");
+
+        assert_eq!(selector.select_for_asn("AS15169"), vec!["8.8.8.8", "1.1.1.1"]);
+        assert_eq!(selector.select_for_asn("24940"), vec!["9.9.9.9", "8.8.8.8"]);
+        assert_eq!(selector.select_for_asn("as64500"), vec!["1.1.1.1", "114.114.114.114"]);
+        assert!(selector.select_for_asn("AS64501").is_empty());
+        assert!(selector.select_for_asn("not-an-asn").is_empty());
+        assert!(GeographicResolverSelector::select_for_country("BR").is_empty());
+    }
+}
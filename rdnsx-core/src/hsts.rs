@@ -0,0 +1,91 @@
+//! HSTS preload list lookups
+
+use std::time::Duration;
+
+use dashmap::DashMap;
+use serde::Deserialize;
+
+use crate::error::{DnsxError, Result};
+
+const STATUS_API: &str = "https://hstspreload.org/api/v2/status";
+
+/// Answer from the preload list status API
+#[derive(Debug, Deserialize)]
+struct PreloadStatus {
+    /// `preloaded`, `pending`, `rejected` or `unknown`
+    status: String,
+}
+
+/// Checks names against the Chromium HSTS preload list
+pub struct HstsChecker {
+    client: reqwest::Client,
+    /// Preload status of exact names; subdomains of one target share their parents' entries
+    cache: DashMap<String, bool>,
+}
+
+impl HstsChecker {
+    /// Create a new checker
+    pub fn new() -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| DnsxError::network("hstspreload.org", format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            cache: DashMap::new(),
+        })
+    }
+
+    /// Whether `domain` or one of its parents is on the preload list
+    ///
+    /// The list only accepts entries with `includeSubDomains`, so a preloaded
+    /// parent covers every name below it.
+    pub async fn is_preloaded(&self, domain: &str) -> Result<bool> {
+        for name in preload_candidates(domain) {
+            if self.status_of(&name).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    async fn status_of(&self, name: &str) -> Result<bool> {
+        if let Some(preloaded) = self.cache.get(name) {
+            return Ok(*preloaded);
+        }
+
+        let response = self
+            .client
+            .get(STATUS_API)
+            .query(&[("domain", name)])
+            .send()
+            .await
+            .map_err(|e| DnsxError::network("hstspreload.org", format!("Status request for {} failed: {}", name, e)))?;
+
+        if !response.status().is_success() {
+            return Err(DnsxError::network(
+                "hstspreload.org",
+                format!("Status request for {} returned {}", name, response.status()),
+            ));
+        }
+
+        let status: PreloadStatus = response
+            .json()
+            .await
+            .map_err(|e| DnsxError::network("hstspreload.org", format!("Invalid status response for {}: {}", name, e)))?;
+
+        let preloaded = status.status == "preloaded";
+        self.cache.insert(name.to_string(), preloaded);
+        Ok(preloaded)
+    }
+}
+
+/// `domain` and all its parents, most specific first
+///
+/// The TLD is included since whole TLDs such as `.dev` and `.app` are preloaded.
+pub(crate) fn preload_candidates(domain: &str) -> Vec<String> {
+    let domain = domain.trim().trim_end_matches('.').to_lowercase();
+    let labels: Vec<&str> = domain.split('.').filter(|l| !l.is_empty()).collect();
+    (0..labels.len()).map(|i| labels[i..].join(".")).collect()
+}
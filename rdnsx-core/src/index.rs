@@ -0,0 +1,227 @@
+//! Bidirectional domain/IP index over resolved records
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::IpAddr;
+
+use ipnetwork::IpNetwork;
+use serde::Serialize;
+
+use crate::cdn_detection::CdnDetector;
+use crate::input::subnet_of;
+use crate::types::{DnsRecord, RecordType, RecordValue};
+
+/// Index of query results by domain and by resolved address
+#[derive(Debug, Default)]
+pub struct DnsResultIndex<'a> {
+    pub by_domain: HashMap<String, Vec<&'a DnsRecord>>,
+    pub by_ip: HashMap<IpAddr, Vec<&'a DnsRecord>>,
+}
+
+impl<'a> DnsResultIndex<'a> {
+    /// Build the index; only A/AAAA values are added to `by_ip`
+    pub fn new(records: &'a [DnsRecord]) -> Self {
+        let mut index = Self::default();
+
+        for record in records {
+            index.by_domain.entry(normalize(&record.domain)).or_default().push(record);
+            if let RecordValue::Ip(ip) = record.value {
+                index.by_ip.entry(ip).or_default().push(record);
+            }
+        }
+
+        index
+    }
+
+    /// Records that resolved to `ip`
+    pub fn domains_for_ip(&self, ip: IpAddr) -> &[&'a DnsRecord] {
+        self.by_ip.get(&ip).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Distinct addresses `domain` resolved to, in the order they were seen
+    pub fn ips_for_domain(&self, domain: &str) -> Vec<IpAddr> {
+        let mut ips = Vec::new();
+        for record in self.by_domain.get(&normalize(domain)).into_iter().flatten() {
+            if let RecordValue::Ip(ip) = record.value {
+                if !ips.contains(&ip) {
+                    ips.push(ip);
+                }
+            }
+        }
+        ips
+    }
+}
+
+/// Prefix that addresses without a recognized owner are grouped by
+const IPV4_CLUSTER_PREFIX: u8 = 24;
+const IPV6_CLUSTER_PREFIX: u8 = 48;
+
+/// Addresses that look like they share an owner, with the domains that resolve to them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IpCluster {
+    /// The address most of the cluster's domains resolve to
+    pub representative_ip: IpAddr,
+    pub domains: Vec<String>,
+    /// CDN provider or ASN the addresses were matched to, if any
+    pub owner_hint: Option<String>,
+    /// Smallest network covering every address in the cluster
+    pub network: IpNetwork,
+}
+
+/// What a cluster's addresses have in common; the address family is part of
+/// the key so a provider's IPv4 and IPv6 addresses form separate clusters
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum ClusterKey {
+    Cdn(String, bool),
+    Asn(u32, bool),
+    Network(IpNetwork),
+}
+
+/// A cluster's owner, if known, and the domains seen at each of its addresses
+type ClusterMembers = (Option<String>, BTreeMap<IpAddr, BTreeSet<String>>);
+
+/// Groups resolved addresses by likely owner
+pub struct IpClusterer;
+
+impl IpClusterer {
+    /// Clusters of the A/AAAA answers in `records`, largest first
+    ///
+    /// Addresses of domains aliased to a known CDN are grouped by provider,
+    /// addresses in a known provider range by ASN, and everything else by
+    /// /24 (/48 for IPv6).
+    pub fn cluster(records: &[DnsRecord]) -> Vec<IpCluster> {
+        // CNAME targets, including chain hops stored under the queried type
+        let cdn_by_domain: HashMap<String, String> = records
+            .iter()
+            .filter(|r| !matches!(r.record_type, RecordType::Ns | RecordType::Ptr | RecordType::Mx))
+            .filter_map(|r| match &r.value {
+                RecordValue::Domain(target) => Some((normalize(&r.domain), CdnDetector::builtin_cdn_provider(target)?)),
+                _ => None,
+            })
+            .collect();
+
+        let mut groups: BTreeMap<ClusterKey, ClusterMembers> = BTreeMap::new();
+        for record in records {
+            let RecordValue::Ip(ip) = record.value else {
+                continue;
+            };
+            if !matches!(record.record_type, RecordType::A | RecordType::Aaaa) {
+                continue;
+            }
+
+            let domain = normalize(&record.domain);
+            let (key, owner) = if let Some(provider) = cdn_by_domain.get(&domain) {
+                (ClusterKey::Cdn(provider.clone(), ip.is_ipv4()), Some(provider.clone()))
+            } else if let Some((asn, org)) = CdnDetector::builtin_asn(ip) {
+                (ClusterKey::Asn(asn, ip.is_ipv4()), Some(format!("AS{} {}", asn, org)))
+            } else {
+                (ClusterKey::Network(default_subnet(ip)), None)
+            };
+
+            let (_, ips) = groups.entry(key).or_insert_with(|| (owner, BTreeMap::new()));
+            ips.entry(ip).or_default().insert(domain);
+        }
+
+        let mut clusters: Vec<IpCluster> = groups
+            .into_iter()
+            .map(|(key, (owner_hint, ips))| {
+                // Most domains wins; the lowest address on a tie
+                let representative_ip = ips
+                    .iter()
+                    .max_by(|a, b| a.1.len().cmp(&b.1.len()).then_with(|| b.0.cmp(a.0)))
+                    .map(|(ip, _)| *ip)
+                    .expect("clusters have at least one address");
+                let addresses: Vec<IpAddr> = ips.keys().copied().collect();
+                let domains: BTreeSet<String> = ips.into_values().flatten().collect();
+                let network = match key {
+                    ClusterKey::Network(network) => network,
+                    _ => covering_network(&addresses),
+                };
+                IpCluster { representative_ip, domains: domains.into_iter().collect(), owner_hint, network }
+            })
+            .collect();
+
+        clusters.sort_by(|a, b| b.domains.len().cmp(&a.domains.len()).then_with(|| a.network.cmp(&b.network)));
+        clusters
+    }
+}
+
+fn default_subnet(ip: IpAddr) -> IpNetwork {
+    subnet_of(ip, if ip.is_ipv4() { IPV4_CLUSTER_PREFIX } else { IPV6_CLUSTER_PREFIX })
+}
+
+/// The longest-prefix network, no narrower than the default cluster subnet, that contains all of `ips`
+fn covering_network(ips: &[IpAddr]) -> IpNetwork {
+    let mut network = default_subnet(ips[0]);
+    while network.prefix() > 0 && !ips.iter().all(|ip| network.contains(*ip)) {
+        network = subnet_of(ips[0], network.prefix() - 1);
+    }
+    network
+}
+
+fn normalize(domain: &str) -> String {
+    domain.trim().trim_end_matches('.').to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{RecordType, ResponseCode};
+
+    fn record(domain: &str, record_type: RecordType, value: RecordValue) -> DnsRecord {
+        DnsRecord::new(domain.to_string(), record_type, value, 300, ResponseCode::NoError, "8.8.8.8:53".to_string(), 0.0)
+    }
+
+    #[test]
+    fn test_bidirectional_lookup() {
+        let shared: IpAddr = "192.0.2.10".parse().unwrap();
+        let records = vec![
+            record("www.example.com.", RecordType::A, RecordValue::Ip(shared)),
+            record("www.example.com", RecordType::A, RecordValue::Ip(shared)),
+            record("www.example.com", RecordType::Aaaa, RecordValue::Ip("2001:db8::1".parse().unwrap())),
+            record("api.example.com", RecordType::A, RecordValue::Ip(shared)),
+            record("mail.example.com", RecordType::Cname, RecordValue::Domain("ghs.example.net".to_string())),
+        ];
+
+        let index = DnsResultIndex::new(&records);
+
+        let domains: Vec<&str> = index.domains_for_ip(shared).iter().map(|r| r.domain.as_str()).collect();
+        // DnsRecord::new drops the trailing dot, so both www records carry the same name
+        assert_eq!(domains, vec!["www.example.com", "www.example.com", "api.example.com"]);
+        assert!(index.domains_for_ip("198.51.100.1".parse().unwrap()).is_empty());
+
+        assert_eq!(index.ips_for_domain("WWW.example.com"), vec![shared, "2001:db8::1".parse().unwrap()]);
+        assert!(index.ips_for_domain("mail.example.com").is_empty());
+        assert_eq!(index.by_domain.len(), 3);
+    }
+
+    #[test]
+    fn test_ip_clusters() {
+        let ip = |s: &str| RecordValue::Ip(s.parse().unwrap());
+        let records = vec![
+            record("www.example.com", RecordType::A, ip("192.0.2.10")),
+            record("api.example.com", RecordType::A, ip("192.0.2.10")),
+            record("mail.example.com", RecordType::A, ip("192.0.2.25")),
+            record("static.example.com", RecordType::A, RecordValue::Domain("d111.cloudfront.net.".to_string())),
+            record("static.example.com", RecordType::A, ip("198.51.100.7")),
+            record("cdn.example.com", RecordType::A, RecordValue::Domain("d222.cloudfront.net.".to_string())),
+            record("cdn.example.com", RecordType::A, ip("203.0.113.9")),
+            record("edge.example.com", RecordType::A, ip("103.21.244.1")),
+            record("example.com", RecordType::Mx, RecordValue::Domain("mx.cloudflare.net".to_string())),
+        ];
+
+        let clusters = IpClusterer::cluster(&records);
+        assert_eq!(clusters.len(), 3);
+
+        assert_eq!(clusters[0].domains, vec!["api.example.com", "mail.example.com", "www.example.com"]);
+        assert_eq!(clusters[0].representative_ip, "192.0.2.10".parse::<IpAddr>().unwrap());
+        assert_eq!(clusters[0].network, "192.0.2.0/24".parse::<IpNetwork>().unwrap());
+        assert_eq!(clusters[0].owner_hint, None);
+
+        assert_eq!(clusters[1].domains, vec!["cdn.example.com", "static.example.com"]);
+        assert_eq!(clusters[1].owner_hint.as_deref(), Some("Amazon CloudFront"));
+        assert_eq!(clusters[1].network, "192.0.0.0/4".parse::<IpNetwork>().unwrap());
+
+        assert_eq!(clusters[2].owner_hint.as_deref(), Some("AS13335 Cloudflare"));
+        assert_eq!(clusters[2].network, "103.21.244.0/24".parse::<IpNetwork>().unwrap());
+    }
+}
@@ -1,10 +1,17 @@
 //! Input handling for domains, IP ranges, and wordlists
 
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::io::{self, BufRead};
 use std::fs::File;
+use std::net::IpAddr;
 use std::path::Path;
 
+use base64::Engine;
+use ipnetwork::IpNetwork;
+use serde::Serialize;
+
 use crate::error::{DnsxError, Result};
+use crate::types::{DnsRecord, RecordType, RecordValue, ResponseCode};
 
 /// Input source for domains
 pub enum InputSource {
@@ -25,16 +32,16 @@ pub fn read_domains(source: InputSource) -> Result<Vec<String>> {
                 .lock()
                 .lines()
                 .collect::<io::Result<Vec<String>>>()
-                .map_err(|e| DnsxError::Other(format!("Failed to read from stdin: {}", e)))?;
+                .map_err(|e| DnsxError::io("Failed to read from stdin", e))?;
             Ok(lines.into_iter().filter(|s| !s.trim().is_empty()).collect())
         }
         InputSource::File(path) => {
             let file = File::open(&path)
-                .map_err(|e| DnsxError::Other(format!("Failed to open file {}: {}", path, e)))?;
+                .map_err(|e| DnsxError::io(format!("Failed to open file {}", path), e))?;
             let lines: Vec<String> = io::BufReader::new(file)
                 .lines()
                 .collect::<io::Result<Vec<String>>>()
-                .map_err(|e| DnsxError::Other(format!("Failed to read file {}: {}", path, e)))?;
+                .map_err(|e| DnsxError::io(format!("Failed to read file {}", path), e))?;
             Ok(lines.into_iter().filter(|s| !s.trim().is_empty()).collect())
         }
         InputSource::Args(domains) => {
@@ -43,6 +50,33 @@ pub fn read_domains(source: InputSource) -> Result<Vec<String>> {
     }
 }
 
+/// Read saved DNS records from a JSON array or a JSONL file
+pub fn read_records(path: &Path) -> Result<Vec<DnsRecord>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| DnsxError::io(format!("Failed to read records file {}", path.display()), e))?;
+
+    parse_records(&content, &path.display().to_string())
+}
+
+/// Read saved DNS records (JSON array or JSONL) from stdin
+pub fn read_records_stdin() -> Result<Vec<DnsRecord>> {
+    let content = io::read_to_string(io::stdin())
+        .map_err(|e| DnsxError::io("Failed to read from stdin", e))?;
+
+    parse_records(&content, "stdin")
+}
+
+fn parse_records(content: &str, origin: &str) -> Result<Vec<DnsRecord>> {
+    if let Ok(records) = serde_json::from_str::<Vec<DnsRecord>>(content) {
+        return Ok(records);
+    }
+
+    serde_json::Deserializer::from_str(content)
+        .into_iter::<DnsRecord>()
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|e| DnsxError::parse(origin.to_string(), format!("Invalid record file: {}", e)))
+}
+
 /// Read wordlist from file or stdin
 pub fn read_wordlist(source: &str) -> Result<Vec<String>> {
     if source == "-" {
@@ -52,16 +86,16 @@ pub fn read_wordlist(source: &str) -> Result<Vec<String>> {
             .lock()
             .lines()
             .collect::<io::Result<Vec<String>>>()
-            .map_err(|e| DnsxError::Other(format!("Failed to read wordlist from stdin: {}", e)))?;
+            .map_err(|e| DnsxError::io("Failed to read wordlist from stdin", e))?;
         Ok(lines.into_iter().filter(|s| !s.trim().is_empty()).collect())
     } else if Path::new(source).exists() {
         // Read from file
         let file = File::open(source)
-            .map_err(|e| DnsxError::Other(format!("Failed to open wordlist file {}: {}", source, e)))?;
+            .map_err(|e| DnsxError::io(format!("Failed to open wordlist file {}", source), e))?;
         let lines: Vec<String> = io::BufReader::new(file)
             .lines()
             .collect::<io::Result<Vec<String>>>()
-            .map_err(|e| DnsxError::Other(format!("Failed to read wordlist file {}: {}", source, e)))?;
+            .map_err(|e| DnsxError::io(format!("Failed to read wordlist file {}", source), e))?;
         Ok(lines.into_iter().filter(|s| !s.trim().is_empty()).collect())
     } else if source.contains(',') {
         // Comma-separated words
@@ -74,8 +108,6 @@ pub fn read_wordlist(source: &str) -> Result<Vec<String>> {
 
 /// Parse IP network range (CIDR) into individual IPs
 pub fn parse_ip_range(range: &str) -> Result<Vec<std::net::IpAddr>> {
-    use ipnetwork::IpNetwork;
-    
     let network: IpNetwork = range
         .parse()
         .map_err(|e| DnsxError::invalid_input(format!("Invalid IP range {}: {}", range, e)))?;
@@ -122,3 +154,456 @@ pub fn reverse_ip(ip: &std::net::IpAddr) -> String {
         }
     }
 }
+
+/// Address a reverse DNS name such as `4.3.2.1.in-addr.arpa` stands for
+pub(crate) fn ip_from_ptr_name(name: &str) -> Option<IpAddr> {
+    let name = name.trim().trim_end_matches('.').to_ascii_lowercase();
+
+    if let Some(labels) = name.strip_suffix(".in-addr.arpa") {
+        let octets: Vec<u8> = labels.split('.').rev().map(|l| l.parse().ok()).collect::<Option<_>>()?;
+        let octets: [u8; 4] = octets.try_into().ok()?;
+        return Some(IpAddr::from(octets));
+    }
+
+    let nibbles: Vec<u8> = name
+        .strip_suffix(".ip6.arpa")?
+        .split('.')
+        .rev()
+        .map(|l| u8::from_str_radix(l, 16).ok().filter(|_| l.len() == 1))
+        .collect::<Option<_>>()?;
+    if nibbles.len() != 32 {
+        return None;
+    }
+    let mut octets = [0u8; 16];
+    for (octet, pair) in octets.iter_mut().zip(nibbles.chunks(2)) {
+        *octet = (pair[0] << 4) | pair[1];
+    }
+    Some(IpAddr::from(octets))
+}
+
+/// Hostname suffixes whose PTR names give away the network's operator
+const PTR_ORG_SUFFIXES: &[(&str, &str)] = &[
+    ("1e100.net", "Google"),
+    ("google.com", "Google"),
+    ("dns.google", "Google"),
+    ("googleusercontent.com", "Google Cloud"),
+    ("amazonaws.com", "Amazon AWS"),
+    ("cloudfront.net", "Amazon CloudFront"),
+    ("akamaitechnologies.com", "Akamai"),
+    ("akamai.net", "Akamai"),
+    ("cloudapp.net", "Microsoft Azure"),
+    ("cloudapp.azure.com", "Microsoft Azure"),
+    ("msn.com", "Microsoft"),
+    ("fbcdn.net", "Meta"),
+    ("facebook.com", "Meta"),
+    ("cloudflare.com", "Cloudflare"),
+    ("linode.com", "Linode"),
+    ("linodeusercontent.com", "Linode"),
+    ("your-server.de", "Hetzner"),
+    ("ovh.net", "OVH"),
+    ("comcast.net", "Comcast"),
+    ("verizon.net", "Verizon"),
+    ("rr.com", "Charter Spectrum"),
+    ("sbcglobal.net", "AT&T"),
+];
+
+/// Sample hostnames kept per subnet in a [`PtrSubnetSummary`]
+pub const PTR_SUMMARY_SAMPLES: usize = 5;
+
+/// What a block of scanned addresses resolves to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PtrSubnetSummary {
+    /// Addresses of the block that were queried
+    pub total_ips_scanned: u32,
+    /// Addresses with at least one PTR record
+    pub resolved_count: u32,
+    /// A few of the hostnames found, alphabetically
+    pub sample_domains: Vec<String>,
+    /// Operator guessed from the hostnames, e.g. `Google` for `*.1e100.net`
+    pub likely_org: Option<String>,
+}
+
+/// Groups PTR scan results into subnets
+pub struct PtrSummary;
+
+impl PtrSummary {
+    /// Summaries of the `/prefix_len` blocks that have PTR records, keyed by block
+    ///
+    /// Every address of a block is assumed to have been scanned; use
+    /// [`group_scan`](Self::group_scan) when only part of a range was queried.
+    /// The prefix is capped at the address length, so IPv6 results of a /24
+    /// grouping end up in /24 blocks of the IPv6 space.
+    pub fn group_by_subnet(records: &[DnsRecord], prefix_len: u8) -> HashMap<IpNetwork, PtrSubnetSummary> {
+        Self::group(None, records, prefix_len)
+    }
+
+    /// Like [`group_by_subnet`](Self::group_by_subnet), counting only the addresses in `scanned`
+    ///
+    /// Blocks where nothing resolved are included with a `resolved_count` of 0.
+    pub fn group_scan(
+        scanned: &[IpAddr],
+        records: &[DnsRecord],
+        prefix_len: u8,
+    ) -> HashMap<IpNetwork, PtrSubnetSummary> {
+        Self::group(Some(scanned), records, prefix_len)
+    }
+
+    /// The `n` organizations with the most resolved addresses, largest first
+    pub fn top_orgs(summaries: &HashMap<IpNetwork, PtrSubnetSummary>, n: usize) -> Vec<(String, u32)> {
+        let mut counts: HashMap<&str, u32> = HashMap::new();
+        for summary in summaries.values() {
+            if let Some(org) = &summary.likely_org {
+                *counts.entry(org).or_default() += summary.resolved_count;
+            }
+        }
+
+        let mut orgs: Vec<(String, u32)> = counts.into_iter().map(|(org, count)| (org.to_string(), count)).collect();
+        orgs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        orgs.truncate(n);
+        orgs
+    }
+
+    fn group(
+        scanned: Option<&[IpAddr]>,
+        records: &[DnsRecord],
+        prefix_len: u8,
+    ) -> HashMap<IpNetwork, PtrSubnetSummary> {
+        let mut blocks: HashMap<IpNetwork, (BTreeSet<IpAddr>, BTreeSet<String>)> = HashMap::new();
+        for record in records {
+            let (Some(ip), RecordValue::Domain(hostname)) = (ip_from_ptr_name(&record.domain), &record.value) else {
+                continue;
+            };
+            let (resolved, hostnames) = blocks.entry(subnet_of(ip, prefix_len)).or_default();
+            resolved.insert(ip);
+            hostnames.insert(hostname.trim_end_matches('.').to_lowercase());
+        }
+
+        let mut scanned_counts: HashMap<IpNetwork, u32> = HashMap::new();
+        for ip in scanned.unwrap_or_default() {
+            let subnet = subnet_of(*ip, prefix_len);
+            *scanned_counts.entry(subnet).or_default() += 1;
+            blocks.entry(subnet).or_default();
+        }
+
+        blocks
+            .into_iter()
+            .map(|(subnet, (resolved, hostnames))| {
+                let total_ips_scanned = match scanned {
+                    Some(_) => scanned_counts.get(&subnet).copied().unwrap_or_default(),
+                    None => block_size(subnet),
+                };
+                let summary = PtrSubnetSummary {
+                    total_ips_scanned,
+                    resolved_count: resolved.len() as u32,
+                    sample_domains: hostnames.iter().take(PTR_SUMMARY_SAMPLES).cloned().collect(),
+                    likely_org: likely_org(&hostnames),
+                };
+                (subnet, summary)
+            })
+            .collect()
+    }
+}
+
+/// The `/prefix_len` block containing `ip`
+pub(crate) fn subnet_of(ip: IpAddr, prefix_len: u8) -> IpNetwork {
+    let prefix = prefix_len.min(if ip.is_ipv4() { 32 } else { 128 });
+    let network = IpNetwork::new(ip, prefix).expect("prefix is capped at the address length").network();
+    IpNetwork::new(network, prefix).expect("prefix is capped at the address length")
+}
+
+/// Number of addresses in `subnet`, saturating at `u32::MAX`
+fn block_size(subnet: IpNetwork) -> u32 {
+    let width: u32 = if subnet.is_ipv4() { 32 } else { 128 };
+    let bits = width - u32::from(subnet.prefix());
+    if bits >= 32 {
+        u32::MAX
+    } else {
+        1 << bits
+    }
+}
+
+/// Operator most of `hostnames` point to: a known provider, else their most common parent domain
+fn likely_org(hostnames: &BTreeSet<String>) -> Option<String> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for hostname in hostnames {
+        let known = PTR_ORG_SUFFIXES
+            .iter()
+            .find(|(suffix, _)| hostname.as_str() == *suffix || hostname.ends_with(&format!(".{}", suffix)))
+            .map(|(_, org)| org.to_string());
+        if let Some(org) = known.or_else(|| registrable_domain(hostname)) {
+            *counts.entry(org).or_default() += 1;
+        }
+    }
+
+    // Ties go to the alphabetically first name
+    counts.into_iter().rev().max_by_key(|(_, count)| *count).map(|(org, _)| org)
+}
+
+/// Last two labels of `hostname`, or three under a short second-level label such as `co.uk`
+fn registrable_domain(hostname: &str) -> Option<String> {
+    let labels: Vec<&str> = hostname.split('.').filter(|l| !l.is_empty()).collect();
+    if labels.len() < 2 {
+        return None;
+    }
+    let tld = labels[labels.len() - 1];
+    let second = labels[labels.len() - 2];
+    let take = if labels.len() >= 3 && tld.len() == 2 && second.len() <= 3 { 3 } else { 2 };
+    Some(labels[labels.len() - take..].join("."))
+}
+
+/// Read a master-format zone file (RFC 1035 section 5)
+///
+/// `origin` is used for relative names until a `$ORIGIN` directive changes it.
+pub fn read_zone_file(path: &Path, origin: Option<&str>) -> Result<Vec<DnsRecord>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| DnsxError::io(format!("Failed to read zone file {}", path.display()), e))?;
+
+    parse_zone_file(&content, origin)
+}
+
+/// Parse master-format zone file text into records
+///
+/// Supports `$ORIGIN`, `$TTL`, `@`, relative and blank owner names, and
+/// parenthesized multi-line entries. Types without a structured
+/// [`RecordValue`] keep their RDATA text as [`RecordValue::Other`].
+pub fn parse_zone_file(content: &str, origin: Option<&str>) -> Result<Vec<DnsRecord>> {
+    let mut origin = origin.map(|o| o.trim().trim_end_matches('.').to_string());
+    let mut default_ttl: Option<u32> = None;
+    let mut last_ttl: Option<u32> = None;
+    let mut owner: Option<String> = None;
+    let mut records = Vec::new();
+
+    for (line_number, inherits_owner, tokens) in zone_entries(content)? {
+        let fail = |message: String| DnsxError::parse(format!("line {}", line_number), message);
+        let mut tokens = tokens.into_iter().peekable();
+
+        if !inherits_owner {
+            let first = tokens.next().ok_or_else(|| fail("empty entry".to_string()))?;
+            match first.to_uppercase().as_str() {
+                "$ORIGIN" => {
+                    let name = tokens.next().ok_or_else(|| fail("$ORIGIN without a name".to_string()))?;
+                    origin = Some(zone_name(&name, origin.as_deref()).map_err(fail)?);
+                    continue;
+                }
+                "$TTL" => {
+                    let ttl = tokens.next().ok_or_else(|| fail("$TTL without a value".to_string()))?;
+                    default_ttl = Some(parse_ttl(&ttl).ok_or_else(|| fail(format!("invalid TTL {}", ttl)))?);
+                    continue;
+                }
+                directive if directive.starts_with('$') => {
+                    return Err(fail(format!("unsupported directive {}", first)));
+                }
+                _ => owner = Some(zone_name(&first, origin.as_deref()).map_err(fail)?),
+            }
+        }
+        let domain = owner.clone().ok_or_else(|| fail("no owner name".to_string()))?;
+
+        // TTL and class may appear in either order before the type
+        let mut ttl = None;
+        while let Some(token) = tokens.peek() {
+            if let Some(value) = parse_ttl(token) {
+                ttl = Some(value);
+            } else if ["CH", "HS", "CS"].iter().any(|c| token.eq_ignore_ascii_case(c)) {
+                return Err(fail(format!("unsupported class {}", token)));
+            } else if !token.eq_ignore_ascii_case("IN") {
+                break;
+            }
+            tokens.next();
+        }
+
+        let type_name = tokens.next().ok_or_else(|| fail("missing record type".to_string()))?;
+        let record_type = RecordType::all()
+            .into_iter()
+            .find(|t| t.to_string().eq_ignore_ascii_case(&type_name))
+            .ok_or_else(|| fail(format!("unknown record type {}", type_name)))?;
+        let rdata: Vec<String> = tokens.collect();
+        let value = zone_value(record_type, &rdata, origin.as_deref()).map_err(fail)?;
+
+        // $TTL is the default (RFC 2308); without it the previous record's TTL carries over (RFC 1035)
+        let ttl = ttl.or(default_ttl).or(last_ttl).ok_or_else(|| fail("no TTL and no $TTL directive".to_string()))?;
+        last_ttl = Some(ttl);
+
+        records.push(DnsRecord::new(domain, record_type, value, ttl, ResponseCode::NoError, "zone".to_string(), 0.0));
+    }
+
+    Ok(records)
+}
+
+/// Logical zone file entries: line number, whether the owner is inherited, and tokens
+fn zone_entries(content: &str) -> Result<Vec<(usize, bool, Vec<String>)>> {
+    let mut entries: Vec<(usize, bool, Vec<String>)> = Vec::new();
+    let mut depth = 0usize;
+
+    for (index, line) in content.lines().enumerate() {
+        if depth == 0 {
+            entries.push((index + 1, line.starts_with([' ', '\t']), Vec::new()));
+        }
+        let tokens = &mut entries.last_mut().expect("an entry is open").2;
+
+        let mut chars = line.chars().peekable();
+        let mut current = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                ';' => break,
+                '(' | ')' | ' ' | '\t' => {
+                    if !current.is_empty() {
+                        tokens.push(std::mem::take(&mut current));
+                    }
+                    match c {
+                        '(' => depth += 1,
+                        ')' => {
+                            depth = depth.checked_sub(1).ok_or_else(|| {
+                                DnsxError::parse(format!("line {}", index + 1), "unbalanced ')'")
+                            })?
+                        }
+                        _ => {}
+                    }
+                }
+                '"' => {
+                    // Quoted strings become one token, quotes removed
+                    let mut quoted = String::new();
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '\\' => quoted.extend(chars.next()),
+                            '"' => break,
+                            _ => quoted.push(c),
+                        }
+                    }
+                    tokens.push(quoted);
+                }
+                _ => current.push(c),
+            }
+        }
+        if !current.is_empty() {
+            tokens.push(current);
+        }
+    }
+
+    if depth > 0 {
+        return Err(DnsxError::parse("zone file", "unterminated '(' at end of file"));
+    }
+    Ok(entries.into_iter().filter(|(_, _, tokens)| !tokens.is_empty()).collect())
+}
+
+/// Absolute name without the trailing dot
+fn zone_name(name: &str, origin: Option<&str>) -> std::result::Result<String, String> {
+    if name == "@" {
+        return origin.map(str::to_string).ok_or_else(|| "'@' used without an origin".to_string());
+    }
+    if let Some(absolute) = name.strip_suffix('.') {
+        return Ok(absolute.to_string());
+    }
+    match origin {
+        Some("") => Ok(name.to_string()),
+        Some(origin) => Ok(format!("{}.{}", name, origin)),
+        None => Err(format!("relative name {} used without an origin", name)),
+    }
+}
+
+/// TTL in seconds, accepting BIND-style unit suffixes such as `1h30m`
+fn parse_ttl(token: &str) -> Option<u32> {
+    if let Ok(seconds) = token.parse() {
+        return Some(seconds);
+    }
+
+    let mut total: u32 = 0;
+    let mut number = String::new();
+    for c in token.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            'w' => 604_800,
+            _ => return None,
+        };
+        total = total.checked_add(number.parse::<u32>().ok()?.checked_mul(unit)?)?;
+        number.clear();
+    }
+    number.is_empty().then_some(total)
+}
+
+fn zone_value(
+    record_type: RecordType,
+    rdata: &[String],
+    origin: Option<&str>,
+) -> std::result::Result<RecordValue, String> {
+    let field = |index: usize| {
+        rdata
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("{} record is missing fields", record_type))
+    };
+    let number = |index: usize| -> std::result::Result<u32, String> {
+        let token = field(index)?;
+        parse_ttl(token).ok_or_else(|| format!("invalid number {} in {} record", token, record_type))
+    };
+    let small = |index: usize, max: u32| -> std::result::Result<u32, String> {
+        let value = number(index)?;
+        (value <= max).then_some(value).ok_or_else(|| format!("{} is out of range in {} record", value, record_type))
+    };
+    let name = |index: usize| zone_name(field(index)?, origin);
+    let rest = |index: usize| rdata.get(index..).unwrap_or_default().concat();
+    let hex_rest =
+        |index: usize| hex::decode(rest(index)).map_err(|e| format!("invalid hex in {} record: {}", record_type, e));
+
+    Ok(match record_type {
+        RecordType::A | RecordType::Aaaa => {
+            let address = field(0)?;
+            let ip: IpAddr = address.parse().map_err(|e| format!("invalid address {}: {}", address, e))?;
+            if ip.is_ipv4() != (record_type == RecordType::A) {
+                return Err(format!("{} is not a valid {} address", ip, record_type));
+            }
+            RecordValue::Ip(ip)
+        }
+        RecordType::Ns | RecordType::Cname | RecordType::Ptr | RecordType::Dname => RecordValue::Domain(name(0)?),
+        RecordType::Mx => RecordValue::Mx { priority: small(0, 65_535)? as u16, exchange: name(1)? },
+        RecordType::Txt => RecordValue::Text(rdata.concat()),
+        RecordType::Soa => RecordValue::Soa {
+            mname: name(0)?,
+            rname: name(1)?,
+            serial: number(2)?,
+            refresh: number(3)? as i32,
+            retry: number(4)? as i32,
+            expire: number(5)? as i32,
+            minimum: number(6)?,
+        },
+        RecordType::Srv => RecordValue::Srv {
+            priority: small(0, 65_535)? as u16,
+            weight: small(1, 65_535)? as u16,
+            port: small(2, 65_535)? as u16,
+            target: name(3)?,
+        },
+        RecordType::Caa => RecordValue::Caa { flags: small(0, 255)? as u8, tag: field(1)?.to_string(), value: rest(2) },
+        RecordType::Ds => RecordValue::Ds {
+            key_tag: small(0, 65_535)? as u16,
+            algorithm: small(1, 255)? as u8,
+            digest_type: small(2, 255)? as u8,
+            digest: hex_rest(3)?,
+        },
+        RecordType::Dnskey => RecordValue::Dnskey {
+            flags: small(0, 65_535)? as u16,
+            protocol: small(1, 255)? as u8,
+            algorithm: small(2, 255)? as u8,
+            public_key: base64::engine::general_purpose::STANDARD
+                .decode(rest(3))
+                .map_err(|e| format!("invalid base64 in DNSKEY record: {}", e))?,
+        },
+        RecordType::Tlsa => RecordValue::Tlsa {
+            cert_usage: small(0, 255)? as u8,
+            selector: small(1, 255)? as u8,
+            matching_type: small(2, 255)? as u8,
+            cert_data: hex_rest(3)?,
+        },
+        RecordType::Sshfp => RecordValue::Sshfp {
+            algorithm: small(0, 255)? as u8,
+            fingerprint_type: small(1, 255)? as u8,
+            fingerprint: hex_rest(2)?,
+        },
+        _ => RecordValue::Other(rdata.join(" ")),
+    })
+}
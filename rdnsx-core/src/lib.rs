@@ -3,49 +3,91 @@
 //! This library provides a high-performance DNS resolution engine with support for
 //! multiple record types, wildcard filtering, subdomain enumeration, and database exports.
 
+pub mod audit;
 pub mod bruteforce;
 pub mod cache;
+pub mod categorize;
 pub mod cdn_detection;
 pub mod client;
 pub mod concurrency;
 pub mod config;
+pub mod consistency;
+pub mod ct_enumeration;
+pub mod dane;
+pub mod diff;
+pub mod dnsbl;
 pub mod dns_records;
 pub mod dnssec_analysis;
 pub mod email_security;
 pub mod enumeration;
 pub mod enumeration_types;
 pub mod error;
+pub mod expiry;
 pub mod export;
+pub mod geo_resolver;
+pub mod hsts;
+pub mod index;
 pub mod input;
+pub mod monitor;
+pub mod notify;
 pub mod output;
+pub mod pipeline;
+pub mod ptr_scan;
 pub mod query;
+pub mod rebinding;
 pub mod record_types;
 pub mod record_values;
 pub mod resolver;
 pub mod response_codes;
+pub mod takeover;
+pub mod tls;
+pub mod tunneling;
 pub mod types;
 pub mod utils;
+pub mod vhost;
+pub mod whois;
 pub mod wildcard;
 pub mod zone_transfer;
 
 pub use cache::{DnsCache, CachedDnsClient, CacheStats, DnsQuery};
 pub use client::DnsxClient;
-pub use concurrency::{ConcurrentProcessor, ConcurrencyConfig, ProcessingMetrics, DomainStreamer, AdaptiveBatchSizer, RateLimiter};
-pub use config::{DnsxOptions, ExportConfig, DEFAULT_RESOLVERS};
-pub use enumeration::{DnsEnumerator, PassiveSubdomain, HistoricalIp};
-pub use zone_transfer::ZoneTransferResult;
-pub use email_security::EmailSecurityResult;
-pub use cdn_detection::CdnDetectionResult;
-pub use dnssec_analysis::{DnssecEnumerationResult, ZoneWalkingResult};
+pub use concurrency::{ConcurrentProcessor, ConcurrencyConfig, ProcessingMetrics, QueryOutcome, ResolverStats, DomainStreamer, DomainFilter, DomainEntry, JsonlReader, AdaptiveBatchSizer, RateLimiter};
+pub use config::{ConfigError, DnsxOptions, ExportConfig, IlmConfig, DEFAULT_RESOLVERS};
+pub use enumeration::{DnsEnumerator, PassiveSubdomain, HistoricalIp, HinfoEnumerator, HinfoFinding, TxtSecretScanner, SecretFinding, ServiceDiscovery, ServiceMap, ServiceEntry, CloudVerification, CloudAssetDiscoverer, CloudAsset, MxAnalyzer, MxSummary, MxEntry, LameDelegationDetector, LameDelegationResult};
+pub use zone_transfer::{ZoneTransferClient, ZoneTransferResult, ZoneDiff, ZoneDiffer};
+pub use diff::{DnsChange, RecordChange, RecordSetDiff};
+pub use email_security::{EmailSecurityEnumerator, EmailSecurityResult, EmailSecurityScore, EmailSecurityScorer, DmarcInheritanceChecker, DmarcInheritanceResult, DmarcReportAuthChecker, DmarcReportAuthorization, DmarcAuthStatus, DkimKeyStrength, KeyStrength, SpfConsistencyChecker, SpfConsistencyReport, SpfGroup, SpfOutlier, SpfOutlierReason};
+pub use cdn_detection::{CdnDetectionResult, CdnPattern, CdnPatternDatabase};
+pub use dnssec_analysis::{DnssecEnumerationResult, ZoneWalkingResult, Nsec3SecurityReport, Nsec3Recommendation, DnskeyPrivate, DnssecSigner};
 pub use enumeration_types::{Ipv6EnumerationResult, DnsServerFingerprint, PassiveDnsResult};
 pub use error::{DnsxError, Result};
-pub use types::{DnsRecord, RecordType, ResponseCode, RecordValue};
-pub use export::{Exporter, CassandraExporter, ElasticsearchExporter, MongodbExporter};
-pub use export::cassandra::{CassandraConfig, CassandraMetrics};
-pub use bruteforce::Bruteforcer;
-pub use wildcard::{WildcardFilter, WildcardAnalysis, WildcardBypassAttempt};
-pub use resolver::ResolverPool;
-pub use input::{parse_asn, parse_ip_range, reverse_ip};
+pub use types::{DnsRecord, DnsRecordValidator, RecordType, ResponseCode, RecordValue, ValidationError};
+pub use export::{Exporter, ExportOrchestrator, ExportStats, CassandraExporter, CassandraStats, DomainCount, ElasticsearchExporter, MongodbAnalyzer, MongodbExporter};
+pub use export::cassandra::{CassandraConfig, CassandraMetrics, CassandraPartitionStrategy, PreparedStatementCache, ReplicationStrategy};
+pub use bruteforce::{default_wordlist, subdomain_priority, BruteforceCheckpoint, BruteforceResult, Bruteforcer};
+pub use wildcard::{WildcardFilter, WildcardAnalysis, WildcardBypassAttempt, WildcardBypassTester, BypassResult, WildcardConfidence};
+pub use resolver::{DebugLogger, ResolverPool, ResolverPoolStats, ResolverRole, TcpConnectionPool, TcpPipeline, MAX_CNAME_HOPS};
+pub use rebinding::{RebindingDetector, RebindingAnalysis};
+pub use consistency::{ConsistencyChecker, PoisoningOutlier, PoisoningReport};
+pub use ct_enumeration::{CtCertificate, CtEnumerationResult, CtEnumerator};
+pub use monitor::{SoaTracker, SoaChange};
+pub use notify::{HttpMethod, WebhookNotifier};
+pub use pipeline::{PipelineReport, PipelineRunner, ScanPipeline};
+pub use expiry::{ExpiryAnalyzer, ExpiryWarning};
+pub use index::{DnsResultIndex, IpCluster, IpClusterer};
+pub use ptr_scan::{AsnToRangeResolver, PtrScanner};
+pub use takeover::{SubdomainTakeoverDetector, TakeoverFinding, TakeoverFingerprint, TakeoverSeverity};
+pub use tls::TlsSanEnumerator;
+pub use vhost::VhostEnumerator;
+pub use tunneling::{TunnelingDetector, TunnelingScore};
+pub use whois::{IpWhoisInfo, WhoisEnricher};
+pub use hsts::HstsChecker;
+pub use categorize::{DomainCategorizer, DomainTag};
+pub use geo_resolver::{GeographicResolverSelector, ResolverRegion};
+pub use audit::{AuditEntry, AuditLogger};
+pub use dane::{CertInfo, DaneHttpsResult, DaneTlsaValidator, TlsaRecord, TlsaValidation};
+pub use dnsbl::{DnsblChecker, DnsblListing, DnsblResult, DEFAULT_DNSBLS};
+pub use input::{parse_asn, parse_ip_range, reverse_ip, read_records, read_zone_file, parse_zone_file, PtrSubnetSummary, PtrSummary};
 
 #[cfg(test)]
 mod tests;
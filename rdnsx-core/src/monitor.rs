@@ -0,0 +1,160 @@
+//! Zone change monitoring via SOA serial tracking
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::error::{DnsxError, Result};
+use crate::types::{DnsRecord, RecordValue};
+
+/// A change in SOA serial between two observations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoaChange {
+    pub old_serial: u32,
+    pub new_serial: u32,
+    /// Serial number difference using RFC 1982 serial arithmetic
+    pub delta: i32,
+}
+
+/// Tracks the last-seen SOA serial per domain
+#[derive(Debug, Default)]
+pub struct SoaTracker {
+    serials: HashMap<String, u32>,
+    changes: HashMap<String, SoaChange>,
+}
+
+impl SoaTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load previously seen serials from a JSON file (missing files start empty)
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        let serials: HashMap<String, u32> = serde_json::from_str(&content)
+            .map_err(|e| DnsxError::Serialization(format!("Invalid SOA cache {}: {}", path.display(), e)))?;
+
+        Ok(Self {
+            serials,
+            changes: HashMap::new(),
+        })
+    }
+
+    /// Save the last-seen serials to a JSON file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(&self.serials)
+            .map_err(|e| DnsxError::Serialization(e.to_string()))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Record an observed SOA record; non-SOA records are ignored
+    pub fn record(&mut self, soa_record: &DnsRecord) {
+        let RecordValue::Soa { serial, .. } = soa_record.value else {
+            return;
+        };
+
+        let domain = soa_record.domain.trim_end_matches('.').to_lowercase();
+        if let Some(old_serial) = self.serials.insert(domain.clone(), serial) {
+            if old_serial != serial {
+                self.changes.insert(
+                    domain,
+                    SoaChange {
+                        old_serial,
+                        new_serial: serial,
+                        delta: serial.wrapping_sub(old_serial) as i32,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Return the most recent serial change recorded for `domain`, if any
+    pub fn has_changed(&self, domain: &str) -> Option<SoaChange> {
+        self.changes
+            .get(&domain.trim_end_matches('.').to_lowercase())
+            .copied()
+    }
+
+    /// Last-seen serial for `domain`
+    pub fn serial(&self, domain: &str) -> Option<u32> {
+        self.serials
+            .get(&domain.trim_end_matches('.').to_lowercase())
+            .copied()
+    }
+}
+
+/// Whether `serial` is newer than `previous` under RFC 1982 serial arithmetic
+///
+/// Serials may wrap around, so 5 is newer than 4294967290. Serials exactly
+/// 2^31 apart are incomparable and count as not newer.
+pub(crate) fn serial_is_newer(serial: u32, previous: u32) -> bool {
+    (serial.wrapping_sub(previous) as i32) > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{RecordType, ResponseCode};
+
+    fn soa(domain: &str, serial: u32) -> DnsRecord {
+        DnsRecord::new(
+            domain.to_string(),
+            RecordType::Soa,
+            RecordValue::Soa {
+                mname: "ns1.example.com".to_string(),
+                rname: "hostmaster.example.com".to_string(),
+                serial,
+                refresh: 7200,
+                retry: 3600,
+                expire: 1209600,
+                minimum: 300,
+            },
+            3600,
+            ResponseCode::NoError,
+            "8.8.8.8:53".to_string(),
+            1.0,
+        )
+    }
+
+    #[test]
+    fn test_soa_change_detection() {
+        let mut tracker = SoaTracker::new();
+        tracker.record(&soa("example.com", 2024010101));
+        assert_eq!(tracker.has_changed("example.com"), None);
+
+        tracker.record(&soa("example.com", 2024010101));
+        assert_eq!(tracker.has_changed("example.com"), None);
+
+        tracker.record(&soa("example.com", 2024010105));
+        let change = tracker.has_changed("example.com").unwrap();
+        assert_eq!(change.old_serial, 2024010101);
+        assert_eq!(change.new_serial, 2024010105);
+        assert_eq!(change.delta, 4);
+    }
+
+    #[test]
+    fn test_soa_serial_wraparound() {
+        let mut tracker = SoaTracker::new();
+        tracker.record(&soa("example.com", u32::MAX));
+        tracker.record(&soa("example.com", 1));
+        assert_eq!(tracker.has_changed("example.com").unwrap().delta, 2);
+    }
+
+    #[test]
+    fn test_serial_is_newer() {
+        assert!(serial_is_newer(2024010105, 2024010101));
+        assert!(!serial_is_newer(2024010101, 2024010101));
+        assert!(!serial_is_newer(2024010101, 2024010105));
+        assert!(serial_is_newer(5, u32::MAX - 5));
+        assert!(!serial_is_newer(1 << 31, 0));
+    }
+}
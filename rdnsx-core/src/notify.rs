@@ -0,0 +1,178 @@
+//! Webhook notifications for DNS record changes
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use ring::hmac;
+use tracing::{debug, warn};
+
+use crate::config::{BackoffConfig, WebhookConfig};
+use crate::diff::DnsChange;
+use crate::error::{DnsxError, Result};
+
+/// Header carrying the hex HMAC-SHA256 of the request body
+pub const SIGNATURE_HEADER: &str = "X-RDNSx-Signature";
+
+/// HTTP method used to deliver a change
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HttpMethod {
+    #[default]
+    Post,
+    Put,
+}
+
+impl From<HttpMethod> for reqwest::Method {
+    fn from(method: HttpMethod) -> Self {
+        match method {
+            HttpMethod::Post => reqwest::Method::POST,
+            HttpMethod::Put => reqwest::Method::PUT,
+        }
+    }
+}
+
+/// Sends each record change as JSON to a webhook
+///
+/// With a secret set, the body is signed so the receiver can check it came
+/// from this scanner. 5xx responses and failed connections are retried with
+/// exponential backoff; any other non-2xx response fails immediately.
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+    method: HttpMethod,
+    headers: HashMap<String, String>,
+    secret: Option<String>,
+    retry_count: u32,
+    timeout: Duration,
+    backoff: BackoffConfig,
+}
+
+impl WebhookNotifier {
+    /// Create a notifier with 3 retries, a 10 second timeout and no signature
+    pub fn new(url: String, method: HttpMethod, headers: HashMap<String, String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            method,
+            headers,
+            secret: None,
+            retry_count: 3,
+            timeout: Duration::from_secs(10),
+            backoff: BackoffConfig::default(),
+        }
+    }
+
+    /// Create a notifier from the `[notifications.webhooks]` config section, or `None` if it has no URL
+    pub fn from_config(config: &WebhookConfig) -> Option<Self> {
+        if config.url.is_empty() {
+            return None;
+        }
+        Some(
+            Self::new(config.url.clone(), HttpMethod::Post, HashMap::new())
+                .with_secret(Some(config.secret.clone()).filter(|secret| !secret.is_empty()))
+                .with_retry_count(config.retry_count)
+                .with_timeout(Duration::from_secs(config.timeout_secs)),
+        )
+    }
+
+    /// Sign request bodies with `secret`
+    pub fn with_secret(mut self, secret: Option<String>) -> Self {
+        self.secret = secret;
+        self
+    }
+
+    /// Retry up to `retry_count` times after a 5xx response or a failed connection
+    pub fn with_retry_count(mut self, retry_count: u32) -> Self {
+        self.retry_count = retry_count;
+        self
+    }
+
+    /// Give up on a single request after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Wait between retries according to `backoff`
+    pub fn with_backoff(mut self, backoff: BackoffConfig) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// The webhook URL
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Deliver one change
+    pub async fn notify(&self, change: &DnsChange) -> Result<()> {
+        let body = serde_json::to_vec(change)
+            .map_err(|e| DnsxError::Serialization(format!("Failed to serialize change: {}", e)))?;
+
+        let mut attempt = 0;
+        loop {
+            let error = match self.send(&body).await {
+                Ok(response) if response.status().is_success() => {
+                    debug!("Webhook {} accepted change to {}", self.url, change.domain());
+                    return Ok(());
+                }
+                Ok(response) if response.status().is_server_error() => {
+                    DnsxError::network(&self.url, format!("Webhook returned HTTP {}", response.status()))
+                }
+                Ok(response) => {
+                    return Err(DnsxError::network(&self.url, format!("Webhook returned HTTP {}", response.status())))
+                }
+                Err(e) => DnsxError::network(&self.url, format!("Webhook request failed: {}", e)),
+            };
+
+            if attempt >= self.retry_count {
+                return Err(error);
+            }
+            attempt += 1;
+            let delay = self.backoff.delay(attempt);
+            warn!("{}; retrying in {:?} ({}/{})", error, delay, attempt, self.retry_count);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn send(&self, body: &[u8]) -> reqwest::Result<reqwest::Response> {
+        let mut request = self
+            .client
+            .request(self.method.into(), &self.url)
+            .timeout(self.timeout)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret, body));
+        }
+        request.body(body.to_vec()).send().await
+    }
+}
+
+/// Hex HMAC-SHA256 of `body` keyed with `secret`
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hex::encode(hmac::sign(&key, body).as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            signature("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let config = WebhookConfig { url: String::new(), ..WebhookConfig::default() };
+        assert!(WebhookNotifier::from_config(&config).is_none());
+        let config = WebhookConfig { url: "https://hooks.example.com/dns".to_string(), ..config };
+        let notifier = WebhookNotifier::from_config(&config).unwrap();
+        assert_eq!(notifier.url(), "https://hooks.example.com/dns");
+        assert!(notifier.secret.is_none());
+    }
+}
@@ -0,0 +1,243 @@
+//! Multi-stage scans run in a single process
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use futures::StreamExt;
+use tracing::{info, warn};
+
+use crate::bruteforce::{default_wordlist, Bruteforcer};
+use crate::cdn_detection::{CdnDetectionResult, CdnDetector};
+use crate::client::DnsxClient;
+use crate::config::DnsxOptions;
+use crate::email_security::{EmailSecurityEnumerator, EmailSecurityResult};
+use crate::error::{DnsxError, Result};
+use crate::export::Exporter;
+use crate::input::read_wordlist;
+use crate::resolver::ResolverPool;
+use crate::types::{DnsRecord, RecordType};
+
+/// Placeholder bruteforce stages use for the word in a candidate name
+const BRUTEFORCE_PLACEHOLDER: &str = "FUZZ";
+
+enum Stage {
+    Bruteforce { wordlist: Option<String>, domain: String },
+    Domains(Vec<String>),
+    CdnDetect,
+    EmailSecurity,
+    Export(Arc<dyn Exporter>),
+}
+
+impl Stage {
+    fn name(&self) -> &'static str {
+        match self {
+            Stage::Bruteforce { .. } => "bruteforce",
+            Stage::Domains(_) => "domains",
+            Stage::CdnDetect => "cdn_detect",
+            Stage::EmailSecurity => "email_security",
+            Stage::Export(_) => "export",
+        }
+    }
+}
+
+/// What a pipeline produced: the domains and records passed along its
+/// stages, and the findings of each analysis stage
+#[derive(Debug, Clone, Default)]
+pub struct PipelineReport {
+    /// Domains in the order they were found, without duplicates
+    pub domains: Vec<String>,
+    pub records: Vec<DnsRecord>,
+    pub cdn: Vec<CdnDetectionResult>,
+    pub email_security: Vec<EmailSecurityResult>,
+}
+
+impl PipelineReport {
+    fn add_domain(&mut self, domain: String, seen: &mut HashSet<String>) {
+        if seen.insert(domain.clone()) {
+            self.domains.push(domain);
+        }
+    }
+}
+
+/// Describes a chain of scan stages, e.g. bruteforce, then CDN detection,
+/// then email security, then export
+///
+/// The first stage must produce domains ([`bruteforce`](Self::bruteforce) or
+/// [`domains`](Self::domains)); every later stage works on the domains and
+/// records collected up to that point.
+pub struct ScanPipeline {
+    stages: Vec<Stage>,
+    options: DnsxOptions,
+}
+
+impl ScanPipeline {
+    /// Start an empty pipeline using the default resolver options
+    pub fn new() -> Self {
+        Self { stages: Vec::new(), options: DnsxOptions::default() }
+    }
+
+    /// Resolvers, timeouts and concurrency for every stage
+    pub fn with_options(mut self, options: DnsxOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Find subdomains of `domain` with a wordlist file or comma-separated words (`None` for the built-in list)
+    pub fn bruteforce(mut self, wordlist: Option<String>, domain: impl Into<String>) -> Self {
+        self.stages.push(Stage::Bruteforce { wordlist, domain: domain.into() });
+        self
+    }
+
+    /// Add a fixed list of domains
+    pub fn domains(mut self, domains: Vec<String>) -> Self {
+        self.stages.push(Stage::Domains(domains));
+        self
+    }
+
+    /// Detect the CDN in front of each domain
+    pub fn then_cdn_detect(mut self) -> Self {
+        self.stages.push(Stage::CdnDetect);
+        self
+    }
+
+    /// Check the SPF, DMARC and DKIM setup of each domain
+    pub fn then_email_security(mut self) -> Self {
+        self.stages.push(Stage::EmailSecurity);
+        self
+    }
+
+    /// Send the records collected so far to `exporter` and flush it
+    pub fn then_export(mut self, exporter: Arc<dyn Exporter>) -> Self {
+        self.stages.push(Stage::Export(exporter));
+        self
+    }
+
+    /// Check the stage order and create the resolvers the stages share
+    pub fn build(self) -> Result<PipelineRunner> {
+        match self.stages.first() {
+            None => return Err(DnsxError::validation("A pipeline needs at least one stage")),
+            Some(Stage::Bruteforce { .. } | Stage::Domains(_)) => {}
+            Some(stage) => {
+                return Err(DnsxError::validation(format!(
+                    "A pipeline must start with bruteforce or domains, not {}",
+                    stage.name()
+                )))
+            }
+        }
+
+        Ok(PipelineRunner {
+            client: Arc::new(DnsxClient::with_options(self.options.clone())?),
+            resolver_pool: Arc::new(ResolverPool::new(&self.options)?),
+            concurrency: self.options.concurrency.max(1),
+            stages: self.stages,
+        })
+    }
+}
+
+impl Default for ScanPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A validated [`ScanPipeline`], ready to run
+pub struct PipelineRunner {
+    stages: Vec<Stage>,
+    client: Arc<DnsxClient>,
+    resolver_pool: Arc<ResolverPool>,
+    concurrency: usize,
+}
+
+impl PipelineRunner {
+    /// Run every stage in order
+    ///
+    /// A domain that fails CDN detection or the email security checks is
+    /// skipped with a warning; failing to read a wordlist or export stops the
+    /// pipeline.
+    pub async fn run(&self) -> Result<PipelineReport> {
+        let mut report = PipelineReport::default();
+        let mut seen = HashSet::new();
+
+        for (i, stage) in self.stages.iter().enumerate() {
+            info!("Pipeline stage {} ({}) with {} domains", i + 1, stage.name(), report.domains.len());
+            match stage {
+                Stage::Bruteforce { wordlist, domain } => {
+                    let words = match wordlist {
+                        Some(source) => read_wordlist(source)?,
+                        None => default_wordlist().to_vec(),
+                    };
+                    let results = Bruteforcer::new(Arc::clone(&self.client), self.concurrency)
+                        .enumerate_words_with_records(domain, words, BRUTEFORCE_PLACEHOLDER, &[RecordType::A])
+                        .await?;
+                    for result in results {
+                        report.records.extend(result.records.into_values().flatten());
+                        report.add_domain(result.subdomain, &mut seen);
+                    }
+                }
+                Stage::Domains(domains) => {
+                    for domain in domains {
+                        report.add_domain(domain.trim().trim_end_matches('.').to_lowercase(), &mut seen);
+                    }
+                }
+                Stage::CdnDetect => {
+                    let detector = CdnDetector::new(Arc::clone(&self.resolver_pool));
+                    report.cdn = self.for_each_domain(&report.domains, "CDN detection", |domain| {
+                        let detector = &detector;
+                        async move { detector.detect(&domain).await }
+                    })
+                    .await;
+                }
+                Stage::EmailSecurity => {
+                    let enumerator = EmailSecurityEnumerator::new(Arc::clone(&self.resolver_pool));
+                    report.email_security = self.for_each_domain(&report.domains, "Email security check", |domain| {
+                        let enumerator = &enumerator;
+                        async move { enumerator.enumerate(&domain).await }
+                    })
+                    .await;
+                }
+                Stage::Export(exporter) => {
+                    exporter.export_batch(report.records.clone()).await?;
+                    exporter.flush().await?;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Run `check` on every domain, keeping the successful results in domain order
+    async fn for_each_domain<T, F, Fut>(&self, domains: &[String], what: &str, check: F) -> Vec<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        futures::stream::iter(domains.iter().cloned())
+            .map(|domain| {
+                let result = check(domain.clone());
+                async move { (domain, result.await) }
+            })
+            .buffered(self.concurrency)
+            .filter_map(|(domain, result)| async move {
+                match result {
+                    Ok(value) => Some(value),
+                    Err(e) => {
+                        warn!("{} failed for {}: {}", what, domain, e);
+                        None
+                    }
+                }
+            })
+            .collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_must_start_with_a_source() {
+        assert!(ScanPipeline::new().build().is_err());
+        assert!(ScanPipeline::new().then_cdn_detect().domains(vec!["example.com".to_string()]).build().is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! ASN-driven reverse DNS (PTR) scanning
+
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use ipnetwork::IpNetwork;
+use tracing::{info, warn};
+
+use crate::client::DnsxClient;
+use crate::concurrency::{ConcurrencyConfig, ConcurrentProcessor};
+use crate::config::DnsxOptions;
+use crate::enumeration::DnsEnumerator;
+use crate::error::Result;
+use crate::input::reverse_ip;
+use crate::resolver::ResolverPool;
+use crate::types::{DnsRecord, RecordType, RecordValue};
+
+/// Resolves an ASN to the CIDR blocks it announces
+pub struct AsnToRangeResolver {
+    enumerator: DnsEnumerator,
+}
+
+impl AsnToRangeResolver {
+    /// Create a new ASN range resolver
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        Self {
+            enumerator: DnsEnumerator::new(resolver_pool),
+        }
+    }
+
+    /// Get the IPv4 and IPv6 prefixes announced by `asn`
+    pub async fn get_cidrs(&self, asn: &str) -> Result<Vec<IpNetwork>> {
+        let asn_result = self.enumerator.asn_enumeration(asn).await?;
+
+        let mut cidrs = Vec::new();
+        for prefix in asn_result.ipv4_prefixes.iter().chain(&asn_result.ipv6_prefixes) {
+            match prefix.parse::<IpNetwork>() {
+                Ok(network) => cidrs.push(network),
+                Err(e) => warn!("Skipping invalid prefix {} for {}: {}", prefix, asn, e),
+            }
+        }
+
+        Ok(cidrs)
+    }
+}
+
+/// Runs PTR lookups across whole address ranges
+pub struct PtrScanner {
+    client: Arc<DnsxClient>,
+    asn_resolver: AsnToRangeResolver,
+    concurrency: ConcurrencyConfig,
+}
+
+impl PtrScanner {
+    /// Create a new PTR scanner
+    pub fn new(options: &DnsxOptions) -> Result<Self> {
+        Ok(Self {
+            client: Arc::new(DnsxClient::with_options(options.clone())?),
+            asn_resolver: AsnToRangeResolver::new(Arc::new(ResolverPool::new(options)?)),
+            concurrency: ConcurrencyConfig {
+                max_concurrent: options.concurrency,
+                batch_size: 1000,
+                timeout: options.timeout,
+                rate_limit: options.rate_limit,
+                rate_limit_burst_multiplier: options.rate_limit_burst_multiplier,
+            },
+        })
+    }
+
+    /// ASN range resolver used by this scanner
+    pub fn asn_resolver(&self) -> &AsnToRangeResolver {
+        &self.asn_resolver
+    }
+
+    /// Number of IPv4 addresses covered by `cidrs` (IPv6 ranges are not scanned)
+    pub fn count_ips(cidrs: &[IpNetwork]) -> u64 {
+        cidrs
+            .iter()
+            .filter_map(|cidr| match cidr {
+                IpNetwork::V4(v4) => Some(v4.size() as u64),
+                IpNetwork::V6(_) => None,
+            })
+            .sum()
+    }
+
+    /// Run PTR lookups for every IPv4 address announced by `asn`
+    pub async fn scan_asn(&self, asn: &str) -> Result<Vec<DnsRecord>> {
+        let cidrs = self.asn_resolver.get_cidrs(asn).await?;
+        self.scan_cidrs(&cidrs).await
+    }
+
+    /// Run PTR lookups for every IPv4 address in `cidrs`
+    pub async fn scan_cidrs(&self, cidrs: &[IpNetwork]) -> Result<Vec<DnsRecord>> {
+        let ips = cidrs.iter().filter_map(|cidr| match cidr {
+            IpNetwork::V4(v4) => Some(v4.iter().map(IpAddr::V4)),
+            IpNetwork::V6(_) => None,
+        });
+
+        info!("Scanning {} IPv4 addresses for PTR records", Self::count_ips(cidrs));
+
+        let client = Arc::clone(&self.client);
+        let processor = ConcurrentProcessor::new(self.concurrency.clone(), move |ip: IpAddr| {
+            let client = Arc::clone(&client);
+            Box::pin(async move {
+                let mut records = client.query(&reverse_ip(&ip), RecordType::Ptr).await?;
+                records.retain(|r| !matches!(r.value, RecordValue::Other(_)));
+                Ok(records.into())
+            })
+        });
+
+        let (records, _) = processor.process_stream(ips.flatten()).await?;
+        Ok(records)
+    }
+}
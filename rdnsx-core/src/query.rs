@@ -1,30 +1,88 @@
 //! DNS query engine
 
 use std::net::IpAddr;
+use std::sync::Arc;
 use std::time::Instant;
 
 use hickory_resolver::proto::rr::RData;
-use tracing::debug;
+use chrono::Utc;
+use tracing::{debug, warn};
 
+use crate::audit::{AuditEntry, AuditLogger};
 use crate::error::Result;
 use crate::resolver::ResolverPool;
 use crate::types::{DnsRecord, RecordType, RecordValue, ResponseCode};
+use crate::utils::{canonicalize_domain, normalize_domain};
 
 /// DNS query engine
 pub struct QueryEngine {
     resolver_pool: ResolverPool,
+    /// Send queries to the zone's authoritative nameservers instead of the resolvers
+    authoritative: bool,
+    /// Receives one entry per query, successful or not
+    audit_logger: Option<Arc<AuditLogger>>,
 }
 
 impl QueryEngine {
     /// Create a new query engine
     pub fn new(resolver_pool: ResolverPool) -> Self {
-        Self { resolver_pool }
+        Self { resolver_pool, authoritative: false, audit_logger: None }
+    }
+
+    /// Query authoritative nameservers directly (non-recursive)
+    pub fn with_authoritative(mut self, authoritative: bool) -> Self {
+        self.authoritative = authoritative;
+        self
+    }
+
+    /// Record every query in an audit log
+    pub fn with_audit_logger(mut self, audit_logger: Option<Arc<AuditLogger>>) -> Self {
+        self.audit_logger = audit_logger;
+        self
+    }
+
+    /// Build and check every resolver in the pool ahead of the first query
+    pub async fn warm_up(&self) -> Result<()> {
+        self.resolver_pool.warm_up().await
     }
 
     /// Query a domain for a specific record type
     pub async fn query(&self, domain: &str, record_type: RecordType) -> Result<Vec<DnsRecord>> {
+        let Some(audit_logger) = &self.audit_logger else {
+            return self.run_query(domain, record_type).await;
+        };
+
         let start = Instant::now();
-        let (lookup, resolver_addr) = self.resolver_pool.query(domain, record_type).await?;
+        let result = self.run_query(domain, record_type).await;
+        let response_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        let entry = match &result {
+            Ok(records) => AuditEntry {
+                timestamp: Utc::now(),
+                domain: domain.to_string(),
+                record_type,
+                resolver: records.first().map(|r| r.resolver.clone()),
+                response_code: records.first().map(|r| r.response_code).unwrap_or(ResponseCode::NoError).to_string(),
+                response_time_ms,
+                records_returned: records.iter().filter(|r| !is_empty_answer(r)).count(),
+            },
+            Err(e) => AuditEntry::from_error(domain, record_type, e, response_time_ms),
+        };
+        if let Err(e) = audit_logger.log(&entry) {
+            warn!("Failed to write audit log entry for {} ({}): {}", domain, record_type, e);
+        }
+
+        result
+    }
+
+    async fn run_query(&self, domain: &str, record_type: RecordType) -> Result<Vec<DnsRecord>> {
+        let domain = &canonicalize_domain(domain)?;
+        let start = Instant::now();
+        let (lookup, resolver_addr) = if self.authoritative {
+            self.resolver_pool.query_authoritative(domain, record_type).await?
+        } else {
+            self.resolver_pool.query(domain, record_type).await?
+        };
 
         let query_time_ms = start.elapsed().as_secs_f64() * 1000.0;
         // Lookup represents successful queries, so assume NOERROR
@@ -55,7 +113,7 @@ impl QueryEngine {
             records.push(DnsRecord::new(
                 domain.to_string(),
                 record_type,
-                RecordValue::Other("No records found".to_string()),
+                RecordValue::Other(NO_RECORDS_FOUND.to_string()),
                 0,
                 response_code,
                 resolver_addr,
@@ -75,6 +133,18 @@ impl QueryEngine {
     pub async fn lookup_ipv6(&self, domain: &str) -> Result<Vec<std::net::Ipv6Addr>> {
         self.resolver_pool.lookup_ipv6(domain).await
     }
+
+    /// Follow CNAME records and return the final A and AAAA addresses
+    pub async fn resolve_to_ips(&self, domain: &str) -> Result<Vec<std::net::IpAddr>> {
+        self.resolver_pool.resolve_to_ips(domain).await
+    }
+}
+
+/// Placeholder value of the record returned for a NOERROR answer without data
+const NO_RECORDS_FOUND: &str = "No records found";
+
+fn is_empty_answer(record: &DnsRecord) -> bool {
+    matches!(&record.value, RecordValue::Other(text) if text == NO_RECORDS_FOUND)
 }
 
 /// Parse RData into RecordValue
@@ -82,9 +152,9 @@ pub fn parse_rdata(rdata: &RData) -> Result<RecordValue> {
     match rdata {
         RData::A(ipv4) => Ok(RecordValue::Ip(IpAddr::V4(**ipv4))),
         RData::AAAA(ipv6) => Ok(RecordValue::Ip(IpAddr::V6(**ipv6))),
-        RData::CNAME(cname) => Ok(RecordValue::Domain(cname.to_string())),
-        RData::PTR(ptr) => Ok(RecordValue::Domain(ptr.to_string())),
-        RData::NS(ns) => Ok(RecordValue::Domain(ns.to_string())),
+        RData::CNAME(cname) => Ok(RecordValue::Domain(normalize_domain(&cname.to_string()))),
+        RData::PTR(ptr) => Ok(RecordValue::Domain(normalize_domain(&ptr.to_string()))),
+        RData::NS(ns) => Ok(RecordValue::Domain(normalize_domain(&ns.to_string()))),
         RData::MX(mx) => Ok(RecordValue::Mx {
             priority: mx.preference(),
             exchange: mx.exchange().to_string(),
@@ -0,0 +1,140 @@
+//! DNS rebinding attack detection
+
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{debug, info};
+
+use crate::error::Result;
+use crate::resolver::ResolverPool;
+use crate::types::RecordType;
+
+/// Results from DNS rebinding analysis
+#[derive(Debug, Clone)]
+pub struct RebindingAnalysis {
+    pub domain: String,
+    pub is_rebinding_candidate: bool,
+    pub public_ips: Vec<IpAddr>,
+    pub private_ips: Vec<IpAddr>,
+    /// Classification of each query round in order ("public", "private", "mixed" or "empty")
+    pub change_pattern: Vec<String>,
+}
+
+/// Detects domains that flip between public and private addresses across queries
+pub struct RebindingDetector {
+    resolver_pool: Arc<ResolverPool>,
+}
+
+impl RebindingDetector {
+    /// Create a new rebinding detector
+    pub fn new(resolver_pool: Arc<ResolverPool>) -> Self {
+        Self { resolver_pool }
+    }
+
+    /// Query `domain` `repeat` times, sleeping `interval` between rounds, and
+    /// flag it if both public and private addresses were returned
+    pub async fn check(&self, domain: &str, repeat: u8, interval: Duration) -> Result<RebindingAnalysis> {
+        info!("Checking DNS rebinding for: {}", domain);
+
+        let mut public_ips = BTreeSet::new();
+        let mut private_ips = BTreeSet::new();
+        let mut change_pattern = Vec::new();
+
+        for round in 0..repeat.max(1) {
+            if round > 0 {
+                tokio::time::sleep(interval).await;
+            }
+
+            let round_ips = self.resolve_round(domain).await;
+            let (private, public): (Vec<IpAddr>, Vec<IpAddr>) =
+                round_ips.into_iter().partition(is_private_ip);
+
+            let label = match (public.is_empty(), private.is_empty()) {
+                (true, true) => "empty",
+                (false, true) => "public",
+                (true, false) => "private",
+                (false, false) => "mixed",
+            };
+            debug!("Rebinding round {} for {}: {}", round + 1, domain, label);
+            change_pattern.push(label.to_string());
+
+            public_ips.extend(public);
+            private_ips.extend(private);
+        }
+
+        Ok(RebindingAnalysis {
+            domain: domain.to_string(),
+            is_rebinding_candidate: !public_ips.is_empty() && !private_ips.is_empty(),
+            public_ips: public_ips.into_iter().collect(),
+            private_ips: private_ips.into_iter().collect(),
+            change_pattern,
+        })
+    }
+
+    /// Collect A and AAAA addresses for a single query round
+    async fn resolve_round(&self, domain: &str) -> Vec<IpAddr> {
+        let mut ips = Vec::new();
+
+        for record_type in [RecordType::A, RecordType::Aaaa] {
+            if let Ok((lookup, _)) = self.resolver_pool.query(domain, record_type).await {
+                for rdata in lookup.iter() {
+                    match rdata {
+                        hickory_resolver::proto::rr::RData::A(a) => ips.push(IpAddr::V4(a.0)),
+                        hickory_resolver::proto::rr::RData::AAAA(aaaa) => ips.push(IpAddr::V6(aaaa.0)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        ips
+    }
+}
+
+/// Check whether an address is private, loopback, link-local or otherwise non-routable
+pub fn is_private_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                // 100.64.0.0/10 carrier-grade NAT
+                || (v4.octets()[0] == 100 && (v4.octets()[1] & 0xc0) == 64)
+        }
+        IpAddr::V6(v6) => {
+            if let Some(mapped) = v6.to_ipv4_mapped() {
+                return is_private_ip(&IpAddr::V4(mapped));
+            }
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                // fc00::/7 unique local
+                || (first & 0xfe00) == 0xfc00
+                // fe80::/10 link-local
+                || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_private_ip() {
+        assert!(is_private_ip(&"10.0.0.1".parse().unwrap()));
+        assert!(is_private_ip(&"192.168.1.1".parse().unwrap()));
+        assert!(is_private_ip(&"172.16.5.4".parse().unwrap()));
+        assert!(is_private_ip(&"127.0.0.1".parse().unwrap()));
+        assert!(is_private_ip(&"::1".parse().unwrap()));
+        assert!(is_private_ip(&"fd00::1".parse().unwrap()));
+        assert!(is_private_ip(&"::ffff:192.168.0.1".parse().unwrap()));
+
+        assert!(!is_private_ip(&"8.8.8.8".parse().unwrap()));
+        assert!(!is_private_ip(&"172.32.0.1".parse().unwrap()));
+        assert!(!is_private_ip(&"2001:4860:4860::8888".parse().unwrap()));
+    }
+}
@@ -1,9 +1,10 @@
 //! DNS record types enumeration and conversion utilities
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// DNS record types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum RecordType {
     /// A record (IPv4 address)
@@ -50,6 +51,8 @@ pub enum RecordType {
     Nsec,
     /// NSEC3 record (DNSSEC)
     Nsec3,
+    /// NSEC3PARAM record (DNSSEC hashing parameters)
+    Nsec3param,
     /// OPT record (EDNS options)
     Opt,
     /// RRSIG record (DNSSEC signature)
@@ -90,6 +93,7 @@ impl RecordType {
             RecordType::Naptr,
             RecordType::Nsec,
             RecordType::Nsec3,
+            RecordType::Nsec3param,
             RecordType::Opt,
             RecordType::Rrsig,
             RecordType::Sshfp,
@@ -115,7 +119,8 @@ impl RecordType {
             // RecordType::Afsdb => HRecordType::AFSDB,
             RecordType::Caa => HRecordType::CAA,
             // RecordType::Cert => HRecordType::CERT,
-            RecordType::Dname => HRecordType::ANAME,
+            // hickory has no DNAME variant (RFC 6672, type 39)
+            RecordType::Dname => HRecordType::Unknown(39),
             RecordType::Dnskey => HRecordType::DNSKEY,
             RecordType::Ds => HRecordType::DS,
             RecordType::Hinfo => HRecordType::HINFO,
@@ -125,6 +130,7 @@ impl RecordType {
             RecordType::Naptr => HRecordType::NAPTR,
             RecordType::Nsec => HRecordType::NSEC,
             RecordType::Nsec3 => HRecordType::NSEC3,
+            RecordType::Nsec3param => HRecordType::NSEC3PARAM,
             RecordType::Opt => HRecordType::OPT,
             RecordType::Rrsig => HRecordType::RRSIG,
             RecordType::Sshfp => HRecordType::SSHFP,
@@ -135,6 +141,39 @@ impl RecordType {
             RecordType::Afsdb | RecordType::Cert | RecordType::Loc | RecordType::Uri => HRecordType::A,
         }
     }
+
+    /// Convert from a hickory-dns RecordType, `None` for types without a counterpart here
+    pub fn from_hickory(record_type: hickory_resolver::proto::rr::RecordType) -> Option<Self> {
+        use hickory_resolver::proto::rr::RecordType as HRecordType;
+        Some(match record_type {
+            HRecordType::A => RecordType::A,
+            HRecordType::AAAA => RecordType::Aaaa,
+            HRecordType::CNAME => RecordType::Cname,
+            HRecordType::MX => RecordType::Mx,
+            HRecordType::TXT => RecordType::Txt,
+            HRecordType::NS => RecordType::Ns,
+            HRecordType::SOA => RecordType::Soa,
+            HRecordType::PTR => RecordType::Ptr,
+            HRecordType::SRV => RecordType::Srv,
+            HRecordType::CAA => RecordType::Caa,
+            HRecordType::Unknown(39) => RecordType::Dname,
+            HRecordType::DNSKEY => RecordType::Dnskey,
+            HRecordType::DS => RecordType::Ds,
+            HRecordType::HINFO => RecordType::Hinfo,
+            HRecordType::HTTPS => RecordType::Https,
+            HRecordType::KEY => RecordType::Key,
+            HRecordType::NAPTR => RecordType::Naptr,
+            HRecordType::NSEC => RecordType::Nsec,
+            HRecordType::NSEC3 => RecordType::Nsec3,
+            HRecordType::NSEC3PARAM => RecordType::Nsec3param,
+            HRecordType::OPT => RecordType::Opt,
+            HRecordType::RRSIG => RecordType::Rrsig,
+            HRecordType::SSHFP => RecordType::Sshfp,
+            HRecordType::SVCB => RecordType::Svcb,
+            HRecordType::TLSA => RecordType::Tlsa,
+            _ => return None,
+        })
+    }
 }
 
 impl std::fmt::Display for RecordType {
@@ -162,6 +201,7 @@ impl std::fmt::Display for RecordType {
             RecordType::Naptr => write!(f, "NAPTR"),
             RecordType::Nsec => write!(f, "NSEC"),
             RecordType::Nsec3 => write!(f, "NSEC3"),
+            RecordType::Nsec3param => write!(f, "NSEC3PARAM"),
             RecordType::Opt => write!(f, "OPT"),
             RecordType::Rrsig => write!(f, "RRSIG"),
             RecordType::Sshfp => write!(f, "SSHFP"),
@@ -1,10 +1,11 @@
 //! DNS record value types and implementations
 
 use std::net::IpAddr;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// DNS record value
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum RecordValue {
     /// IP address (A or AAAA)
@@ -1,41 +1,647 @@
 //! DNS resolver pool implementation
 
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
-use hickory_resolver::config::{ResolverConfig, ResolverOpts};
+use deadpool::managed::{self, Metrics, Object, RecycleError, RecycleResult};
+use hickory_resolver::config::{NameServerConfig, Protocol, ResolverConfig, ResolverOpts, TlsClientConfig};
+use hickory_resolver::error::{ResolveError, ResolveErrorKind};
+use hickory_resolver::lookup::Lookup;
+use hickory_resolver::name_server::{ConnectionProvider, GenericConnection, TokioConnectionProvider};
+use hickory_resolver::proto::error::ProtoErrorKind;
+use hickory_resolver::proto::iocompat::AsyncIoTokioAsStd;
+use hickory_resolver::proto::op::{Edns, Message, MessageType, NoopMessageFinalizer, Query};
 use hickory_resolver::proto::rr::RData;
-use hickory_resolver::TokioAsyncResolver;
-use tokio::sync::Semaphore;
+use hickory_resolver::proto::tcp::TcpClientStream;
+use hickory_resolver::proto::xfer::{DnsExchange, DnsHandle, DnsMultiplexer, DnsRequest, DnsRequestOptions, DnsResponse};
+use hickory_resolver::proto::TokioTime;
+use hickory_resolver::AsyncResolver;
+use dashmap::{DashMap, DashSet};
+use futures::future::{join_all, BoxFuture};
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::{OnceCell, Semaphore};
 use tracing::{debug, trace, warn};
 
-use crate::config::DnsxOptions;
+use crate::config::{BackoffConfig, DnsxOptions, DoTConfig, MAX_EDNS_BUFFER_SIZE};
 use crate::error::{DnsxError, Result};
-use crate::types::RecordType;
+use crate::types::{RecordType, ResponseCode};
 use crate::utils;
 
+/// Environment variable that turns on [`DebugLogger`] output when set to `1`
+pub const DEBUG_DNS_ENV: &str = "RDNSX_DEBUG_DNS";
+
+/// CNAME hops [`ResolverPool::resolve_to_ips`] follows before giving up
+pub const MAX_CNAME_HOPS: usize = 10;
+
+/// Which of the pool's servers a query went to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolverRole {
+    /// The resolver picked for the query
+    Primary,
+    /// A resolver tried after the picked one failed
+    Backup,
+    /// A zone's nameserver, queried directly
+    Authoritative,
+}
+
+impl std::fmt::Display for ResolverRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ResolverRole::Primary => "primary",
+            ResolverRole::Backup => "backup",
+            ResolverRole::Authoritative => "authoritative",
+        })
+    }
+}
+
+/// Dumps the DNS messages a pool sends and receives at TRACE level
+///
+/// Needs both the `debug-dns` feature and `RDNSX_DEBUG_DNS=1`; without the
+/// feature every call compiles to nothing. Answers from UDP resolvers are
+/// rebuilt from their question and records, since hickory's resolver does not
+/// return the raw response; TCP responses are logged whole.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DebugLogger {
+    enabled: bool,
+}
+
+impl DebugLogger {
+    /// Enabled when `RDNSX_DEBUG_DNS=1` and the `debug-dns` feature is on
+    pub fn from_env() -> Self {
+        Self {
+            enabled: std::env::var(DEBUG_DNS_ENV).is_ok_and(|value| value == "1"),
+        }
+    }
+
+    /// Whether anything will be logged
+    pub fn is_enabled(&self) -> bool {
+        cfg!(feature = "debug-dns") && self.enabled
+    }
+
+    /// Log the question about to be sent to `resolver`
+    pub fn log_query(
+        &self,
+        resolver: &str,
+        role: ResolverRole,
+        name: &hickory_resolver::proto::rr::Name,
+        record_type: RecordType,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut message = Message::new();
+        message.add_query(Query::query(name.clone(), record_type.to_hickory()));
+        trace!("Sending to {} resolver {}:\n{}", role, resolver, message);
+    }
+
+    /// Log a complete response received from `resolver`
+    pub fn log_response(&self, resolver: &str, message: &Message) {
+        if self.is_enabled() {
+            trace!("Response from {}:\n{}", resolver, message);
+        }
+    }
+
+    /// Log the answer hickory's resolver returned, as a response message
+    pub fn log_lookup(&self, resolver: &str, lookup: &Lookup) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut message = Message::new();
+        message
+            .set_message_type(MessageType::Response)
+            .add_query(lookup.query().clone())
+            .add_answers(lookup.records().iter().cloned());
+        self.log_response(resolver, &message);
+    }
+}
+
+/// Counters describing how the pool answered queries
+#[derive(Debug, Default)]
+pub struct ResolverPoolStats {
+    tcp_retries: AtomicU64,
+}
+
+impl ResolverPoolStats {
+    /// Number of queries re-sent over TCP after a truncated UDP response
+    pub fn tcp_retries(&self) -> u64 {
+        self.tcp_retries.load(Ordering::Relaxed)
+    }
+}
+
+/// A resolver slot whose [`Resolver`] is built on first use
+struct LazyResolver {
+    /// Resolver address
+    addr: String,
+    /// Servers the resolver is built for
+    target: ResolverTarget,
+    /// Transport the resolver is reached over
+    protocol: Protocol,
+    /// Try the system resolver configuration before `config` (primary UDP slot only)
+    prefer_system_conf: bool,
+    /// Plain UDP to the same server, tried when a DoT query fails (`tls_fallback_to_udp` only)
+    udp_fallback: Option<Box<LazyResolver>>,
+    /// The resolver, once built
+    cell: OnceCell<Arc<Resolver>>,
+}
+
+/// Where a [`LazyResolver`] sends its queries
+enum ResolverTarget {
+    /// Servers given by address
+    Config(ResolverConfig),
+    /// A server given by host name, looked up when the resolver is built
+    Host {
+        host: String,
+        port: u16,
+        tls_config: Option<TlsClientConfig>,
+    },
+}
+
+impl LazyResolver {
+    fn new(addr: String, target: ResolverTarget, protocol: Protocol, prefer_system_conf: bool) -> Self {
+        Self {
+            addr,
+            target,
+            protocol,
+            prefer_system_conf,
+            udp_fallback: None,
+            cell: OnceCell::new(),
+        }
+    }
+
+    fn with_udp_fallback(mut self, fallback: LazyResolver) -> Self {
+        self.udp_fallback = Some(Box::new(fallback));
+        self
+    }
+
+    /// Get the resolver, building it if this is the first use
+    ///
+    /// Fails when the server's host name does not resolve; the next call tries again.
+    async fn get(&self, opts: &ResolverOpts, connector: &EdnsConnector) -> Result<Arc<Resolver>> {
+        self.cell
+            .get_or_try_init(|| async { self.build(opts, connector).await.map(Arc::new) })
+            .await
+            .cloned()
+    }
+
+    async fn build(&self, opts: &ResolverOpts, connector: &EdnsConnector) -> Result<Resolver> {
+        if self.prefer_system_conf {
+            // Try system resolver first
+            debug!("Attempting to use system resolver configuration");
+            match Resolver::from_system_conf(connector.clone()) {
+                Ok(resolver) => {
+                    debug!("Successfully created system resolver");
+                    return Ok(resolver);
+                }
+                Err(e) => debug!("System resolver failed ({}), using manual configuration", e),
+            }
+        }
+
+        let config = match &self.target {
+            ResolverTarget::Config(config) => config.clone(),
+            ResolverTarget::Host { host, port, tls_config } => {
+                create_host_resolver_config(host, *port, self.protocol, tls_config.clone()).await?
+            }
+        };
+        debug!("Creating resolver with config: {:?}", config);
+        debug!("Resolver options: timeout={:?}, attempts={}, validate={}", opts.timeout, opts.attempts, opts.validate);
+        Ok(Resolver::new(config, opts.clone(), connector.clone()))
+    }
+}
+
+/// EDNS0 payload size put on every outgoing query, and the servers already warned about
+struct EdnsPayload {
+    size: u16,
+    warned: DashSet<SocketAddr>,
+}
+
+impl EdnsPayload {
+    fn new(size: u16) -> Self {
+        Self {
+            size,
+            warned: DashSet::new(),
+        }
+    }
+
+    /// Add an OPT record advertising the configured size; 512 bytes and below leave EDNS0 off
+    fn apply(&self, message: &mut Message) {
+        if self.size > 512 {
+            message.extensions_mut().get_or_insert_with(Edns::new).set_max_payload(self.size);
+        }
+    }
+
+    /// Warn once per server whose OPT record is missing or advertises less than the configured size
+    fn check(&self, addr: SocketAddr, response: &Message) {
+        if self.size <= 512 {
+            return;
+        }
+        let advertised = response.extensions().as_ref().map(Edns::max_payload);
+        if advertised.is_none_or(|payload| payload < self.size) && self.warned.insert(addr) {
+            match advertised {
+                Some(payload) => warn!(
+                    "{} does not support the requested EDNS0 buffer size of {} bytes (advertises {})",
+                    addr, self.size, payload
+                ),
+                None => warn!("{} does not support EDNS0; requested a buffer size of {} bytes", addr, self.size),
+            }
+        }
+    }
+}
+
+/// hickory resolver whose queries advertise our EDNS0 payload size
+pub(crate) type Resolver = AsyncResolver<EdnsConnector>;
+
+/// hickory's Tokio connections, with the OPT record of each query set to our EDNS0 payload size
+///
+/// hickory always advertises 1232 bytes; its UDP stream sizes the receive buffer from
+/// the query's OPT record, so rewriting it here is enough for larger answers to arrive.
+#[derive(Clone)]
+pub(crate) struct EdnsConnector {
+    inner: TokioConnectionProvider,
+    edns: Arc<EdnsPayload>,
+}
+
+impl EdnsConnector {
+    pub(crate) fn new(size: u16) -> Self {
+        Self {
+            inner: TokioConnectionProvider::default(),
+            edns: Arc::new(EdnsPayload::new(size)),
+        }
+    }
+}
+
+impl ConnectionProvider for EdnsConnector {
+    type Conn = EdnsConnection;
+    type FutureConn = BoxFuture<'static, std::result::Result<EdnsConnection, ResolveError>>;
+    type RuntimeProvider = <TokioConnectionProvider as ConnectionProvider>::RuntimeProvider;
+
+    fn new_connection(&self, config: &NameServerConfig, options: &ResolverOpts) -> Self::FutureConn {
+        let connecting = self.inner.new_connection(config, options);
+        let edns = Arc::clone(&self.edns);
+        let server = config.socket_addr;
+        Box::pin(async move {
+            Ok(EdnsConnection {
+                inner: connecting.await?,
+                edns,
+                server,
+            })
+        })
+    }
+}
+
+/// A connection made by [`EdnsConnector`]
+#[derive(Clone)]
+pub(crate) struct EdnsConnection {
+    inner: GenericConnection,
+    edns: Arc<EdnsPayload>,
+    server: SocketAddr,
+}
+
+impl DnsHandle for EdnsConnection {
+    type Response = BoxStream<'static, std::result::Result<DnsResponse, ResolveError>>;
+    type Error = ResolveError;
+
+    fn send<R: Into<DnsRequest> + Unpin + Send + 'static>(&self, request: R) -> Self::Response {
+        let mut request = request.into();
+        // hickory only adds an OPT record when `ResolverOpts::edns0` is set
+        if let Some(opt) = request.extensions_mut() {
+            opt.set_max_payload(self.edns.size);
+        }
+        let edns = Arc::clone(&self.edns);
+        let server = self.server;
+        self.inner.send(request).inspect_ok(move |response| edns.check(server, response)).boxed()
+    }
+}
+
+/// Pooled TCP connections idle for longer than this are closed instead of reused
+const TCP_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Opens TCP connections to one resolver for [`TcpConnectionPool`]
+struct TcpStreamManager {
+    addr: SocketAddr,
+    connect_timeout: Duration,
+}
+
+impl managed::Manager for TcpStreamManager {
+    type Type = TcpStream;
+    type Error = io::Error;
+
+    async fn create(&self) -> io::Result<TcpStream> {
+        let stream = tokio::time::timeout(self.connect_timeout, TcpStream::connect(self.addr))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, format!("connecting to {} timed out", self.addr)))??;
+        stream.set_nodelay(true)?;
+        Ok(stream)
+    }
+
+    async fn recycle(&self, stream: &mut TcpStream, metrics: &Metrics) -> RecycleResult<io::Error> {
+        if metrics.last_used() > TCP_IDLE_TIMEOUT {
+            return Err(RecycleError::Message("idle timeout".into()));
+        }
+        // Nothing is pending between queries, so a readable socket means the server closed it
+        match stream.try_read(&mut [0u8; 1]) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            _ => Err(RecycleError::Message("closed by server".into())),
+        }
+    }
+}
+
+/// Reusable TCP connections for DNS queries, one pool per resolver address and port
+pub struct TcpConnectionPool {
+    pools: DashMap<SocketAddr, managed::Pool<TcpStreamManager>>,
+    max_size: usize,
+    timeout: Duration,
+    edns: EdnsPayload,
+    debug_logger: DebugLogger,
+}
+
+impl TcpConnectionPool {
+    /// Keep up to `max_size` connections per resolver; `timeout` bounds connects and exchanges
+    pub fn new(max_size: usize, timeout: Duration) -> Self {
+        Self {
+            pools: DashMap::new(),
+            max_size: max_size.max(1),
+            timeout,
+            edns: EdnsPayload::new(512),
+            debug_logger: DebugLogger::from_env(),
+        }
+    }
+
+    /// Advertise an EDNS0 buffer of `size` bytes on every query; 512 (the default) sends no OPT record
+    pub fn with_edns_buffer_size(mut self, size: u16) -> Self {
+        self.edns = EdnsPayload::new(size);
+        self
+    }
+
+    /// Send `query` to `addr` over a pooled connection
+    ///
+    /// A connection that fails mid-exchange is discarded and the query is
+    /// retried once, since the server may have closed it since the last check.
+    pub async fn query(&self, addr: SocketAddr, query: Query) -> Result<Lookup> {
+        let pool = self.pool_for(addr)?;
+
+        let mut request = Message::new();
+        request.set_id(rand::random()).set_recursion_desired(true).add_query(query.clone());
+        self.edns.apply(&mut request);
+        let request = request
+            .to_vec()
+            .map_err(|e| DnsxError::from(e).with_context("Failed to encode query"))?;
+
+        let mut last_error = None;
+        for _ in 0..2 {
+            let mut stream = pool
+                .get()
+                .await
+                .map_err(|e| DnsxError::network_source(addr.to_string(), "TCP connection failed", e))?;
+
+            match tokio::time::timeout(self.timeout, exchange(&mut stream, &request)).await {
+                Ok(Ok(response)) => {
+                    return lookup_from_response(&request, &response, query, addr, &self.debug_logger, &self.edns)
+                }
+                Ok(Err(e)) => {
+                    let _ = Object::take(stream);
+                    last_error = Some(DnsxError::network(addr.to_string(), format!("TCP exchange failed: {}", e)));
+                }
+                Err(_) => {
+                    let _ = Object::take(stream);
+                    last_error = Some(DnsxError::timeout(addr.to_string(), query.name().to_string(), self.timeout));
+                }
+            }
+        }
+
+        Err(last_error.expect("at least one attempt was made"))
+    }
+
+    fn pool_for(&self, addr: SocketAddr) -> Result<managed::Pool<TcpStreamManager>> {
+        if let Some(pool) = self.pools.get(&addr) {
+            return Ok(pool.clone());
+        }
+
+        let manager = TcpStreamManager {
+            addr,
+            connect_timeout: self.timeout,
+        };
+        let pool = managed::Pool::builder(manager)
+            .max_size(self.max_size)
+            .build()
+            .map_err(|e| DnsxError::ResolverConfig(format!("Failed to create TCP pool for {}: {}", addr, e)))?;
+        Ok(self.pools.entry(addr).or_insert(pool).clone())
+    }
+}
+
+/// One length-prefixed DNS message each way (RFC 1035 section 4.2.2)
+async fn exchange(stream: &mut TcpStream, request: &[u8]) -> io::Result<Vec<u8>> {
+    let mut framed = Vec::with_capacity(request.len() + 2);
+    framed.extend_from_slice(&(request.len() as u16).to_be_bytes());
+    framed.extend_from_slice(request);
+    stream.write_all(&framed).await?;
+
+    let len = stream.read_u16().await? as usize;
+    let mut response = vec![0u8; len];
+    stream.read_exact(&mut response).await?;
+    Ok(response)
+}
+
+fn lookup_from_response(
+    request: &[u8],
+    response: &[u8],
+    query: Query,
+    addr: SocketAddr,
+    debug_logger: &DebugLogger,
+    edns: &EdnsPayload,
+) -> Result<Lookup> {
+    let mut message = Message::from_vec(response)
+        .map_err(|e| DnsxError::parse_source(hex::encode(response), format!("Malformed TCP response from {}", addr), e))?;
+    debug_logger.log_response(&addr.to_string(), &message);
+    edns.check(addr, &message);
+
+    if message.id() != u16::from_be_bytes([request[0], request[1]]) {
+        return Err(DnsxError::network(addr.to_string(), "TCP response ID does not match the query"));
+    }
+    if message.response_code() != hickory_resolver::proto::op::ResponseCode::NoError {
+        return Err(DnsxError::resolve(format!(
+            "{} answered {} over TCP with {}",
+            addr,
+            query.name(),
+            message.response_code()
+        )));
+    }
+
+    Ok(Lookup::new_with_max_ttl(query, Arc::from(message.take_answers())))
+}
+
+/// Queries multiplexed over one TCP connection per server (RFC 7766 section 6.2.1)
+///
+/// Up to `depth` queries are in flight on a connection at once; hickory's
+/// multiplexer matches responses to queries by message ID, so answers may
+/// arrive in any order.
+pub struct TcpPipeline {
+    /// One cell per server, so concurrent first queries wait for a single connect
+    connections: DashMap<SocketAddr, Arc<OnceCell<Arc<PipelinedConnection>>>>,
+    depth: usize,
+    timeout: Duration,
+    edns: EdnsPayload,
+    debug_logger: DebugLogger,
+}
+
+/// One open connection and the slots for queries outstanding on it
+struct PipelinedConnection {
+    exchange: DnsExchange,
+    in_flight: Semaphore,
+}
+
+impl TcpPipeline {
+    /// Allow `depth` outstanding queries per connection; `timeout` bounds connects and each answer
+    pub fn new(depth: usize, timeout: Duration) -> Self {
+        Self {
+            connections: DashMap::new(),
+            depth: depth.max(1),
+            timeout,
+            edns: EdnsPayload::new(512),
+            debug_logger: DebugLogger::from_env(),
+        }
+    }
+
+    /// Advertise an EDNS0 buffer of `size` bytes on every query; 512 (the default) sends no OPT record
+    pub fn with_edns_buffer_size(mut self, size: u16) -> Self {
+        self.edns = EdnsPayload::new(size);
+        self
+    }
+
+    /// Send `query` to `addr` over the shared connection, opening it if needed
+    ///
+    /// A closed connection is replaced and the query re-sent once. Empty and
+    /// negative answers are returned as [`DnsxError::Rcode`], like hickory's resolver does.
+    pub async fn query(&self, addr: SocketAddr, query: Query, recursion_desired: bool) -> Result<Lookup> {
+        let mut message = Message::new();
+        message.set_recursion_desired(recursion_desired).add_query(query.clone());
+        self.edns.apply(&mut message);
+
+        let mut last_error = None;
+        for _ in 0..2 {
+            let connection = self.connection(addr).await?;
+            let _slot = connection
+                .in_flight
+                .acquire()
+                .await
+                .map_err(|e| DnsxError::Other(format!("Failed to acquire pipeline slot: {}", e)))?;
+
+            let request = DnsRequest::new(message.clone(), DnsRequestOptions::default());
+            match tokio::time::timeout(self.timeout, connection.exchange.send(request).next()).await {
+                Ok(Some(Ok(response))) => {
+                    let response = response.into_message();
+                    self.debug_logger.log_response(&addr.to_string(), &response);
+                    self.edns.check(addr, &response);
+                    return lookup_from_message(response, query, addr);
+                }
+                Ok(Some(Err(e))) if matches!(e.kind(), ProtoErrorKind::Timeout) => {
+                    return Err(DnsxError::timeout(addr.to_string(), query.name().to_string(), self.timeout));
+                }
+                Ok(other) => {
+                    // The background task has gone away or the connection broke: reconnect
+                    let reason = other.and_then(|r| r.err()).map_or_else(|| "connection closed".to_string(), |e| e.to_string());
+                    self.connections
+                        .remove_if(&addr, |_, current| current.get().is_some_and(|c| Arc::ptr_eq(c, &connection)));
+                    last_error = Some(DnsxError::network(addr.to_string(), format!("TCP pipeline failed: {}", reason)));
+                }
+                Err(_) => return Err(DnsxError::timeout(addr.to_string(), query.name().to_string(), self.timeout)),
+            }
+        }
+
+        Err(last_error.expect("at least one attempt was made"))
+    }
+
+    async fn connection(&self, addr: SocketAddr) -> Result<Arc<PipelinedConnection>> {
+        // Clone the cell out so the map shard is not locked while connecting
+        let cell = Arc::clone(self.connections.entry(addr).or_default().value());
+        cell.get_or_try_init(|| self.connect(addr)).await.cloned()
+    }
+
+    async fn connect(&self, addr: SocketAddr) -> Result<Arc<PipelinedConnection>> {
+        let (stream, handle) = TcpClientStream::<AsyncIoTokioAsStd<TcpStream>>::with_timeout(addr, self.timeout);
+        let multiplexer = DnsMultiplexer::<_, NoopMessageFinalizer>::with_timeout(stream, handle, self.timeout, None);
+        let (exchange, background) = DnsExchange::connect::<_, _, TokioTime>(multiplexer)
+            .await
+            .map_err(|e| DnsxError::network_source(addr.to_string(), "TCP connection failed", e))?;
+        tokio::spawn(async move {
+            if let Err(e) = background.await {
+                debug!("TCP pipeline to {} closed: {}", addr, e);
+            }
+        });
+        debug!("Opened TCP pipeline to {} ({} queries in flight)", addr, self.depth);
+
+        Ok(Arc::new(PipelinedConnection {
+            exchange,
+            in_flight: Semaphore::new(self.depth),
+        }))
+    }
+}
+
+/// Turn a response into a `Lookup`, mapping empty or negative answers to [`DnsxError::Rcode`]
+fn lookup_from_message(mut message: Message, query: Query, addr: SocketAddr) -> Result<Lookup> {
+    let rcode = message.response_code();
+    if rcode != hickory_resolver::proto::op::ResponseCode::NoError || message.answers().is_empty() {
+        debug!("{} answered {} over TCP with {} and {} records", addr, query.name(), rcode, message.answers().len());
+        return Err(DnsxError::Rcode {
+            resolver: addr.to_string(),
+            domain: query.name().to_string(),
+            rcode: ResponseCode::from_hickory(rcode),
+        });
+    }
+
+    Ok(Lookup::new_with_max_ttl(query, Arc::from(message.take_answers())))
+}
+
 /// DNS resolver pool with connection reuse
+///
+/// Resolvers are created lazily on their first query; call [`ResolverPool::warm_up`]
+/// to build them all up front instead.
 pub struct ResolverPool {
-    /// Primary resolver
-    resolver: Arc<TokioAsyncResolver>,
-    /// Primary resolver address
-    primary_resolver_addr: String,
-    /// Backup resolvers
-    backup_resolvers: Vec<Arc<TokioAsyncResolver>>,
-    /// Backup resolver addresses
-    _backup_resolver_addrs: Vec<String>,
+    /// Resolvers in round-robin order (primary first)
+    resolvers: Vec<LazyResolver>,
+    /// Options shared by every resolver
+    resolver_opts: ResolverOpts,
+    /// Connections shared by every resolver, advertising the configured EDNS0 payload size
+    connector: EdnsConnector,
     /// Concurrency semaphore
     semaphore: Arc<Semaphore>,
     /// Query timeout
     timeout: Duration,
-    /// Number of retries
-    _retries: u32,
+    /// Timeouts overriding `timeout` for specific record types
+    per_type_timeout: HashMap<RecordType, Duration>,
+    /// Number of retries after the first attempt (and its failover) fails
+    retries: u32,
+    /// Delay between retries
+    backoff: BackoffConfig,
+    /// Answers larger than this are discarded
+    max_response_size: usize,
+    /// Answers larger than this are logged; 0 disables the warning
+    warn_response_size: usize,
     /// Round-robin index for load balancing
     rr_index: std::sync::atomic::AtomicUsize,
+    /// Connections used to retry truncated answers; `None` when TCP fallback is disabled
+    tcp_pool: Option<TcpConnectionPool>,
+    /// Send every query over pipelined TCP instead of UDP; `None` unless enabled
+    tcp_pipeline: Option<TcpPipeline>,
+    /// Query statistics
+    stats: ResolverPoolStats,
+    /// Addresses of authoritative nameservers, by hostname, kept for the pool's lifetime
+    nameserver_addrs: DashMap<String, Vec<Ipv4Addr>>,
+    /// Message dumps for `RDNSX_DEBUG_DNS=1`
+    debug_logger: DebugLogger,
 }
 
 impl ResolverPool {
     /// Create a new resolver pool
+    ///
+    /// Only parses and validates the resolver addresses; no resolver is built, and
+    /// no DoH or DoT host name looked up, until it is first queried.
     pub fn new(options: &DnsxOptions) -> Result<Self> {
         let resolvers = if options.resolvers.is_empty() {
             return Err(DnsxError::validation("At least one resolver is required"));
@@ -43,166 +649,516 @@ impl ResolverPool {
             options.resolvers.clone()
         };
 
-        // Parse and validate resolvers
-        let mut resolver_configs = Vec::new();
+        // Parse and validate resolvers; DoH and DoT servers keep their URL as the address
+        let mut resolver_addrs = Vec::new();
         for resolver_str in &resolvers {
-            let addr = utils::parse_resolver(resolver_str)?;
-            resolver_configs.push(addr);
+            if utils::is_doh_url(resolver_str) {
+                utils::parse_doh_url(resolver_str)?;
+                resolver_addrs.push(resolver_str.clone());
+            } else if utils::is_dot_url(resolver_str) {
+                utils::parse_dot_url(resolver_str)?;
+                resolver_addrs.push(resolver_str.clone());
+            } else {
+                resolver_addrs.push(utils::parse_resolver(resolver_str)?.to_string());
+            }
         }
 
-        // Store primary resolver address
-        let primary_resolver_addr = resolver_configs[0].clone();
+        // Our own `tokio::time::timeout` enforces the per-type limits, so hickory
+        // and the TCP fallback must not give up before the longest of them
+        let longest_timeout = options.per_type_timeout.values().copied().fold(options.timeout, Duration::max);
 
-        // Create primary resolver
-        let primary_config = create_resolver_config(&resolver_configs[0..1].iter().map(|addr| addr.to_string()).collect::<Vec<_>>())?;
         let mut resolver_opts = ResolverOpts::default();
-        resolver_opts.timeout = options.timeout;
-        resolver_opts.attempts = options.retries as usize;
+        resolver_opts.timeout = longest_timeout;
+        // Retries are driven by `query` so they can back off between attempts
+        resolver_opts.attempts = 0;
         resolver_opts.validate = false; // Don't validate, just resolve
         resolver_opts.use_hosts_file = false; // Don't use hosts file
         resolver_opts.ip_strategy = hickory_resolver::config::LookupIpStrategy::Ipv4thenIpv6; // Prefer IPv4
 
-        // Try system resolver first
-        debug!("Attempting to use system resolver configuration");
-        let system_resolver = TokioAsyncResolver::tokio_from_system_conf();
-
-        let resolver = match system_resolver {
-            Ok(resolver) => {
-                debug!("Successfully created system resolver");
-                resolver
-            }
-            Err(e) => {
-                debug!("System resolver failed ({}), using manual configuration", e);
-                debug!("Creating resolver with config: {:?}", primary_config);
-                debug!("Resolver options: timeout={:?}, attempts={}, validate={}", resolver_opts.timeout, resolver_opts.attempts, resolver_opts.validate);
+        // Advertise EDNS0 so large answers (DNSKEY, NSEC3, long TXT) are not truncated to 512 bytes
+        if options.edns_buffer_size > MAX_EDNS_BUFFER_SIZE {
+            return Err(DnsxError::validation(format!(
+                "EDNS0 buffer size {} exceeds the {} bytes hickory can receive over UDP",
+                options.edns_buffer_size, MAX_EDNS_BUFFER_SIZE
+            )));
+        }
+        resolver_opts.edns0 = options.edns_buffer_size > 512;
 
-                TokioAsyncResolver::tokio(primary_config, resolver_opts.clone())
-            }
+        let dot_tls_config = if resolver_addrs.iter().any(|addr| utils::is_dot_url(addr)) {
+            dot_tls_config(&options.dot)?
+        } else {
+            None
         };
 
-        // Create backup resolvers if any
-        let mut backup_resolvers = Vec::new();
-        let mut backup_resolver_addrs = Vec::new();
-        if resolver_configs.len() > 1 {
-            for config in &resolver_configs[1..] {
-                let backup_config = create_resolver_config(&[config.to_string()])?;
-                let backup_resolver = TokioAsyncResolver::tokio(
-                    backup_config,
-                    resolver_opts.clone(),
-                );
-                backup_resolvers.push(backup_resolver);
-                backup_resolver_addrs.push(config.to_string());
+        let mut udp_resolvers = Vec::new();
+        for (i, addr) in resolver_addrs.iter().enumerate() {
+            if utils::is_doh_url(addr) {
+                let (host, port) = utils::parse_doh_url(addr)?;
+                let target = ResolverTarget::Host { host, port, tls_config: None };
+                udp_resolvers.push(LazyResolver::new(addr.clone(), target, Protocol::Https, false));
+            } else if utils::is_dot_url(addr) {
+                let (host, port) = utils::parse_dot_url(addr)?;
+                let target = ResolverTarget::Host { host: host.clone(), port, tls_config: dot_tls_config.clone() };
+                let mut slot = LazyResolver::new(addr.clone(), target, Protocol::Tls, false);
+                if options.tls_fallback_to_udp {
+                    let udp_addr = match host.parse::<IpAddr>() {
+                        Ok(ip) => SocketAddr::new(ip, 53).to_string(),
+                        Err(_) => format!("{}:53", host),
+                    };
+                    let target = ResolverTarget::Host { host, port: 53, tls_config: None };
+                    slot = slot.with_udp_fallback(LazyResolver::new(udp_addr, target, Protocol::Udp, false));
+                }
+                udp_resolvers.push(slot);
+            } else {
+                let config = create_resolver_config(std::slice::from_ref(addr), Protocol::Udp)?;
+                let target = ResolverTarget::Config(config);
+                udp_resolvers.push(LazyResolver::new(addr.clone(), target, Protocol::Udp, i == 0));
             }
         }
 
+        // The UDP configs carry no TCP servers, so hickory never retries on its
+        // own and every fallback goes through the pool and is counted here
+        let tcp_pool = options.tcp_fallback.then(|| {
+            TcpConnectionPool::new(options.tcp_pool_size, longest_timeout)
+                .with_edns_buffer_size(options.edns_buffer_size)
+        });
+        let tcp_pipeline = options.tcp_pipeline.then(|| {
+            TcpPipeline::new(options.tcp_pipeline_depth, longest_timeout)
+                .with_edns_buffer_size(options.edns_buffer_size)
+        });
+
         Ok(Self {
-            resolver: Arc::new(resolver),
-            primary_resolver_addr: primary_resolver_addr.to_string(),
-            backup_resolvers: backup_resolvers.into_iter().map(Arc::new).collect(),
-            _backup_resolver_addrs: backup_resolver_addrs,
+            resolvers: udp_resolvers,
+            resolver_opts,
+            connector: EdnsConnector::new(options.edns_buffer_size),
             semaphore: Arc::new(Semaphore::new(options.concurrency)),
             timeout: options.timeout,
-            _retries: options.retries,
+            per_type_timeout: options.per_type_timeout.clone(),
+            retries: options.retries,
+            backoff: options.retry_backoff,
+            max_response_size: options.max_response_size_bytes,
+            warn_response_size: options.warn_on_large_response_bytes,
             rr_index: std::sync::atomic::AtomicUsize::new(0),
+            tcp_pool,
+            tcp_pipeline,
+            stats: ResolverPoolStats::default(),
+            nameserver_addrs: DashMap::new(),
+            debug_logger: DebugLogger::from_env(),
         })
     }
 
+    /// Build every resolver now and check each one answers
+    ///
+    /// Resolvers are otherwise created on their first query. Each UDP resolver
+    /// is sent a root NS query in parallel; the first failure is returned.
+    pub async fn warm_up(&self) -> Result<()> {
+        let root = hickory_resolver::Name::root();
+
+        let probes = self.resolvers.iter().map(|slot| {
+            let root = root.clone();
+            async move {
+                let resolver = slot.get(&self.resolver_opts, &self.connector).await?;
+                match tokio::time::timeout(self.timeout, resolver.lookup(root, RecordType::Ns.to_hickory())).await {
+                    Ok(Ok(_)) => Ok(()),
+                    Ok(Err(e)) => Err(classify_resolve_error(&e, ".", &slot.addr, self.timeout)),
+                    Err(_) => Err(DnsxError::timeout(slot.addr.clone(), ".", self.timeout)),
+                }
+            }
+        });
+        let results = join_all(probes).await;
+        debug!("Warmed up {} resolvers", self.resolvers.len());
+
+        results.into_iter().collect()
+    }
+
     /// Query DNS with a specific record type
+    ///
+    /// Timeouts, network errors and SERVFAIL are retried up to the configured
+    /// number of times with exponential backoff between attempts. Answers over
+    /// the size limit fail with [`DnsxError::ResponseTooLarge`].
     pub async fn query(
         &self,
         domain: &str,
         record_type: RecordType,
+    ) -> Result<(hickory_resolver::lookup::Lookup, String)> {
+        let mut attempt = 0;
+        loop {
+            match self.query_once(domain, record_type).await {
+                Err(e) if attempt < self.retries && is_transient(&e) => {
+                    attempt += 1;
+                    let delay = self.backoff.delay(attempt);
+                    debug!(
+                        "Retrying {} ({}) in {:?}, attempt {}/{}: {}",
+                        domain, record_type, delay, attempt, self.retries, e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => {
+                    return result.and_then(|(lookup, resolver)| {
+                        self.check_response_size(domain, record_type, &lookup, &resolver)?;
+                        Ok((lookup, resolver))
+                    })
+                }
+            }
+        }
+    }
+
+    /// Reject answers above the size limit and warn about large ones
+    fn check_response_size(
+        &self,
+        domain: &str,
+        record_type: RecordType,
+        lookup: &Lookup,
+        resolver: &str,
+    ) -> Result<()> {
+        let size = response_size(lookup);
+        if size > self.max_response_size {
+            warn!("Discarding {} byte response for {} ({}) from {}", size, domain, record_type, resolver);
+            return Err(DnsxError::ResponseTooLarge { size, limit: self.max_response_size });
+        }
+        if self.warn_response_size > 0 && size > self.warn_response_size {
+            warn!("Large {} byte response for {} ({}) from {}", size, domain, record_type, resolver);
+        }
+        Ok(())
+    }
+
+    /// One pass over the resolvers: the round-robin pick, then failover
+    async fn query_once(
+        &self,
+        domain: &str,
+        record_type: RecordType,
     ) -> Result<(hickory_resolver::lookup::Lookup, String)> {
         let _permit = self.semaphore.acquire().await.map_err(|e| {
             DnsxError::Other(format!("Failed to acquire semaphore: {}", e))
         })?;
 
         let domain_name = hickory_resolver::Name::parse(domain, None)
-            .map_err(|e| DnsxError::invalid_input(format!("Invalid domain name: {}", e)))?;
+            .map_err(|e| DnsxError::from(e).with_context(format!("Invalid domain name {}", domain)))?;
 
         // Use round-robin load balancing across all resolvers
-        let resolver_index = self.rr_index.fetch_add(1, std::sync::atomic::Ordering::Relaxed) % (self.backup_resolvers.len() + 1);
+        let resolver_index = self.rr_index.fetch_add(1, std::sync::atomic::Ordering::Relaxed) % self.resolvers.len();
 
-        let (resolver, resolver_addr) = if resolver_index == 0 {
-            // Primary resolver
-            (&self.resolver, self.primary_resolver_addr.clone())
-        } else {
-            // Backup resolver
-            let backup_index = resolver_index - 1;
-            (&self.backup_resolvers[backup_index], "backup-resolver".to_string())
+        let slot = &self.resolvers[resolver_index];
+        let timeout = self.timeout_for(record_type);
+
+        // Encrypted transports are never downgraded to plain TCP
+        if let Some(pipeline) = self.tcp_pipeline.as_ref().filter(|_| slot.protocol == Protocol::Udp) {
+            return self.query_pipelined(pipeline, &domain_name, record_type, resolver_index, timeout).await;
+        }
+
+        let resolver = match slot.get(&self.resolver_opts, &self.connector).await {
+            Ok(resolver) => resolver,
+            Err(error) => return self.try_failover_resolvers(&domain_name, record_type, resolver_index, error).await,
         };
+        let resolver_addr = slot.addr.clone();
 
-        debug!("Querying {} ({}) using resolver at {}", domain, record_type, resolver_addr);
-        let result = tokio::time::timeout(self.timeout, resolver.lookup(domain_name.clone(), record_type.to_hickory()))
+        debug!("Querying {} ({}) using resolver at {} with {:?} timeout", domain, record_type, resolver_addr, timeout);
+        self.debug_logger.log_query(&resolver_addr, ResolverRole::Primary, &domain_name, record_type);
+        let result = tokio::time::timeout(timeout, resolver.lookup(domain_name.clone(), record_type.to_hickory()))
             .await;
 
         match result {
             Ok(Ok(lookup)) => {
+                self.debug_logger.log_lookup(&resolver_addr, &lookup);
                 debug!("Query successful for {} ({}), lookup contains {} records", domain, record_type, lookup.iter().count());
                 for rdata in lookup.iter() {
                     debug!("Found record: {:?}", rdata);
                 }
+                let lookup = self.retry_truncated(lookup, resolver_index, &domain_name, record_type).await;
                 Ok((lookup, resolver_addr.to_string()))
             }
             Ok(Err(e)) => {
                 debug!("Resolver {} failed for {}: {}", resolver_addr, domain, e);
+                let error = classify_resolve_error(&e, domain, &resolver_addr, timeout);
+                if let Some(answer) = self.query_udp_fallback(slot, &domain_name, record_type, &error).await {
+                    return Ok(answer);
+                }
                 // Try other resolvers with failover
-                self.try_failover_resolvers(&domain_name, record_type, resolver_index).await
+                self.try_failover_resolvers(&domain_name, record_type, resolver_index, error).await
             }
             Err(_) => {
                 warn!("Query timeout for {} ({}) on resolver {}", domain, record_type, resolver_addr);
+                let error = DnsxError::timeout(resolver_addr, domain, timeout);
+                if let Some(answer) = self.query_udp_fallback(slot, &domain_name, record_type, &error).await {
+                    return Ok(answer);
+                }
                 // Try other resolvers with failover
-                self.try_failover_resolvers(&domain_name, record_type, resolver_index).await
+                self.try_failover_resolvers(&domain_name, record_type, resolver_index, error).await
+            }
+        }
+    }
+
+    /// Re-send a query that failed over DoT in plain UDP to the same server
+    ///
+    /// Only slots built with `tls_fallback_to_udp` have a fallback, and only
+    /// network errors and timeouts use it; `None` when it is not used or fails too.
+    async fn query_udp_fallback(
+        &self,
+        slot: &LazyResolver,
+        domain_name: &hickory_resolver::proto::rr::Name,
+        record_type: RecordType,
+        error: &DnsxError,
+    ) -> Option<(hickory_resolver::lookup::Lookup, String)> {
+        let fallback = slot.udp_fallback.as_deref().filter(|_| is_transient(error))?;
+        warn!("DoT query to {} failed ({}), retrying over UDP to {}", slot.addr, error, fallback.addr);
+
+        let resolver = match fallback.get(&self.resolver_opts, &self.connector).await {
+            Ok(resolver) => resolver,
+            Err(e) => {
+                debug!("UDP fallback to {} unavailable: {}", fallback.addr, e);
+                return None;
+            }
+        };
+        let timeout = self.timeout_for(record_type);
+        match tokio::time::timeout(timeout, resolver.lookup(domain_name.clone(), record_type.to_hickory())).await {
+            Ok(Ok(lookup)) => Some((lookup, fallback.addr.clone())),
+            Ok(Err(e)) => {
+                debug!("UDP fallback to {} failed for {}: {}", fallback.addr, domain_name, e);
+                None
             }
+            Err(_) => {
+                debug!("UDP fallback to {} timed out for {}", fallback.addr, domain_name);
+                None
+            }
+        }
+    }
+
+    /// Send a query to the resolver at `resolver_index` over its TCP pipeline
+    ///
+    /// Failures other than a DNS answer fall back to the remaining resolvers over UDP.
+    async fn query_pipelined(
+        &self,
+        pipeline: &TcpPipeline,
+        domain_name: &hickory_resolver::proto::rr::Name,
+        record_type: RecordType,
+        resolver_index: usize,
+        timeout: Duration,
+    ) -> Result<(hickory_resolver::lookup::Lookup, String)> {
+        let resolver_addr = self.resolvers[resolver_index].addr.clone();
+        let addr: SocketAddr = resolver_addr
+            .parse()
+            .map_err(|e| DnsxError::from(e).with_context(format!("Invalid resolver address {}", resolver_addr)))?;
+
+        debug!("Querying {} ({}) over TCP pipeline to {} with {:?} timeout", domain_name, record_type, addr, timeout);
+        self.debug_logger.log_query(&resolver_addr, ResolverRole::Primary, domain_name, record_type);
+        let query = Query::query(domain_name.clone(), record_type.to_hickory());
+        let error = match tokio::time::timeout(timeout, pipeline.query(addr, query, true)).await {
+            Ok(Ok(lookup)) => return Ok((lookup, resolver_addr)),
+            Ok(Err(e @ DnsxError::Rcode { .. })) => return Err(e),
+            Ok(Err(e)) => e,
+            Err(_) => DnsxError::timeout(resolver_addr, domain_name.to_string(), timeout),
+        };
+        self.try_failover_resolvers(domain_name, record_type, resolver_index, error).await
+    }
+
+    /// Query the zone's authoritative nameservers directly, bypassing recursion
+    ///
+    /// The zone's NS records are found through the configured resolvers, then
+    /// each nameserver address is sent a non-recursive (RD=0) query until one
+    /// answers. An authoritative negative answer is returned as-is.
+    pub async fn query_authoritative(
+        &self,
+        domain: &str,
+        record_type: RecordType,
+    ) -> Result<(hickory_resolver::lookup::Lookup, String)> {
+        let domain_name = hickory_resolver::Name::parse(domain, None)
+            .map_err(|e| DnsxError::from(e).with_context(format!("Invalid domain name {}", domain)))?;
+        let nameservers = self.zone_nameservers(domain).await?;
+
+        let _permit = self.semaphore.acquire().await.map_err(|e| {
+            DnsxError::Other(format!("Failed to acquire semaphore: {}", e))
+        })?;
+
+        let mut opts = self.resolver_opts.clone();
+        opts.recursion_desired = false;
+        opts.cache_size = 0;
+
+        let timeout = self.timeout_for(record_type);
+        let mut last_error = DnsxError::resolve(format!("No authoritative nameserver address found for {}", domain));
+        for nameserver in &nameservers {
+            for ip in self.nameserver_addresses(nameserver).await {
+                let socket_addr = SocketAddr::from((ip, 53));
+                let addr = socket_addr.to_string();
+
+                // Many names under one zone share a connection to each of its nameservers
+                if let Some(pipeline) = &self.tcp_pipeline {
+                    debug!("Querying {} ({}) at authoritative server {} ({}) over TCP pipeline", domain, record_type, nameserver, addr);
+                    self.debug_logger.log_query(&addr, ResolverRole::Authoritative, &domain_name, record_type);
+                    let query = Query::query(domain_name.clone(), record_type.to_hickory());
+                    match tokio::time::timeout(timeout, pipeline.query(socket_addr, query, false)).await {
+                        Ok(Ok(lookup)) => return Ok((lookup, addr)),
+                        Ok(Err(e @ DnsxError::Rcode { rcode: ResponseCode::NxDomain | ResponseCode::NoError, .. })) => return Err(e),
+                        Ok(Err(e)) => last_error = e,
+                        Err(_) => last_error = DnsxError::timeout(addr, domain, timeout),
+                    }
+                    continue;
+                }
+
+                let config = create_resolver_config(std::slice::from_ref(&addr), Protocol::Udp)?;
+                let resolver = Resolver::new(config, opts.clone(), self.connector.clone());
+
+                debug!("Querying {} ({}) at authoritative server {} ({}) with {:?} timeout", domain, record_type, nameserver, addr, timeout);
+                self.debug_logger.log_query(&addr, ResolverRole::Authoritative, &domain_name, record_type);
+                match tokio::time::timeout(timeout, resolver.lookup(domain_name.clone(), record_type.to_hickory())).await {
+                    Ok(Ok(lookup)) => {
+                        self.debug_logger.log_lookup(&addr, &lookup);
+                        return Ok((lookup, addr));
+                    }
+                    Ok(Err(e)) => {
+                        let error = classify_resolve_error(&e, domain, &addr, timeout);
+                        if matches!(error, DnsxError::Rcode { rcode: ResponseCode::NxDomain | ResponseCode::NoError, .. }) {
+                            return Err(error);
+                        }
+                        debug!("Authoritative server {} failed for {}: {}", addr, domain, e);
+                        last_error = error;
+                    }
+                    Err(_) => last_error = DnsxError::timeout(addr, domain, timeout),
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+
+    /// NS hostnames of the closest enclosing zone of `domain`
+    async fn zone_nameservers(&self, domain: &str) -> Result<Vec<String>> {
+        for zone in zone_candidates(domain) {
+            let Ok((lookup, _)) = self.query(&zone, RecordType::Ns).await else {
+                continue;
+            };
+            let nameservers: Vec<String> = lookup
+                .iter()
+                .filter_map(|rdata| match rdata {
+                    RData::NS(ns) => Some(ns.to_string()),
+                    _ => None,
+                })
+                .collect();
+            if !nameservers.is_empty() {
+                debug!("Zone of {} is {} served by {:?}", domain, zone, nameservers);
+                return Ok(nameservers);
+            }
+        }
+
+        Err(DnsxError::resolve(format!("No NS records found for any zone enclosing {}", domain)))
+    }
+
+    /// IPv4 addresses of a nameserver, resolved once and then served from the cache
+    async fn nameserver_addresses(&self, nameserver: &str) -> Vec<Ipv4Addr> {
+        if let Some(addrs) = self.nameserver_addrs.get(nameserver) {
+            return addrs.clone();
+        }
+
+        let addrs = self.lookup_ipv4(nameserver).await.unwrap_or_else(|e| {
+            debug!("Could not resolve nameserver {}: {}", nameserver, e);
+            Vec::new()
+        });
+        if !addrs.is_empty() {
+            self.nameserver_addrs.insert(nameserver.to_string(), addrs.clone());
         }
+        addrs
     }
 
     /// Get primary resolver address
     pub fn primary_resolver(&self) -> &str {
-        &self.primary_resolver_addr
+        &self.resolvers[0].addr
+    }
+
+    /// Query statistics for this pool
+    pub fn stats(&self) -> &ResolverPoolStats {
+        &self.stats
+    }
+
+    /// Re-send a query over TCP when the UDP answer was truncated
+    ///
+    /// `Lookup` does not expose the response header, but hickory only returns an
+    /// empty successful lookup when the TC bit was set (an untruncated empty
+    /// answer becomes a `NoRecordsFound` error), so that is used as the signal.
+    async fn retry_truncated(
+        &self,
+        lookup: hickory_resolver::lookup::Lookup,
+        resolver_index: usize,
+        domain_name: &hickory_resolver::proto::rr::Name,
+        record_type: RecordType,
+    ) -> hickory_resolver::lookup::Lookup {
+        if !lookup.records().is_empty() {
+            return lookup;
+        }
+        let Some(tcp_pool) = &self.tcp_pool else {
+            return lookup;
+        };
+        let Some(addr) = self.resolvers.get(resolver_index).and_then(|slot| slot.addr.parse::<SocketAddr>().ok()) else {
+            return lookup;
+        };
+
+        debug!("Truncated response for {} ({}), retrying over TCP", domain_name, record_type);
+        self.stats.tcp_retries.fetch_add(1, Ordering::Relaxed);
+
+        match tcp_pool.query(addr, Query::query(domain_name.clone(), record_type.to_hickory())).await {
+            Ok(tcp_lookup) => tcp_lookup,
+            Err(e) => {
+                debug!("TCP retry failed for {}: {}", domain_name, e);
+                lookup
+            }
+        }
+    }
+
+    /// Timeout for queries of `record_type`: its configured override, else the global timeout
+    fn timeout_for(&self, record_type: RecordType) -> Duration {
+        self.per_type_timeout.get(&record_type).copied().unwrap_or(self.timeout)
     }
 
     /// Try failover resolvers if the selected resolver fails
+    ///
+    /// Returns the error from the last resolver tried when none of them succeed.
     async fn try_failover_resolvers(
         &self,
         domain_name: &hickory_resolver::proto::rr::Name,
         record_type: RecordType,
         failed_index: usize,
+        mut last_error: DnsxError,
     ) -> Result<(hickory_resolver::lookup::Lookup, String)> {
-        let total_resolvers = self.backup_resolvers.len() + 1;
+        let domain = domain_name.to_string();
+        let timeout = self.timeout_for(record_type);
 
         // Try all other resolvers except the failed one
-        for i in 0..total_resolvers {
+        for (i, slot) in self.resolvers.iter().enumerate() {
             if i == failed_index {
                 continue; // Skip the failed resolver
             }
 
-            let (resolver, resolver_addr) = if i == 0 {
-                // Primary resolver
-                (&self.resolver, self.primary_resolver_addr.clone())
-            } else {
-                // Backup resolver
-                (&self.backup_resolvers[i - 1], "backup-resolver".to_string())
+            let resolver = match slot.get(&self.resolver_opts, &self.connector).await {
+                Ok(resolver) => resolver,
+                Err(e) => {
+                    debug!("Failover resolver {} unavailable: {}", slot.addr, e);
+                    last_error = e;
+                    continue;
+                }
             };
+            let resolver_addr = slot.addr.clone();
 
-            let result = tokio::time::timeout(self.timeout, resolver.lookup(domain_name.clone(), record_type.to_hickory()))
+            self.debug_logger.log_query(&resolver_addr, ResolverRole::Backup, domain_name, record_type);
+            let result = tokio::time::timeout(timeout, resolver.lookup(domain_name.clone(), record_type.to_hickory()))
                 .await;
 
             match result {
                 Ok(Ok(response)) => {
                     trace!("Failover resolver {} succeeded", resolver_addr);
+                    self.debug_logger.log_lookup(&resolver_addr, &response);
+                    let response = self.retry_truncated(response, i, domain_name, record_type).await;
                     return Ok((response, resolver_addr.to_string()));
                 }
                 Ok(Err(e)) => {
                     debug!("Failover resolver {} failed: {}", resolver_addr, e);
+                    last_error = classify_resolve_error(&e, &domain, &resolver_addr, timeout);
                 }
                 Err(_) => {
                     debug!("Failover resolver {} timeout", resolver_addr);
+                    last_error = DnsxError::timeout(resolver_addr, domain.clone(), timeout);
                 }
             }
         }
 
-        Err(DnsxError::resolve("All resolvers failed"))
+        Err(last_error)
     }
 
     /// Lookup A records (IPv4)
@@ -232,11 +1188,105 @@ impl ResolverPool {
 
         Ok(ips)
     }
+
+    /// A and AAAA addresses at the end of `domain`'s CNAME chain
+    ///
+    /// At most [`MAX_CNAME_HOPS`] aliases are followed; longer and looping
+    /// chains are errors. A chain ending in a name without addresses gives an
+    /// empty list.
+    pub async fn resolve_to_ips(&self, domain: &str) -> Result<Vec<IpAddr>> {
+        let mut chain = vec![chain_name(domain)];
+        loop {
+            let name = chain[chain.len() - 1].clone();
+            let (ips, aliases) = self.addresses_and_aliases(&name).await?;
+
+            // Resolvers usually chase the chain themselves; take whatever part of it they returned
+            let mut last = name.clone();
+            while let Some(target) = aliases.get(&last) {
+                push_cname_hop(&mut chain, target)?;
+                last = target.clone();
+            }
+            if !ips.is_empty() {
+                debug!("Resolved {} to {} address(es) via {}", domain, ips.len(), chain.join(" -> "));
+                return Ok(ips);
+            }
+
+            // No alias came with the address queries, e.g. because its target does not resolve
+            if last == name {
+                match self.cname_target(&name).await? {
+                    Some(target) => push_cname_hop(&mut chain, &target)?,
+                    None => {
+                        debug!("CNAME chain {} ends without addresses", chain.join(" -> "));
+                        return Ok(Vec::new());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Addresses in the A and AAAA answers for `name`, and the aliases (owner to target) that led to them
+    async fn addresses_and_aliases(&self, name: &str) -> Result<(Vec<IpAddr>, HashMap<String, String>)> {
+        let mut ips = Vec::new();
+        let mut aliases = HashMap::new();
+        for record_type in [RecordType::A, RecordType::Aaaa] {
+            let lookup = match self.query(name, record_type).await {
+                Ok((lookup, _)) => lookup,
+                Err(DnsxError::Rcode { rcode: ResponseCode::NoError | ResponseCode::NxDomain, .. }) => continue,
+                Err(e) => return Err(e),
+            };
+            for record in lookup.records() {
+                match record.data() {
+                    Some(RData::A(a)) => ips.push(IpAddr::V4(**a)),
+                    Some(RData::AAAA(aaaa)) => ips.push(IpAddr::V6(**aaaa)),
+                    Some(RData::CNAME(target)) => {
+                        aliases.insert(chain_name(&record.name().to_string()), chain_name(&target.to_string()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok((ips, aliases))
+    }
+
+    /// Target of the CNAME record at `name`, if it has one
+    pub(crate) async fn cname_target(&self, name: &str) -> Result<Option<String>> {
+        let lookup = match self.query(name, RecordType::Cname).await {
+            Ok((lookup, _)) => lookup,
+            Err(DnsxError::Rcode { rcode: ResponseCode::NoError | ResponseCode::NxDomain, .. }) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        Ok(lookup.records().iter().find_map(|record| match record.data() {
+            Some(RData::CNAME(target)) if chain_name(&record.name().to_string()) == name => {
+                Some(chain_name(&target.to_string()))
+            }
+            _ => None,
+        }))
+    }
+}
+
+/// Name as it appears in a CNAME chain: lowercase, without the trailing dot
+fn chain_name(name: &str) -> String {
+    name.trim_end_matches('.').to_lowercase()
+}
+
+/// Append `target` to a CNAME chain, failing on loops and on chains longer than [`MAX_CNAME_HOPS`]
+pub(crate) fn push_cname_hop(chain: &mut Vec<String>, target: &str) -> Result<()> {
+    if chain.iter().any(|name| name == target) {
+        return Err(DnsxError::validation(format!("CNAME loop: {} -> {}", chain.join(" -> "), target)));
+    }
+    if chain.len() > MAX_CNAME_HOPS {
+        return Err(DnsxError::validation(format!(
+            "CNAME chain from {} is longer than {} hops",
+            chain[0], MAX_CNAME_HOPS
+        )));
+    }
+    chain.push(target.to_string());
+    Ok(())
 }
 
 /// Create resolver config from resolver addresses
-fn create_resolver_config(addrs: &[String]) -> Result<ResolverConfig> {
-    use hickory_resolver::config::{NameServerConfig, Protocol};
+pub(crate) fn create_resolver_config(addrs: &[String], protocol: Protocol) -> Result<ResolverConfig> {
+    use hickory_resolver::config::NameServerConfig;
     use std::net::{SocketAddr, ToSocketAddrs};
 
     let mut config = ResolverConfig::new();
@@ -244,13 +1294,13 @@ fn create_resolver_config(addrs: &[String]) -> Result<ResolverConfig> {
     for addr in addrs {
         let socket_addr: SocketAddr = addr
             .to_socket_addrs()
-            .map_err(|e| DnsxError::ResolverConfig(format!("Invalid resolver address {}: {}", addr, e)))?
+            .map_err(|e| DnsxError::from(e).with_context(format!("Invalid resolver address {}", addr)))?
             .next()
             .ok_or_else(|| DnsxError::ResolverConfig(format!("Failed to resolve {}", addr)))?;
 
         config.add_name_server(NameServerConfig {
             socket_addr,
-            protocol: Protocol::Udp, // Default to UDP
+            protocol,
             tls_dns_name: None,
             trust_negative_responses: false,
             bind_addr: None,
@@ -260,3 +1310,136 @@ fn create_resolver_config(addrs: &[String]) -> Result<ResolverConfig> {
 
     Ok(config)
 }
+
+/// Create resolver config for a server given by host name
+///
+/// The host is looked up with the system resolver when the resolver is first
+/// built; every address it resolves to becomes a name server. DoH and DoT
+/// certificates are checked against the host name.
+async fn create_host_resolver_config(
+    host: &str,
+    port: u16,
+    protocol: Protocol,
+    tls_config: Option<TlsClientConfig>,
+) -> Result<ResolverConfig> {
+    use hickory_resolver::config::NameServerConfigGroup;
+
+    let resolver = format!("{}:{}", host, port);
+    let socket_addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| DnsxError::network_source(&resolver, format!("Failed to resolve resolver host {}", host), e))?
+        .collect();
+    if socket_addrs.is_empty() {
+        return Err(DnsxError::network(resolver, format!("Resolver host {} has no addresses", host)));
+    }
+
+    let mut name_servers: NameServerConfigGroup = socket_addrs
+        .into_iter()
+        .map(|socket_addr| NameServerConfig {
+            socket_addr,
+            protocol,
+            tls_dns_name: (protocol != Protocol::Udp).then(|| host.to_string()),
+            trust_negative_responses: false,
+            bind_addr: None,
+            tls_config: None,
+        })
+        .collect::<Vec<_>>()
+        .into();
+    // hickory ignores `NameServerConfig::tls_config` and only uses the group's client config
+    if let Some(TlsClientConfig(client_config)) = tls_config {
+        name_servers = name_servers.with_client_config(client_config);
+    }
+    Ok(ResolverConfig::from_parts(None, Vec::new(), name_servers))
+}
+
+/// TLS client config for DoT servers, or `None` for hickory's own web PKI verification
+fn dot_tls_config(dot: &DoTConfig) -> Result<Option<TlsClientConfig>> {
+    use rustls::pki_types::pem::PemObject;
+    use rustls::pki_types::CertificateDer;
+
+    if !dot.insecure_skip_verify && dot.root_ca.is_none() {
+        return Ok(None);
+    }
+
+    let mut roots = hickory_rustls::RootCertStore::empty();
+    if let Some(path) = &dot.root_ca {
+        let read_error = |e: rustls::pki_types::pem::Error| {
+            DnsxError::ResolverConfig(format!("Failed to read DoT root CA {}: {}", path.display(), e))
+        };
+        for cert in CertificateDer::pem_file_iter(path).map_err(read_error)? {
+            let cert = cert.map_err(read_error)?;
+            roots.add(&hickory_rustls::Certificate(cert.to_vec())).map_err(|e| {
+                DnsxError::ResolverConfig(format!("Invalid certificate in DoT root CA {}: {}", path.display(), e))
+            })?;
+        }
+        if roots.is_empty() {
+            return Err(DnsxError::ResolverConfig(format!("No certificates in DoT root CA {}", path.display())));
+        }
+    }
+
+    let mut config = hickory_rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    if dot.insecure_skip_verify {
+        warn!("DoT certificate verification is disabled");
+        config.dangerous().set_certificate_verifier(Arc::new(AcceptAnyDotCertificate));
+    }
+    Ok(Some(TlsClientConfig(Arc::new(config))))
+}
+
+/// Accepts any DoT server certificate, for `insecure_skip_verify`
+struct AcceptAnyDotCertificate;
+
+impl hickory_rustls::client::ServerCertVerifier for AcceptAnyDotCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &hickory_rustls::Certificate,
+        _intermediates: &[hickory_rustls::Certificate],
+        _server_name: &hickory_rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> std::result::Result<hickory_rustls::client::ServerCertVerified, hickory_rustls::Error> {
+        Ok(hickory_rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+/// Map a hickory resolver error onto the matching structured error variant
+/// `domain` and each parent zone down to the TLD, most specific first
+pub(crate) fn zone_candidates(domain: &str) -> Vec<String> {
+    let labels: Vec<&str> = domain.trim_end_matches('.').split('.').filter(|l| !l.is_empty()).collect();
+    (0..labels.len()).map(|i| labels[i..].join(".")).collect()
+}
+
+/// Wire size of the answer rebuilt from its question and records
+///
+/// `Lookup` keeps only the answer section, so authority and additional records
+/// are not counted and the result can undershoot the message actually received.
+pub(crate) fn response_size(lookup: &Lookup) -> usize {
+    let mut message = Message::new();
+    message.add_query(lookup.query().clone()).add_answers(lookup.records().iter().cloned());
+    message.to_vec().map_or(0, |wire| wire.len())
+}
+
+/// Errors worth retrying: the same query may well succeed a moment later
+fn is_transient(error: &DnsxError) -> bool {
+    matches!(
+        error,
+        DnsxError::Timeout { .. }
+            | DnsxError::Network { .. }
+            | DnsxError::Rcode { rcode: ResponseCode::ServFail, .. }
+    )
+}
+
+fn classify_resolve_error(error: &ResolveError, domain: &str, resolver: &str, timeout: Duration) -> DnsxError {
+    match error.kind() {
+        ResolveErrorKind::NoRecordsFound { response_code, .. } => DnsxError::Rcode {
+            resolver: resolver.to_string(),
+            domain: domain.to_string(),
+            rcode: ResponseCode::from_hickory(*response_code),
+        },
+        ResolveErrorKind::Timeout => DnsxError::timeout(resolver, domain, timeout),
+        _ => DnsxError::network(resolver, error.to_string()),
+    }
+}
@@ -1,9 +1,10 @@
 //! DNS response codes and utilities
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// DNS response code
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum ResponseCode {
     /// No error condition
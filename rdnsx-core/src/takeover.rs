@@ -0,0 +1,442 @@
+//! Subdomain takeover detection for CNAMEs pointing at unclaimed services
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::cdn_detection::glob_match;
+use crate::error::{DnsxError, Result};
+use crate::resolver::{push_cname_hop, ResolverPool};
+use crate::types::{DnsRecord, RecordType, RecordValue, ResponseCode};
+use crate::utils::normalize_domain;
+
+/// Confidence of a CNAME matching a fingerprint while the records also hold an address for it
+const PATTERN_CONFIDENCE: f64 = 0.4;
+/// Confidence of a match when no name in the chain has an address in the records
+const NO_ADDRESS_CONFIDENCE: f64 = 0.6;
+/// Confidence of a match whose final target does not exist (NXDOMAIN)
+const NXDOMAIN_CONFIDENCE: f64 = 0.9;
+/// Confidence of a match whose domain serves the service's page for unclaimed names
+const HTTP_SIGNATURE_CONFIDENCE: f64 = 0.95;
+
+/// Findings checked against DNS and HTTP at once
+const VERIFY_CONCURRENCY: usize = 10;
+
+/// How much an attacker gains from taking over a name at a service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TakeoverSeverity {
+    /// Limited content, e.g. a landing page or help center
+    Low,
+    /// Arbitrary pages on the name
+    Medium,
+    /// Arbitrary content and scripts, usually with a valid certificate for the name
+    High,
+}
+
+impl std::fmt::Display for TakeoverSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TakeoverSeverity::Low => write!(f, "low"),
+            TakeoverSeverity::Medium => write!(f, "medium"),
+            TakeoverSeverity::High => write!(f, "high"),
+        }
+    }
+}
+
+/// A hosted service where anyone can claim a name that a dangling CNAME points at
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TakeoverFingerprint {
+    pub service: String,
+    /// CNAME target globs such as `*.herokuapp.com`
+    pub cname_patterns: Vec<String>,
+    /// Text in the page the service serves for a name nobody has claimed; without
+    /// one, only a target that does not resolve counts as unclaimed
+    #[serde(default)]
+    pub http_signature: Option<String>,
+    pub severity: TakeoverSeverity,
+}
+
+impl TakeoverFingerprint {
+    fn builtin(service: &str, patterns: &[&str], http_signature: Option<&str>, severity: TakeoverSeverity) -> Self {
+        Self {
+            service: service.to_string(),
+            cname_patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            http_signature: http_signature.map(str::to_string),
+            severity,
+        }
+    }
+
+    /// Load a JSON array of `{ "service", "cname_patterns", "http_signature", "severity" }` entries
+    pub fn load(path: &Path) -> Result<Vec<Self>> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| DnsxError::io(format!("Failed to read takeover fingerprints {}", path.display()), e))?;
+        let fingerprints: Vec<Self> = serde_json::from_str(&content).map_err(|e| {
+            DnsxError::parse(path.display().to_string(), format!("Invalid takeover fingerprint file: {}", e))
+        })?;
+
+        info!("Loaded {} takeover fingerprints from {}", fingerprints.len(), path.display());
+        Ok(fingerprints)
+    }
+
+    /// Whether `name` (normalized) is one of this service's hostnames
+    fn matches(&self, name: &str) -> bool {
+        self.cname_patterns.iter().any(|pattern| glob_match(&pattern.to_lowercase(), name))
+    }
+}
+
+/// A name whose CNAME chain ends at a service fingerprinted as claimable
+#[derive(Debug, Clone, Serialize)]
+pub struct TakeoverFinding {
+    pub domain: String,
+    /// Every name from `domain` to the final CNAME target, in order
+    pub cname_chain: Vec<String>,
+    pub service: String,
+    pub severity: TakeoverSeverity,
+    /// 0-1: a matching CNAME alone is weak evidence, a target that does not
+    /// exist or serves the service's unclaimed page is strong
+    pub confidence: f64,
+}
+
+/// Finds CNAME records pointing at deprovisioned cloud services
+pub struct SubdomainTakeoverDetector {
+    fingerprints: Vec<TakeoverFingerprint>,
+}
+
+impl SubdomainTakeoverDetector {
+    /// Create a detector using [`builtin_fingerprints`](Self::builtin_fingerprints)
+    pub fn new() -> Self {
+        Self { fingerprints: Self::builtin_fingerprints() }
+    }
+
+    /// Replace the fingerprints; the first one matching a chain wins
+    pub fn with_fingerprints(mut self, fingerprints: Vec<TakeoverFingerprint>) -> Self {
+        self.fingerprints = fingerprints;
+        self
+    }
+
+    /// Fingerprints currently checked, in order
+    pub fn fingerprints(&self) -> &[TakeoverFingerprint] {
+        &self.fingerprints
+    }
+
+    /// Services known to hand out unclaimed names, after the "can I take over XYZ" project
+    pub fn builtin_fingerprints() -> Vec<TakeoverFingerprint> {
+        use TakeoverSeverity::{High, Low, Medium};
+        vec![
+            TakeoverFingerprint::builtin(
+                "AWS S3",
+                &["*.s3.amazonaws.com", "*.s3-website*.amazonaws.com"],
+                Some("NoSuchBucket"),
+                High,
+            ),
+            TakeoverFingerprint::builtin("AWS Elastic Beanstalk", &["*.elasticbeanstalk.com"], None, High),
+            TakeoverFingerprint::builtin("Azure App Service", &["*.azurewebsites.net"], None, High),
+            TakeoverFingerprint::builtin(
+                "Azure Cloud Services",
+                &["*.cloudapp.net", "*.cloudapp.azure.com"],
+                None,
+                High,
+            ),
+            TakeoverFingerprint::builtin("Azure Traffic Manager", &["*.trafficmanager.net"], None, High),
+            TakeoverFingerprint::builtin("Azure Blob Storage", &["*.blob.core.windows.net"], None, High),
+            TakeoverFingerprint::builtin("Azure CDN", &["*.azureedge.net"], None, High),
+            TakeoverFingerprint::builtin(
+                "GitHub Pages",
+                &["*.github.io"],
+                Some("There isn't a GitHub Pages site here."),
+                High,
+            ),
+            TakeoverFingerprint::builtin("Heroku", &["*.herokuapp.com", "*.herokudns.com"], Some("No such app"), High),
+            TakeoverFingerprint::builtin("Bitbucket", &["*.bitbucket.io"], Some("Repository not found"), Medium),
+            TakeoverFingerprint::builtin("Fastly", &["*.fastly.net"], Some("Fastly error: unknown domain"), Medium),
+            TakeoverFingerprint::builtin(
+                "Shopify",
+                &["*.myshopify.com"],
+                Some("Sorry, this shop is currently unavailable."),
+                Medium,
+            ),
+            TakeoverFingerprint::builtin(
+                "Netlify",
+                &["*.netlify.app", "*.netlify.com"],
+                Some("Not Found - Request ID"),
+                Medium,
+            ),
+            TakeoverFingerprint::builtin("Surge.sh", &["*.surge.sh"], Some("project not found"), Medium),
+            TakeoverFingerprint::builtin(
+                "Pantheon",
+                &["*.pantheonsite.io"],
+                Some("The gods are wise, but do not know of the site which you seek."),
+                Medium,
+            ),
+            TakeoverFingerprint::builtin(
+                "Ghost",
+                &["*.ghost.io"],
+                Some("The thing you were looking for is no longer here, or never was"),
+                Medium,
+            ),
+            TakeoverFingerprint::builtin(
+                "Tumblr",
+                &["domains.tumblr.com"],
+                Some("Whatever you were looking for doesn't currently exist at this address."),
+                Medium,
+            ),
+            TakeoverFingerprint::builtin(
+                "WordPress.com",
+                &["*.wordpress.com"],
+                Some("Do you want to register"),
+                Medium,
+            ),
+            TakeoverFingerprint::builtin(
+                "Webflow",
+                &["proxy.webflow.com", "proxy-ssl.webflow.com"],
+                Some("The page you are looking for doesn't exist or has been moved."),
+                Medium,
+            ),
+            TakeoverFingerprint::builtin("Zendesk", &["*.zendesk.com"], Some("Help Center Closed"), Low),
+            TakeoverFingerprint::builtin(
+                "Help Scout",
+                &["*.helpscoutdocs.com"],
+                Some("No settings were found for this company:"),
+                Low,
+            ),
+            TakeoverFingerprint::builtin(
+                "Intercom",
+                &["custom.intercom.help"],
+                Some("Uh oh. That page doesn't exist."),
+                Low,
+            ),
+            TakeoverFingerprint::builtin("Readme.io", &["*.readme.io"], Some("Project doesnt exist... yet!"), Low),
+            TakeoverFingerprint::builtin(
+                "Unbounce",
+                &["unbouncepages.com"],
+                Some("The requested URL was not found on this server."),
+                Low,
+            ),
+            TakeoverFingerprint::builtin("Cargo Collective", &["*.cargocollective.com"], Some("404 Not Found"), Low),
+            TakeoverFingerprint::builtin(
+                "Agile CRM",
+                &["*.agilecrm.com"],
+                Some("Sorry, this page is no longer available."),
+                Low,
+            ),
+        ]
+    }
+
+    /// Names in `records` whose CNAME chain ends at a fingerprinted service
+    ///
+    /// Chains are followed through the CNAME records given, without any
+    /// queries, so the findings are only candidates; confidence is higher when
+    /// no name in the chain has an address among the records.
+    pub fn detect(&self, records: &[DnsRecord]) -> Vec<TakeoverFinding> {
+        let mut owners = Vec::new();
+        let mut cnames: HashMap<String, String> = HashMap::new();
+        let mut addressed = HashSet::new();
+        for record in records {
+            let owner = normalize_domain(&record.domain);
+            match &record.value {
+                // The first CNAME for a name wins
+                RecordValue::Domain(target)
+                    if record.record_type == RecordType::Cname && !cnames.contains_key(&owner) =>
+                {
+                    cnames.insert(owner.clone(), normalize_domain(target));
+                    owners.push(owner);
+                }
+                RecordValue::Ip(_) => {
+                    addressed.insert(owner);
+                }
+                _ => {}
+            }
+        }
+
+        let mut findings = Vec::new();
+        for owner in owners {
+            let mut chain = vec![owner.clone()];
+            while let Some(target) = cnames.get(&chain[chain.len() - 1]) {
+                if push_cname_hop(&mut chain, target).is_err() {
+                    break;
+                }
+            }
+
+            let Some(fingerprint) = self.match_chain(&chain) else {
+                continue;
+            };
+            let confidence = if chain.iter().any(|name| addressed.contains(name)) {
+                PATTERN_CONFIDENCE
+            } else {
+                NO_ADDRESS_CONFIDENCE
+            };
+            debug!("{} points at {} via {}", owner, fingerprint.service, chain.join(" -> "));
+            findings.push(TakeoverFinding {
+                domain: owner,
+                cname_chain: chain,
+                service: fingerprint.service.clone(),
+                severity: fingerprint.severity,
+                confidence,
+            });
+        }
+        findings
+    }
+
+    /// Check candidates from [`detect`](Self::detect) against live DNS and HTTP
+    ///
+    /// Each chain is first followed past the records it came from. A final
+    /// target that does not exist is claimable; otherwise the domain's web page
+    /// is fetched and must carry the service's unclaimed signature. Findings
+    /// whose service evidently serves a claimed name are dropped.
+    pub async fn verify(
+        &self,
+        resolver_pool: &ResolverPool,
+        findings: Vec<TakeoverFinding>,
+    ) -> Result<Vec<TakeoverFinding>> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .user_agent(concat!("rdnsx/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| DnsxError::Other(format!("Failed to create HTTP client: {}", e)))?;
+
+        let verified = futures::stream::iter(findings)
+            .map(|finding| self.verify_finding(resolver_pool, &http, finding))
+            .buffered(VERIFY_CONCURRENCY)
+            .filter_map(|finding| async move { finding })
+            .collect()
+            .await;
+        Ok(verified)
+    }
+
+    async fn verify_finding(
+        &self,
+        resolver_pool: &ResolverPool,
+        http: &reqwest::Client,
+        mut finding: TakeoverFinding,
+    ) -> Option<TakeoverFinding> {
+        loop {
+            let last = &finding.cname_chain[finding.cname_chain.len() - 1];
+            match resolver_pool.cname_target(last).await {
+                Ok(Some(target)) if push_cname_hop(&mut finding.cname_chain, &target).is_ok() => {}
+                _ => break,
+            }
+        }
+
+        let target = &finding.cname_chain[finding.cname_chain.len() - 1];
+        if let Err(DnsxError::Rcode { rcode: ResponseCode::NxDomain, .. }) =
+            resolver_pool.query(target, RecordType::A).await
+        {
+            debug!("{} ends at {}, which does not exist", finding.domain, target);
+            finding.confidence = finding.confidence.max(NXDOMAIN_CONFIDENCE);
+            return Some(finding);
+        }
+
+        let signature = self
+            .fingerprints
+            .iter()
+            .find(|fingerprint| fingerprint.service == finding.service)
+            .and_then(|fingerprint| fingerprint.http_signature.as_deref());
+        let Some(signature) = signature else {
+            debug!("{} resolves and {} has no page signature, assuming it is claimed", target, finding.service);
+            return None;
+        };
+
+        let url = format!("http://{}/", finding.domain);
+        let page = match http.get(&url).send().await {
+            Ok(response) => response.text().await,
+            Err(e) => Err(e),
+        };
+        match page {
+            Ok(body) if body.contains(signature) => {
+                finding.confidence = finding.confidence.max(HTTP_SIGNATURE_CONFIDENCE);
+                Some(finding)
+            }
+            Ok(_) => {
+                debug!("{} serves its own content at {}, not vulnerable", finding.domain, finding.service);
+                None
+            }
+            Err(e) => {
+                // Keep the DNS evidence when the page cannot be checked
+                debug!("Failed to fetch {}: {}", url, e);
+                Some(finding)
+            }
+        }
+    }
+
+    /// First fingerprint matching a name the chain points at, nearest to its end
+    fn match_chain(&self, chain: &[String]) -> Option<&TakeoverFingerprint> {
+        chain.iter().skip(1).rev().find_map(|name| self.fingerprints.iter().find(|f| f.matches(name)))
+    }
+}
+
+impl Default for SubdomainTakeoverDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(domain: &str, record_type: RecordType, value: RecordValue) -> DnsRecord {
+        DnsRecord::new(
+            domain.to_string(),
+            record_type,
+            value,
+            300,
+            ResponseCode::NoError,
+            "8.8.8.8:53".to_string(),
+            0.0,
+        )
+    }
+
+    fn cname(domain: &str, target: &str) -> DnsRecord {
+        record(domain, RecordType::Cname, RecordValue::Domain(target.to_string()))
+    }
+
+    #[test]
+    fn test_detect_follows_cname_chains() {
+        let records = vec![
+            cname("blog.example.com", "blog-example.netlify.app."),
+            cname("shop.example.com", "shop-lb.example.com"),
+            cname("shop-lb.example.com", "example-shop.myshopify.com"),
+            record("shop.example.com", RecordType::A, RecordValue::Ip("23.227.38.65".parse().unwrap())),
+            cname("www.example.com", "example.com"),
+        ];
+
+        let findings = SubdomainTakeoverDetector::new().detect(&records);
+        assert_eq!(findings.len(), 3);
+
+        assert_eq!(findings[0].domain, "blog.example.com");
+        assert_eq!(findings[0].service, "Netlify");
+        assert_eq!(findings[0].confidence, NO_ADDRESS_CONFIDENCE);
+
+        assert_eq!(findings[1].domain, "shop.example.com");
+        assert_eq!(
+            findings[1].cname_chain,
+            vec!["shop.example.com", "shop-lb.example.com", "example-shop.myshopify.com"]
+        );
+        assert_eq!(findings[1].service, "Shopify");
+        assert_eq!(findings[1].confidence, PATTERN_CONFIDENCE);
+        assert_eq!(findings[2].domain, "shop-lb.example.com");
+    }
+
+    #[test]
+    fn test_custom_fingerprints() {
+        assert!(SubdomainTakeoverDetector::builtin_fingerprints().len() >= 20);
+
+        let fingerprints: Vec<TakeoverFingerprint> = serde_json::from_str(
+            r#"[{"service": "Example Hosting", "cname_patterns": ["*.hosting.example"], "severity": "high"}]"#,
+        )
+        .unwrap();
+        let detector = SubdomainTakeoverDetector::new().with_fingerprints(fingerprints);
+        let findings = detector.detect(&[
+            cname("app.example.com", "tenant.hosting.example"),
+            cname("docs.example.com", "example.github.io"),
+        ]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].service, "Example Hosting");
+        assert_eq!(findings[0].severity, TakeoverSeverity::High);
+    }
+}
@@ -0,0 +1,245 @@
+//! Hostname discovery from TLS certificates
+
+use std::collections::BTreeSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tracing::debug;
+
+use crate::dane::tbs_fields;
+use crate::email_security::der_element;
+use crate::error::{DnsxError, Result};
+
+const SEQUENCE: u8 = 0x30;
+const OID: u8 = 0x06;
+const BOOLEAN: u8 = 0x01;
+const OCTET_STRING: u8 = 0x04;
+/// `dNSName [2] IA5String` inside GeneralNames
+const DNS_NAME: u8 = 0x82;
+/// id-ce-subjectAltName, 2.5.29.17
+const SUBJECT_ALT_NAME_OID: &[u8] = &[0x55, 0x1d, 0x11];
+
+/// Handshakes running at once in [`TlsSanEnumerator::expand`]
+const SAN_EXPAND_CONCURRENCY: usize = 20;
+
+/// Reads the DNS names a server's certificate is issued for
+///
+/// Certificates often cover several related hosts, so the SANs of an address
+/// found by a PTR scan or bruteforce point at names no wordlist would guess.
+/// The certificate is not validated: expired, self-signed and mismatched
+/// certificates are read just the same.
+pub struct TlsSanEnumerator {
+    config: Arc<rustls::ClientConfig>,
+    timeout: Duration,
+}
+
+impl TlsSanEnumerator {
+    /// Create an enumerator with a 5 second connect and handshake timeout
+    pub fn new() -> Result<Self> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let config = rustls::ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| DnsxError::Other(format!("Failed to configure TLS: {}", e)))?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate { provider }))
+            .with_no_client_auth();
+
+        Ok(Self {
+            config: Arc::new(config),
+            timeout: Duration::from_secs(5),
+        })
+    }
+
+    /// Give up on servers that take longer than `timeout` to connect and complete the handshake
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// DNS names in the subjectAltName of the certificate served on `ip:port`
+    ///
+    /// No SNI is sent, so servers hosting several certificates answer with their default one.
+    pub async fn enumerate(&self, ip: IpAddr, port: u16) -> Result<Vec<String>> {
+        let addr = SocketAddr::new(ip, port);
+        let endpoint = addr.to_string();
+
+        let handshake = async {
+            let tcp = TcpStream::connect(addr).await?;
+            TlsConnector::from(Arc::clone(&self.config)).connect(ServerName::from(ip), tcp).await
+        };
+        let stream = tokio::time::timeout(self.timeout, handshake)
+            .await
+            .map_err(|_| DnsxError::network(&endpoint, format!("TLS handshake timed out after {:?}", self.timeout)))?
+            .map_err(|e| DnsxError::network(&endpoint, format!("TLS handshake failed: {}", e)))?;
+
+        let leaf = stream
+            .get_ref()
+            .1
+            .peer_certificates()
+            .and_then(|chain| chain.first())
+            .ok_or_else(|| DnsxError::network(&endpoint, "Server sent no certificate"))?;
+        let names = subject_alt_dns_names(leaf);
+        debug!("Certificate on {} covers {} DNS names", endpoint, names.len());
+        Ok(names)
+    }
+
+    /// SAN names of every address in `ips`, lowercased, with `*.` wildcards reduced to their base name
+    ///
+    /// Addresses that refuse the connection or do not speak TLS are skipped.
+    pub async fn expand(&self, ips: &[IpAddr], port: u16) -> BTreeSet<String> {
+        let results: Vec<_> = futures::stream::iter(ips.iter().copied())
+            .map(|ip| async move { (ip, self.enumerate(ip, port).await) })
+            .buffer_unordered(SAN_EXPAND_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut names = BTreeSet::new();
+        for (ip, result) in results {
+            match result {
+                Ok(sans) => names.extend(sans.iter().map(|name| {
+                    let name = name.trim_end_matches('.').to_lowercase();
+                    name.strip_prefix("*.").map(str::to_string).unwrap_or(name)
+                })),
+                Err(e) => debug!("No certificate SANs from {}: {}", ip, e),
+            }
+        }
+        names
+    }
+}
+
+/// Accepts any certificate; only its contents are of interest
+#[derive(Debug)]
+struct AcceptAnyCertificate {
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// `dNSName` entries of a DER certificate's subjectAltName extension, in certificate order
+pub(crate) fn subject_alt_dns_names(cert: &[u8]) -> Vec<String> {
+    let Some(mut extensions) = tbs_fields(cert).and_then(|fields| fields.extensions) else {
+        return Vec::new();
+    };
+
+    while let Some((tag, extension, rest)) = der_element(extensions) {
+        extensions = rest;
+        if tag != SEQUENCE {
+            continue;
+        }
+        let Some((OID, oid, mut value)) = der_element(extension) else {
+            continue;
+        };
+        if oid != SUBJECT_ALT_NAME_OID {
+            continue;
+        }
+        // Skip the optional `critical` flag
+        if value.first() == Some(&BOOLEAN) {
+            value = der_element(value).map_or(&[][..], |(_, _, rest)| rest);
+        }
+        let Some((OCTET_STRING, general_names, _)) = der_element(value) else {
+            return Vec::new();
+        };
+        let Some((SEQUENCE, mut names, _)) = der_element(general_names) else {
+            return Vec::new();
+        };
+
+        let mut dns_names = Vec::new();
+        while let Some((tag, name, rest)) = der_element(names) {
+            names = rest;
+            if tag == DNS_NAME {
+                dns_names.push(String::from_utf8_lossy(name).into_owned());
+            }
+        }
+        return dns_names;
+    }
+
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// DER element with a short-form length
+    fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag, contents.len() as u8];
+        out.extend_from_slice(contents);
+        out
+    }
+
+    #[test]
+    fn test_subject_alt_dns_names() {
+        let general_names = [
+            der(DNS_NAME, b"www.example.com"),
+            der(0x87, &[192, 0, 2, 1]), // iPAddress
+            der(DNS_NAME, b"*.api.example.com"),
+        ]
+        .concat();
+        let san = [
+            der(OID, SUBJECT_ALT_NAME_OID),
+            der(BOOLEAN, &[0]),
+            der(OCTET_STRING, &der(SEQUENCE, &general_names)),
+        ]
+        .concat();
+        let key_usage = [der(OID, &[0x55, 0x1d, 0x0f]), der(OCTET_STRING, &der(0x03, &[0x05, 0xa0]))].concat();
+        let extensions = der(0xa3, &der(SEQUENCE, &[der(SEQUENCE, &key_usage), der(SEQUENCE, &san)].concat()));
+
+        let algorithm = der(SEQUENCE, &der(OID, &[0x2b, 0x65, 0x70]));
+        let tbs = [
+            der(0xa0, &der(0x02, &[2])),
+            der(0x02, &[1]),
+            algorithm.clone(),
+            der(SEQUENCE, &[]),
+            der(SEQUENCE, &[]),
+            der(SEQUENCE, &[]),
+            der(SEQUENCE, &[algorithm.clone(), der(0x03, &[0, 1, 2])].concat()),
+            extensions,
+        ]
+        .concat();
+        let cert = der(SEQUENCE, &[der(SEQUENCE, &tbs), algorithm, der(0x03, &[0])].concat());
+
+        assert_eq!(subject_alt_dns_names(&cert), vec!["www.example.com", "*.api.example.com"]);
+        assert!(subject_alt_dns_names(&cert[..cert.len() - 1]).is_empty());
+    }
+}
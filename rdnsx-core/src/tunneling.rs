@@ -0,0 +1,179 @@
+//! DNS tunneling heuristics
+
+use std::collections::HashMap;
+
+/// Heuristic tunneling score for a domain
+#[derive(Debug, Clone)]
+pub struct TunnelingScore {
+    pub domain: String,
+    /// Likelihood of tunneling between 0.0 and 1.0
+    pub score: f64,
+    pub indicators: Vec<String>,
+}
+
+/// Detects DNS tunneling (iodine, dnscat2 and similar) from query names and TXT payloads
+#[derive(Debug, Clone)]
+pub struct TunnelingDetector {
+    /// Shannon entropy (bits per character) above which a label looks encoded
+    pub entropy_threshold: f64,
+    /// Average subdomain label length above which names look like data carriers
+    pub label_length_threshold: f64,
+    /// Score at or above which a domain is reported as suspicious
+    pub score_threshold: f64,
+}
+
+impl Default for TunnelingDetector {
+    fn default() -> Self {
+        Self {
+            entropy_threshold: 3.5,
+            label_length_threshold: 20.0,
+            score_threshold: 0.5,
+        }
+    }
+}
+
+impl TunnelingDetector {
+    /// Create a detector with default thresholds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Score a domain from its name alone
+    pub fn analyze_domain(&self, domain: &str) -> TunnelingScore {
+        self.analyze_domain_with_txt(domain, &[])
+    }
+
+    /// Score a domain from its name and the TXT payloads it returned
+    pub fn analyze_domain_with_txt(&self, domain: &str, txt_records: &[String]) -> TunnelingScore {
+        let domain = domain.trim().trim_end_matches('.');
+        let labels: Vec<&str> = domain.split('.').filter(|l| !l.is_empty()).collect();
+
+        // Everything left of the registered domain is attacker controlled
+        let subdomain_labels = if labels.len() > 2 {
+            &labels[..labels.len() - 2]
+        } else {
+            &[][..]
+        };
+
+        let mut score = 0.0;
+        let mut indicators = Vec::new();
+
+        if !subdomain_labels.is_empty() {
+            let max_entropy = subdomain_labels
+                .iter()
+                .map(|l| shannon_entropy(l))
+                .fold(0.0, f64::max);
+            if max_entropy >= self.entropy_threshold {
+                score += 0.3;
+                indicators.push(format!("High label entropy ({:.2} bits/char)", max_entropy));
+            }
+
+            let avg_length = subdomain_labels.iter().map(|l| l.len()).sum::<usize>() as f64
+                / subdomain_labels.len() as f64;
+            if avg_length >= self.label_length_threshold {
+                score += 0.2;
+                indicators.push(format!("Long subdomain labels (avg {:.1} chars)", avg_length));
+            }
+            if subdomain_labels.iter().any(|l| l.len() >= 50) {
+                score += 0.1;
+                indicators.push("Label close to the 63 character limit".to_string());
+            }
+
+            if let Some(encoding) = subdomain_labels.iter().find_map(|l| encoding_like(l)) {
+                score += 0.2;
+                indicators.push(format!("{}-like character distribution", encoding));
+            }
+        }
+
+        let txt_bytes: usize = txt_records.iter().map(|t| t.len()).sum();
+        if txt_bytes > 0 {
+            let payload = txt_records.concat();
+            let txt_entropy = shannon_entropy(&payload);
+            if txt_bytes >= 200 && txt_entropy >= 4.5 {
+                score += 0.2;
+                indicators.push(format!(
+                    "Dense TXT payload ({} bytes, {:.2} bits/char)",
+                    txt_bytes, txt_entropy
+                ));
+            }
+        }
+
+        TunnelingScore {
+            domain: domain.to_string(),
+            score: f64::min(score, 1.0),
+            indicators,
+        }
+    }
+
+    /// Whether a score crosses the reporting threshold
+    pub fn is_suspicious(&self, score: &TunnelingScore) -> bool {
+        score.score >= self.score_threshold
+    }
+}
+
+/// Shannon entropy of a string in bits per character
+pub fn shannon_entropy(s: &str) -> f64 {
+    if s.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in s.chars() {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+
+    let len = s.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Identify labels that look like hex, base32 or base64 encoded data
+fn encoding_like(label: &str) -> Option<&'static str> {
+    if label.len() < 16 {
+        return None;
+    }
+
+    let has_digit = label.chars().any(|c| c.is_ascii_digit());
+    let has_upper = label.chars().any(|c| c.is_ascii_uppercase());
+    let has_lower = label.chars().any(|c| c.is_ascii_lowercase());
+
+    if label.chars().all(|c| c.is_ascii_hexdigit()) && has_digit {
+        Some("Hex")
+    } else if has_upper && has_lower && has_digit && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        Some("Base64")
+    } else if has_digit && label.to_ascii_lowercase().chars().all(|c| matches!(c, 'a'..='z' | '2'..='7')) {
+        Some("Base32")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shannon_entropy() {
+        assert_eq!(shannon_entropy(""), 0.0);
+        assert_eq!(shannon_entropy("aaaa"), 0.0);
+        assert!((shannon_entropy("abcd") - 2.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_tunneling_score() {
+        let detector = TunnelingDetector::new();
+
+        let benign = detector.analyze_domain("www.example.com");
+        assert_eq!(benign.score, 0.0);
+        assert!(!detector.is_suspicious(&benign));
+
+        let tunnel = detector.analyze_domain("mzxw6ytboi2dsnzq3fgq7a4kd2hm5xzq.ns1vk3nrhd2a6qdlmzxw6ytb.example.com");
+        assert!(detector.is_suspicious(&tunnel));
+        assert!(!tunnel.indicators.is_empty());
+    }
+}
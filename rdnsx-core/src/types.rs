@@ -4,6 +4,6 @@
 pub use crate::record_types::RecordType;
 pub use crate::response_codes::ResponseCode;
 pub use crate::record_values::RecordValue;
-pub use crate::dns_records::DnsRecord;
+pub use crate::dns_records::{DnsRecord, DnsRecordValidator, ValidationError};
 
 // Modules are declared in lib.rs
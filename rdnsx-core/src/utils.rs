@@ -18,6 +18,65 @@ pub fn parse_resolver(resolver: &str) -> Result<SocketAddr> {
         .map_err(|e| DnsxError::ResolverConfig(format!("Invalid resolver address: {}", e)))
 }
 
+/// Path hickory sends every DNS-over-HTTPS query to
+pub const DOH_QUERY_PATH: &str = "/dns-query";
+
+/// Whether a resolver string is a DNS-over-HTTPS endpoint rather than an address
+pub fn is_doh_url(resolver: &str) -> bool {
+    resolver.starts_with("https://")
+}
+
+/// Host and port of a DNS-over-HTTPS endpoint such as `https://cloudflare-dns.com/dns-query`
+///
+/// The port defaults to 443. Queries always go to [`DOH_QUERY_PATH`], so URLs
+/// with any other path are rejected rather than silently redirected.
+pub fn parse_doh_url(url: &str) -> Result<(String, u16)> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| DnsxError::ResolverConfig(format!("Invalid DoH URL {}: {}", url, e)))?;
+    if parsed.scheme() != "https" {
+        return Err(DnsxError::ResolverConfig(format!("DoH URL {} must use https", url)));
+    }
+    if !matches!(parsed.path(), "" | "/" | DOH_QUERY_PATH) || parsed.query().is_some() {
+        return Err(DnsxError::ResolverConfig(format!(
+            "DoH URL {} must use the {} path",
+            url, DOH_QUERY_PATH
+        )));
+    }
+
+    // IPv6 hosts come back bracketed, as they are written in URLs
+    let host = parsed
+        .host_str()
+        .map(|host| host.trim_start_matches('[').trim_end_matches(']').to_string())
+        .ok_or_else(|| DnsxError::ResolverConfig(format!("DoH URL {} has no host", url)))?;
+    Ok((host, parsed.port_or_known_default().unwrap_or(443)))
+}
+
+/// Port DNS-over-TLS servers listen on unless the resolver string names another
+pub const DOT_DEFAULT_PORT: u16 = 853;
+
+/// Whether a resolver string is a DNS-over-TLS server (`tls://host[:port]`)
+pub fn is_dot_url(resolver: &str) -> bool {
+    resolver.starts_with("tls://")
+}
+
+/// Host and port of a DNS-over-TLS resolver string such as `tls://9.9.9.9:853` or `tls://dns.quad9.net`
+///
+/// The host is also the name the server certificate is checked against.
+pub fn parse_dot_url(url: &str) -> Result<(String, u16)> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| DnsxError::ResolverConfig(format!("Invalid DoT address {}: {}", url, e)))?;
+    if parsed.scheme() != "tls" || !matches!(parsed.path(), "" | "/") || parsed.query().is_some() {
+        return Err(DnsxError::ResolverConfig(format!("DoT address {} must look like tls://host[:port]", url)));
+    }
+
+    let host = parsed
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .map(|host| host.trim_start_matches('[').trim_end_matches(']').to_string())
+        .ok_or_else(|| DnsxError::ResolverConfig(format!("DoT address {} has no host", url)))?;
+    Ok((host, parsed.port().unwrap_or(DOT_DEFAULT_PORT)))
+}
+
 /// Parse a resolver string into a SocketAddr (legacy function - renamed)
 pub fn parse_resolver_string(resolver: &str) -> Result<SocketAddr> {
     parse_resolver(resolver)
@@ -40,6 +99,86 @@ pub fn parse_ip_range(range_spec: &str) -> Result<ipnetwork::IpNetwork> {
         .map_err(|_| DnsxError::InvalidInput(format!("Invalid IP range: {}", range_spec)))
 }
 
+/// Lowercase a domain name and drop surrounding whitespace and a trailing dot
+///
+/// DNS names are case-insensitive, so `Example.COM.` and `example.com` are the
+/// same name; this is the single form they are stored and compared in.
+pub fn normalize_domain(domain: &str) -> String {
+    let trimmed = domain.trim();
+    trimmed.strip_suffix('.').unwrap_or(trimmed).to_ascii_lowercase()
+}
+
+/// Normalize a domain name for querying
+///
+/// Applies [`normalize_domain`], then validates label and total length and
+/// the RFC 1035 character set. Underscores are accepted so service labels
+/// such as `_dmarc` and `_sip._tcp` still work.
+pub fn canonicalize_domain(domain: &str) -> Result<String> {
+    let canonical = normalize_domain(domain);
+
+    if canonical.is_empty() {
+        return Err(DnsxError::invalid_input("Domain name is empty"));
+    }
+    if canonical.len() > 253 {
+        return Err(DnsxError::invalid_input(format!(
+            "Domain name {} is {} characters long (max 253)",
+            canonical,
+            canonical.len()
+        )));
+    }
+
+    for label in canonical.split('.') {
+        if label.is_empty() {
+            return Err(DnsxError::invalid_input(format!("Domain name {} contains an empty label", canonical)));
+        }
+        if label.len() > 63 {
+            return Err(DnsxError::invalid_input(format!(
+                "Label {} in {} is {} characters long (max 63)",
+                label,
+                canonical,
+                label.len()
+            )));
+        }
+        if let Some(c) = label.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_')) {
+            return Err(DnsxError::invalid_input(format!("Label {} in {} contains invalid character {:?}", label, canonical, c)));
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(DnsxError::invalid_input(format!("Label {} in {} starts or ends with a hyphen", label, canonical)));
+        }
+    }
+
+    Ok(canonical)
+}
+
+/// Hostname of a URL such as `https://www.example.com:8443/path?q=1`
+///
+/// Input without a scheme (`example.com/path`, or a bare domain) is read as
+/// an `http://` URL. The port, path and credentials are dropped and the host
+/// is lowercased; with `strip_www`, a leading `www.` is removed as long as a
+/// registrable name remains. Returns `None` for input that is not a URL with
+/// a host.
+pub fn extract_domain_from_url(input: &str, strip_www: bool) -> Option<String> {
+    let input = input.trim();
+    let url = if input.contains("://") {
+        reqwest::Url::parse(input)
+    } else {
+        reqwest::Url::parse(&format!("http://{}", input))
+    }
+    .ok()?;
+
+    // IPv6 hosts come back bracketed, as they are written in URLs
+    let host = url.host_str()?.trim_start_matches('[').trim_end_matches(']');
+    let host = host.strip_suffix('.').unwrap_or(host).to_lowercase();
+    if host.is_empty() {
+        return None;
+    }
+
+    match host.strip_prefix("www.") {
+        Some(rest) if strip_www && rest.contains('.') => Some(rest.to_string()),
+        _ => Some(host),
+    }
+}
+
 /// Reverse IP address for PTR queries
 pub fn reverse_ip(ip: &str) -> Result<String> {
     use std::net::IpAddr;
@@ -84,6 +223,40 @@ mod tests {
         assert_eq!(result.unwrap(), "8.8.8.8:53".parse::<SocketAddr>().unwrap());
     }
 
+    #[test]
+    fn test_parse_doh_url() {
+        assert!(is_doh_url("https://cloudflare-dns.com/dns-query"));
+        assert!(!is_doh_url("1.1.1.1:53"));
+
+        let (host, port) = parse_doh_url("https://cloudflare-dns.com/dns-query").unwrap();
+        assert_eq!((host.as_str(), port), ("cloudflare-dns.com", 443));
+        assert_eq!(parse_doh_url("https://[2606:4700::1111]:8443").unwrap(), ("2606:4700::1111".to_string(), 8443));
+        assert!(parse_doh_url("https://dns.example.com/resolve").is_err());
+        assert!(parse_doh_url("http://dns.example.com/dns-query").is_err());
+    }
+
+    #[test]
+    fn test_parse_dot_url() {
+        assert!(is_dot_url("tls://9.9.9.9:853"));
+        assert!(!is_dot_url("9.9.9.9"));
+
+        let (host, port) = parse_dot_url("tls://9.9.9.9:853").unwrap();
+        assert_eq!((host.as_str(), port), ("9.9.9.9", 853));
+        let (host, port) = parse_dot_url("tls://dns.quad9.net").unwrap();
+        assert_eq!((host.as_str(), port), ("dns.quad9.net", DOT_DEFAULT_PORT));
+        let (host, _) = parse_dot_url("tls://[2620:fe::fe]:853").unwrap();
+        assert_eq!(host, "2620:fe::fe");
+        assert!(parse_dot_url("tls://9.9.9.9/dns-query").is_err());
+        assert!(parse_dot_url("tls://").is_err());
+    }
+
+    #[test]
+    fn test_normalize_domain() {
+        assert_eq!(normalize_domain(" Www.Example.COM. "), "www.example.com");
+        assert_eq!(normalize_domain("example.com"), "example.com");
+        assert_eq!(normalize_domain("."), "");
+    }
+
     #[test]
     fn test_parse_resolver_invalid() {
         let result = parse_resolver("invalid");
@@ -130,6 +303,23 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_canonicalize_domain() {
+        assert_eq!(canonicalize_domain("  WWW.Example.COM. ").unwrap(), "www.example.com");
+        assert_eq!(canonicalize_domain("_dmarc.example.com").unwrap(), "_dmarc.example.com");
+        assert_eq!(canonicalize_domain("1.0.168.192.in-addr.arpa").unwrap(), "1.0.168.192.in-addr.arpa");
+    }
+
+    #[test]
+    fn test_canonicalize_domain_invalid() {
+        assert!(canonicalize_domain("").is_err());
+        assert!(canonicalize_domain("example..com").is_err());
+        assert!(canonicalize_domain("-bad.example.com").is_err());
+        assert!(canonicalize_domain("exa mple.com").is_err());
+        assert!(canonicalize_domain(&format!("{}.com", "a".repeat(64))).is_err());
+        assert!(canonicalize_domain(&vec!["abcdefghi"; 26].join(".")).is_err());
+    }
+
     #[test]
     fn test_reverse_ip_ipv4() {
         let result = reverse_ip("192.168.1.1");
@@ -149,4 +339,19 @@ mod tests {
         let result = reverse_ip("invalid");
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_extract_domain_from_url() {
+        assert_eq!(
+            extract_domain_from_url("https://www.Example.com:8443/path?q=1", false).as_deref(),
+            Some("www.example.com")
+        );
+        assert_eq!(extract_domain_from_url("https://www.example.com/path", true).as_deref(), Some("example.com"));
+        assert_eq!(extract_domain_from_url("api.example.com/v1/users", true).as_deref(), Some("api.example.com"));
+        assert_eq!(extract_domain_from_url("example.com:8080", false).as_deref(), Some("example.com"));
+        assert_eq!(extract_domain_from_url("http://user:pw@[2001:db8::1]:80/", false).as_deref(), Some("2001:db8::1"));
+        assert_eq!(extract_domain_from_url("www.com", true).as_deref(), Some("www.com"));
+        assert_eq!(extract_domain_from_url("https://", false), None);
+        assert_eq!(extract_domain_from_url("not a url", false), None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Virtual host discovery from certificate transparency logs
+
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::client::DnsxClient;
+use crate::error::{DnsxError, Result};
+use crate::tls::TlsSanEnumerator;
+
+/// crt.sh search endpoint
+pub const CRT_SH_URL: &str = "https://crt.sh/";
+
+/// Candidate names resolved at once when checking which ones are co-hosted
+const VHOST_VERIFY_CONCURRENCY: usize = 20;
+
+/// One row of crt.sh JSON output
+#[derive(Deserialize)]
+pub(crate) struct CrtShEntry {
+    #[serde(default)]
+    pub(crate) common_name: Option<String>,
+    /// SANs of the certificate, newline-separated
+    #[serde(default)]
+    pub(crate) name_value: String,
+    #[serde(default)]
+    pub(crate) issuer_name: Option<String>,
+    /// UTC, without a zone suffix (`2024-01-15T00:00:00`)
+    #[serde(default)]
+    pub(crate) not_before: Option<String>,
+    #[serde(default)]
+    pub(crate) not_after: Option<String>,
+    #[serde(default)]
+    pub(crate) serial_number: Option<String>,
+}
+
+/// Finds the other domains served from the same address as a target
+///
+/// Virtual hosts on one address tend to share certificates, so the names on
+/// certificates logged for the address's /24 and on the certificate it serves
+/// are the candidates. Only those that resolve back to the address are kept.
+pub struct VhostEnumerator {
+    client: Arc<DnsxClient>,
+    http: reqwest::Client,
+    ct_url: String,
+}
+
+impl VhostEnumerator {
+    /// Create an enumerator that searches crt.sh
+    pub fn new(client: Arc<DnsxClient>) -> Result<Self> {
+        // crt.sh is slow on wide searches
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .user_agent(concat!("rdnsx/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| DnsxError::Other(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self { client, http, ct_url: CRT_SH_URL.to_string() })
+    }
+
+    /// Search a crt.sh-compatible mirror instead of crt.sh itself
+    pub fn with_ct_url(mut self, url: impl Into<String>) -> Self {
+        self.ct_url = url.into();
+        self
+    }
+
+    /// Domains co-hosted on `ip`, sorted
+    ///
+    /// Candidates come from certificate transparency and from the certificate
+    /// served on `ip:port`; an error is returned only when neither source
+    /// could be read.
+    pub async fn enumerate_by_ip(&self, ip: IpAddr, port: u16) -> Result<Vec<String>> {
+        info!("Enumerating virtual hosts on {}", ip);
+
+        let served_names = match TlsSanEnumerator::new() {
+            Ok(enumerator) => enumerator.enumerate(ip, port).await,
+            Err(e) => Err(e),
+        };
+        let mut candidates = match (self.ct_names(ip).await, &served_names) {
+            (Ok(names), _) => names,
+            (Err(e), Err(_)) => return Err(e),
+            (Err(e), Ok(_)) => {
+                warn!("Certificate transparency search for {} failed: {}", ip, e);
+                BTreeSet::new()
+            }
+        };
+        match served_names {
+            Ok(names) => candidates.extend(names.iter().filter_map(|name| normalize_name(name))),
+            Err(e) => debug!("No certificate from {}:{}: {}", ip, port, e),
+        }
+        debug!("{} candidate virtual hosts for {}", candidates.len(), ip);
+
+        let mut cohosted: Vec<String> = futures::stream::iter(candidates)
+            .map(|name| async move { self.resolves_to(&name, ip).await.then_some(name) })
+            .buffer_unordered(VHOST_VERIFY_CONCURRENCY)
+            .filter_map(|name| async move { name })
+            .collect()
+            .await;
+        cohosted.sort();
+        Ok(cohosted)
+    }
+
+    /// Names on certificates logged for addresses in the same network as `ip`
+    async fn ct_names(&self, ip: IpAddr) -> Result<BTreeSet<String>> {
+        let response = self
+            .http
+            .get(&self.ct_url)
+            .query(&[("q", ct_search_pattern(ip).as_str()), ("output", "json")])
+            .send()
+            .await
+            .map_err(|e| DnsxError::network(&self.ct_url, format!("Certificate transparency search failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(DnsxError::network(
+                &self.ct_url,
+                format!("Certificate transparency search returned HTTP {}", response.status()),
+            ));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| DnsxError::network(&self.ct_url, format!("Failed to read search results: {}", e)))?;
+        names_from_crt_sh(&body)
+    }
+
+    async fn resolves_to(&self, name: &str, ip: IpAddr) -> bool {
+        let addresses: Vec<IpAddr> = match ip {
+            IpAddr::V4(_) => self.client.lookup_ipv4(name).await.map(|ips| ips.into_iter().map(IpAddr::V4).collect()),
+            IpAddr::V6(_) => self.client.lookup_ipv6(name).await.map(|ips| ips.into_iter().map(IpAddr::V6).collect()),
+        }
+        .unwrap_or_default();
+        addresses.contains(&ip)
+    }
+}
+
+/// crt.sh identity pattern covering the /24 of an IPv4 address; IPv6 addresses are searched as-is
+pub(crate) fn ct_search_pattern(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            format!("{}.{}.{}.%", a, b, c)
+        }
+        IpAddr::V6(v6) => v6.to_string(),
+    }
+}
+
+/// DNS names in a crt.sh JSON response, lowercased and with `*.` wildcards reduced to their base name
+pub(crate) fn names_from_crt_sh(body: &str) -> Result<BTreeSet<String>> {
+    let entries: Vec<CrtShEntry> = serde_json::from_str(body)
+        .map_err(|e| DnsxError::Serialization(format!("Invalid certificate transparency response: {}", e)))?;
+
+    Ok(entries
+        .iter()
+        .flat_map(|entry| entry.name_value.lines().chain(entry.common_name.as_deref()))
+        .filter_map(normalize_name)
+        .collect())
+}
+
+/// Lowercased host name, or `None` for IP address identities and other non-DNS names
+pub(crate) fn normalize_name(name: &str) -> Option<String> {
+    let name = name.trim().trim_end_matches('.').to_lowercase();
+    let name = name.strip_prefix("*.").map(str::to_string).unwrap_or(name);
+    let is_host = name.contains('.')
+        && name.parse::<IpAddr>().is_err()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+    is_host.then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_from_crt_sh() {
+        let body = r#"[
+            {"common_name": "shop.example.com", "name_value": "shop.example.com\n*.Example.com\n192.0.2.10"},
+            {"common_name": "192.0.2.10", "name_value": "blog.example.org."},
+            {"name_value": "admin@example.net"}
+        ]"#;
+        let names: Vec<String> = names_from_crt_sh(body).unwrap().into_iter().collect();
+        assert_eq!(names, vec!["blog.example.org", "example.com", "shop.example.com"]);
+        assert!(names_from_crt_sh("<html>rate limited</html>").is_err());
+
+        assert_eq!(ct_search_pattern("192.0.2.10".parse().unwrap()), "192.0.2.%");
+        assert_eq!(ct_search_pattern("2001:db8::1".parse().unwrap()), "2001:db8::1");
+    }
+}
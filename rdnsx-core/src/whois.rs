@@ -0,0 +1,255 @@
+//! RDAP lookups for the network owning an IP address
+
+use std::net::IpAddr;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use ipnetwork::IpNetwork;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::error::{DnsxError, Result};
+
+const ARIN: &str = "https://rdap.arin.net/registry/ip/";
+const RIPE: &str = "https://rdap.db.ripe.net/ip/";
+const APNIC: &str = "https://rdap.apnic.net/ip/";
+const LACNIC: &str = "https://rdap.lacnic.net/rdap/ip/";
+const AFRINIC: &str = "https://rdap.afrinic.net/rdap/ip/";
+
+/// Registration data for the network an address belongs to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IpWhoisInfo {
+    pub cidr: IpNetwork,
+    /// Network handle name, e.g. `GOOGLE`
+    pub name: String,
+    /// Registrant organization, empty when the registry does not publish one
+    pub organization: String,
+    /// ISO 3166 country code, empty when unknown
+    pub country: String,
+    pub abuse_email: Option<String>,
+}
+
+/// Looks up IP ownership over RDAP, caching one answer per /24 (IPv4) or /48 (IPv6)
+pub struct WhoisEnricher {
+    client: reqwest::Client,
+    /// `None` records that no registry answered, so a range is not retried for every address
+    cache: DashMap<IpNetwork, Option<IpWhoisInfo>>,
+}
+
+impl WhoisEnricher {
+    /// Create a new enricher
+    pub fn new() -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .build()
+            .map_err(|e| DnsxError::network("rdap", format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            cache: DashMap::new(),
+        })
+    }
+
+    /// Network registration for `ip`
+    ///
+    /// The registry most likely to hold the address is asked first; ARIN and
+    /// the remaining RIRs are tried in turn if it has no record.
+    pub async fn enrich(&self, ip: IpAddr) -> Result<IpWhoisInfo> {
+        let key = cache_key(ip);
+        if let Some(cached) = self.cache.get(&key) {
+            return cached
+                .clone()
+                .ok_or_else(|| DnsxError::network("rdap", format!("No registry answered for {}", key)));
+        }
+
+        let mut last_error = None;
+        for registry in registries_for(ip) {
+            match self.query(registry, ip).await {
+                Ok(info) => {
+                    self.cache.insert(key, Some(info.clone()));
+                    return Ok(info);
+                }
+                Err(e) => {
+                    debug!("RDAP lookup of {} at {} failed: {}", ip, registry, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        self.cache.insert(key, None);
+        Err(last_error.unwrap_or_else(|| DnsxError::network("rdap", format!("No registry answered for {}", ip))))
+    }
+
+    async fn query(&self, registry: &str, ip: IpAddr) -> Result<IpWhoisInfo> {
+        let url = format!("{}{}", registry, ip);
+        let response = self
+            .client
+            .get(&url)
+            .header("Accept", "application/rdap+json")
+            .send()
+            .await
+            .map_err(|e| DnsxError::network(registry, format!("RDAP request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(DnsxError::network(registry, format!("RDAP returned status {}", response.status())));
+        }
+
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| DnsxError::network(registry, format!("Invalid RDAP response: {}", e)))?;
+
+        parse_rdap_network(&body).ok_or_else(|| DnsxError::network(registry, "RDAP response has no network range"))
+    }
+}
+
+/// Network the cache entry for `ip` covers
+fn cache_key(ip: IpAddr) -> IpNetwork {
+    let prefix = if ip.is_ipv4() { 24 } else { 48 };
+    let network = IpNetwork::new(ip, prefix).expect("prefix is valid for the address family");
+    IpNetwork::new(network.network(), prefix).expect("prefix is valid for the address family")
+}
+
+/// RDAP base URLs in the order to try them for `ip`
+fn registries_for(ip: IpAddr) -> Vec<&'static str> {
+    let preferred = match ip {
+        IpAddr::V4(v4) => match v4.octets()[0] {
+            2 | 5 | 31 | 37 | 46 | 62 | 77..=95 | 109 | 141 | 145 | 151 | 176 | 178 | 185 | 188 | 193..=195 | 212
+            | 213 | 217 => RIPE,
+            1 | 14 | 27 | 36 | 39 | 42 | 43 | 49 | 58..=61 | 101 | 103 | 106 | 110..=126 | 150 | 153 | 175 | 180
+            | 182 | 183 | 202 | 203 | 210 | 211 | 218..=223 => APNIC,
+            177 | 179 | 181 | 186 | 187 | 189..=191 | 200 | 201 => LACNIC,
+            41 | 102 | 105 | 154 | 196 | 197 => AFRINIC,
+            _ => ARIN,
+        },
+        IpAddr::V6(v6) => match v6.segments()[0] {
+            0x2001 => match v6.segments()[1] >> 8 {
+                0x02 | 0x0c | 0x0d | 0x0e | 0x44 => APNIC,
+                0x12 | 0x13 => LACNIC,
+                0x43 => AFRINIC,
+                0x06..=0x0b | 0x14..=0x1f | 0x40..=0x42 | 0x45..=0x4f => RIPE,
+                _ => ARIN,
+            },
+            0x2400..=0x24ff => APNIC,
+            0x2600..=0x26ff => ARIN,
+            0x2800..=0x28ff => LACNIC,
+            0x2a00..=0x2aff => RIPE,
+            0x2c00..=0x2cff => AFRINIC,
+            _ => ARIN,
+        },
+    };
+
+    let mut registries = vec![preferred];
+    registries.extend([ARIN, RIPE, APNIC, LACNIC, AFRINIC].into_iter().filter(|r| *r != preferred));
+    registries
+}
+
+/// Extract network details from an RDAP `ip network` object
+pub(crate) fn parse_rdap_network(body: &Value) -> Option<IpWhoisInfo> {
+    let cidr = body["cidr0_cidrs"]
+        .as_array()
+        .and_then(|cidrs| cidrs.first())
+        .and_then(|cidr| {
+            let prefix = cidr["v4prefix"].as_str().or_else(|| cidr["v6prefix"].as_str())?;
+            IpNetwork::new(prefix.parse().ok()?, cidr["length"].as_u64()? as u8).ok()
+        })
+        .or_else(|| {
+            let start: IpAddr = body["startAddress"].as_str()?.parse().ok()?;
+            let end: IpAddr = body["endAddress"].as_str()?.parse().ok()?;
+            covering_network(start, end)
+        })?;
+
+    let entities = body["entities"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let organization = find_entity(entities, "registrant")
+        .and_then(|entity| vcard_field(entity, "fn"))
+        .unwrap_or_default();
+    let abuse_email = find_entity(entities, "abuse").and_then(|entity| vcard_field(entity, "email"));
+
+    Some(IpWhoisInfo {
+        cidr,
+        name: body["name"].as_str().unwrap_or_default().to_string(),
+        organization,
+        country: body["country"].as_str().unwrap_or_default().to_string(),
+        abuse_email,
+    })
+}
+
+/// Smallest network containing both ends of an address range
+fn covering_network(start: IpAddr, end: IpAddr) -> Option<IpNetwork> {
+    let (common, bits) = match (start, end) {
+        (IpAddr::V4(s), IpAddr::V4(e)) => ((u32::from(s) ^ u32::from(e)).leading_zeros(), 32),
+        (IpAddr::V6(s), IpAddr::V6(e)) => ((u128::from(s) ^ u128::from(e)).leading_zeros(), 128),
+        _ => return None,
+    };
+    let network = IpNetwork::new(start, common.min(bits) as u8).ok()?;
+    IpNetwork::new(network.network(), network.prefix()).ok()
+}
+
+/// First entity with `role`, searching nested entities depth-first
+fn find_entity<'a>(entities: &'a [Value], role: &str) -> Option<&'a Value> {
+    entities.iter().find_map(|entity| {
+        let has_role = entity["roles"]
+            .as_array()
+            .is_some_and(|roles| roles.iter().any(|r| r.as_str() == Some(role)));
+        if has_role {
+            return Some(entity);
+        }
+        find_entity(entity["entities"].as_array().map(Vec::as_slice).unwrap_or_default(), role)
+    })
+}
+
+/// Text value of a jCard property (`["fn", {}, "text", "Example Org"]`)
+fn vcard_field(entity: &Value, name: &str) -> Option<String> {
+    entity["vcardArray"][1]
+        .as_array()?
+        .iter()
+        .find(|property| property[0].as_str() == Some(name))
+        .and_then(|property| property[3].as_str())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_rdap_network() {
+        let body = json!({
+            "objectClassName": "ip network",
+            "name": "EXAMPLE-NET",
+            "country": "US",
+            "startAddress": "192.0.2.0",
+            "endAddress": "192.0.2.255",
+            "entities": [
+                {
+                    "roles": ["registrant"],
+                    "vcardArray": ["vcard", [["version", {}, "text", "4.0"], ["fn", {}, "text", "Example Org"]]],
+                    "entities": [{
+                        "roles": ["abuse"],
+                        "vcardArray": ["vcard", [["fn", {}, "text", "Abuse"], ["email", {}, "text", "abuse@example.org"]]]
+                    }]
+                }
+            ]
+        });
+
+        let info = parse_rdap_network(&body).unwrap();
+        assert_eq!(info.cidr, "192.0.2.0/24".parse::<IpNetwork>().unwrap());
+        assert_eq!(info.name, "EXAMPLE-NET");
+        assert_eq!(info.organization, "Example Org");
+        assert_eq!(info.country, "US");
+        assert_eq!(info.abuse_email.as_deref(), Some("abuse@example.org"));
+
+        let cidr0 = json!({ "cidr0_cidrs": [{ "v4prefix": "198.51.100.0", "length": 22 }] });
+        assert_eq!(parse_rdap_network(&cidr0).unwrap().cidr, "198.51.100.0/22".parse::<IpNetwork>().unwrap());
+        assert!(parse_rdap_network(&json!({ "errorCode": 404 })).is_none());
+    }
+
+    #[test]
+    fn test_registry_order_and_cache_key() {
+        assert_eq!(registries_for("193.0.6.139".parse().unwrap())[0], RIPE);
+        assert_eq!(registries_for("8.8.8.8".parse().unwrap()), vec![ARIN, RIPE, APNIC, LACNIC, AFRINIC]);
+        assert_eq!(cache_key("203.0.113.77".parse().unwrap()), "203.0.113.0/24".parse::<IpNetwork>().unwrap());
+    }
+}
@@ -1,17 +1,23 @@
 //! Wildcard DNS filtering
 
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use std::time::Duration;
 
 use dashmap::DashMap;
 use rand::Rng;
+use ring::digest;
 use tracing::debug;
 
-use crate::error::Result;
+use crate::error::{DnsxError, Result};
 use crate::resolver::ResolverPool;
 use crate::types::RecordType;
 
 use crate::types::DnsRecord;
+use crate::utils::normalize_domain;
 
 /// Wildcard detection and bypass results
 #[derive(Debug, Clone)]
@@ -24,6 +30,31 @@ pub struct WildcardAnalysis {
     pub confidence_score: f64, // 0.0 to 1.0
 }
 
+/// How many random probes answered during wildcard detection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WildcardConfidence {
+    /// One of three probes resolved
+    Low,
+    /// Two of three probes resolved, or all three with differing answers
+    Medium,
+    /// All three probes resolved to the same addresses with the same TTL
+    High,
+}
+
+impl WildcardConfidence {
+    /// Rough likelihood that an answer under a domain with this confidence is a wildcard
+    pub fn probability(self) -> f64 {
+        match self {
+            WildcardConfidence::Low => 0.3,
+            WildcardConfidence::Medium => 0.6,
+            WildcardConfidence::High => 0.9,
+        }
+    }
+}
+
+/// Lengths of the random labels used to probe for wildcards
+const WILDCARD_PROBE_LENGTHS: [usize; 3] = [8, 16, 32];
+
 /// Attempt to bypass wildcard detection
 #[derive(Debug, Clone)]
 pub struct WildcardBypassAttempt {
@@ -31,13 +62,212 @@ pub struct WildcardBypassAttempt {
     pub test_domain: String,
     pub success: bool,
     pub response_ip: Option<String>,
+    /// What the outcome was based on, for techniques that go beyond DNS
+    pub evidence: Option<String>,
+}
+
+/// Outcome of an HTTP-level bypass test against one wildcard address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BypassResult {
+    pub technique: String,
+    pub test_domain: String,
+    /// The wildcard address served content specific to `test_domain`
+    pub success: bool,
+    /// Status codes and body hashes that were compared
+    pub evidence: String,
+}
+
+impl From<BypassResult> for WildcardBypassAttempt {
+    fn from(result: BypassResult) -> Self {
+        Self {
+            technique: result.technique,
+            test_domain: result.test_domain,
+            success: result.success,
+            response_ip: None,
+            evidence: Some(result.evidence),
+        }
+    }
+}
+
+/// Status code and SHA-256 of the body of one HTTP response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HttpFingerprint {
+    pub(crate) status: u16,
+    pub(crate) body_sha256: String,
+}
+
+impl HttpFingerprint {
+    /// The `specific` response is a real site rather than the catch-all `generic` one
+    pub(crate) fn is_distinct_site(specific: &Self, generic: &Self) -> bool {
+        specific.status == 200 && (generic.status != 200 || specific.body_sha256 != generic.body_sha256)
+    }
+}
+
+/// Bodies whose simhashes differ in at most this many bits count as the same page
+pub(crate) const SIMHASH_MAX_DISTANCE: u32 = 3;
+
+/// 64-bit simhash of a response body over its lowercased words
+///
+/// Catch-all pages often carry a request ID or timestamp, so an exact hash
+/// would tell two wildcard responses apart; near-identical bodies end up a few
+/// bits apart instead.
+pub(crate) fn simhash(body: &str) -> u64 {
+    let mut weights = [0i64; 64];
+    for word in body.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+        let mut hasher = DefaultHasher::new();
+        word.to_lowercase().hash(&mut hasher);
+        let hash = hasher.finish();
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if (hash >> bit) & 1 == 1 { 1 } else { -1 };
+        }
+    }
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0, |hash, (bit, _)| hash | (1 << bit))
+}
+
+/// `body` without the host name it was requested for, which catch-all pages like to echo
+///
+/// The host and its first label are only removed where they stand alone, so a
+/// short label such as `a` or `www` does not eat into other words.
+pub(crate) fn without_host(body: &str, host: &str) -> String {
+    let label = host.split('.').next().unwrap_or(host);
+    remove_name(&remove_name(body, host), label)
+}
+
+/// `text` without the occurrences of `name` that are not part of a longer label
+fn remove_name(text: &str, name: &str) -> String {
+    if name.is_empty() {
+        return text.to_string();
+    }
+    let is_label_char = |c: char| c.is_ascii_alphanumeric() || c == '-';
+
+    let mut result = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (start, _) in text.match_indices(name) {
+        let end = start + name.len();
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        if before.is_some_and(is_label_char) || after.is_some_and(is_label_char) {
+            continue;
+        }
+        result.push_str(&text[copied..start]);
+        copied = end;
+    }
+    result.push_str(&text[copied..]);
+    result
+}
+
+/// Target of a CNAME record, including the aliases that come with A and AAAA answers
+fn alias_target(record: &DnsRecord) -> Option<&str> {
+    match (&record.value, record.record_type) {
+        (crate::types::RecordValue::Domain(target), RecordType::Cname | RecordType::A | RecordType::Aaaa) => {
+            Some(target)
+        }
+        _ => None,
+    }
+}
+
+/// Client for probing web servers directly, without following redirects
+fn http_client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| DnsxError::Other(format!("Failed to create HTTP client: {}", e)))
+}
+
+/// Looks for real virtual hosts behind addresses that wildcard DNS points everything at
+///
+/// A wildcard record often leads to a CDN or load balancer that routes on the
+/// `Host` header, so a name with its own site can hide behind the same address
+/// as every made-up name.
+pub struct WildcardBypassTester {
+    client: reqwest::Client,
+}
+
+impl WildcardBypassTester {
+    /// Create a new tester
+    pub fn new() -> Result<Self> {
+        Ok(Self { client: http_client()? })
+    }
+
+    /// Compare how each wildcard address answers `Host: {domain}` and `Host: real-{random}.{domain}`
+    ///
+    /// One result per address; it succeeds when `domain` gets a 200 whose body
+    /// differs from what the random name gets.
+    pub async fn test_host_header(&self, domain: &str, wildcard_ips: &[IpAddr]) -> Result<Vec<BypassResult>> {
+        let domain = domain.trim().trim_end_matches('.').to_lowercase();
+        let random_host = format!("real-{}", WildcardFilter::random_subdomain(&domain, 12));
+
+        let mut results = Vec::with_capacity(wildcard_ips.len());
+        for &ip in wildcard_ips {
+            let specific = self.fingerprint(ip, &domain).await;
+            let generic = self.fingerprint(ip, &random_host).await;
+
+            let (success, evidence) = match (specific, generic) {
+                (Ok(specific), Ok(generic)) => (
+                    HttpFingerprint::is_distinct_site(&specific, &generic),
+                    format!(
+                        "{}: {} ({}), {}: {} ({})",
+                        domain,
+                        specific.status,
+                        &specific.body_sha256[..16],
+                        random_host,
+                        generic.status,
+                        &generic.body_sha256[..16]
+                    ),
+                ),
+                (Err(e), _) | (_, Err(e)) => (false, format!("HTTP request to {} failed: {}", ip, e)),
+            };
+            debug!("Host header test of {} at {}: {}", domain, ip, evidence);
+
+            results.push(BypassResult {
+                technique: "host_header".to_string(),
+                test_domain: domain.clone(),
+                success,
+                evidence,
+            });
+        }
+
+        Ok(results)
+    }
+
+    /// Fetch `/` from `ip` over plain HTTP as `host`
+    async fn fingerprint(&self, ip: IpAddr, host: &str) -> Result<HttpFingerprint> {
+        let url = format!("http://{}/", SocketAddr::new(ip, 80));
+        let response = self
+            .client
+            .get(&url)
+            .header(reqwest::header::HOST, host)
+            .send()
+            .await
+            .map_err(|e| DnsxError::network(ip.to_string(), format!("Request for {} failed: {}", host, e)))?;
+
+        let status = response.status().as_u16();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| DnsxError::network(ip.to_string(), format!("Reading body for {} failed: {}", host, e)))?;
+
+        Ok(HttpFingerprint {
+            status,
+            body_sha256: hex::encode(digest::digest(&digest::SHA256, &body)),
+        })
+    }
 }
 
 /// Enhanced wildcard filter for DNS records with bypass techniques
 #[derive(Clone)]
 pub struct WildcardFilter {
-    /// Wildcard patterns detected (domain -> is_wildcard)
-    patterns: Arc<DashMap<String, bool>>,
+    /// Wildcard probe results (domain -> confidence, `None` when no probe resolved)
+    patterns: Arc<DashMap<String, Option<WildcardConfidence>>>,
+    /// Addresses returned by wildcard probes for domains at or above the threshold
+    wildcard_ips: Arc<DashMap<String, HashSet<IpAddr>>>,
+    /// Minimum probe confidence for a domain to be treated as a wildcard
+    confidence_threshold: WildcardConfidence,
     /// Domain for wildcard detection
     base_domain: Option<String>,
     /// Resolver pool for testing wildcards
@@ -46,6 +276,10 @@ pub struct WildcardFilter {
     threshold: usize,
     /// Wildcard analysis results
     analysis_cache: Arc<DashMap<String, WildcardAnalysis>>,
+    /// Set when DNS wildcards must also serve identical HTTP content to be filtered
+    http_client: Option<reqwest::Client>,
+    /// HTTP verification results by wildcard level
+    http_verdicts: Arc<DashMap<String, bool>>,
 }
 
 /// Helper struct for domain resolution testing
@@ -63,13 +297,33 @@ impl WildcardFilter {
     ) -> Self {
         Self {
             patterns: Arc::new(DashMap::new()),
+            wildcard_ips: Arc::new(DashMap::new()),
+            confidence_threshold: WildcardConfidence::Medium,
             base_domain,
             resolver_pool,
             threshold,
             analysis_cache: Arc::new(DashMap::new()),
+            http_client: None,
+            http_verdicts: Arc::new(DashMap::new()),
         }
     }
 
+    /// Only filter a wildcard's records when random names also get the same web page
+    ///
+    /// Some setups point every name at one address but serve each name its own
+    /// site; with this set, [`WildcardFilter::test_http_wildcard`] must agree
+    /// before records are dropped.
+    pub fn with_http_verification(mut self) -> Result<Self> {
+        self.http_client = Some(http_client()?);
+        Ok(self)
+    }
+
+    /// Set the minimum probe confidence required to treat a domain as a wildcard
+    pub fn with_confidence_threshold(mut self, threshold: WildcardConfidence) -> Self {
+        self.confidence_threshold = threshold;
+        self
+    }
+
     /// Perform comprehensive wildcard analysis for a domain
     pub async fn analyze_wildcard(&self, domain: &str) -> Result<WildcardAnalysis> {
         // Check cache first
@@ -89,7 +343,7 @@ impl WildcardFilter {
         // Test multiple random subdomains for consistency
         let mut test_results = Vec::new();
         for _ in 0..5 {
-            let test_domain = Self::random_subdomain(domain);
+            let test_domain = Self::random_subdomain(domain, 16);
             if let Ok((lookup, _)) = self.resolver_pool.query(&test_domain, RecordType::A).await {
                 for rdata in lookup.iter() {
                     if let hickory_resolver::proto::rr::RData::A(ip) = rdata {
@@ -104,7 +358,7 @@ impl WildcardFilter {
                             ttl: 300,
                             response_code: crate::types::ResponseCode::NoError,
                             resolver: "".to_string(),
-                            timestamp: std::time::SystemTime::now(),
+                            timestamp: chrono::Utc::now(),
                             query_time_ms: 0.0,
                         };
                         analysis.wildcard_records.push(record);
@@ -159,6 +413,7 @@ impl WildcardFilter {
                 test_domain,
                 success: !result.resolved,
                 response_ip: result.ip,
+                evidence: None,
             });
         }
 
@@ -170,6 +425,7 @@ impl WildcardFilter {
             test_domain: long_subdomain,
             success: !result.resolved,
             response_ip: result.ip,
+            evidence: None,
         });
 
         // Technique 3: Use underscore in subdomain
@@ -180,6 +436,7 @@ impl WildcardFilter {
             test_domain: underscore_domain,
             success: !result.resolved,
             response_ip: result.ip,
+            evidence: None,
         });
 
         attempts
@@ -259,37 +516,195 @@ impl WildcardFilter {
     }
 
     /// Generate a random subdomain for testing wildcards
-    fn random_subdomain(base: &str) -> String {
+    fn random_subdomain(base: &str, length: usize) -> String {
         let mut rng = rand::thread_rng();
-        let random_str: String = (0..16)
+        let random_str: String = (0..length)
             .map(|_| rng.gen_range(b'a'..=b'z') as char)
             .collect();
         format!("{}.{}", random_str, base)
     }
 
+    /// Whether two random names under `domain` get the same DNS answer and the same web page
+    ///
+    /// Both names must resolve to the same addresses (all among `wildcard_ips`
+    /// when given), and `GET /` for each on port 80 must return the same status
+    /// and bodies whose [`simhash`]es are near-identical.
+    pub async fn test_http_wildcard(&self, domain: &str, wildcard_ips: &[IpAddr]) -> Result<bool> {
+        let domain = domain.trim().trim_end_matches('.').to_lowercase();
+        let first = Self::random_subdomain(&domain, 12);
+        let second = Self::random_subdomain(&domain, 12);
+
+        let first_ips = self.resolve_ips(&first).await;
+        let second_ips = self.resolve_ips(&second).await;
+        let outside_wildcard = !wildcard_ips.is_empty() && first_ips.iter().any(|ip| !wildcard_ips.contains(ip));
+        let Some(&ip) = first_ips.iter().next().filter(|_| first_ips == second_ips && !outside_wildcard) else {
+            debug!("Random names under {} got different DNS answers", domain);
+            return Ok(false);
+        };
+
+        let client = match &self.http_client {
+            Some(client) => client.clone(),
+            None => http_client()?,
+        };
+        let (first_status, first_body) = Self::fetch_page(&client, ip, &first).await?;
+        let (second_status, second_body) = Self::fetch_page(&client, ip, &second).await?;
+        let distance = (simhash(&without_host(&first_body, &first)) ^ simhash(&without_host(&second_body, &second)))
+            .count_ones();
+        debug!(
+            "HTTP wildcard test of {} at {}: status {} and {}, simhash distance {}",
+            domain, ip, first_status, second_status, distance
+        );
+
+        Ok(first_status == second_status && distance <= SIMHASH_MAX_DISTANCE)
+    }
+
+    async fn resolve_ips(&self, domain: &str) -> BTreeSet<IpAddr> {
+        let Ok((lookup, _)) = self.resolver_pool.query(domain, RecordType::A).await else {
+            return BTreeSet::new();
+        };
+        lookup
+            .iter()
+            .filter_map(|rdata| match rdata {
+                hickory_resolver::proto::rr::RData::A(ip) => Some(IpAddr::V4(**ip)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Status and body of `http://{host}/`, requested from `ip`
+    async fn fetch_page(client: &reqwest::Client, ip: IpAddr, host: &str) -> Result<(u16, String)> {
+        let response = client
+            .get(format!("http://{}/", SocketAddr::new(ip, 80)))
+            .header(reqwest::header::HOST, host)
+            .send()
+            .await
+            .map_err(|e| DnsxError::network(ip.to_string(), format!("Request for {} failed: {}", host, e)))?;
+        let status = response.status().as_u16();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| DnsxError::network(ip.to_string(), format!("Reading body for {} failed: {}", host, e)))?;
+        Ok((status, body))
+    }
+
+    /// With HTTP verification on, whether the wildcard at `level` also serves one page for every name
+    ///
+    /// Always true without verification; an HTTP failure keeps the DNS verdict.
+    async fn confirmed_by_http(&self, level: &str) -> bool {
+        if self.http_client.is_none() {
+            return true;
+        }
+        if let Some(verdict) = self.http_verdicts.get(level) {
+            return *verdict;
+        }
+
+        let ips: Vec<IpAddr> =
+            self.wildcard_ips.get(level).map(|ips| ips.iter().copied().collect()).unwrap_or_default();
+        let verdict = self.test_http_wildcard(level, &ips).await.unwrap_or_else(|e| {
+            debug!("HTTP wildcard test of {} failed, keeping the DNS verdict: {}", level, e);
+            true
+        });
+        self.http_verdicts.insert(level.to_string(), verdict);
+        verdict
+    }
+
+    /// Probe a domain level with random labels of several lengths
+    ///
+    /// Returns the confidence (if any probe resolved) and every address the probes returned.
+    pub async fn probe_wildcard(&self, domain: &str) -> (Option<WildcardConfidence>, HashSet<IpAddr>) {
+        let mut answers: Vec<(BTreeSet<IpAddr>, u32)> = Vec::new();
+
+        for length in WILDCARD_PROBE_LENGTHS {
+            let test_domain = Self::random_subdomain(domain, length);
+            let Ok((lookup, _)) = self.resolver_pool.query(&test_domain, RecordType::A).await else {
+                continue;
+            };
+
+            let mut ips = BTreeSet::new();
+            let mut ttl = 0;
+            for record in lookup.records() {
+                if let Some(hickory_resolver::proto::rr::RData::A(ip)) = record.data() {
+                    ips.insert(IpAddr::V4(**ip));
+                    ttl = record.ttl();
+                }
+            }
+            if !ips.is_empty() {
+                answers.push((ips, ttl));
+            }
+        }
+
+        let confidence = match answers.len() {
+            0 => None,
+            1 => Some(WildcardConfidence::Low),
+            n if n == WILDCARD_PROBE_LENGTHS.len() && answers.iter().all(|a| *a == answers[0]) => {
+                Some(WildcardConfidence::High)
+            }
+            _ => Some(WildcardConfidence::Medium),
+        };
+
+        let ips = answers.into_iter().flat_map(|(ips, _)| ips).collect();
+        (confidence, ips)
+    }
+
+    /// Wildcard confidence for a domain level, probing on first use
+    pub async fn wildcard_confidence(&self, domain: &str) -> Option<WildcardConfidence> {
+        if let Some(confidence) = self.patterns.get(domain) {
+            return *confidence;
+        }
+
+        let (confidence, ips) = self.probe_wildcard(domain).await;
+        debug!("Wildcard probe for {}: {:?} ({} addresses)", domain, confidence, ips.len());
+
+        if confidence.is_some_and(|c| c >= self.confidence_threshold) {
+            self.wildcard_ips.insert(domain.to_string(), ips);
+        }
+        self.patterns.insert(domain.to_string(), confidence);
+        confidence
+    }
+
     /// Test if a domain level has wildcard DNS
     pub async fn test_wildcard(&self, domain: &str) -> Result<bool> {
-        // Check cache first
-        if let Some(is_wildcard) = self.patterns.get(domain) {
-            return Ok(*is_wildcard);
+        let domain = &normalize_domain(domain);
+        let is_wildcard = self
+            .wildcard_confidence(domain)
+            .await
+            .is_some_and(|c| c >= self.confidence_threshold);
+
+        if is_wildcard {
+            debug!("Detected wildcard DNS for {}", domain);
         }
+        Ok(is_wildcard)
+    }
 
-        // Test with a random subdomain that shouldn't exist
-        let test_domain = Self::random_subdomain(domain);
-        
-        match self.resolver_pool.query(&test_domain, RecordType::A).await {
-            Ok(_) => {
-                // Random domain resolved, likely a wildcard
-                self.patterns.insert(domain.to_string(), true);
-                debug!("Detected wildcard DNS for {}", domain);
-                Ok(true)
+    /// The closest parent of `domain` that has wildcard DNS
+    async fn wildcard_level(&self, domain: &str) -> Option<String> {
+        let parts: Vec<&str> = domain.split('.').collect();
+        for i in 1..parts.len() {
+            let domain_to_test = parts[i..].join(".");
+            if self.test_wildcard(&domain_to_test).await.unwrap_or(false) {
+                return Some(domain_to_test);
             }
-            Err(_) => {
-                // Random domain didn't resolve, not a wildcard
-                self.patterns.insert(domain.to_string(), false);
-                Ok(false)
+        }
+        None
+    }
+
+    /// Likelihood (0-1) that `domain` resolving to `ips` is only a wildcard answer
+    ///
+    /// Based on the closest parent level where any wildcard probe resolved;
+    /// addresses the probes themselves returned make it certain.
+    pub async fn wildcard_probability(&self, domain: &str, ips: &[IpAddr]) -> f64 {
+        let parts: Vec<&str> = domain.split('.').collect();
+        for i in 1..parts.len() {
+            let level = parts[i..].join(".");
+            let Some(confidence) = self.wildcard_confidence(&level).await else {
+                continue;
+            };
+            if self.wildcard_ips.get(&level).is_some_and(|probed| ips.iter().any(|ip| probed.contains(ip))) {
+                return 1.0;
             }
+            return confidence.probability();
         }
+        0.0
     }
 
     /// Check if a domain matches a wildcard pattern
@@ -300,26 +715,36 @@ impl WildcardFilter {
             return Ok(false);
         }
 
-        // Test each level for wildcards
-        for i in 1..parts.len() {
-            let domain_to_test = parts[i..].join(".");
-            if self.test_wildcard(&domain_to_test).await? {
-                return Ok(true);
-            }
-        }
-
-        Ok(false)
+        Ok(self.wildcard_level(domain).await.is_some())
     }
 
     /// Filter records by grouping by IP and detecting wildcards
     pub async fn filter_by_ip(&self, records: Vec<DnsRecord>) -> Result<Vec<DnsRecord>> {
         // Group records by IP address
         let mut ip_to_domains: HashMap<String, Vec<DnsRecord>> = HashMap::new();
-        
+        // Final address of each CNAME target, so aliases are compared by where they end up
+        let mut final_ips: HashMap<String, Option<IpAddr>> = HashMap::new();
+
         for record in records {
             if let crate::types::RecordValue::Ip(ip) = &record.value {
                 let ip_str = ip.to_string();
                 ip_to_domains.entry(ip_str).or_insert_with(Vec::new).push(record);
+            } else if let Some(target) = alias_target(&record).map(str::to_string) {
+                if !final_ips.contains_key(&target) {
+                    let ip = match self.resolver_pool.resolve_to_ips(&target).await {
+                        Ok(ips) => ips.into_iter().min(),
+                        Err(e) => {
+                            debug!("Could not resolve CNAME target {}: {}", target, e);
+                            None
+                        }
+                    };
+                    final_ips.insert(target.clone(), ip);
+                }
+                let key = match final_ips[&target] {
+                    Some(ip) => ip.to_string(),
+                    None => record.value.to_string(),
+                };
+                ip_to_domains.entry(key).or_insert_with(Vec::new).push(record);
             } else {
                 // Non-IP records, keep them with their value as key
                 ip_to_domains
@@ -333,25 +758,48 @@ impl WildcardFilter {
 
         // Check each IP group for wildcard patterns
         for (ip, domain_records) in ip_to_domains {
-            // If many domains point to same IP, likely a wildcard
-            if domain_records.len() >= self.threshold {
-                // Test if it's a wildcard
-                let mut is_wildcard_ip = false;
-                if let Some(first_record) = domain_records.first() {
-                    match self.is_wildcard(&first_record.domain).await {
-                        Ok(true) => is_wildcard_ip = true,
-                        _ => {}
-                    }
-                }
+            let parsed_ip = ip.parse::<IpAddr>().ok();
 
-                if !is_wildcard_ip {
-                    filtered.extend(domain_records);
-                } else {
-                    debug!("Filtered {} wildcard records for IP {}", domain_records.len(), ip);
+            // Addresses already seen answering wildcard probes are filtered outright
+            let known_wildcard_ip = parsed_ip
+                .is_some_and(|addr| self.wildcard_ips.iter().any(|entry| entry.value().contains(&addr)));
+
+            // If many domains point to same IP, likely a wildcard
+            let is_wildcard_ip = if known_wildcard_ip {
+                true
+            } else if domain_records.len() >= self.threshold {
+                match domain_records.first() {
+                    Some(first_record) => match self.wildcard_level(&first_record.domain).await {
+                        // Only drop the group if it matches what the wildcard actually returns
+                        Some(level) => match parsed_ip {
+                            Some(addr) => self.wildcard_ips.get(&level).is_some_and(|ips| ips.contains(&addr)),
+                            None => true,
+                        },
+                        None => false,
+                    },
+                    None => false,
                 }
             } else {
                 // Small number of domains, likely legitimate
+                false
+            };
+
+            // Names sharing a wildcard address may still be distinct sites
+            let is_wildcard_ip = is_wildcard_ip
+                && match domain_records.first() {
+                    Some(first_record) if self.http_client.is_some() => {
+                        match self.wildcard_level(&first_record.domain).await {
+                            Some(level) => self.confirmed_by_http(&level).await,
+                            None => true,
+                        }
+                    }
+                    _ => true,
+                };
+
+            if !is_wildcard_ip {
                 filtered.extend(domain_records);
+            } else {
+                debug!("Filtered {} wildcard records for IP {}", domain_records.len(), ip);
             }
         }
 
@@ -1,11 +1,26 @@
-//! DNS zone transfer (AXFR) enumeration
+//! DNS zone transfer (AXFR/IXFR) enumeration
 
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use hickory_resolver::proto::op::{Message, Query, ResponseCode as HickoryResponseCode};
+use hickory_resolver::proto::rr::rdata::SOA;
+use hickory_resolver::proto::rr::{Name, RData, Record, RecordType as HickoryRecordType};
+use regex::Regex;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
 use tracing::{debug, info};
 
 use crate::error::{DnsxError, Result};
 use crate::resolver::ResolverPool;
-use crate::types::{DnsRecord, RecordType};
+use crate::types::{DnsRecord, RecordType, ResponseCode};
+use crate::utils::parse_resolver;
+
+/// Longest a whole transfer may take, from connecting to the closing SOA
+const DEFAULT_TRANSFER_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Results from zone transfer enumeration
 #[derive(Debug, Clone)]
@@ -16,6 +31,274 @@ pub struct ZoneTransferResult {
     pub records: Vec<DnsRecord>,
 }
 
+impl ZoneTransferResult {
+    /// Records of any of `types`, in transfer order
+    pub fn filter_by_type(&self, types: &[RecordType]) -> Vec<&DnsRecord> {
+        self.records.iter().filter(|r| types.contains(&r.record_type)).collect()
+    }
+
+    /// Records whose owner name matches `pattern` anywhere (anchor it for a full match)
+    pub fn filter_by_subdomain_pattern(&self, pattern: &Regex) -> Vec<&DnsRecord> {
+        self.records.iter().filter(|r| pattern.is_match(&r.domain)).collect()
+    }
+
+    /// Write the records as a master-format zone file, `$ORIGIN` first
+    pub fn to_zone_file(&self, writer: &mut impl Write) -> Result<()> {
+        let write_error = |e| DnsxError::io(format!("Failed to write zone file for {}", self.domain), e);
+        writeln!(writer, "$ORIGIN {}.", self.domain.trim_end_matches('.')).map_err(write_error)?;
+        for record in &self.records {
+            writeln!(writer, "{}", record.to_zone_file_line()).map_err(write_error)?;
+        }
+        Ok(())
+    }
+
+    /// Write the records as JSON, one per line
+    pub fn to_jsonl(&self, writer: &mut impl Write) -> Result<()> {
+        for record in &self.records {
+            let line = serde_json::to_string(record).map_err(|e| DnsxError::Serialization(e.to_string()))?;
+            writeln!(writer, "{}", line)
+                .map_err(|e| DnsxError::io(format!("Failed to write records for {}", self.domain), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Differences between two zone snapshots
+#[derive(Debug, Clone, Default)]
+pub struct ZoneDiff {
+    pub added: Vec<DnsRecord>,
+    pub removed: Vec<DnsRecord>,
+    /// Records present in both snapshots with a different TTL, as (old, new)
+    pub ttl_changed: Vec<(DnsRecord, DnsRecord)>,
+}
+
+impl ZoneDiff {
+    /// Whether the two snapshots are identical
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.ttl_changed.is_empty()
+    }
+}
+
+/// Compares zone snapshots record by record
+pub struct ZoneDiffer;
+
+impl ZoneDiffer {
+    /// Diff two record sets keyed on (domain, record type, value)
+    pub fn diff(old_records: &[DnsRecord], new_records: &[DnsRecord]) -> ZoneDiff {
+        let old_by_key: HashMap<_, _> = old_records.iter().map(|r| (Self::key(r), r)).collect();
+        let new_by_key: HashMap<_, _> = new_records.iter().map(|r| (Self::key(r), r)).collect();
+
+        let mut diff = ZoneDiff::default();
+
+        for record in new_records {
+            match old_by_key.get(&Self::key(record)) {
+                None => diff.added.push(record.clone()),
+                Some(old) if old.ttl != record.ttl => {
+                    diff.ttl_changed.push(((*old).clone(), record.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+
+        for record in old_records {
+            if !new_by_key.contains_key(&Self::key(record)) {
+                diff.removed.push(record.clone());
+            }
+        }
+
+        diff
+    }
+
+    /// Values are compared in their text form so records reloaded from JSON match freshly parsed ones
+    fn key(record: &DnsRecord) -> (String, RecordType, String) {
+        (
+            record.domain.trim_end_matches('.').to_lowercase(),
+            record.record_type,
+            record.value.to_string(),
+        )
+    }
+}
+
+/// Zone transfer client speaking AXFR (RFC 5936) and IXFR (RFC 1995) over TCP
+pub struct ZoneTransferClient {
+    timeout: Duration,
+    serial: Option<u32>,
+}
+
+impl ZoneTransferClient {
+    /// Create a client that only asks for full (AXFR) transfers
+    pub fn new() -> Self {
+        Self { timeout: DEFAULT_TRANSFER_TIMEOUT, serial: None }
+    }
+
+    /// Limit on the whole transfer, not on each message
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Serial of the copy of the zone already held; makes [`transfer`](Self::transfer) try IXFR first
+    pub fn with_serial(mut self, serial: Option<u32>) -> Self {
+        self.serial = serial;
+        self
+    }
+
+    /// Transfer `domain` from `server_addr`, returning its records in the order the server sent them
+    ///
+    /// An AXFR answer comes back as the zone SOA followed by every other
+    /// record. An incremental IXFR answer keeps the SOAs that start each
+    /// deleted and added section, and an IXFR answer for a zone that has not
+    /// changed is the SOA alone. If IXFR fails for any reason, AXFR is tried.
+    pub async fn transfer(&self, domain: &str, server_addr: SocketAddr) -> Result<Vec<DnsRecord>> {
+        let zone = Name::from_ascii(format!("{}.", domain.trim().trim_end_matches('.')))
+            .map_err(|e| DnsxError::from(e).with_context(format!("Invalid domain name {}", domain)))?;
+
+        if let Some(serial) = self.serial {
+            match self.exchange(&zone, server_addr, Some(serial)).await {
+                Ok(records) => return Ok(records),
+                Err(e) => debug!("IXFR of {} from {} failed, falling back to AXFR: {}", zone, server_addr, e),
+            }
+        }
+        self.exchange(&zone, server_addr, None).await
+    }
+
+    /// Run one AXFR, or IXFR from `serial`, over a fresh TCP connection
+    async fn exchange(&self, zone: &Name, server: SocketAddr, serial: Option<u32>) -> Result<Vec<DnsRecord>> {
+        let mut request = Message::new();
+        let qtype = if serial.is_some() { HickoryRecordType::IXFR } else { HickoryRecordType::AXFR };
+        request.set_id(rand::random()).add_query(Query::query(zone.clone(), qtype));
+        if let Some(serial) = serial {
+            // Only the serial of the SOA in the authority section matters to the server
+            let soa = SOA::new(zone.clone(), zone.clone(), serial, 0, 0, 0, 0);
+            request.add_name_server(Record::from_rdata(zone.clone(), 0, RData::SOA(soa)));
+        }
+        let request_bytes = request
+            .to_vec()
+            .map_err(|e| DnsxError::from(e).with_context("Failed to encode zone transfer query"))?;
+
+        let started = Instant::now();
+        let exchange = async {
+            let io_error = |e| DnsxError::network_source(server.to_string(), "Zone transfer over TCP failed", e);
+            let mut stream = TcpStream::connect(server).await.map_err(io_error)?;
+            stream.write_all(&(request_bytes.len() as u16).to_be_bytes()).await.map_err(io_error)?;
+            stream.write_all(&request_bytes).await.map_err(io_error)?;
+
+            let mut transfer = TransferStream::new(serial.is_some());
+            while !transfer.complete {
+                let len = stream.read_u16().await.map_err(io_error)?;
+                let mut buffer = vec![0u8; len as usize];
+                stream.read_exact(&mut buffer).await.map_err(io_error)?;
+                let response = Message::from_vec(&buffer).map_err(|e| {
+                    DnsxError::parse_source(hex::encode(&buffer), format!("Malformed response from {}", server), e)
+                })?;
+
+                if response.id() != request.id() {
+                    return Err(DnsxError::network(server.to_string(), "Zone transfer response has the wrong ID"));
+                }
+                if response.response_code() != HickoryResponseCode::NoError {
+                    return Err(DnsxError::Rcode {
+                        resolver: server.to_string(),
+                        domain: zone.to_string(),
+                        rcode: ResponseCode::from_hickory(response.response_code()),
+                    });
+                }
+                transfer.push_message(response.answers(), server)?;
+            }
+            Ok::<_, DnsxError>(transfer.records)
+        };
+
+        let records = tokio::time::timeout(self.timeout, exchange)
+            .await
+            .map_err(|_| DnsxError::timeout(server.to_string(), zone.to_string(), self.timeout))??;
+        let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+        let mut converted = Vec::with_capacity(records.len());
+        for record in records {
+            let (Some(record_type), Some(rdata)) = (RecordType::from_hickory(record.record_type()), record.data())
+            else {
+                debug!("Skipping unsupported {} record for {} in zone transfer", record.record_type(), record.name());
+                continue;
+            };
+            converted.push(DnsRecord::new(
+                record.name().to_string().trim_end_matches('.').to_string(),
+                record_type,
+                crate::query::parse_rdata(rdata)?,
+                record.ttl(),
+                ResponseCode::NoError,
+                server.to_string(),
+                elapsed_ms,
+            ));
+        }
+        Ok(converted)
+    }
+}
+
+impl Default for ZoneTransferClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Follows the answer records of a transfer up to the SOA that closes it
+struct TransferStream {
+    ixfr: bool,
+    records: Vec<Record>,
+    final_serial: u32,
+    incremental: bool,
+    deleting: bool,
+    complete: bool,
+}
+
+impl TransferStream {
+    fn new(ixfr: bool) -> Self {
+        Self { ixfr, records: Vec::new(), final_serial: 0, incremental: false, deleting: false, complete: false }
+    }
+
+    fn push_message(&mut self, answers: &[Record], server: SocketAddr) -> Result<()> {
+        for record in answers {
+            if self.complete {
+                break;
+            }
+            let soa_serial = match record.data() {
+                Some(RData::SOA(soa)) => Some(soa.serial()),
+                _ => None,
+            };
+
+            if self.records.is_empty() {
+                self.final_serial = soa_serial.ok_or_else(|| {
+                    DnsxError::network(server.to_string(), "Zone transfer does not start with a SOA record")
+                })?;
+                self.records.push(record.clone());
+                continue;
+            }
+
+            if self.ixfr && self.records.len() == 1 {
+                // A second SOA with an older serial marks an incremental answer
+                self.incremental = soa_serial.is_some_and(|serial| serial != self.final_serial);
+            }
+
+            match soa_serial {
+                Some(serial) if self.incremental => {
+                    if !self.deleting && serial == self.final_serial {
+                        self.complete = true;
+                    } else {
+                        self.deleting = !self.deleting;
+                        self.records.push(record.clone());
+                    }
+                }
+                Some(_) => self.complete = true,
+                None => self.records.push(record.clone()),
+            }
+        }
+
+        // An IXFR answer of a lone SOA means the held copy is current
+        if self.ixfr && self.records.len() == 1 {
+            self.complete = true;
+        }
+        Ok(())
+    }
+}
+
 /// Zone transfer enumeration functionality
 pub struct ZoneTransferEnumerator {
     resolver_pool: Arc<ResolverPool>,
@@ -27,10 +310,17 @@ impl ZoneTransferEnumerator {
         Self { resolver_pool }
     }
 
-    /// Attempt DNS zone transfer (AXFR) against specified servers
+    /// Attempt a zone transfer (AXFR) from each of `nameservers`, or from the
+    /// domain's own NS records when none are given
     pub async fn enumerate(&self, domain: &str, nameservers: &[String]) -> Result<ZoneTransferResult> {
         info!("Attempting zone transfer for domain: {}", domain);
 
+        let nameservers = if nameservers.is_empty() {
+            self.authoritative_nameservers(domain).await?
+        } else {
+            nameservers.to_vec()
+        };
+
         let mut results = ZoneTransferResult {
             domain: domain.to_string(),
             successful_transfers: Vec::new(),
@@ -38,7 +328,7 @@ impl ZoneTransferEnumerator {
             records: Vec::new(),
         };
 
-        for ns in nameservers {
+        for ns in &nameservers {
             match self.attempt_axfr(domain, ns).await {
                 Ok(records) => {
                     info!("✅ Zone transfer successful from {}: {} records", ns, records.len());
@@ -55,80 +345,274 @@ impl ZoneTransferEnumerator {
         Ok(results)
     }
 
-    /// Attempt AXFR against a specific nameserver
+    /// NS names the resolvers return for `domain`
+    async fn authoritative_nameservers(&self, domain: &str) -> Result<Vec<String>> {
+        let (lookup, _) = self.resolver_pool.query(domain, RecordType::Ns).await?;
+        let nameservers: Vec<String> = lookup
+            .iter()
+            .filter_map(|rdata| match rdata {
+                RData::NS(ns) => Some(ns.to_string().trim_end_matches('.').to_string()),
+                _ => None,
+            })
+            .collect();
+        if nameservers.is_empty() {
+            return Err(DnsxError::resolve(format!("Failed to enumerate nameservers for {}", domain)));
+        }
+        debug!("Nameservers of {}: {:?}", domain, nameservers);
+        Ok(nameservers)
+    }
+
+    /// Attempt AXFR against a nameserver given as IP[:port] or host name (port 53)
     async fn attempt_axfr(&self, domain: &str, nameserver: &str) -> Result<Vec<DnsRecord>> {
-        // Parse the nameserver address
-        let _ns_addr = if nameserver.contains(':') {
-            nameserver.to_string()
-        } else {
-            format!("{}:53", nameserver)
+        let server_addr = match parse_resolver(nameserver) {
+            Ok(addr) => addr,
+            Err(_) => {
+                // An empty or failed A lookup falls back to AAAA for IPv6-only nameservers
+                let ipv4 = self.resolver_pool.lookup_ipv4(nameserver).await.unwrap_or_default();
+                let ip = match ipv4.first() {
+                    Some(ip) => IpAddr::V4(*ip),
+                    None => {
+                        let ipv6 = self.resolver_pool.lookup_ipv6(nameserver).await?;
+                        let ip = ipv6.first().ok_or_else(|| {
+                            DnsxError::resolve(format!("Nameserver {} has no IPv4 or IPv6 address", nameserver))
+                        })?;
+                        IpAddr::V6(*ip)
+                    }
+                };
+                SocketAddr::new(ip, 53)
+            }
         };
+        debug!("Requesting AXFR of {} from {} ({})", domain, nameserver, server_addr);
 
-        // For now, we'll simulate AXFR by trying comprehensive record queries
-        // In a real implementation, you'd use a proper AXFR client
-        // This is a simplified version that attempts to enumerate common records
-
-        let mut all_records = Vec::new();
-        let record_types = vec![
-            crate::RecordType::A,
-            crate::RecordType::Aaaa,
-            crate::RecordType::Cname,
-            crate::RecordType::Mx,
-            crate::RecordType::Txt,
-            crate::RecordType::Ns,
-            crate::RecordType::Soa,
-            crate::RecordType::Srv,
-            crate::RecordType::Caa,
-            crate::RecordType::Ptr,
-        ];
+        ZoneTransferClient::new().transfer(domain, server_addr).await
+    }
+}
 
-        // Try to get NS records first to find authoritative servers
-        if let Ok((lookup, _)) = self.resolver_pool.query(domain, RecordType::Ns).await {
-            for rdata in lookup.iter() {
-                if let hickory_resolver::proto::rr::RData::NS(ns_record) = rdata {
-                    let ns_domain = ns_record.to_string();
-                    debug!("Found nameserver: {}", ns_domain);
-
-                    // Try to resolve NS to IP and attempt zone transfer
-                    if let Ok((ns_lookup, _)) = self.resolver_pool.query(&ns_domain, RecordType::A).await {
-                        for ns_rdata in ns_lookup.iter() {
-                            if let hickory_resolver::proto::rr::RData::A(ns_ip) = ns_rdata {
-                                debug!("Nameserver IP: {}", ns_ip);
-
-                                // Attempt zone transfer simulation
-                                for record_type in &record_types {
-                                    if let Ok((type_lookup, resolver_addr)) = self.resolver_pool.query(domain, *record_type).await {
-                                        for record in type_lookup.records() {
-                                            let value = crate::query::parse_rdata(record.data().expect("Record data missing"))?;
-                                            let ttl = record.ttl() as u32;
-
-                                            let dns_record = DnsRecord::new(
-                                                domain.to_string(),
-                                                *record_type,
-                                                value,
-                                                ttl,
-                                                crate::ResponseCode::NoError,
-                                                resolver_addr.clone(),
-                                                0.0,
-                                            );
-
-                                            all_records.push(dns_record);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{RecordValue, ResponseCode};
+    use hickory_resolver::proto::op::MessageType;
+    use hickory_resolver::proto::rr::rdata::{A, MX, NS};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    fn name(name: &str) -> Name {
+        Name::from_ascii(name).unwrap()
+    }
+
+    fn zone_record(owner: &str, rdata: RData) -> Record {
+        Record::from_rdata(name(owner), 3600, rdata)
+    }
+
+    fn zone_soa(serial: u32) -> Record {
+        let soa = SOA::new(name("ns1.example.com."), name("hostmaster.example.com."), serial, 7200, 3600, 1209600, 300);
+        zone_record("example.com.", RData::SOA(soa))
+    }
+
+    fn response(query: &Message, rcode: HickoryResponseCode, answers: Vec<Record>) -> Message {
+        let mut message = Message::new();
+        message
+            .set_id(query.id())
+            .set_message_type(MessageType::Response)
+            .set_response_code(rcode)
+            .add_queries(query.queries().to_vec())
+            .add_answers(answers);
+        message
+    }
+
+    /// The example.com zone split over two messages, the second ending in the closing SOA
+    fn full_zone(query: &Message) -> Vec<Message> {
+        vec![
+            response(
+                query,
+                HickoryResponseCode::NoError,
+                vec![
+                    zone_soa(2024010102),
+                    zone_record("example.com.", RData::NS(NS(name("ns1.example.com.")))),
+                    zone_record("www.example.com.", RData::A(A::new(192, 0, 2, 1))),
+                ],
+            ),
+            response(
+                query,
+                HickoryResponseCode::NoError,
+                vec![
+                    zone_record("mail.example.com.", RData::A(A::new(192, 0, 2, 2))),
+                    zone_record("example.com.", RData::MX(MX::new(10, name("mail.example.com.")))),
+                    zone_soa(2024010102),
+                ],
+            ),
+        ]
+    }
+
+    /// Answer `connections` connections in turn with the messages `respond` builds for
+    /// each query, returning the query types received
+    async fn spawn_server(
+        connections: usize,
+        respond: fn(&Message) -> Vec<Message>,
+    ) -> (SocketAddr, JoinHandle<Vec<HickoryRecordType>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let mut qtypes = Vec::new();
+            for _ in 0..connections {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let len = stream.read_u16().await.unwrap();
+                let mut buffer = vec![0u8; len as usize];
+                stream.read_exact(&mut buffer).await.unwrap();
+                let query = Message::from_vec(&buffer).unwrap();
+                qtypes.push(query.queries()[0].query_type());
+                for message in respond(&query) {
+                    let bytes = message.to_vec().unwrap();
+                    stream.write_all(&(bytes.len() as u16).to_be_bytes()).await.unwrap();
+                    stream.write_all(&bytes).await.unwrap();
                 }
             }
-        } else {
-            return Err(DnsxError::Other("Failed to enumerate nameservers".to_string()));
-        }
+            qtypes
+        });
+        (addr, server)
+    }
 
-        if all_records.is_empty() {
-            return Err(DnsxError::Other("Zone transfer not allowed or no records found".to_string()));
-        }
+    #[tokio::test]
+    async fn test_axfr_returns_zone_in_order() {
+        let (addr, server) = spawn_server(1, full_zone).await;
+
+        let records = ZoneTransferClient::new().transfer("example.com", addr).await.unwrap();
+        assert_eq!(server.await.unwrap(), vec![HickoryRecordType::AXFR]);
 
-        Ok(all_records)
+        let summary: Vec<(&str, RecordType)> = records.iter().map(|r| (r.domain.as_str(), r.record_type)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("example.com", RecordType::Soa),
+                ("example.com", RecordType::Ns),
+                ("www.example.com", RecordType::A),
+                ("mail.example.com", RecordType::A),
+                ("example.com", RecordType::Mx),
+            ]
+        );
+        assert_eq!(records[2].value, RecordValue::Ip("192.0.2.1".parse().unwrap()));
+        assert_eq!(records[0].resolver, addr.to_string());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_ixfr_falls_back_to_axfr() {
+        let (addr, server) = spawn_server(2, |query| {
+            if query.queries()[0].query_type() == HickoryRecordType::IXFR {
+                vec![response(query, HickoryResponseCode::NotImp, Vec::new())]
+            } else {
+                full_zone(query)
+            }
+        })
+        .await;
+
+        let client = ZoneTransferClient::new().with_serial(Some(2024010101));
+        let records = client.transfer("example.com", addr).await.unwrap();
+        assert_eq!(server.await.unwrap(), vec![HickoryRecordType::IXFR, HickoryRecordType::AXFR]);
+        assert_eq!(records.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_ixfr() {
+        let (addr, server) = spawn_server(1, |query| {
+            vec![response(
+                query,
+                HickoryResponseCode::NoError,
+                vec![
+                    zone_soa(2024010102),
+                    zone_soa(2024010101),
+                    zone_record("www.example.com.", RData::A(A::new(192, 0, 2, 1))),
+                    zone_soa(2024010102),
+                    zone_record("www.example.com.", RData::A(A::new(192, 0, 2, 10))),
+                    zone_soa(2024010102),
+                ],
+            )]
+        })
+        .await;
+
+        let client = ZoneTransferClient::new().with_serial(Some(2024010101));
+        let records = client.transfer("example.com", addr).await.unwrap();
+        assert_eq!(server.await.unwrap(), vec![HickoryRecordType::IXFR]);
+        let types: Vec<RecordType> = records.iter().map(|r| r.record_type).collect();
+        assert_eq!(types, vec![RecordType::Soa, RecordType::Soa, RecordType::A, RecordType::Soa, RecordType::A]);
+        assert_eq!(records[4].value, RecordValue::Ip("192.0.2.10".parse().unwrap()));
+    }
+
+    fn record(domain: &str, ip: &str, ttl: u32) -> DnsRecord {
+        DnsRecord::new(
+            domain.to_string(),
+            RecordType::A,
+            RecordValue::Ip(ip.parse().unwrap()),
+            ttl,
+            ResponseCode::NoError,
+            "8.8.8.8:53".to_string(),
+            0.0,
+        )
+    }
+
+    #[test]
+    fn test_zone_diff() {
+        let old = vec![
+            record("www.example.com", "192.0.2.1", 300),
+            record("mail.example.com", "192.0.2.2", 300),
+            record("ftp.example.com", "192.0.2.3", 300),
+        ];
+        let new = vec![
+            record("www.example.com", "192.0.2.1", 300),
+            record("mail.example.com", "192.0.2.2", 600),
+            record("api.example.com", "192.0.2.4", 300),
+        ];
+
+        let diff = ZoneDiffer::diff(&old, &new);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].domain, "api.example.com");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].domain, "ftp.example.com");
+        assert_eq!(diff.ttl_changed.len(), 1);
+        assert_eq!(diff.ttl_changed[0].0.ttl, 300);
+        assert_eq!(diff.ttl_changed[0].1.ttl, 600);
+
+        assert!(ZoneDiffer::diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_zone_transfer_filters() {
+        let mut records = vec![
+            record("www.example.com", "192.0.2.1", 300),
+            record("dev-api.example.com", "192.0.2.2", 60),
+        ];
+        records.push(DnsRecord::new(
+            "example.com".to_string(),
+            RecordType::Mx,
+            RecordValue::Mx { priority: 10, exchange: "mail.example.com".to_string() },
+            3600,
+            ResponseCode::NoError,
+            "8.8.8.8:53".to_string(),
+            0.0,
+        ));
+        let result = ZoneTransferResult {
+            domain: "example.com".to_string(),
+            successful_transfers: vec!["ns1.example.com".to_string()],
+            failed_transfers: Vec::new(),
+            records,
+        };
+
+        assert_eq!(result.filter_by_type(&[RecordType::A, RecordType::Aaaa]).len(), 2);
+        let dev = result.filter_by_subdomain_pattern(&Regex::new(".*dev.*").unwrap());
+        assert_eq!(dev.len(), 1);
+        assert_eq!(dev[0].domain, "dev-api.example.com");
+
+        let mut zone = Vec::new();
+        result.to_zone_file(&mut zone).unwrap();
+        let zone = String::from_utf8(zone).unwrap();
+        assert_eq!(zone.lines().next(), Some("$ORIGIN example.com."));
+        assert!(zone.contains("www.example.com. 300 IN A 192.0.2.1"));
+
+        let mut jsonl = Vec::new();
+        result.to_jsonl(&mut jsonl).unwrap();
+        let parsed: Vec<DnsRecord> =
+            String::from_utf8(jsonl).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[2].record_type, RecordType::Mx);
+    }
+}
@@ -35,9 +35,9 @@ mod tests {
         let client = DnsxClient::new().unwrap();
         let ips = client.lookup_ipv4("github.com").await.unwrap();
         assert!(!ips.is_empty());
-        // Verify all returned values are valid IPv4 addresses
+        // Verify all returned values are usable IPv4 addresses
         for ip in ips {
-            assert!(ip.is_private() || ip.is_global());
+            assert!(!ip.is_unspecified() && !ip.is_broadcast());
         }
     }
 
@@ -50,7 +50,7 @@ mod tests {
         assert!(!a_records.is_empty());
 
         // Test CNAME records (may not exist for root domain)
-        let cname_result = client.query("www.google.com", RecordType::CNAME).await;
+        let cname_result = client.query("www.google.com", RecordType::Cname).await;
         // CNAME query should not fail, even if no records exist
         assert!(cname_result.is_ok());
     }
@@ -58,7 +58,7 @@ mod tests {
     #[tokio::test]
     async fn test_txt_record_parsing() {
         let client = DnsxClient::new().unwrap();
-        let records = client.query("google.com", RecordType::TXT).await.unwrap();
+        let records = client.query("google.com", RecordType::Txt).await.unwrap();
 
         // TXT records may or may not exist, but parsing should work
         for record in records {
@@ -71,13 +71,12 @@ mod tests {
     #[tokio::test]
     async fn test_mx_record_parsing() {
         let client = DnsxClient::new().unwrap();
-        let records = client.query("google.com", RecordType::MX).await.unwrap();
+        let records = client.query("google.com", RecordType::Mx).await.unwrap();
         assert!(!records.is_empty());
 
         // Verify MX records have priority and exchange
         for record in records {
-            if let rdnsx_core::types::RecordValue::Mx { priority, exchange } = &record.value {
-                assert!(*priority >= 0);
+            if let rdnsx_core::types::RecordValue::Mx { exchange, .. } = &record.value {
                 assert!(!exchange.is_empty());
             }
         }
@@ -104,18 +103,20 @@ mod tests {
 
         // Should either return empty results or an error, but not panic
         // The exact behavior depends on the DNS server response
-        assert!(result.is_ok() || matches!(result, Err(_)));
+        if let Ok(records) = result {
+            assert!(records.is_empty());
+        }
     }
 
     #[tokio::test]
     async fn test_concurrent_queries() {
-        let client = DnsxClient::new().unwrap();
+        let client = std::sync::Arc::new(DnsxClient::new().unwrap());
 
-        let domains = vec!["google.com", "github.com", "stackoverflow.com"];
+        let domains = ["google.com", "github.com", "stackoverflow.com"];
         let mut handles = Vec::new();
 
         for domain in domains {
-            let client = &client;
+            let client = std::sync::Arc::clone(&client);
             let domain = domain.to_string();
             let handle = tokio::spawn(async move {
                 client.query(&domain, RecordType::A).await
@@ -145,4 +146,324 @@ mod tests {
         let records = client.query("google.com", RecordType::A).await.unwrap();
         assert!(!records.is_empty());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_doh_resolution() {
+        use rdnsx_core::types::{RecordValue, ResponseCode};
+
+        let client = DnsxClient::with_options(DnsxOptions {
+            resolvers: vec!["https://1.1.1.1/dns-query".to_string()],
+            ..Default::default()
+        }).unwrap();
+
+        let records = client.query("cloudflare.com", RecordType::A).await.unwrap();
+        assert!(!records.is_empty());
+        for record in &records {
+            assert_eq!(record.response_code, ResponseCode::NoError);
+            assert_eq!(record.resolver, "https://1.1.1.1/dns-query");
+            assert!(matches!(record.value, RecordValue::Ip(_)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dot_resolution() {
+        let client = DnsxClient::with_options(DnsxOptions {
+            resolvers: vec!["tls://9.9.9.9:853".to_string()],
+            ..Default::default()
+        }).unwrap();
+
+        let records = client.query("quad9.net", RecordType::A).await.unwrap();
+        assert!(!records.is_empty());
+        assert_eq!(records[0].resolver, "tls://9.9.9.9:853");
+    }
+
+    /// DoT server on localhost with a certificate from `tests/fixtures/dot-ca.pem`,
+    /// answering every query with 192.0.2.1
+    async fn spawn_dot_server() -> std::net::SocketAddr {
+        use hickory_resolver::proto::op::{Message, MessageType};
+        use hickory_resolver::proto::rr::{rdata::A, RData, Record};
+        use rustls::pki_types::pem::PemObject;
+        use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+        use std::sync::Arc;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let certs = CertificateDer::pem_file_iter(fixtures.join("dot-server.pem"))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let key = PrivateKeyDer::from_pem_file(fixtures.join("dot-server.key")).unwrap();
+        let config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .unwrap();
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((tcp, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    // Clients that reject the certificate end here
+                    let Ok(mut stream) = acceptor.accept(tcp).await else {
+                        return;
+                    };
+                    while let Ok(len) = stream.read_u16().await {
+                        let mut buf = vec![0u8; len as usize];
+                        stream.read_exact(&mut buf).await.unwrap();
+                        let request = Message::from_vec(&buf).unwrap();
+
+                        let mut response = Message::new();
+                        response
+                            .set_id(request.id())
+                            .set_message_type(MessageType::Response)
+                            .set_recursion_available(true)
+                            .add_queries(request.queries().to_vec())
+                            .add_answer(Record::from_rdata(
+                                request.queries()[0].name().clone(),
+                                60,
+                                RData::A(A::new(192, 0, 2, 1)),
+                            ));
+                        let bytes = response.to_vec().unwrap();
+                        stream.write_all(&(bytes.len() as u16).to_be_bytes()).await.unwrap();
+                        stream.write_all(&bytes).await.unwrap();
+                        stream.flush().await.unwrap();
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_dot_certificate_verification() {
+        use rdnsx_core::config::DoTConfig;
+        use rdnsx_core::types::RecordValue;
+
+        let addr = spawn_dot_server().await;
+        let options = |dot: DoTConfig| DnsxOptions {
+            resolvers: vec![format!("tls://{}", addr)],
+            timeout: std::time::Duration::from_secs(2),
+            retries: 0,
+            dot,
+            ..Default::default()
+        };
+
+        // The test CA is not in the web PKI roots
+        let client = DnsxClient::with_options(options(DoTConfig::default())).unwrap();
+        assert!(client.query("dot.example.com", RecordType::A).await.is_err());
+
+        let trusted = DoTConfig {
+            root_ca: Some(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/dot-ca.pem")),
+            ..Default::default()
+        };
+        let client = DnsxClient::with_options(options(trusted)).unwrap();
+        let records = client.query("dot.example.com", RecordType::A).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].value, RecordValue::Ip("192.0.2.1".parse().unwrap()));
+
+        let insecure = DoTConfig { insecure_skip_verify: true, ..Default::default() };
+        let client = DnsxClient::with_options(options(insecure)).unwrap();
+        assert_eq!(client.query("dot.example.com", RecordType::A).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_retry_backoff_delays() {
+        use rdnsx_core::config::BackoffConfig;
+        use std::time::Duration;
+
+        let backoff = BackoffConfig { jitter: false, ..Default::default() };
+        assert_eq!(backoff.delay(1), Duration::from_millis(100));
+        assert_eq!(backoff.delay(2), Duration::from_millis(200));
+        assert_eq!(backoff.delay(4), Duration::from_millis(800));
+        assert_eq!(backoff.delay(10), Duration::from_millis(5000));
+
+        let jittered = BackoffConfig::default().delay(3);
+        assert!(jittered >= Duration::from_millis(360) && jittered <= Duration::from_millis(440));
+    }
+
+    #[tokio::test]
+    async fn test_tcp_pool_reuses_connection() {
+        use hickory_resolver::proto::op::{Message, MessageType, Query};
+        use hickory_resolver::proto::rr::{rdata::A, Name, RData, Record, RecordType as WireType};
+        use rdnsx_core::TcpConnectionPool;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Answers every query on a connection with one A record and counts connections
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let accepted = Arc::new(AtomicUsize::new(0));
+        tokio::spawn({
+            let accepted = Arc::clone(&accepted);
+            async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    accepted.fetch_add(1, Ordering::SeqCst);
+                    tokio::spawn(async move {
+                        while let Ok(len) = stream.read_u16().await {
+                            let mut buf = vec![0u8; len as usize];
+                            stream.read_exact(&mut buf).await.unwrap();
+                            let request = Message::from_vec(&buf).unwrap();
+
+                            let mut response = Message::new();
+                            response
+                                .set_id(request.id())
+                                .set_message_type(MessageType::Response)
+                                .add_queries(request.queries().to_vec())
+                                .add_answer(Record::from_rdata(
+                                    request.queries()[0].name().clone(),
+                                    60,
+                                    RData::A(A::new(192, 0, 2, 1)),
+                                ));
+                            let bytes = response.to_vec().unwrap();
+                            stream.write_all(&(bytes.len() as u16).to_be_bytes()).await.unwrap();
+                            stream.write_all(&bytes).await.unwrap();
+                        }
+                    });
+                }
+            }
+        });
+
+        let pool = TcpConnectionPool::new(4, Duration::from_secs(2));
+        for _ in 0..3 {
+            let query = Query::query(Name::from_ascii("large.example.com.").unwrap(), WireType::A);
+            let lookup = pool.query(addr, query).await.unwrap();
+            assert_eq!(lookup.records().len(), 1);
+        }
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_pipeline_out_of_order_and_reconnect() {
+        use hickory_resolver::proto::op::{Message, MessageType, Query};
+        use hickory_resolver::proto::rr::{rdata::A, Name, RData, Record, RecordType as WireType};
+        use rdnsx_core::TcpPipeline;
+        use std::net::{IpAddr, Ipv4Addr};
+        use std::time::Duration;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        // Answers a.example.com with 192.0.2.1, b.example.com with 192.0.2.2 and so on
+        fn answer(request: &Message) -> Vec<u8> {
+            let name = request.queries()[0].name().clone();
+            let host = name.to_ascii().as_bytes()[0] - b'a' + 1;
+            let mut response = Message::new();
+            response
+                .set_id(request.id())
+                .set_message_type(MessageType::Response)
+                .add_queries(request.queries().to_vec())
+                .add_answer(Record::from_rdata(name, 60, RData::A(A::new(192, 0, 2, host))));
+            let bytes = response.to_vec().unwrap();
+            [(bytes.len() as u16).to_be_bytes().to_vec(), bytes].concat()
+        }
+
+        async fn read_request(stream: &mut TcpStream) -> Option<Message> {
+            let len = stream.read_u16().await.ok()?;
+            let mut buf = vec![0u8; len as usize];
+            stream.read_exact(&mut buf).await.ok()?;
+            Some(Message::from_vec(&buf).unwrap())
+        }
+
+        // The first connection answers two queries in reverse order and is then closed;
+        // the second answers in order until the client goes away
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let first = read_request(&mut stream).await.unwrap();
+            let second = read_request(&mut stream).await.unwrap();
+            assert_ne!(first.id(), second.id());
+            stream.write_all(&answer(&second)).await.unwrap();
+            stream.write_all(&answer(&first)).await.unwrap();
+            stream.flush().await.unwrap();
+            drop(stream);
+
+            let (mut stream, _) = listener.accept().await.unwrap();
+            while let Some(request) = read_request(&mut stream).await {
+                stream.write_all(&answer(&request)).await.unwrap();
+            }
+        });
+
+        let pipeline = TcpPipeline::new(4, Duration::from_secs(2));
+        let query = |name: &str| Query::query(Name::from_ascii(name).unwrap(), WireType::A);
+        let address = |lookup: hickory_resolver::lookup::Lookup| lookup.iter().next().and_then(|rdata| rdata.ip_addr());
+
+        let (a, b) = tokio::join!(
+            pipeline.query(addr, query("a.example.com."), true),
+            pipeline.query(addr, query("b.example.com."), true)
+        );
+        assert_eq!(address(a.unwrap()), Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))));
+        assert_eq!(address(b.unwrap()), Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2))));
+
+        // The server has closed the first connection, so this query needs a new one
+        let c = pipeline.query(addr, query("c.example.com."), true).await.unwrap();
+        assert_eq!(address(c), Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 3))));
+        server.abort();
+    }
+
+    #[test]
+    fn test_cassandra_replication_cql() {
+        use rdnsx_core::ReplicationStrategy;
+        use std::collections::HashMap;
+
+        let simple = ReplicationStrategy::Simple { factor: 3 };
+        assert_eq!(simple.to_cql(), "{'class': 'SimpleStrategy', 'replication_factor': 3}");
+
+        let topology = ReplicationStrategy::NetworkTopology {
+            datacenter_factors: HashMap::from([("dc2".to_string(), 2), ("dc1".to_string(), 3)]),
+        };
+        assert_eq!(topology.to_cql(), "{'class': 'NetworkTopologyStrategy', 'dc1': 3, 'dc2': 2}");
+    }
+
+    #[test]
+    fn test_cassandra_time_bucket() {
+        use rdnsx_core::CassandraPartitionStrategy;
+
+        assert_eq!(CassandraPartitionStrategy::ByDomain.time_bucket(1_700_000_000_000), None);
+
+        let daily = CassandraPartitionStrategy::DAILY;
+        assert_eq!(daily.time_bucket(1_700_000_000_000), Some(19675));
+        // The last millisecond of a day stays in that day's bucket
+        assert_eq!(daily.time_bucket(86_399_999), Some(0));
+        assert_eq!(daily.time_bucket(86_400_000), Some(1));
+    }
+
+    /// Requires a Cassandra container, e.g.
+    /// `docker run -d -p 9042:9042 cassandra:4` with `RDNSX_TEST_CASSANDRA=127.0.0.1:9042`
+    #[tokio::test]
+    #[ignore]
+    async fn test_cassandra_keyspace_replication_strategies() {
+        use rdnsx_core::{CassandraConfig, CassandraExporter, ReplicationStrategy};
+        use std::collections::HashMap;
+
+        let contact_point = std::env::var("RDNSX_TEST_CASSANDRA").unwrap_or_else(|_| "127.0.0.1:9042".to_string());
+
+        let strategies = [
+            ("rdnsx_test_simple", ReplicationStrategy::Simple { factor: 1 }),
+            (
+                "rdnsx_test_topology",
+                // The official image names its single datacenter "datacenter1"
+                ReplicationStrategy::NetworkTopology {
+                    datacenter_factors: HashMap::from([("datacenter1".to_string(), 1)]),
+                },
+            ),
+        ];
+
+        for (keyspace, replication_strategy) in strategies {
+            let exporter = CassandraExporter::with_config(CassandraConfig {
+                contact_points: vec![contact_point.clone()],
+                keyspace: keyspace.to_string(),
+                num_workers: 1,
+                replication_strategy,
+                ..Default::default()
+            })
+            .await;
+            assert!(exporter.is_ok(), "keyspace {} failed: {:?}", keyspace, exporter.err());
+        }
+    }
+}
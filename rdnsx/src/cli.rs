@@ -2,10 +2,17 @@
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::commands::{bruteforce, enumerate, ptr, query};
-use rdnsx_core::config::Config as CoreConfig;
+use crate::commands::{analyze, bruteforce, cluster, diff, dnsbl, enumerate, export_stats, index, pipeline, ptr, query, schema, server, sign_zone, stats, validate_config};
+use rdnsx_core::config::{
+    Config as CoreConfig, DnsxOptions, DEFAULT_HTTP_TIMEOUT, DEFAULT_RATE_LIMIT_BURST_MULTIPLIER,
+    MAX_RATE_LIMIT_BURST_MULTIPLIER,
+};
+use rdnsx_core::{AuditLogger, GeographicResolverSelector, RecordType};
 
 #[derive(Parser)]
 #[command(name = "rdnsx")]
@@ -27,10 +34,89 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub json: bool,
 
+    /// Text output template, e.g. '{{domain}},{{value}},{{ttl}}' (ignored with --json)
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    pub output_template: Option<String>,
+
     /// Silent mode (minimal output)
     #[arg(long, global = true)]
     pub silent: bool,
 
+    /// Verbose mode (detailed statistics)
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// EDNS0 UDP payload size in bytes (512-4096, the most hickory can receive over UDP)
+    #[arg(long = "edns-buf-size", value_name = "BYTES", global = true, default_value = "4096",
+          value_parser = clap::value_parser!(u16).range(512..=4096))]
+    pub edns_buf_size: u16,
+
+    /// Do not retry truncated UDP responses over TCP
+    #[arg(long, global = true)]
+    pub no_tcp_fallback: bool,
+
+    /// Burst capacity as a multiple of the rate limit, e.g. 5.0 lets 5 seconds' worth of queries start at once (1.0-10.0)
+    #[arg(long = "rate-limit-burst", value_name = "MULTIPLIER", global = true, default_value = "1.0",
+          value_parser = parse_burst_multiplier)]
+    pub rate_limit_burst: f64,
+
+    /// Send queries over TCP, several at once per connection, instead of UDP
+    #[arg(long, global = true)]
+    pub tcp_pipeline: bool,
+
+    /// Queries in flight per pipelined TCP connection
+    #[arg(long, value_name = "N", global = true, default_value = "10", requires = "tcp_pipeline",
+          value_parser = clap::value_parser!(u16).range(1..))]
+    pub tcp_pipeline_depth: u16,
+
+    /// Discard DNS responses larger than this many bytes
+    #[arg(long, value_name = "BYTES", global = true, default_value = "65535")]
+    pub max_response_size: usize,
+
+    /// Log a warning for DNS responses larger than this many bytes (0 disables)
+    #[arg(long, value_name = "BYTES", global = true, default_value = "4096")]
+    pub warn_large_response: usize,
+
+    /// Use public resolvers in the same region as this ASN (e.g. AS15169) for geo-DNS accuracy
+    #[arg(long, value_name = "ASN", global = true)]
+    pub geo_resolvers: Option<String>,
+
+    /// Initialize and check all resolvers before the first query
+    #[arg(long, global = true)]
+    pub warm_up: bool,
+
+    /// Seconds NXDOMAIN and SERVFAIL answers stay in the DNS cache
+    #[arg(long, value_name = "SECS", global = true, default_value = "60")]
+    pub negative_cache_ttl: u64,
+
+    /// Append a JSON line for every DNS query (including failures) to this file
+    #[arg(long, value_name = "PATH", global = true)]
+    pub audit_log: Option<PathBuf>,
+
+    /// Rotate the audit log once it reaches this size in megabytes
+    #[arg(long, value_name = "MB", global = true, default_value = "100")]
+    pub audit_log_max_mb: u64,
+
+    /// Timeout in seconds for DNSKEY queries (default: the resolver timeout)
+    #[arg(long, value_name = "SECS", global = true)]
+    pub timeout_dnskey: Option<u64>,
+
+    /// Timeout in seconds for DS queries
+    #[arg(long, value_name = "SECS", global = true)]
+    pub timeout_ds: Option<u64>,
+
+    /// Timeout in seconds for RRSIG queries
+    #[arg(long, value_name = "SECS", global = true)]
+    pub timeout_rrsig: Option<u64>,
+
+    /// Timeout in seconds for NSEC queries
+    #[arg(long, value_name = "SECS", global = true)]
+    pub timeout_nsec: Option<u64>,
+
+    /// Timeout in seconds for NSEC3 queries
+    #[arg(long, value_name = "SECS", global = true)]
+    pub timeout_nsec3: Option<u64>,
+
     /// Create example configuration file and exit
     #[arg(long, help = "Create an example configuration file at the specified path")]
     pub create_config: Option<PathBuf>,
@@ -42,7 +128,56 @@ pub struct Config {
     pub core_config: CoreConfig,
     pub output_file: Option<String>,
     pub json_output: bool,
+    pub output_template: Option<String>,
     pub silent: bool,
+    pub verbose: bool,
+    pub edns_buffer_size: u16,
+    pub tcp_fallback: bool,
+    pub tcp_pipeline: bool,
+    pub tcp_pipeline_depth: usize,
+    pub max_response_size: usize,
+    pub warn_large_response: usize,
+    pub warm_up: bool,
+    /// Rate limiter burst capacity as a multiple of the configured rate limit
+    pub rate_limit_burst_multiplier: f64,
+    /// How long NXDOMAIN and SERVFAIL answers stay cached
+    pub negative_cache_ttl: Duration,
+    /// Query timeouts overriding the resolver timeout for specific record types
+    pub per_type_timeout: HashMap<RecordType, Duration>,
+    /// Shared by every client so all queries land in one rotating file
+    pub audit_logger: Option<Arc<AuditLogger>>,
+}
+
+impl Config {
+    /// Resolver options from the config file and global flags
+    ///
+    /// Commands with their own timeout, concurrency, cache file or HTTP timeout
+    /// flags override those fields afterwards.
+    pub fn dns_options(&self) -> DnsxOptions {
+        let resolvers = &self.core_config.resolvers;
+        DnsxOptions {
+            resolvers: resolvers.servers.clone(),
+            timeout: Duration::from_secs(resolvers.timeout),
+            per_type_timeout: self.per_type_timeout.clone(),
+            retries: resolvers.retries,
+            concurrency: self.core_config.performance.threads,
+            rate_limit: self.core_config.performance.rate_limit,
+            rate_limit_burst_multiplier: self.rate_limit_burst_multiplier,
+            edns_buffer_size: self.edns_buffer_size,
+            tcp_fallback: self.tcp_fallback,
+            tcp_pool_size: resolvers.tcp_pool_size,
+            tcp_pipeline: self.tcp_pipeline,
+            tcp_pipeline_depth: self.tcp_pipeline_depth,
+            retry_backoff: resolvers.retry_backoff,
+            max_response_size_bytes: self.max_response_size,
+            warn_on_large_response_bytes: self.warn_large_response,
+            dot: resolvers.dot.clone(),
+            tls_fallback_to_udp: resolvers.tls_fallback_to_udp,
+            negative_cache_ttl: self.negative_cache_ttl,
+            cache_file: None,
+            http_timeout: DEFAULT_HTTP_TIMEOUT,
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -55,6 +190,30 @@ pub enum Commands {
     Ptr(ptr::PtrArgs),
     /// Advanced DNS enumeration techniques (including ASN enumeration)
     Enumerate(enumerate::EnumerateArgs),
+    /// DNS security analysis (rebinding and tunneling detection)
+    Analyze(analyze::AnalyzeArgs),
+    /// WebSocket server streaming live DNS events
+    Server(server::ServerArgs),
+    /// Compare two saved record snapshots
+    Diff(diff::DiffArgs),
+    /// Analytics over exported records
+    Stats(stats::StatsArgs),
+    /// Pivot between domains and IPs in saved results
+    Index(index::IndexArgs),
+    /// Group the addresses in saved results by network, ASN and CDN provider
+    Cluster(cluster::ClusterArgs),
+    /// Print the record schema (JSON Schema or Avro)
+    Schema(schema::SchemaArgs),
+    /// Record counts and top domains/resolvers in the configured export backends
+    ExportStats(export_stats::ExportStatsArgs),
+    /// Sign a zone file with DNSSEC keys (RRSIG, DNSKEY and an NSEC3 chain)
+    SignZone(sign_zone::SignZoneArgs),
+    /// Look addresses up in DNS-based blocklists
+    Dnsbl(dnsbl::DnsblArgs),
+    /// Check the config file (resolvers, export backends, limits) and exit 1 on any problem
+    ValidateConfig(validate_config::ValidateConfigArgs),
+    /// Run the stages under [[pipeline.stages]] of the config file in one process
+    Pipeline(pipeline::PipelineArgs),
 }
 
 impl Cli {
@@ -68,15 +227,63 @@ impl Cli {
         // Require a command when not creating config
         let command = self.command.ok_or_else(|| anyhow::anyhow!("A subcommand is required (use --help for more information)"))?;
 
+        // Validation reads the file itself, since loading below falls back to defaults on errors
+        if let Commands::ValidateConfig(args) = command {
+            return validate_config::run(args, self.config.as_deref(), self.json, self.silent).await;
+        }
+
         // Load configuration
-        let core_config = CoreConfig::load_with_fallback(self.config.as_deref())?;
+        let mut core_config = CoreConfig::load_with_fallback(self.config.as_deref())?;
+
+        if let Some(asn) = &self.geo_resolvers {
+            let resolvers = GeographicResolverSelector::new().select_for_asn(asn);
+            if resolvers.is_empty() {
+                if !self.silent {
+                    eprintln!("No regional resolvers known for {}, keeping the configured resolvers", asn);
+                }
+            } else {
+                if !self.silent {
+                    eprintln!("🗺️  Using regional resolvers for {}: {}", asn, resolvers.join(", "));
+                }
+                core_config.resolvers.servers = resolvers;
+            }
+        }
+
+        let per_type_timeout = [
+            (RecordType::Dnskey, self.timeout_dnskey),
+            (RecordType::Ds, self.timeout_ds),
+            (RecordType::Rrsig, self.timeout_rrsig),
+            (RecordType::Nsec, self.timeout_nsec),
+            (RecordType::Nsec3, self.timeout_nsec3),
+        ]
+        .into_iter()
+        .filter_map(|(record_type, secs)| Some((record_type, Duration::from_secs(secs?))))
+        .collect();
+
+        let audit_logger = match &self.audit_log {
+            Some(path) => Some(Arc::new(AuditLogger::new(path, self.audit_log_max_mb * 1024 * 1024)?)),
+            None => None,
+        };
 
         // Override config with CLI arguments
         let config = Config {
             core_config,
             output_file: self.output,
             json_output: self.json,
+            output_template: self.output_template,
             silent: self.silent,
+            verbose: self.verbose,
+            edns_buffer_size: self.edns_buf_size,
+            tcp_fallback: !self.no_tcp_fallback,
+            tcp_pipeline: self.tcp_pipeline,
+            tcp_pipeline_depth: self.tcp_pipeline_depth as usize,
+            max_response_size: self.max_response_size,
+            warn_large_response: self.warn_large_response,
+            warm_up: self.warm_up,
+            rate_limit_burst_multiplier: self.rate_limit_burst,
+            negative_cache_ttl: Duration::from_secs(self.negative_cache_ttl),
+            per_type_timeout,
+            audit_logger,
         };
 
         match command {
@@ -84,6 +291,30 @@ impl Cli {
             Commands::Bruteforce(args) => bruteforce::run(args, config).await,
             Commands::Ptr(args) => ptr::run(args, config).await,
             Commands::Enumerate(args) => enumerate::run(args, config).await,
+            Commands::Analyze(args) => analyze::run(args, config).await,
+            Commands::Server(args) => server::run(args, config).await,
+            Commands::Diff(args) => diff::run(args, config).await,
+            Commands::Stats(args) => stats::run(args, config).await,
+            Commands::Index(args) => index::run(args, config).await,
+            Commands::Cluster(args) => cluster::run(args, config).await,
+            Commands::Schema(args) => schema::run(args, config).await,
+            Commands::ExportStats(args) => export_stats::run(args, config).await,
+            Commands::SignZone(args) => sign_zone::run(args, config).await,
+            Commands::Dnsbl(args) => dnsbl::run(args, config).await,
+            Commands::Pipeline(args) => pipeline::run(args, config).await,
+            Commands::ValidateConfig(_) => unreachable!("handled before the config is loaded"),
         }
     }
 }
+
+fn parse_burst_multiplier(value: &str) -> std::result::Result<f64, String> {
+    let multiplier: f64 = value.parse().map_err(|_| format!("'{}' is not a number", value))?;
+    if (DEFAULT_RATE_LIMIT_BURST_MULTIPLIER..=MAX_RATE_LIMIT_BURST_MULTIPLIER).contains(&multiplier) {
+        Ok(multiplier)
+    } else {
+        Err(format!(
+            "must be between {} and {}",
+            DEFAULT_RATE_LIMIT_BURST_MULTIPLIER, MAX_RATE_LIMIT_BURST_MULTIPLIER
+        ))
+    }
+}
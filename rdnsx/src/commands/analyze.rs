@@ -0,0 +1,363 @@
+//! DNS security analysis command implementation
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Args;
+use rdnsx_core::{ConsistencyChecker, DnsRecord, DnsxClient, ExpiryAnalyzer, LameDelegationDetector, RebindingDetector, RecordType, RecordValue, ResolverPool, TunnelingDetector, TxtSecretScanner, config::DnsxOptions};
+use rdnsx_core::input::{read_domains, read_records, read_records_stdin, InputSource};
+
+use crate::cli::Config;
+
+/// DNS security analysis command arguments
+#[derive(Args)]
+pub struct AnalyzeArgs {
+    /// Check the domain for DNS rebinding behaviour (public/private address flipping)
+    #[arg(long)]
+    pub rebinding: bool,
+
+    /// Score domains from --list/stdin for DNS tunneling indicators
+    #[arg(long)]
+    pub tunneling: bool,
+
+    /// Check the domain's delegated nameservers for lame delegation (not authoritative for the zone)
+    #[arg(long)]
+    pub lame_delegation: bool,
+
+    /// Look for credentials (API keys, private keys, tokens) published in the domain's TXT records
+    #[arg(long)]
+    pub secret_scan: bool,
+
+    /// Compare the domain's A records across six independent public resolvers to spot cache poisoning
+    #[arg(long)]
+    pub poison_check: bool,
+
+    /// Compare AAAA instead of A records for --poison-check
+    #[arg(long, requires = "poison_check")]
+    pub ipv6: bool,
+
+    /// Report RRSIG/CAA records from --list/stdin (JSON or JSONL) that are about to expire
+    #[arg(long)]
+    pub expiry: bool,
+
+    /// Warn about records expiring within this many days
+    #[arg(long, default_value = "30", requires = "expiry")]
+    pub warn_within_days: u64,
+
+    /// Input file for list-based analyses (default: stdin)
+    #[arg(short, long)]
+    pub list: Option<String>,
+
+    /// Also fetch TXT records when scoring tunneling (payload density check)
+    #[arg(long)]
+    pub with_txt: bool,
+
+    /// Target domain to analyze
+    #[arg(short, long)]
+    pub domain: Option<String>,
+
+    /// Number of repeated queries for rebinding detection
+    #[arg(long, default_value = "5")]
+    pub repeat: u8,
+
+    /// Delay between repeated queries (milliseconds)
+    #[arg(long, default_value = "1000")]
+    pub interval: u64,
+}
+
+pub async fn run(args: AnalyzeArgs, config: Config) -> Result<()> {
+    let dns_options = config.dns_options();
+
+    if args.rebinding {
+        let domain = args
+            .domain
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("--rebinding requires --domain"))?;
+        let resolver_pool = Arc::new(ResolverPool::new(&dns_options)?);
+        return perform_rebinding_check(resolver_pool, domain, args.repeat, Duration::from_millis(args.interval), &config).await;
+    }
+
+    if args.lame_delegation {
+        let domain = args
+            .domain
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("--lame-delegation requires --domain"))?;
+        let resolver_pool = Arc::new(ResolverPool::new(&dns_options)?);
+        return perform_lame_delegation_check(resolver_pool, domain, &config).await;
+    }
+
+    if args.secret_scan {
+        let domain = args
+            .domain
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("--secret-scan requires --domain"))?;
+        let resolver_pool = Arc::new(ResolverPool::new(&dns_options)?);
+        return perform_secret_scan(resolver_pool, domain, &config).await;
+    }
+
+    if args.poison_check {
+        let domain = args
+            .domain
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("--poison-check requires --domain"))?;
+        let record_type = if args.ipv6 { RecordType::Aaaa } else { RecordType::A };
+        return perform_poison_check(&dns_options, domain, record_type, &config).await;
+    }
+
+    if args.tunneling {
+        let source = match args.list {
+            Some(path) => InputSource::File(path),
+            None => InputSource::Stdin,
+        };
+        let domains = read_domains(source)?;
+        let client = DnsxClient::with_options(dns_options)?.with_audit_logger(config.audit_logger.clone());
+        if config.warm_up {
+            client.warm_up().await?;
+        }
+        return perform_tunneling_analysis(&client, &domains, args.with_txt, &config).await;
+    }
+
+    if args.expiry {
+        let records = match &args.list {
+            Some(path) => read_records(std::path::Path::new(path))?,
+            None => read_records_stdin()?,
+        };
+        return perform_expiry_check(&records, args.warn_within_days, &config);
+    }
+
+    anyhow::bail!(
+        "No analysis selected (use --rebinding, --lame-delegation, --secret-scan, --poison-check, --tunneling \
+         or --expiry)"
+    )
+}
+
+async fn perform_rebinding_check(
+    resolver_pool: Arc<ResolverPool>,
+    domain: &str,
+    repeat: u8,
+    interval: Duration,
+    config: &Config,
+) -> Result<()> {
+    if !config.silent {
+        eprintln!("🔁 Checking DNS rebinding for {} ({} queries, {:?} apart)", domain, repeat, interval);
+    }
+
+    let detector = RebindingDetector::new(resolver_pool);
+    let analysis = detector.check(domain, repeat, interval).await?;
+
+    println!("🛡️  DNS Rebinding Analysis for {}", analysis.domain);
+    println!("{}", "=".repeat(50));
+    println!("🔄 Change pattern: {}", analysis.change_pattern.join(" -> "));
+
+    if !analysis.public_ips.is_empty() {
+        println!("\n🌐 Public addresses:");
+        for ip in &analysis.public_ips {
+            println!("  • {}", ip);
+        }
+    }
+
+    if !analysis.private_ips.is_empty() {
+        println!("\n🏠 Private/loopback addresses:");
+        for ip in &analysis.private_ips {
+            println!("  • {}", ip);
+        }
+    }
+
+    if analysis.is_rebinding_candidate {
+        println!("\n⚠️  Rebinding candidate: domain resolves to both public and private addresses");
+    } else {
+        println!("\n✅ No rebinding behaviour observed");
+    }
+
+    Ok(())
+}
+
+async fn perform_lame_delegation_check(resolver_pool: Arc<ResolverPool>, domain: &str, config: &Config) -> Result<()> {
+    if !config.silent {
+        eprintln!("🏛️  Checking nameserver delegation for {}", domain);
+    }
+
+    let result = LameDelegationDetector::new(resolver_pool).check(domain).await?;
+
+    if config.json_output {
+        println!("{}", serde_json::json!({ "domain": domain, "lame_delegation": result }));
+        return Ok(());
+    }
+
+    println!("🧭 Delegation Analysis for {}", domain);
+    println!("{}", "=".repeat(50));
+    for ns in &result.authoritative_ns {
+        println!("  ✅ {} (authoritative)", ns);
+    }
+    for ns in &result.lame_ns {
+        println!("  ❌ {} (lame)", ns);
+    }
+
+    if result.lame_ns.is_empty() {
+        println!("\n✅ All {} delegated nameservers are authoritative", result.authoritative_ns.len());
+    } else {
+        println!(
+            "\n⚠️  {} of {} delegated nameservers are lame ({:.0}%)",
+            result.lame_ns.len(),
+            result.lame_ns.len() + result.authoritative_ns.len(),
+            result.lame_percentage
+        );
+    }
+
+    Ok(())
+}
+
+async fn perform_secret_scan(resolver_pool: Arc<ResolverPool>, domain: &str, config: &Config) -> Result<()> {
+    if !config.silent {
+        eprintln!("🔑 Scanning TXT records of {} for exposed credentials", domain);
+    }
+
+    let findings = TxtSecretScanner::new(resolver_pool).scan(domain).await?;
+
+    if config.json_output {
+        for finding in &findings {
+            println!("{}", serde_json::json!({ "domain": domain, "finding": finding }));
+        }
+        return Ok(());
+    }
+
+    if findings.is_empty() {
+        println!("✅ No credentials found in TXT records of {}", domain);
+        return Ok(());
+    }
+
+    println!("🔑 Possible Secrets in TXT Records of {}", domain);
+    println!("{}", "=".repeat(50));
+    for finding in &findings {
+        let icon = if finding.confidence >= 0.8 { "❌" } else { "⚠️ " };
+        let confidence = finding.confidence;
+        println!("  {} {} (confidence {:.2}): {}", icon, finding.pattern_name, confidence, finding.matched_value);
+    }
+
+    Ok(())
+}
+
+async fn perform_poison_check(
+    dns_options: &DnsxOptions,
+    domain: &str,
+    record_type: RecordType,
+    config: &Config,
+) -> Result<()> {
+    if !config.silent {
+        eprintln!("☣️  Comparing {} answers for {} across independent resolvers", record_type, domain);
+    }
+
+    let report = ConsistencyChecker::new(dns_options)?.detect_poisoning(domain, record_type).await?;
+
+    if config.json_output {
+        println!("{}", serde_json::to_string(&report)?);
+        return Ok(());
+    }
+
+    println!("☣️  DNS Poisoning Check for {}", report.domain);
+    println!("{}", "=".repeat(50));
+    let majority: Vec<String> = report.majority_answer.iter().map(|ip| ip.to_string()).collect();
+    println!("🗳️  Majority answer ({} resolvers answered): {}", report.resolvers_answered, majority.join(", "));
+
+    if report.outliers.is_empty() {
+        println!("\n✅ All resolvers agree");
+        return Ok(());
+    }
+
+    println!("\n⚠️  Minority answers:");
+    for outlier in &report.outliers {
+        let share = outlier.share * 100.0;
+        println!("  • {} from {} ({:.0}% of resolvers)", outlier.ip, outlier.resolvers.join(", "), share);
+    }
+    println!("\n📈 Poisoning likelihood: {:.2}", report.poisoning_likelihood);
+
+    Ok(())
+}
+
+fn perform_expiry_check(records: &[DnsRecord], warn_within_days: u64, config: &Config) -> Result<()> {
+    if !config.silent {
+        eprintln!("⏳ Checking {} records for expiry within {} days", records.len(), warn_within_days);
+    }
+
+    let warnings = ExpiryAnalyzer::new().find_expiring(records, Duration::from_secs(warn_within_days * 86_400));
+
+    if config.json_output {
+        for warning in &warnings {
+            println!("{}", serde_json::to_string(warning)?);
+        }
+        return Ok(());
+    }
+
+    if warnings.is_empty() {
+        println!("✅ No records expire within {} days", warn_within_days);
+        return Ok(());
+    }
+
+    println!("⏳ Expiring DNS Records");
+    println!("{}", "=".repeat(50));
+    for warning in &warnings {
+        let icon = if warning.days_remaining < 0 { "❌" } else { "⚠️ " };
+        let when = if warning.days_remaining < 0 {
+            format!("expired {} days ago", -warning.days_remaining)
+        } else {
+            format!("{} days remaining", warning.days_remaining)
+        };
+        println!(
+            "  {} {} {:?} expires {} ({})",
+            icon,
+            warning.domain,
+            warning.record_type,
+            warning.expires_at.format("%Y-%m-%d %H:%M UTC"),
+            when
+        );
+    }
+
+    Ok(())
+}
+
+async fn perform_tunneling_analysis(
+    client: &DnsxClient,
+    domains: &[String],
+    with_txt: bool,
+    config: &Config,
+) -> Result<()> {
+    if !config.silent {
+        eprintln!("🕳️  Scoring {} domains for DNS tunneling indicators", domains.len());
+    }
+
+    let detector = TunnelingDetector::new();
+    let mut flagged = 0;
+
+    for domain in domains {
+        let domain = domain.trim();
+
+        let mut txt_records = Vec::new();
+        if with_txt {
+            if let Ok(records) = client.query(domain, RecordType::Txt).await {
+                for record in records {
+                    if let RecordValue::Text(text) = record.value {
+                        txt_records.push(text);
+                    }
+                }
+            }
+        }
+
+        let score = detector.analyze_domain_with_txt(domain, &txt_records);
+        if detector.is_suspicious(&score) {
+            flagged += 1;
+            println!("⚠️  {} (score {:.2})", score.domain, score.score);
+            for indicator in &score.indicators {
+                println!("    • {}", indicator);
+            }
+        } else if config.verbose {
+            println!("✅ {} (score {:.2})", score.domain, score.score);
+        }
+    }
+
+    if !config.silent {
+        eprintln!("\n📊 {} of {} domains flagged as possible tunneling", flagged, domains.len());
+    }
+
+    Ok(())
+}
@@ -1,10 +1,16 @@
 //! Bruteforce command implementation
 
-use std::sync::Arc;
+use std::collections::{BTreeSet, HashSet};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
 use clap::Args;
-use rdnsx_core::{DnsxClient, RecordType};
+use futures::future::join_all;
+use rdnsx_core::{default_wordlist, BruteforceCheckpoint, BruteforceResult, Bruteforcer, ConcurrencyConfig, ConcurrentProcessor, DnsRecord, DnsxClient, HstsChecker, RecordType, RecordValue, ResolverPool, TlsSanEnumerator, VhostEnumerator, WildcardFilter};
+use rdnsx_core::input::read_wordlist;
+use tokio_util::sync::CancellationToken;
 
 use crate::cli::Config;
 use crate::output_writer::OutputWriter;
@@ -15,85 +21,379 @@ pub struct BruteforceArgs {
     #[arg(short, long, required = true)]
     pub domain: Vec<String>,
 
-    /// Wordlist file or comma-separated words (use - for stdin)
+    /// Wordlist file or comma-separated words (use - for stdin; default: built-in list)
     #[arg(short, long)]
-    pub wordlist: String,
+    pub wordlist: Option<String>,
 
     /// Placeholder string (default: FUZZ)
     #[arg(long, default_value = "FUZZ")]
     pub placeholder: String,
 
-    /// Record type to query (default: A)
-    #[arg(long, default_value = "A")]
-    pub record_type: String,
+    /// Record type to query; repeat to query several per candidate (default: A)
+    #[arg(short = 't', long, default_value = "A")]
+    pub record_type: Vec<String>,
+
+    /// Resume an interrupted run after the last candidate it processed (default: the most recent checkpoint
+    /// saved under ~/.rdnsx for these domains)
+    #[arg(long, value_name = "CHECKPOINT_FILE", num_args = 0..=1)]
+    pub resume: Option<Option<PathBuf>>,
+
+    /// Report whether each found subdomain is covered by the HSTS preload list
+    #[arg(long)]
+    pub hsts_check: bool,
+
+    /// Try the most commonly used labels (www, mail, api, ...) first so hits show up early
+    #[arg(long)]
+    pub smart_order: bool,
+
+    /// Read the TLS certificates served on port 443 by found addresses and resolve new in-scope names from their SANs
+    #[arg(long)]
+    pub tls_san_expand: bool,
+
+    /// Add domains co-hosted on the targets' addresses (from certificate transparency) to the candidates
+    #[arg(long)]
+    pub vhost_expand: bool,
+
+    /// Order found subdomains by this key instead of discovery order
+    #[arg(long, value_enum)]
+    pub sort_by: Option<SortBy>,
+}
+
+/// Output orderings for found subdomains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortBy {
+    /// Most likely real hosts first, scored on wildcard behaviour, unique addresses and response time
+    Confidence,
 }
 
 pub async fn run(args: BruteforceArgs, config: Config) -> Result<()> {
-    eprintln!("DEBUG: Starting bruteforce command");
-    // Parse record type
-    let record_type = match args.record_type.to_uppercase().as_str() {
-        "A" => RecordType::A,
-        "AAAA" => RecordType::Aaaa,
-        "CNAME" => RecordType::Cname,
-        "MX" => RecordType::Mx,
-        "TXT" => RecordType::Txt,
-        "NS" => RecordType::Ns,
-        "SOA" => RecordType::Soa,
-        "PTR" => RecordType::Ptr,
-        "SRV" => RecordType::Srv,
-        "CAA" => RecordType::Caa,
-        _ => {
-            eprintln!("Unsupported record type: {}", args.record_type);
-            std::process::exit(1);
+    // Parse record types
+    let mut record_types = Vec::new();
+    for record_type in &args.record_type {
+        let record_type = match record_type.to_uppercase().as_str() {
+            "A" => RecordType::A,
+            "AAAA" => RecordType::Aaaa,
+            "CNAME" => RecordType::Cname,
+            "MX" => RecordType::Mx,
+            "TXT" => RecordType::Txt,
+            "NS" => RecordType::Ns,
+            "SOA" => RecordType::Soa,
+            "PTR" => RecordType::Ptr,
+            "SRV" => RecordType::Srv,
+            "CAA" => RecordType::Caa,
+            _ => {
+                eprintln!("Unsupported record type: {}", record_type);
+                std::process::exit(1);
+            }
+        };
+        if !record_types.contains(&record_type) {
+            record_types.push(record_type);
         }
-    };
+    }
+    let record_types = Arc::new(record_types);
 
     // Create DNS client
-    let dns_options = rdnsx_core::config::DnsxOptions {
-        resolvers: config.core_config.resolvers.servers.clone(),
+    let dns_options = config.dns_options();
+    let client = Arc::new(DnsxClient::with_options(dns_options.clone())?.with_audit_logger(config.audit_logger.clone()));
+    if config.warm_up {
+        client.warm_up().await?;
+    }
+
+    // Create output writer
+    let mut output = OutputWriter::from_config(&config)?;
+
+    // Build the candidate list for every target domain
+    let mut words = match &args.wordlist {
+        Some(source) => read_wordlist(source)?,
+        None => {
+            let words = default_wordlist().to_vec();
+            if !config.silent {
+                eprintln!("Using built-in wordlist ({} words)", words.len());
+            }
+            words
+        }
+    };
+    if args.smart_order {
+        Bruteforcer::prioritize_words(&mut words);
+    }
+    let mut candidates: Vec<String> = args
+        .domain
+        .iter()
+        .flat_map(|domain| Bruteforcer::generate_subdomains(domain, words.clone(), &args.placeholder))
+        .collect();
+
+    if args.vhost_expand {
+        let vhosts = discover_vhosts(&client, &args.domain, &config).await?;
+        let known: HashSet<&str> = candidates.iter().map(String::as_str).collect();
+        let vhosts: Vec<String> = vhosts.into_iter().filter(|name| !known.contains(name.as_str())).collect();
+        candidates.extend(vhosts);
+    }
+
+    // Skip candidates already processed by an interrupted run
+    let checkpoint_key = args.domain.join(",");
+    let mut found_before = Vec::new();
+    let mut resumed_from = None;
+    if let Some(resume) = &args.resume {
+        let path = match resume {
+            Some(path) => path.clone(),
+            None => BruteforceCheckpoint::latest_for_domain(&checkpoint_key)?
+                .ok_or_else(|| anyhow::anyhow!("No checkpoint found for {}", checkpoint_key))?,
+        };
+        let (last_domain, found) = BruteforceCheckpoint::load(&path)?;
+        let before = candidates.len();
+        candidates = BruteforceCheckpoint::remaining(candidates, &last_domain);
+        if !config.silent {
+            eprintln!(
+                "Resuming from {} after {}: skipping {} already processed candidates ({} found before)",
+                path.display(),
+                last_domain,
+                before - candidates.len(),
+                found.len()
+            );
+        }
+        found_before = found;
+        resumed_from = Some(path);
+    }
+
+    if !config.silent {
+        eprintln!("Enumerating {} subdomain candidates for {}", candidates.len(), args.domain.join(", "));
+    }
+
+    // Stop between batches on CTRL+C so partial results can be saved
+    let cancel = CancellationToken::new();
+    tokio::spawn({
+        let cancel = cancel.clone();
+        async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancel.cancel();
+            }
+        }
+    });
+
+    let concurrency_config = ConcurrencyConfig {
+        max_concurrent: config.core_config.performance.threads,
+        batch_size: 500,
         timeout: std::time::Duration::from_secs(config.core_config.resolvers.timeout),
-        retries: config.core_config.resolvers.retries,
-        concurrency: config.core_config.performance.threads,
         rate_limit: config.core_config.performance.rate_limit,
+        rate_limit_burst_multiplier: config.rate_limit_burst_multiplier,
     };
-    let client = Arc::new(DnsxClient::with_options(dns_options)?);
 
-    // Create output writer
-    let mut output = OutputWriter::new(config.output_file.clone(), config.json_output, config.silent)?;
+    // Ranking needs the whole result per candidate, not just its records
+    let found_results: Option<Arc<Mutex<Vec<BruteforceResult>>>> = args.sort_by.map(|_| Arc::default());
 
-    // Process each domain
-    for domain in &args.domain {
-        if !config.silent {
-            eprintln!("Enumerating subdomains for {}", domain);
+    let processor = ConcurrentProcessor::new(concurrency_config, {
+        let client = Arc::clone(&client);
+        let found_results = found_results.clone();
+        let record_types = Arc::clone(&record_types);
+        move |subdomain: String| {
+            let client = Arc::clone(&client);
+            let record_types = Arc::clone(&record_types);
+            let found_results = found_results.clone();
+            Box::pin(async move {
+                // All record types are queried at once and merged per candidate
+                let result = Bruteforcer::resolve_candidate(&client, subdomain, &record_types, None).await;
+                if let Some(found_results) = found_results.filter(|_| result.is_found()) {
+                    found_results.lock().unwrap().push(result.clone());
+                }
+                Ok(result.into_records().into())
+            })
+        }
+    })
+    .with_cancellation(cancel.clone());
+
+    let (mut records, metrics) = processor.process_stream(candidates.clone().into_iter()).await?;
+
+    match found_results {
+        Some(found_results) if !metrics.cancelled => {
+            let results = std::mem::take(&mut *found_results.lock().unwrap());
+            let wildcard_filter = WildcardFilter::new(None, Arc::new(ResolverPool::new(&dns_options)?), 10);
+            write_ranked_results(&mut output, results, &wildcard_filter, &config).await?;
+        }
+        _ => {
+            for record in &records {
+                output.write_record(record, false)?;
+            }
+        }
+    }
+    output.flush()?;
+
+    if args.tls_san_expand && !metrics.cancelled {
+        let expanded = resolve_tls_sans(&client, &records, &args.domain, &record_types, &config).await?;
+        for record in &expanded {
+            output.write_record(record, false)?;
+        }
+        output.flush()?;
+        records.extend(expanded);
+    }
+
+    if args.hsts_check && !metrics.cancelled {
+        write_hsts_status(&mut output, &records, &config).await?;
+    }
+
+    if metrics.cancelled {
+        let Some(last_domain) = candidates[..metrics.processed_items].last() else {
+            eprintln!("Interrupted before any candidate completed, no checkpoint saved");
+            std::process::exit(130);
+        };
+        let mut found = found_before;
+        for record in &records {
+            let domain = record.domain.trim_end_matches('.');
+            if !found.iter().any(|f| f == domain) {
+                found.push(domain.to_string());
+            }
+        }
+        let checkpoint = BruteforceCheckpoint::default_path(&words, &checkpoint_key)?;
+        BruteforceCheckpoint::save(&checkpoint, last_domain, &found)?;
+        eprintln!("Interrupted, saved progress ({} subdomains found) to {}", found.len(), checkpoint.display());
+        std::process::exit(130);
+    }
+
+    // The run this one continued is complete now
+    if let Some(path) = resumed_from {
+        if let Err(e) = std::fs::remove_file(&path) {
+            eprintln!("Warning: failed to remove checkpoint {}: {}", path.display(), e);
+        }
+    }
+
+    if !config.silent {
+        eprintln!("Found {} records across {} candidates", records.len(), metrics.processed_items);
+    }
+
+    Ok(())
+}
+
+/// Found subdomains by descending confidence, each with its score and tags
+async fn write_ranked_results(
+    output: &mut OutputWriter,
+    mut results: Vec<BruteforceResult>,
+    wildcard_filter: &WildcardFilter,
+    config: &Config,
+) -> Result<()> {
+    if !config.silent {
+        eprintln!("Scoring {} found subdomains", results.len());
+    }
+    Bruteforcer::score_results(&mut results, Some(wildcard_filter)).await;
+    Bruteforcer::sort_by_confidence(&mut results);
+
+    for result in &results {
+        if config.json_output {
+            output.write_line(&serde_json::to_string(result)?)?;
+            continue;
+        }
+
+        let tags = if result.tags.is_empty() { String::new() } else { format!(" ({})", result.tags.join(", ")) };
+        output.write_line(&format!("{} [confidence {:.2}]{}", result.subdomain, result.confidence, tags))?;
+        for record in result.records.values().flatten() {
+            output.write_record(record, false)?;
         }
+    }
+
+    Ok(())
+}
 
-        // Simple test: just try www.{domain}
-        let test_subdomain = format!("www.{}", domain);
-
-        match client.query(&test_subdomain, record_type).await {
-            Ok(records) => {
-                if !records.is_empty() {
-                    if !config.silent {
-                        eprintln!("Found subdomain: {} with {} records", test_subdomain, records.len());
-                    }
-                    for record in records {
-                        output.write_record(&record, false)?;
-                    }
-                } else {
-                    if !config.silent {
-                        eprintln!("No records found for: {}", test_subdomain);
-                    }
+/// Resolve names under the target domains found in the certificates of the addresses resolved so far
+async fn resolve_tls_sans(
+    client: &DnsxClient,
+    records: &[DnsRecord],
+    domains: &[String],
+    record_types: &[RecordType],
+    config: &Config,
+) -> Result<Vec<DnsRecord>> {
+    let ips: BTreeSet<IpAddr> = records
+        .iter()
+        .filter_map(|r| match r.value {
+            RecordValue::Ip(ip) => Some(ip),
+            _ => None,
+        })
+        .collect();
+    let ips: Vec<IpAddr> = ips.into_iter().collect();
+    if !config.silent {
+        eprintln!("🔐 Reading TLS certificate SANs from {} addresses", ips.len());
+    }
+
+    let known: HashSet<String> = records.iter().map(|r| r.domain.trim_end_matches('.').to_lowercase()).collect();
+    let suffixes: Vec<String> = domains.iter().map(|d| d.trim().trim_end_matches('.').to_lowercase()).collect();
+    let in_scope = |name: &str| suffixes.iter().any(|d| name == d || name.ends_with(&format!(".{}", d)));
+    let names: Vec<String> = TlsSanEnumerator::new()?
+        .expand(&ips, 443)
+        .await
+        .into_iter()
+        .filter(|name| !known.contains(name) && in_scope(name))
+        .collect();
+
+    if !config.silent {
+        eprintln!("🔐 {} new names from TLS certificates, resolving them", names.len());
+    }
+    let lookups = names.into_iter().map(|name| Bruteforcer::resolve_candidate(client, name, record_types, None));
+    let results = join_all(lookups).await;
+    Ok(results.into_iter().flat_map(BruteforceResult::into_records).collect())
+}
+
+/// Domains sharing an address with any of the target domains
+async fn discover_vhosts(client: &Arc<DnsxClient>, domains: &[String], config: &Config) -> Result<BTreeSet<String>> {
+    let mut ips = BTreeSet::new();
+    for domain in domains {
+        ips.extend(client.lookup(domain).await?);
+    }
+    if !config.silent {
+        eprintln!("🏘️  Looking for virtual hosts co-hosted on {} addresses", ips.len());
+    }
+
+    let enumerator = VhostEnumerator::new(Arc::clone(client))?;
+    let mut vhosts = BTreeSet::new();
+    for ip in ips {
+        match enumerator.enumerate_by_ip(ip, 443).await {
+            Ok(names) => vhosts.extend(names),
+            Err(e) => {
+                if !config.silent {
+                    eprintln!("Virtual host discovery for {} failed: {}", ip, e);
                 }
             }
+        }
+    }
+
+    // The targets themselves are already enumerated
+    for domain in domains {
+        vhosts.remove(&domain.trim().trim_end_matches('.').to_lowercase());
+    }
+    if !config.silent {
+        eprintln!("🏘️  Adding {} co-hosted domains to the candidates", vhosts.len());
+    }
+    Ok(vhosts)
+}
+
+/// One line per found subdomain with its HSTS preload status
+async fn write_hsts_status(output: &mut OutputWriter, records: &[DnsRecord], config: &Config) -> Result<()> {
+    let checker = HstsChecker::new()?;
+    let subdomains: BTreeSet<&str> = records.iter().map(|r| r.domain.trim_end_matches('.')).collect();
+
+    if !config.silent {
+        eprintln!("Checking HSTS preload status for {} subdomains", subdomains.len());
+    }
+
+    for subdomain in subdomains {
+        let preloaded = match checker.is_preloaded(subdomain).await {
+            Ok(preloaded) => Some(preloaded),
             Err(e) => {
                 if !config.silent {
-                    eprintln!("Error querying {}: {}", test_subdomain, e);
+                    eprintln!("HSTS preload check for {} failed: {}", subdomain, e);
                 }
+                None
             }
+        };
+
+        if config.json_output {
+            output.write_line(&serde_json::json!({ "domain": subdomain, "hsts_preloaded": preloaded }).to_string())?;
+        } else {
+            let status = match preloaded {
+                Some(true) => "HSTS preloaded",
+                Some(false) => "not preloaded",
+                None => "HSTS status unknown",
+            };
+            output.write_line(&format!("{} [{}]", subdomain, status))?;
         }
     }
 
-    output.flush()?;
-    eprintln!("DEBUG: Finished bruteforce command");
     Ok(())
 }
+
@@ -0,0 +1,84 @@
+//! IP ownership clustering command implementation
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use rdnsx_core::{read_records, IpCluster, IpClusterer};
+
+use crate::cli::Config;
+
+/// Domains listed per cluster in the summary table
+const DOMAIN_SAMPLES: usize = 3;
+
+/// Cluster command arguments
+#[derive(Args)]
+pub struct ClusterArgs {
+    /// Saved query results (JSON array or JSONL of records)
+    #[arg(short, long)]
+    pub input: PathBuf,
+}
+
+pub async fn run(args: ClusterArgs, config: Config) -> Result<()> {
+    let records = read_records(&args.input)?;
+    let clusters = IpClusterer::cluster(&records);
+
+    if !config.silent {
+        eprintln!("🧩 Grouped the addresses in {} records into {} clusters", records.len(), clusters.len());
+    }
+
+    if config.json_output {
+        for cluster in &clusters {
+            println!("{}", serde_json::to_string(cluster)?);
+        }
+    } else {
+        print_table(&clusters);
+    }
+
+    Ok(())
+}
+
+fn print_table(clusters: &[IpCluster]) {
+    let rows: Vec<[String; 4]> = clusters
+        .iter()
+        .map(|cluster| {
+            let mut domains = cluster.domains.iter().take(DOMAIN_SAMPLES).cloned().collect::<Vec<_>>().join(", ");
+            if cluster.domains.len() > DOMAIN_SAMPLES {
+                domains.push_str(&format!(" (+{} more)", cluster.domains.len() - DOMAIN_SAMPLES));
+            }
+            [
+                cluster.network.to_string(),
+                cluster.owner_hint.clone().unwrap_or_else(|| "-".to_string()),
+                cluster.representative_ip.to_string(),
+                domains,
+            ]
+        })
+        .collect();
+
+    let headings = ["Network", "Owner", "Representative"];
+    let widths: Vec<usize> = (0..headings.len())
+        .map(|i| rows.iter().map(|row| row[i].chars().count()).chain([headings[i].len()]).max().unwrap_or(0))
+        .collect();
+
+    println!(
+        "{:<w0$}  {:<w1$}  {:<w2$}  Domains",
+        headings[0],
+        headings[1],
+        headings[2],
+        w0 = widths[0],
+        w1 = widths[1],
+        w2 = widths[2]
+    );
+    for row in &rows {
+        println!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2]
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Record snapshot diff command implementation
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use rdnsx_core::{read_records, DnsRecord, RecordSetDiff, WebhookNotifier};
+
+use crate::cli::Config;
+
+/// Diff command arguments
+#[derive(Args)]
+pub struct DiffArgs {
+    /// Earlier snapshot (JSON array or JSONL of records)
+    #[arg(long)]
+    pub before: PathBuf,
+
+    /// Later snapshot (JSON array or JSONL of records)
+    #[arg(long)]
+    pub after: PathBuf,
+
+    /// Diff output format
+    #[arg(long, value_enum, default_value = "json")]
+    pub format: DiffFormat,
+
+    /// Send each change to the webhook in [notifications.webhooks]
+    #[arg(long)]
+    pub notify: bool,
+
+    /// Send each change to this webhook instead of the configured one (implies --notify)
+    #[arg(long, value_name = "URL")]
+    pub webhook_url: Option<String>,
+
+    /// Sign webhook bodies with this HMAC-SHA256 key instead of the configured secret
+    #[arg(long, value_name = "SECRET")]
+    pub webhook_secret: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum DiffFormat {
+    /// Structured JSON with added, removed and modified records
+    Json,
+    /// One line per change (+ added, - removed, ~ modified)
+    Text,
+    /// change,domain,type,old_value,new_value,old_ttl,new_ttl
+    Csv,
+}
+
+pub async fn run(args: DiffArgs, config: Config) -> Result<()> {
+    let before = read_records(&args.before)?;
+    let after = read_records(&args.after)?;
+    let diff = RecordSetDiff::compute(&before, &after);
+
+    let rendered = match args.format {
+        DiffFormat::Json => format!("{}\n", serde_json::to_string_pretty(&diff)?),
+        DiffFormat::Text => render_text(&diff),
+        DiffFormat::Csv => render_csv(&diff),
+    };
+
+    match &config.output_file {
+        Some(path) => std::fs::File::create(path)?.write_all(rendered.as_bytes())?,
+        None => print!("{}", rendered),
+    }
+
+    if !config.silent {
+        eprintln!(
+            "📊 {} added, {} removed, {} modified ({} -> {} records)",
+            diff.added.len(),
+            diff.removed.len(),
+            diff.modified.len(),
+            before.len(),
+            after.len()
+        );
+    }
+
+    if args.notify || args.webhook_url.is_some() {
+        notify_changes(&args, &diff, &config).await?;
+    }
+
+    Ok(())
+}
+
+/// POST each change to the webhook, reporting every failed delivery before giving up
+async fn notify_changes(args: &DiffArgs, diff: &RecordSetDiff, config: &Config) -> Result<()> {
+    let mut webhook = config.core_config.notifications.webhooks.clone();
+    if let Some(url) = &args.webhook_url {
+        webhook.url = url.clone();
+    }
+    if let Some(secret) = &args.webhook_secret {
+        webhook.secret = secret.clone();
+    }
+    let Some(notifier) = WebhookNotifier::from_config(&webhook) else {
+        anyhow::bail!("--notify needs a webhook URL in [notifications.webhooks] or --webhook-url");
+    };
+
+    let changes = diff.changes();
+    let mut failed = 0;
+    for change in &changes {
+        if let Err(e) = notifier.notify(change).await {
+            eprintln!("Warning: Failed to notify {} of change to {}: {}", notifier.url(), change.domain(), e.report());
+            failed += 1;
+        }
+    }
+
+    if !config.silent {
+        eprintln!("🔔 Sent {} of {} change(s) to {}", changes.len() - failed, changes.len(), notifier.url());
+    }
+    if failed > 0 {
+        anyhow::bail!("{} change notification(s) could not be delivered", failed);
+    }
+    Ok(())
+}
+
+fn render_text(diff: &RecordSetDiff) -> String {
+    let mut out = String::new();
+    for record in &diff.added {
+        out.push_str(&format!("+ {} {} {}\n", record.domain, record.record_type, record.value.to_string()));
+    }
+    for record in &diff.removed {
+        out.push_str(&format!("- {} {} {}\n", record.domain, record.record_type, record.value.to_string()));
+    }
+    for change in &diff.modified {
+        out.push_str(&format!(
+            "~ {} {} {} -> {}\n",
+            change.after.domain,
+            change.after.record_type,
+            change.before.value.to_string(),
+            change.after.value.to_string()
+        ));
+    }
+    out
+}
+
+fn render_csv(diff: &RecordSetDiff) -> String {
+    let mut out = String::from("change,domain,type,old_value,new_value,old_ttl,new_ttl\n");
+    let row = |change: &str, old: Option<&DnsRecord>, new: Option<&DnsRecord>| {
+        let record = new.or(old).expect("change has at least one side");
+        [
+            change.to_string(),
+            record.domain.clone(),
+            record.record_type.to_string(),
+            old.map(|r| r.value.to_string()).unwrap_or_default(),
+            new.map(|r| r.value.to_string()).unwrap_or_default(),
+            old.map(|r| r.ttl.to_string()).unwrap_or_default(),
+            new.map(|r| r.ttl.to_string()).unwrap_or_default(),
+        ]
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+            + "\n"
+    };
+
+    for record in &diff.added {
+        out.push_str(&row("added", None, Some(record)));
+    }
+    for record in &diff.removed {
+        out.push_str(&row("removed", Some(record), None));
+    }
+    for change in &diff.modified {
+        out.push_str(&row("modified", Some(&change.before), Some(&change.after)));
+    }
+    out
+}
+
+/// Quote a CSV field when it contains separators, quotes or newlines
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,67 @@
+//! DNSBL reputation check command implementation
+
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use clap::Args;
+use rdnsx_core::input::{read_domains, InputSource};
+use rdnsx_core::{DnsblChecker, DnsblResult, ResolverPool};
+
+use crate::cli::Config;
+
+/// DNSBL command arguments
+#[derive(Args)]
+pub struct DnsblArgs {
+    /// Address to look up (repeatable)
+    #[arg(long, required = true)]
+    pub ip: Vec<IpAddr>,
+
+    /// File of DNSBL zones to check, one per line (default: 10 common public lists)
+    #[arg(long)]
+    pub list_file: Option<String>,
+}
+
+pub async fn run(args: DnsblArgs, config: Config) -> Result<()> {
+    let dns_options = config.dns_options();
+
+    let lists = match &args.list_file {
+        Some(path) => read_domains(InputSource::File(path.clone()))?
+            .into_iter()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.starts_with('#'))
+            .collect(),
+        None => DnsblChecker::default_lists(),
+    };
+    if lists.is_empty() {
+        anyhow::bail!("No DNSBL zones to check");
+    }
+
+    if !config.silent {
+        eprintln!("🚫 Checking {} address(es) against {} DNSBLs", args.ip.len(), lists.len());
+    }
+
+    let checker = DnsblChecker::new(Arc::new(ResolverPool::new(&dns_options)?));
+    for ip in &args.ip {
+        let result = checker.check_ip(*ip, &lists).await?;
+        if config.json_output {
+            println!("{}", serde_json::to_string(&result)?);
+        } else {
+            print_result(&result);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_result(result: &DnsblResult) {
+    if result.is_listed() {
+        println!("⚠️  {} is listed on {} DNSBL(s)", result.ip, result.listed_on.len());
+        for listing in &result.listed_on {
+            println!("  • {} ({}): {}", listing.list_name, listing.return_code, listing.meaning);
+        }
+    } else {
+        println!("✅ {} is not listed", result.ip);
+    }
+    println!("  Clear on {} list(s): {}", result.clear_on.len(), result.clear_on.join(", "));
+}
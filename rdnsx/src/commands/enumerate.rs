@@ -1,10 +1,15 @@
 //! DNS enumeration command implementation
 
+use std::collections::BTreeMap;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use anyhow::Result;
 use clap::Args;
-use rdnsx_core::{DnsEnumerator, ResolverPool, config::DnsxOptions};
+use futures::StreamExt;
+use regex::Regex;
+use rdnsx_core::{DmarcAuthStatus, DnsEnumerator, DnsRecord, DnsxClient, EmailSecurityScorer, KeyStrength, RecordType, ResolverPool, ZoneDiffer, ZoneTransferResult, SpfOutlierReason, WildcardBypassAttempt, WildcardBypassTester, config::DnsxOptions, read_records};
+use rdnsx_core::input::{read_domains, InputSource};
 
 use crate::cli::Config;
 
@@ -16,10 +21,14 @@ pub struct EnumerateArgs {
     pub technique: EnumerationTechnique,
 
     /// Target domain or ASN for enumeration (use ASN format like AS15169 for ASN enumeration)
-    #[arg(short, long)]
-    pub target: String,
+    #[arg(short, long, required_unless_present_any = ["target_list", "records"])]
+    pub target: Option<String>,
 
-    /// Custom nameservers for enumeration (comma-separated)
+    /// File of domains (one per line), used by SpfConsistency, EmailSecurity and TakeoverCheck
+    #[arg(long, visible_alias = "list", value_name = "FILE")]
+    pub target_list: Option<String>,
+
+    /// Custom nameservers for enumeration (comma-separated); also the servers zone transfers are requested from
     #[arg(long)]
     pub nameservers: Option<String>,
 
@@ -30,6 +39,38 @@ pub struct EnumerateArgs {
     /// Timeout for enumeration operations (seconds)
     #[arg(long, default_value = "30")]
     pub timeout: u64,
+
+    /// Timeout for HTTP requests to passive sources such as crt.sh (seconds)
+    #[arg(long, default_value = "60")]
+    pub http_timeout: u64,
+
+    /// Resolve the subdomains found in certificate transparency logs
+    #[arg(long)]
+    pub resolve: bool,
+
+    /// Previous zone transfer output (JSON) to diff the new results against
+    #[arg(long, value_name = "FILE")]
+    pub diff_against: Option<std::path::PathBuf>,
+
+    /// Only keep zone transfer records of these types (comma-separated, e.g. A,AAAA)
+    #[arg(long, value_delimiter = ',')]
+    pub zone_filter_type: Vec<String>,
+
+    /// Only keep zone transfer records whose name matches this regex (e.g. '.*dev.*')
+    #[arg(long)]
+    pub zone_filter_pattern: Option<String>,
+
+    /// JSON file of extra CDN provider patterns (provider, patterns, asns)
+    #[arg(long, value_name = "FILE")]
+    pub cdn_patterns: Option<std::path::PathBuf>,
+
+    /// Records from an earlier run (JSON, JSONL or zone file) to check for subdomain takeovers
+    #[arg(long, value_name = "FILE")]
+    pub records: Option<std::path::PathBuf>,
+
+    /// JSON file of extra takeover fingerprints (service, cname_patterns, http_signature, severity)
+    #[arg(long, value_name = "FILE")]
+    pub takeover_fingerprints: Option<std::path::PathBuf>,
 }
 
 /// Enumeration techniques available
@@ -55,6 +96,18 @@ pub enum EnumerationTechnique {
     PassiveDns,
     /// Enumerate ASN information and associated IP ranges
     AsnEnumeration,
+    /// Look for HINFO records disclosing host hardware and OS
+    Hinfo,
+    /// Compare SPF policies across a list of domains
+    SpfConsistency,
+    /// Discover services from HTTPS/TLSA records and cloud verification TXT tokens
+    ServiceDiscovery,
+    /// Validate the HTTPS endpoint's TLSA records against its certificate chain (DANE)
+    DaneTls,
+    /// Find subdomains on certificates in certificate transparency logs (crt.sh)
+    CtLogs,
+    /// Find CNAMEs pointing at deprovisioned services that anyone could claim
+    TakeoverCheck,
     /// Comprehensive enumeration (all techniques)
     Comprehensive,
 }
@@ -62,11 +115,10 @@ pub enum EnumerationTechnique {
 pub async fn run(args: EnumerateArgs, config: Config) -> Result<()> {
     // Create DNS options with custom settings
     let mut dns_options = DnsxOptions {
-        resolvers: config.core_config.resolvers.servers.clone(),
         timeout: std::time::Duration::from_secs(args.timeout),
-        retries: config.core_config.resolvers.retries,
         concurrency: args.concurrent,
-        rate_limit: config.core_config.performance.rate_limit,
+        http_timeout: std::time::Duration::from_secs(args.http_timeout),
+        ..config.dns_options()
     };
 
     // Override nameservers if specified
@@ -76,61 +128,189 @@ pub async fn run(args: EnumerateArgs, config: Config) -> Result<()> {
             .map(|s| s.trim().to_string())
             .collect();
     }
+    // Zone transfers go to the --nameservers given, else to the domain's own NS records
+    let transfer_servers = if args.nameservers.is_some() { dns_options.resolvers.clone() } else { Vec::new() };
 
     // Create resolver pool and enumerator
     let resolver_pool = Arc::new(ResolverPool::new(&dns_options)?);
-    let enumerator = DnsEnumerator::new(resolver_pool.clone());
+    let mut enumerator = DnsEnumerator::new(resolver_pool.clone())
+        .with_options(&dns_options)
+        .with_ct_resolution(args.resolve);
+    if let Some(path) = &args.cdn_patterns {
+        enumerator = enumerator.with_cdn_patterns(path)?;
+    }
+    if let Some(path) = &args.takeover_fingerprints {
+        enumerator = enumerator.with_takeover_fingerprints(path)?;
+    }
+
+    let target = args.target.clone().unwrap_or_default();
+    if args.target_list.is_some()
+        && !matches!(
+            args.technique,
+            EnumerationTechnique::SpfConsistency
+                | EnumerationTechnique::EmailSecurity
+                | EnumerationTechnique::TakeoverCheck
+        )
+    {
+        anyhow::bail!(
+            "--target-list is only supported by the SpfConsistency, EmailSecurity and TakeoverCheck techniques"
+        );
+    }
+    if args.records.is_some() && !matches!(args.technique, EnumerationTechnique::TakeoverCheck) {
+        anyhow::bail!("--records is only supported by the TakeoverCheck technique");
+    }
 
     match args.technique {
         EnumerationTechnique::ZoneTransfer => {
-            perform_zone_transfer(&enumerator, &args.target, &dns_options.resolvers).await?;
-        }
-        EnumerationTechnique::EmailSecurity => {
-            perform_email_security_enumeration(&enumerator, &args.target).await?;
+            let filter = ZoneFilter::from_args(&args)?;
+            let result = perform_zone_transfer(&enumerator, &target, &transfer_servers, &filter).await?;
+            let records = result.as_ref().map_or(&[][..], |result| result.records.as_slice());
+
+            if let Some(previous) = &args.diff_against {
+                print_zone_diff(previous, records)?;
+            }
+
+            // Save the snapshot so it can be diffed against on the next run
+            if let Some(output_file) = &config.output_file {
+                match &result {
+                    Some(result) if output_file.ends_with(".zone") => {
+                        let mut file = std::io::BufWriter::new(std::fs::File::create(output_file)?);
+                        result.to_zone_file(&mut file)?;
+                    }
+                    _ => std::fs::write(output_file, serde_json::to_string_pretty(records)?)?,
+                }
+            }
         }
+        EnumerationTechnique::EmailSecurity => match &args.target_list {
+            Some(path) => {
+                let domains = read_domains(InputSource::File(path.clone()))?;
+                perform_bulk_email_security(&enumerator, &domains, args.concurrent, config.silent).await?;
+            }
+            None => perform_email_security_enumeration(&enumerator, &target).await?,
+        },
         EnumerationTechnique::CdnDetection => {
-            perform_cdn_detection(&enumerator, &args.target).await?;
+            perform_cdn_detection(&enumerator, &target).await?;
         }
         EnumerationTechnique::Ipv6Enumeration => {
-            perform_ipv6_enumeration(&enumerator, &args.target).await?;
+            perform_ipv6_enumeration(&enumerator, &target).await?;
         }
         EnumerationTechnique::ServerFingerprint => {
-            perform_server_fingerprinting(&enumerator, &args.target, &dns_options.resolvers).await?;
+            perform_server_fingerprinting(&enumerator, &target, &dns_options.resolvers).await?;
         }
         EnumerationTechnique::DnssecEnumeration => {
-            perform_dnssec_enumeration(&enumerator, &args.target).await?;
+            perform_dnssec_enumeration(&enumerator, &target).await?;
         }
         EnumerationTechnique::DnssecZoneWalking => {
-            perform_dnssec_zone_walking(&enumerator, &args.target).await?;
+            perform_dnssec_zone_walking(&enumerator, &target).await?;
         }
         EnumerationTechnique::WildcardAnalysis => {
-            perform_wildcard_analysis(&enumerator, &args.target).await?;
+            perform_wildcard_analysis(&enumerator, &target).await?;
         }
         EnumerationTechnique::PassiveDns => {
-            perform_passive_dns_enumeration(&enumerator, &args.target).await?;
+            perform_passive_dns_enumeration(&enumerator, &target).await?;
         }
         EnumerationTechnique::AsnEnumeration => {
-            perform_asn_enumeration(&enumerator, &args.target).await?;
+            perform_asn_enumeration(&enumerator, &target).await?;
+        }
+        EnumerationTechnique::Hinfo => {
+            perform_hinfo_scan(&enumerator, &target).await?;
+        }
+        EnumerationTechnique::ServiceDiscovery => {
+            perform_service_discovery(&enumerator, &target).await?;
+        }
+        EnumerationTechnique::DaneTls => {
+            perform_dane_validation(&enumerator, &target, config.json_output).await?;
+        }
+        EnumerationTechnique::CtLogs => {
+            perform_ct_enumeration(&enumerator, &target, config.json_output).await?;
+        }
+        EnumerationTechnique::TakeoverCheck => {
+            let records = match &args.records {
+                Some(path) => read_records(path)?,
+                None => {
+                    let domains = match &args.target_list {
+                        Some(path) => read_domains(InputSource::File(path.clone()))?,
+                        None => vec![target.clone()],
+                    };
+                    let client = DnsxClient::with_options(dns_options.clone())?;
+                    query_cnames(&client, &domains, args.concurrent).await
+                }
+            };
+            perform_takeover_check(&enumerator, &records, config.json_output).await?;
+        }
+        EnumerationTechnique::SpfConsistency => {
+            let domains = match &args.target_list {
+                Some(path) => read_domains(InputSource::File(path.clone()))?,
+                None => target.split(',').map(|d| d.trim().to_string()).collect(),
+            };
+            perform_spf_consistency(&enumerator, &domains).await?;
         }
         EnumerationTechnique::Comprehensive => {
-            perform_comprehensive_enumeration(&enumerator, &args.target, &dns_options.resolvers).await?;
+            perform_comprehensive_enumeration(&enumerator, &target, &dns_options.resolvers, &transfer_servers).await?;
         }
     }
 
     Ok(())
 }
 
+/// Record type and name filters applied to zone transfer results
+#[derive(Default)]
+struct ZoneFilter {
+    types: Vec<RecordType>,
+    pattern: Option<Regex>,
+}
+
+impl ZoneFilter {
+    fn from_args(args: &EnumerateArgs) -> Result<Self> {
+        let types = args
+            .zone_filter_type
+            .iter()
+            .map(|name| {
+                RecordType::all()
+                    .into_iter()
+                    .find(|t| t.to_string().eq_ignore_ascii_case(name.trim()))
+                    .ok_or_else(|| anyhow::anyhow!("Unknown record type in --zone-filter-type: {}", name))
+            })
+            .collect::<Result<_>>()?;
+        let pattern = args
+            .zone_filter_pattern
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Invalid --zone-filter-pattern: {}", e))?;
+        Ok(Self { types, pattern })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.types.is_empty() && self.pattern.is_none()
+    }
+
+    fn apply(&self, result: &mut ZoneTransferResult) {
+        if !self.types.is_empty() {
+            result.records = result.filter_by_type(&self.types).into_iter().cloned().collect();
+        }
+        if let Some(pattern) = &self.pattern {
+            result.records = result.filter_by_subdomain_pattern(pattern).into_iter().cloned().collect();
+        }
+    }
+}
+
 async fn perform_zone_transfer(
     enumerator: &DnsEnumerator,
     domain: &str,
     nameservers: &[String],
-) -> Result<()> {
+    filter: &ZoneFilter,
+) -> Result<Option<ZoneTransferResult>> {
     println!("🔄 Attempting DNS zone transfer for: {}", domain);
-    println!("📡 Using nameservers: {:?}", nameservers);
+    if nameservers.is_empty() {
+        println!("📡 Using the authoritative nameservers of {}", domain);
+    } else {
+        println!("📡 Using nameservers: {:?}", nameservers);
+    }
     println!();
 
     match enumerator.zone_transfer(domain, nameservers).await {
-        Ok(result) => {
+        Ok(mut result) => {
             println!("📊 Zone Transfer Results for {}", result.domain);
             println!("{}", "=".repeat(50));
 
@@ -149,6 +329,10 @@ async fn perform_zone_transfer(
             }
 
             println!("\n📋 Discovered records: {}", result.records.len());
+            if !filter.is_empty() {
+                filter.apply(&mut result);
+                println!("🔎 Matching the zone filters: {}", result.records.len());
+            }
 
             if !result.records.is_empty() {
                 println!("\n🔍 Record Summary:");
@@ -171,10 +355,88 @@ async fn perform_zone_transfer(
                     println!("  ... and {} more records", result.records.len() - 20);
                 }
             }
+
+            Ok(Some(result))
         }
         Err(e) => {
             eprintln!("❌ Zone transfer failed: {}", e);
+            Ok(None)
+        }
+    }
+}
+
+/// Load a previous zone snapshot (JSON array or one record per line) and print what changed
+fn print_zone_diff(previous: &std::path::Path, records: &[DnsRecord]) -> Result<()> {
+    let old_records = read_records(previous)?;
+
+    let diff = ZoneDiffer::diff(&old_records, records);
+
+    println!("\n🔀 Zone Changes since {}", previous.display());
+    println!("{}", "=".repeat(50));
+
+    if diff.is_empty() {
+        println!("✅ No changes detected");
+        return Ok(());
+    }
+
+    for record in &diff.added {
+        println!("  + {} {} {}", record.domain, record.record_type, record.value.to_string());
+    }
+    for record in &diff.removed {
+        println!("  - {} {} {}", record.domain, record.record_type, record.value.to_string());
+    }
+    for (old, new) in &diff.ttl_changed {
+        println!("  ~ {} {} {} (TTL {} -> {})", new.domain, new.record_type, new.value.to_string(), old.ttl, new.ttl);
+    }
+
+    println!("\n📊 {} added, {} removed, {} TTL changes", diff.added.len(), diff.removed.len(), diff.ttl_changed.len());
+
+    Ok(())
+}
+
+/// One JSON line per domain as it completes, then the DMARC policy distribution on stderr
+async fn perform_bulk_email_security(
+    enumerator: &DnsEnumerator,
+    domains: &[String],
+    concurrency: usize,
+    silent: bool,
+) -> Result<()> {
+    if !silent {
+        eprintln!("🔐 Enumerating email security for {} domains ({} concurrent)", domains.len(), concurrency);
+    }
+
+    let scorer = EmailSecurityScorer::new();
+    let mut policies: BTreeMap<String, usize> = BTreeMap::new();
+    let mut grades: BTreeMap<char, usize> = BTreeMap::new();
+    let mut results = enumerator.email_security_stream(domains, concurrency);
+    while let Some(result) = results.next().await {
+        let result = result?;
+        let score = scorer.score(&result);
+        // Top-level score and grade so the JSONL can be sorted with e.g. `jq -s 'sort_by(.score)'`
+        let mut line = serde_json::to_value(&result)?;
+        line["score"] = score.score.into();
+        line["grade"] = score.grade.to_string().into();
+        println!("{}", line);
+
+        let policy = result.dmarc_policy().unwrap_or_else(|| "missing".to_string());
+        *policies.entry(policy.to_lowercase()).or_default() += 1;
+        *grades.entry(score.grade).or_default() += 1;
+    }
+
+    if !silent {
+        let total: usize = policies.values().sum();
+        eprintln!("\n📊 DMARC policy distribution ({} domains)", total);
+        eprintln!("+------------+---------+---------+");
+        eprintln!("| {:<10} | {:>7} | {:>7} |", "Policy", "Domains", "Share");
+        eprintln!("+------------+---------+---------+");
+        for (policy, count) in &policies {
+            let share = if total > 0 { *count as f64 * 100.0 / total as f64 } else { 0.0 };
+            eprintln!("| {:<10} | {:>7} | {:>6.1}% |", policy, count, share);
         }
+        eprintln!("+------------+---------+---------+");
+
+        let grades: Vec<String> = grades.iter().map(|(grade, count)| format!("{}: {}", grade, count)).collect();
+        eprintln!("🏅 Grades: {}", grades.join(", "));
     }
 
     Ok(())
@@ -192,6 +454,9 @@ async fn perform_email_security_enumeration(
             println!("📧 Email Security Analysis for {}", result.domain);
             println!("{}", "=".repeat(50));
 
+            let score = EmailSecurityScorer::new().score(&result);
+            println!("\n🏅 Email Security Grade: {} ({}/100)", score.grade, score.score);
+
             if !result.spf_records.is_empty() {
                 println!("\n📋 SPF Records:");
                 for (i, spf) in result.spf_records.iter().enumerate() {
@@ -210,15 +475,57 @@ async fn perform_email_security_enumeration(
                 println!("\n❌ No DMARC record found");
             }
 
+            if let Some(inheritance) = &result.dmarc_inheritance {
+                match &inheritance.applicable_domain {
+                    Some(applicable) if !inheritance.is_direct => {
+                        println!("\n🧬 DMARC Inheritance:");
+                        println!("  Inherited from: {}", applicable);
+                        println!("  Effective policy: {}", inheritance.policy.as_deref().unwrap_or("none"));
+                        println!("  Checked: {}", inheritance.inheritance_chain.join(" -> "));
+                    }
+                    None if inheritance.inheritance_chain.len() > 1 => {
+                        println!("\n🧬 No DMARC record on any parent ({})", inheritance.inheritance_chain.join(" -> "));
+                    }
+                    _ => {}
+                }
+            }
+
+            if !result.rua_authorizations.is_empty() {
+                println!("\n📨 DMARC Report Destinations:");
+                for auth in &result.rua_authorizations {
+                    let status = match auth.status {
+                        DmarcAuthStatus::Authorized => "✅ authorized",
+                        DmarcAuthStatus::NotRequired => "✅ same organization",
+                        DmarcAuthStatus::Unauthorized => "❌ not authorized",
+                    };
+                    println!("  • {} ({})", auth.uri, status);
+                }
+            }
+
             if !result.dkim_selectors.is_empty() {
                 println!("\n🔑 DKIM Selectors:");
                 for dkim in &result.dkim_selectors {
                     println!("  • {}: {}", dkim.selector, dkim.record);
+                    let key = &dkim.key_strength;
+                    let icon = match key.strength {
+                        KeyStrength::Strong => "✅",
+                        KeyStrength::Weak => "⚠️ ",
+                        KeyStrength::Insecure | KeyStrength::Revoked | KeyStrength::Invalid => "❌",
+                    };
+                    match key.key_bits {
+                        Some(bits) => println!("    {} Key: {} {} bits ({})", icon, key.algorithm, bits, key.strength),
+                        None => println!("    {} Key: {} ({})", icon, key.algorithm, key.strength),
+                    }
                 }
             } else {
                 println!("\n❌ No DKIM selectors found");
             }
 
+            match &result.bimi_record {
+                Some(bimi) => println!("\n🖼️  BIMI Record:\n  {}", bimi),
+                None => println!("\n❌ No BIMI record found"),
+            }
+
             // Provide security recommendations
             println!("\n💡 Security Recommendations:");
 
@@ -226,13 +533,36 @@ async fn perform_email_security_enumeration(
                 println!("  • Add SPF record to prevent email spoofing");
             }
 
-            if result.dmarc_record.is_none() {
+            let inherits_dmarc = result
+                .dmarc_inheritance
+                .as_ref()
+                .is_some_and(|i| i.applicable_domain.is_some());
+            if result.dmarc_record.is_none() && !inherits_dmarc {
                 println!("  • Add DMARC record for email authentication");
             }
 
+            for auth in &result.rua_authorizations {
+                if auth.status == DmarcAuthStatus::Unauthorized {
+                    println!(
+                        "  • Ask {} to authorize reports via a _report._dmarc record, or drop {} from rua",
+                        auth.reporting_domain, auth.uri
+                    );
+                }
+            }
+
             if result.dkim_selectors.is_empty() {
                 println!("  • Configure DKIM for email signing");
             }
+
+            for dkim in &result.dkim_selectors {
+                match dkim.key_strength.strength {
+                    KeyStrength::Insecure | KeyStrength::Weak => {
+                        println!("  • Rotate DKIM selector '{}' to a 2048-bit RSA or Ed25519 key", dkim.selector)
+                    }
+                    KeyStrength::Invalid => println!("  • Fix the public key published for DKIM selector '{}'", dkim.selector),
+                    KeyStrength::Strong | KeyStrength::Revoked => {}
+                }
+            }
         }
         Err(e) => {
             eprintln!("❌ Email security enumeration failed: {}", e);
@@ -284,6 +614,70 @@ async fn perform_cdn_detection(
         }
     }
 
+    match enumerator.cloud_asset_discovery(domain).await {
+        Ok(assets) if assets.is_empty() => {
+            println!("\n☁️  No cloud assets found matching the domain name");
+        }
+        Ok(assets) => {
+            println!("\n☁️  Cloud Assets ({}):", assets.len());
+            for asset in &assets {
+                let access = if asset.is_accessible { "🔓 publicly accessible" } else { "🔒 not accessible" };
+                println!("  • {} [{} {}] {}", asset.domain, asset.cloud_provider, asset.service_type, access);
+            }
+        }
+        Err(e) => {
+            eprintln!("❌ Cloud asset discovery failed: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+async fn perform_dane_validation(
+    enumerator: &DnsEnumerator,
+    domain: &str,
+    json_output: bool,
+) -> Result<()> {
+    let result = enumerator.dane_tls_validation(domain).await?;
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    println!("🔏 DANE TLSA Validation for {}:443", result.domain);
+    println!("{}", "=".repeat(50));
+
+    println!("\n📜 Certificate Chain:");
+    for cert in &result.certificate_chain {
+        println!("  {}. {} (issuer: {})", cert.depth, cert.subject, cert.issuer);
+        println!("     SHA-256: {}", cert.sha256);
+        println!("     SPKI SHA-256: {}", cert.spki_sha256);
+    }
+    let pkix = if result.pkix_valid { "✅ trusted" } else { "❌ not trusted" };
+    println!("  Web PKI: {}", pkix);
+
+    if result.validation_results.is_empty() {
+        println!("\n❌ No TLSA records published at _443._tcp.{}", result.domain);
+        return Ok(());
+    }
+
+    println!("\n🔍 TLSA Records:");
+    for validation in &result.validation_results {
+        let record = &validation.record;
+        let icon = if validation.valid { "✅" } else { "❌" };
+        println!("  {} {} {} {} {}", icon, record.cert_usage, record.selector, record.matching_type, record.cert_data);
+        match validation.matched_depth {
+            Some(depth) => println!("     {} (certificate {} in chain)", validation.reason, depth),
+            None => println!("     {}", validation.reason),
+        }
+    }
+
+    if result.is_valid() {
+        println!("\n✅ DANE validation succeeded");
+    } else {
+        println!("\n❌ No TLSA record matches the served certificates");
+    }
+
     Ok(())
 }
 
@@ -337,6 +731,217 @@ async fn perform_ipv6_enumeration(
     Ok(())
 }
 
+async fn perform_hinfo_scan(
+    enumerator: &DnsEnumerator,
+    domain: &str,
+) -> Result<()> {
+    println!("🖥️  Scanning HINFO records for: {}", domain);
+    println!();
+
+    // HINFO is usually left behind on the apex or on individual hosts
+    let hosts: Vec<String> = std::iter::once(domain.to_string())
+        .chain(["www", "mail", "ns1", "ns2"].iter().map(|h| format!("{}.{}", h, domain)))
+        .collect();
+
+    match enumerator.hinfo_scan(&hosts).await {
+        Ok(findings) if findings.is_empty() => {
+            println!("✅ No HINFO records found");
+        }
+        Ok(findings) => {
+            println!("🖥️  HINFO Records for {}", domain);
+            println!("{}", "=".repeat(50));
+
+            for finding in &findings {
+                println!("  • {}: CPU \"{}\", OS \"{}\"", finding.domain, finding.cpu_string, finding.os_string);
+                if finding.security_risk {
+                    println!("    ⚠️  OS string discloses a specific version");
+                }
+            }
+
+            println!("\n💡 Recommendation: Remove HINFO records unless they are deliberately published");
+        }
+        Err(e) => {
+            eprintln!("❌ HINFO scan failed: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+async fn perform_ct_enumeration(enumerator: &DnsEnumerator, domain: &str, json_output: bool) -> Result<()> {
+    if !json_output {
+        println!("📜 Searching certificate transparency logs for: {}", domain);
+        println!();
+    }
+
+    let result = match enumerator.ct_enumeration(domain).await {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("❌ Certificate transparency search failed: {}", e);
+            return Ok(());
+        }
+    };
+
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    println!("📜 Certificate Transparency for {}", result.domain);
+    println!("{}", "=".repeat(50));
+    println!("  • Certificates: {}", result.certificates.len());
+    println!("  • Subdomains: {}", result.total_discovered);
+    if !result.resolved.is_empty() {
+        println!("  • Resolving: {}", result.resolved.len());
+    }
+
+    if !result.subdomains.is_empty() {
+        println!("\n🌐 Subdomains:");
+        for name in &result.subdomains {
+            match result.resolved.get(name) {
+                Some(ips) => {
+                    let ips: Vec<String> = ips.iter().map(|ip| ip.to_string()).collect();
+                    println!("  • {} → {}", name, ips.join(", "));
+                }
+                None => println!("  • {}", name),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// CNAME records of `domains`; names without one are skipped
+async fn query_cnames(client: &DnsxClient, domains: &[String], concurrent: usize) -> Vec<DnsRecord> {
+    futures::stream::iter(domains)
+        .map(|domain| async move { client.query(domain, RecordType::Cname).await.unwrap_or_default() })
+        .buffer_unordered(concurrent.max(1))
+        .flat_map(futures::stream::iter)
+        .collect()
+        .await
+}
+
+async fn perform_takeover_check(enumerator: &DnsEnumerator, records: &[DnsRecord], json_output: bool) -> Result<()> {
+    if !json_output {
+        println!("🎯 Checking {} records for subdomain takeovers", records.len());
+        println!();
+    }
+
+    let findings = match enumerator.check_takeover(records).await {
+        Ok(findings) => findings,
+        Err(e) => {
+            eprintln!("❌ Takeover check failed: {}", e);
+            return Ok(());
+        }
+    };
+
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+        return Ok(());
+    }
+
+    if findings.is_empty() {
+        println!("✅ No dangling CNAMEs found");
+        return Ok(());
+    }
+
+    println!("⚠️  Possible Subdomain Takeovers");
+    println!("{}", "=".repeat(50));
+    for finding in &findings {
+        println!(
+            "  • {} [{}] {} ({:.0}% confidence)",
+            finding.domain,
+            finding.severity,
+            finding.service,
+            finding.confidence * 100.0
+        );
+        println!("    {}", finding.cname_chain.join(" → "));
+    }
+
+    println!("\n💡 Recommendation: Remove the CNAME records or claim the names at the services they point to");
+    Ok(())
+}
+
+async fn perform_service_discovery(
+    enumerator: &DnsEnumerator,
+    domain: &str,
+) -> Result<()> {
+    println!("🧭 Discovering DNS-advertised services for: {}", domain);
+    println!();
+
+    match enumerator.service_discovery(domain).await {
+        Ok(map) => {
+            println!("🧭 Service Map for {}", domain);
+            println!("{}", "=".repeat(50));
+
+            if map.detected_services.is_empty() {
+                println!("\n❌ No HTTPS or TLSA records found");
+            } else {
+                println!("\n🔌 Detected Services:");
+                for entry in &map.detected_services {
+                    println!("  • {} ({}/{:?}) {}: {}", entry.service, entry.port, entry.record_type, entry.name, entry.value);
+                }
+            }
+
+            if !map.cloud_verifications.is_empty() {
+                println!("\n☁️  Cloud Verification Tokens:");
+                for verification in &map.cloud_verifications {
+                    println!("  • {}: {} ({})", verification.provider, verification.token, verification.name);
+                }
+                println!("\n💡 Verification tokens reveal which providers the organization uses; remove stale ones");
+            }
+        }
+        Err(e) => {
+            eprintln!("❌ Service discovery failed: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+async fn perform_spf_consistency(
+    enumerator: &DnsEnumerator,
+    domains: &[String],
+) -> Result<()> {
+    println!("📧 Comparing SPF across {} domains", domains.len());
+    println!();
+
+    match enumerator.spf_consistency(domains).await {
+        Ok(report) => {
+            println!("📊 SPF Consistency Report");
+            println!("{}", "=".repeat(50));
+
+            for (i, group) in report.groups.iter().enumerate() {
+                println!("\n📋 Policy {} ({} domains):", i + 1, group.domains.len());
+                println!("  {}", group.representative_record);
+                for domain in &group.domains {
+                    println!("  • {}", domain);
+                }
+            }
+
+            if !report.outliers.is_empty() {
+                println!("\n⚠️  Outliers:");
+                for outlier in &report.outliers {
+                    println!("  • {}: {}", outlier.domain, outlier.reason);
+                    if let Some(record) = &outlier.record {
+                        println!("    {}", record);
+                    }
+                }
+            }
+
+            if report.is_consistent() {
+                println!("\n✅ All domains share the same SPF policy");
+            } else if report.outliers.iter().any(|o| o.reason == SpfOutlierReason::MissingSpf) {
+                println!("\n💡 Recommendation: Publish SPF (at least \"v=spf1 -all\") on every domain, including ones that send no mail");
+            }
+        }
+        Err(e) => {
+            eprintln!("❌ SPF consistency check failed: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
 async fn perform_server_fingerprinting(
     enumerator: &DnsEnumerator,
     domain: &str,
@@ -403,6 +1008,13 @@ async fn perform_dnssec_enumeration(
                 println!("  • NSEC records: {}", result.nsec_records);
                 println!("  • NSEC3 records: {}", result.nsec3_records);
 
+                if let Some(nsec3) = &result.nsec3_security {
+                    println!("\n🧂 NSEC3 Parameters:");
+                    println!("  • Iterations: {}", nsec3.current_iterations);
+                    println!("  • Salt: {}", if nsec3.salt_hex.is_empty() { "(none)" } else { nsec3.salt_hex.as_str() });
+                    println!("  • Assessment: {:?}", nsec3.recommendation);
+                }
+
                 if !result.security_issues.is_empty() {
                     println!("\n⚠️  Security Issues:");
                     for issue in &result.security_issues {
@@ -473,7 +1085,11 @@ async fn perform_wildcard_analysis(
     println!();
 
     match enumerator.wildcard_analysis(domain).await {
-        Ok(analysis) => {
+        Ok(mut analysis) => {
+            if analysis.has_wildcard {
+                add_host_header_attempts(&mut analysis).await;
+            }
+
             println!("🎭 Wildcard Analysis Results for {}", analysis.domain);
             println!("{}", "=".repeat(50));
 
@@ -507,6 +1123,9 @@ async fn perform_wildcard_analysis(
                     println!("  ✅ Successful bypass techniques:");
                     for attempt in &successful_bypasses {
                         println!("    • {}: {}", attempt.technique, attempt.test_domain);
+                        if let Some(evidence) = &attempt.evidence {
+                            println!("      {}", evidence);
+                        }
                     }
                 } else {
                     println!("  ❌ No bypass techniques successful");
@@ -540,6 +1159,20 @@ async fn perform_wildcard_analysis(
     Ok(())
 }
 
+/// Ask the wildcard addresses over HTTP whether the domain itself has a site of its own behind them
+async fn add_host_header_attempts(analysis: &mut rdnsx_core::WildcardAnalysis) {
+    let ips: Vec<IpAddr> = analysis.wildcard_ips.iter().filter_map(|ip| ip.parse().ok()).collect();
+    let results = match WildcardBypassTester::new() {
+        Ok(tester) => tester.test_host_header(&analysis.domain, &ips).await,
+        Err(e) => Err(e),
+    };
+
+    match results {
+        Ok(results) => analysis.bypass_attempts.extend(results.into_iter().map(WildcardBypassAttempt::from)),
+        Err(e) => eprintln!("⚠️  Host header bypass test failed: {}", e),
+    }
+}
+
 async fn perform_passive_dns_enumeration(
     enumerator: &DnsEnumerator,
     domain: &str,
@@ -659,6 +1292,13 @@ async fn perform_asn_enumeration(
             if result.total_ipv4_addresses > 1000000 {
                 println!("  • Large ASN - consider rate limiting for PTR enumeration");
             }
+            if !result.suggested_resolvers.is_empty() {
+                println!(
+                    "  • Geo-DNS targets in this network answer best from regional resolvers: --geo-resolvers {} ({})",
+                    result.asn,
+                    result.suggested_resolvers.join(", ")
+                );
+            }
         }
         Err(e) => {
             eprintln!("❌ ASN enumeration failed: {}", e);
@@ -677,13 +1317,14 @@ async fn perform_comprehensive_enumeration(
     enumerator: &DnsEnumerator,
     domain: &str,
     nameservers: &[String],
+    transfer_servers: &[String],
 ) -> Result<()> {
     println!("🔬 Performing comprehensive DNS enumeration for: {}", domain);
     println!("{}", "=".repeat(60));
     println!();
 
     // Zone Transfer
-    if let Err(e) = perform_zone_transfer(enumerator, domain, nameservers).await {
+    if let Err(e) = perform_zone_transfer(enumerator, domain, transfer_servers, &ZoneFilter::default()).await {
         eprintln!("Zone transfer enumeration failed: {}", e);
     }
 
@@ -731,6 +1372,20 @@ async fn perform_comprehensive_enumeration(
 
     println!("\n{}\n", "=".repeat(60));
 
+    // HINFO Disclosure
+    if let Err(e) = perform_hinfo_scan(enumerator, domain).await {
+        eprintln!("HINFO scan failed: {}", e);
+    }
+
+    println!("\n{}\n", "=".repeat(60));
+
+    // Service Discovery
+    if let Err(e) = perform_service_discovery(enumerator, domain).await {
+        eprintln!("Service discovery failed: {}", e);
+    }
+
+    println!("\n{}\n", "=".repeat(60));
+
     // Server Fingerprinting
     if let Err(e) = perform_server_fingerprinting(enumerator, domain, nameservers).await {
         eprintln!("Server fingerprinting failed: {}", e);
@@ -0,0 +1,124 @@
+//! Export backend statistics command implementation
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use clap::Args;
+use rdnsx_core::{CassandraConfig, CassandraExporter, ElasticsearchExporter, ExportStats, MongodbAnalyzer};
+
+use crate::cli::Config;
+
+/// Export-stats command arguments
+#[derive(Args)]
+pub struct ExportStatsArgs {
+    /// Only report on Elasticsearch
+    #[arg(long)]
+    pub elasticsearch: bool,
+
+    /// Only report on MongoDB
+    #[arg(long)]
+    pub mongodb: bool,
+
+    /// Only report on Cassandra
+    #[arg(long)]
+    pub cassandra: bool,
+}
+
+pub async fn run(args: ExportStatsArgs, config: Config) -> Result<()> {
+    let export = &config.core_config.export;
+    // Without a backend flag, every backend enabled in the config is queried
+    let all = !(args.elasticsearch || args.mongodb || args.cassandra);
+    let mut results: BTreeMap<&str, ExportStats> = BTreeMap::new();
+
+    if args.elasticsearch || (all && export.elasticsearch.enabled) {
+        let es = &export.elasticsearch;
+        if !config.silent {
+            eprintln!("📡 Reading Elasticsearch index {} at {}", es.index, es.url);
+        }
+        let exporter = ElasticsearchExporter::new(&es.url, &es.index, export.batch_size)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to Elasticsearch: {}", e))?;
+        results.insert("elasticsearch", exporter.query_stats().await?);
+    }
+
+    if args.mongodb || (all && export.mongodb.enabled) {
+        let mongo = &export.mongodb;
+        if !config.silent {
+            eprintln!("📡 Reading MongoDB collection {}/{}.{}", mongo.url, mongo.database, mongo.collection);
+        }
+        let analyzer = MongodbAnalyzer::new(&mongo.url, &mongo.database, &mongo.collection).await?;
+        results.insert("mongodb", analyzer.query_stats().await?);
+    }
+
+    if args.cassandra || (all && export.cassandra.enabled) {
+        let cassandra = &export.cassandra;
+        if !config.silent {
+            eprintln!("📡 Reading Cassandra table {}.{}", cassandra.keyspace, cassandra.table);
+        }
+        let exporter = CassandraExporter::with_config(CassandraConfig {
+            contact_points: cassandra.contact_points.clone(),
+            username: Some(cassandra.username.clone()),
+            password: Some(cassandra.password.clone()),
+            keyspace: cassandra.keyspace.clone(),
+            table: cassandra.table.clone(),
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to Cassandra: {}", e))?;
+        results.insert("cassandra", exporter.query_stats().await?);
+    }
+
+    if results.is_empty() {
+        anyhow::bail!("No export backend enabled (configure [export.*] or pass --elasticsearch/--mongodb/--cassandra)");
+    }
+
+    if config.json_output {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+        return Ok(());
+    }
+
+    for (backend, stats) in &results {
+        print_stats(backend, stats);
+    }
+
+    Ok(())
+}
+
+fn print_stats(backend: &str, stats: &ExportStats) {
+    println!("\n📊 {} ({} records)", backend, stats.total_records);
+    println!("{}", "=".repeat(50));
+
+    let range = match (stats.earliest, stats.latest) {
+        (Some(earliest), Some(latest)) => format!("{} → {}", earliest.to_rfc3339(), latest.to_rfc3339()),
+        _ => "-".to_string(),
+    };
+    println!("🕒 {}", range);
+
+    let by_count = |counts: &BTreeMap<String, u64>| {
+        let mut rows: Vec<(String, u64)> = counts.iter().map(|(k, v)| (k.clone(), *v)).collect();
+        rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        rows
+    };
+
+    print_table("Record type", &by_count(&stats.by_record_type), stats.total_records);
+    print_table("Response code", &by_count(&stats.by_response_code), stats.total_records);
+    print_table("Top domains", &stats.top_domains, stats.total_records);
+    print_table("Top resolvers", &stats.top_resolvers, stats.total_records);
+}
+
+fn print_table(heading: &str, rows: &[(String, u64)], total: u64) {
+    let width = rows.iter().map(|(key, _)| key.chars().count()).chain([heading.len()]).max().unwrap_or(0);
+    let rule = format!("+-{}-+------------+---------+", "-".repeat(width));
+
+    println!("\n{}", rule);
+    println!("| {:<width$} | {:>10} | {:>7} |", heading, "Records", "Share", width = width);
+    println!("{}", rule);
+    for (key, count) in rows {
+        let share = if total > 0 { *count as f64 * 100.0 / total as f64 } else { 0.0 };
+        println!("| {:<width$} | {:>10} | {:>6.1}% |", key, count, share, width = width);
+    }
+    if rows.is_empty() {
+        println!("| {:<width$} | {:>10} | {:>7} |", "(none)", "-", "-", width = width);
+    }
+    println!("{}", rule);
+}
@@ -0,0 +1,68 @@
+//! Domain/IP pivot command implementation
+
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use rdnsx_core::{read_records, DnsResultIndex};
+
+use crate::cli::Config;
+
+/// Index command arguments
+#[derive(Args)]
+pub struct IndexArgs {
+    /// Saved query results (JSON array or JSONL of records)
+    #[arg(short, long)]
+    pub input: PathBuf,
+
+    /// List the domains that resolved to this address
+    #[arg(long, required_unless_present = "lookup_domain")]
+    pub lookup_ip: Option<IpAddr>,
+
+    /// List the addresses this domain resolved to
+    #[arg(long)]
+    pub lookup_domain: Option<String>,
+}
+
+pub async fn run(args: IndexArgs, config: Config) -> Result<()> {
+    let records = read_records(&args.input)?;
+    let index = DnsResultIndex::new(&records);
+
+    if !config.silent {
+        eprintln!(
+            "📇 Indexed {} records ({} domains, {} addresses)",
+            records.len(),
+            index.by_domain.len(),
+            index.by_ip.len()
+        );
+    }
+
+    if let Some(ip) = args.lookup_ip {
+        // The same domain shows up once per resolver and per run in saved results
+        let domains: BTreeSet<&str> = index.domains_for_ip(ip).iter().map(|r| r.domain.as_str()).collect();
+        if config.json_output {
+            println!("{}", serde_json::json!({ "ip": ip, "domains": domains }));
+        } else {
+            println!("🔗 Domains resolving to {} ({}):", ip, domains.len());
+            for domain in &domains {
+                println!("  • {}", domain);
+            }
+        }
+    }
+
+    if let Some(domain) = &args.lookup_domain {
+        let ips = index.ips_for_domain(domain);
+        if config.json_output {
+            println!("{}", serde_json::json!({ "domain": domain, "ips": ips }));
+        } else {
+            println!("🌐 Addresses for {} ({}):", domain, ips.len());
+            for ip in &ips {
+                println!("  • {}", ip);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,6 +1,18 @@
 //! CLI commands
 
+pub mod analyze;
 pub mod bruteforce;
+pub mod cluster;
+pub mod diff;
+pub mod dnsbl;
 pub mod enumerate;
+pub mod export_stats;
+pub mod index;
+pub mod pipeline;
 pub mod ptr;
 pub mod query;
+pub mod schema;
+pub mod server;
+pub mod sign_zone;
+pub mod stats;
+pub mod validate_config;
@@ -0,0 +1,137 @@
+//! Pipeline command implementation
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use clap::Args;
+use rdnsx_core::config::PipelineStageConfig;
+use rdnsx_core::{
+    CassandraConfig, CassandraExporter, ElasticsearchExporter, ExportOrchestrator, MongodbExporter, PipelineReport,
+    ScanPipeline,
+};
+
+use crate::cli::Config;
+
+/// Pipeline command arguments
+///
+/// The stages come from `[[pipeline.stages]]` in the file given with `--config`.
+#[derive(Args)]
+pub struct PipelineArgs {}
+
+pub async fn run(_args: PipelineArgs, config: Config) -> Result<()> {
+    let stages = &config.core_config.pipeline.stages;
+    if stages.is_empty() {
+        anyhow::bail!("No pipeline stages configured (add [[pipeline.stages]] entries to the --config file)");
+    }
+
+    let dns_options = config.dns_options();
+
+    let mut pipeline = ScanPipeline::new().with_options(dns_options);
+    for stage in stages {
+        pipeline = match stage {
+            PipelineStageConfig::Bruteforce { domain, wordlist } => pipeline.bruteforce(wordlist.clone(), domain),
+            PipelineStageConfig::Domains { domains } => pipeline.domains(domains.clone()),
+            PipelineStageConfig::CdnDetect => pipeline.then_cdn_detect(),
+            PipelineStageConfig::EmailSecurity => pipeline.then_email_security(),
+            PipelineStageConfig::Export => pipeline.then_export(Arc::new(export_backends(&config).await?)),
+        };
+    }
+    let runner = pipeline.build()?;
+
+    if !config.silent {
+        let names: Vec<&str> = stages.iter().map(PipelineStageConfig::name).collect();
+        eprintln!("🔗 Running pipeline: {}", names.join(" → "));
+    }
+    let report = runner.run().await?;
+
+    if config.json_output {
+        print_json(&report)?;
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
+
+/// Every backend enabled under `[export]`, behind one orchestrator
+async fn export_backends(config: &Config) -> Result<ExportOrchestrator> {
+    let export = &config.core_config.export;
+    let mut exporters = ExportOrchestrator::new(export.max_concurrent_exports);
+
+    if export.elasticsearch.enabled {
+        let es = &export.elasticsearch;
+        let exporter = ElasticsearchExporter::with_ilm(&es.url, &es.index, export.batch_size, es.ilm_policy.as_ref())
+            .await
+            .map_err(|e| anyhow::Error::new(e).context("Failed to create Elasticsearch exporter"))?;
+        exporters = exporters.with_exporter("Elasticsearch", Arc::new(exporter));
+    }
+
+    if export.mongodb.enabled {
+        let mongo = &export.mongodb;
+        let exporter = MongodbExporter::new(&mongo.url, &mongo.database, &mongo.collection, export.batch_size)
+            .await
+            .map_err(|e| anyhow::Error::new(e).context("Failed to create MongoDB exporter"))?;
+        exporters = exporters.with_exporter("MongoDB", Arc::new(exporter));
+    }
+
+    if export.cassandra.enabled {
+        let cassandra = &export.cassandra;
+        let exporter = CassandraExporter::with_config(CassandraConfig {
+            contact_points: cassandra.contact_points.clone(),
+            username: Some(cassandra.username.clone()),
+            password: Some(cassandra.password.clone()),
+            keyspace: cassandra.keyspace.clone(),
+            table: cassandra.table.clone(),
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| anyhow::Error::new(e).context("Failed to create Cassandra exporter"))?;
+        exporters = exporters.with_exporter("Cassandra", Arc::new(exporter));
+    }
+
+    if exporters.exporters().next().is_none() {
+        anyhow::bail!("The export stage needs a backend enabled under [export]");
+    }
+    Ok(exporters)
+}
+
+fn print_json(report: &PipelineReport) -> Result<()> {
+    let cdn: Vec<_> = report
+        .cdn
+        .iter()
+        .map(|result| serde_json::json!({ "domain": result.domain, "cdn_provider": result.cdn_provider }))
+        .collect();
+    let output = serde_json::json!({
+        "domains": report.domains,
+        "records": report.records,
+        "cdn": cdn,
+        "email_security": report.email_security,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+fn print_report(report: &PipelineReport) {
+    println!("🌐 {} domains, {} records", report.domains.len(), report.records.len());
+    for domain in &report.domains {
+        println!("  {}", domain);
+    }
+
+    if !report.cdn.is_empty() {
+        println!("\n☁️  CDN detection:");
+        for result in &report.cdn {
+            println!("  {}: {}", result.domain, result.cdn_provider.as_deref().unwrap_or("none"));
+        }
+    }
+
+    if !report.email_security.is_empty() {
+        println!("\n📧 Email security:");
+        for result in &report.email_security {
+            println!(
+                "  {}: SPF {}, DMARC {}",
+                result.domain,
+                if result.spf_records.is_empty() { "missing" } else { "present" },
+                result.dmarc_policy().unwrap_or_else(|| "missing".to_string())
+            );
+        }
+    }
+}
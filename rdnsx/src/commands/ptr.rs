@@ -1,11 +1,15 @@
 //! PTR command implementation
 
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use anyhow::Result;
 use clap::Args;
+use futures::StreamExt;
 use tokio;
-use rdnsx_core::{DnsxClient, DnsEnumerator, RecordType, ResolverPool, parse_ip_range, reverse_ip};
+use ipnetwork::IpNetwork;
+use rdnsx_core::{DnsRecord, DnsxClient, DnsEnumerator, PtrScanner, PtrSubnetSummary, PtrSummary, RecordType, RecordValue, ResolverPool, TlsSanEnumerator, WhoisEnricher, parse_ip_range, reverse_ip};
 
 use crate::cli::Config;
 use crate::output_writer::OutputWriter;
@@ -13,29 +17,64 @@ use crate::output_writer::OutputWriter;
 #[derive(Args)]
 pub struct PtrArgs {
     /// IP range (CIDR) or ASN (e.g., 173.0.84.0/24 or AS17012)
-    #[arg()]
-    pub input: String,
+    #[arg(required_unless_present_any = ["asn", "cidr"])]
+    pub input: Option<String>,
+
+    /// IP range to scan (same as giving it as the positional argument)
+    #[arg(long, value_name = "CIDR", conflicts_with_all = ["input", "asn"])]
+    pub cidr: Option<String>,
+
+    /// Scan every IPv4 address announced by an ASN (e.g., AS15169)
+    #[arg(long, conflicts_with = "input")]
+    pub asn: Option<String>,
+
+    /// Allow ASN scans larger than 1 million addresses
+    #[arg(long)]
+    pub force: bool,
+
+    /// Append RDAP network owner, country and abuse contact to each result
+    #[arg(long, conflicts_with = "asn")]
+    pub whois_enrich: bool,
+
+    /// Read the TLS certificates served on port 443 by addresses with PTR records and resolve the names in their SANs
+    #[arg(long, conflicts_with = "asn")]
+    pub tls_san_expand: bool,
+
+    /// After the scan, summarize results per subnet with a guess at who operates each
+    #[arg(long)]
+    pub summarize: bool,
+
+    /// Prefix length of the subnets in the summary
+    #[arg(long, value_name = "BITS", default_value = "24", requires = "summarize",
+          value_parser = clap::value_parser!(u8).range(1..=128))]
+    pub summary_prefix: u8,
+
+    /// Also list the N organizations with the most resolved addresses
+    #[arg(long, value_name = "N", requires = "summarize")]
+    pub top_orgs: Option<usize>,
 }
 
+/// ASN scans above this many addresses require --force
+const ASN_SCAN_CONFIRM_THRESHOLD: u64 = 1_000_000;
+
 pub async fn run(args: PtrArgs, config: Config) -> Result<()> {
     // Create DNS client
-    let dns_options = rdnsx_core::config::DnsxOptions {
-        resolvers: config.core_config.resolvers.servers.clone(),
-        timeout: std::time::Duration::from_secs(config.core_config.resolvers.timeout),
-        retries: config.core_config.resolvers.retries,
-        concurrency: config.core_config.performance.threads,
-        rate_limit: config.core_config.performance.rate_limit,
-    };
+    let dns_options = config.dns_options();
     let _client = DnsxClient::with_options(dns_options.clone())?;
 
+    if let Some(asn) = &args.asn {
+        return run_asn_scan(asn, &args, &dns_options, &config).await;
+    }
+    let input = args.input.clone().or(args.cidr.clone()).unwrap_or_default();
+
     // Create output writer
-    let mut output = OutputWriter::new(config.output_file.clone(), config.json_output, config.silent)?;
+    let mut output = OutputWriter::from_config(&config)?;
 
     // Parse input - check if it's an ASN or IP range
-    let ips = if args.input.to_uppercase().starts_with("AS") {
+    let ips = if input.to_uppercase().starts_with("AS") {
         // ASN input - use ASN enumeration to get IP ranges
         if !config.silent {
-            eprintln!("🔍 Enumerating ASN {} for IP ranges...", args.input);
+            eprintln!("🔍 Enumerating ASN {} for IP ranges...", input);
         }
 
         // Create resolver pool and enumerator for ASN lookup
@@ -43,8 +82,8 @@ pub async fn run(args: PtrArgs, config: Config) -> Result<()> {
         let enumerator = DnsEnumerator::new(resolver_pool);
 
         // Get ASN information
-        let asn_result = enumerator.asn_enumeration(&args.input).await
-            .map_err(|e| anyhow::anyhow!("Failed to enumerate ASN {}: {}", args.input, e))?;
+        let asn_result = enumerator.asn_enumeration(&input).await
+            .map_err(|e| anyhow::anyhow!("Failed to enumerate ASN {}: {}", input, e))?;
 
         if !config.silent {
             eprintln!("📊 Found {} IPv4 prefixes and {} IPv6 prefixes for {}",
@@ -68,7 +107,7 @@ pub async fn run(args: PtrArgs, config: Config) -> Result<()> {
 
                     if total_ips >= 10000 { // Overall limit of 10,000 IPs
                         if !config.silent {
-                            eprintln!("⚠️  Limiting to 10,000 IPs total (ASN {} has many ranges)", args.input);
+                            eprintln!("⚠️  Limiting to 10,000 IPs total (ASN {} has many ranges)", input);
                         }
                         break;
                     }
@@ -82,13 +121,13 @@ pub async fn run(args: PtrArgs, config: Config) -> Result<()> {
         }
 
         if all_ips.is_empty() {
-            anyhow::bail!("No valid IP addresses found for ASN {}", args.input);
+            anyhow::bail!("No valid IP addresses found for ASN {}", input);
         }
 
         all_ips
-    } else if args.input.contains('/') {
+    } else if input.contains('/') {
         // CIDR notation - add size limits
-        let range_ips = parse_ip_range(&args.input)
+        let range_ips = parse_ip_range(&input)
             .map_err(|e| anyhow::anyhow!("Failed to parse IP range: {}", e))?;
 
         // Limit large ranges to prevent excessive lookups
@@ -96,7 +135,7 @@ pub async fn run(args: PtrArgs, config: Config) -> Result<()> {
         if range_ips.len() > max_ips {
             if !config.silent {
                 eprintln!("⚠️  Limiting {}/{} range to {} IPs (was {} total)",
-                         args.input, args.input.split('/').nth(1).unwrap_or(""),
+                         input, input.split('/').nth(1).unwrap_or(""),
                          max_ips, range_ips.len());
             }
             range_ips.into_iter().take(max_ips).collect()
@@ -105,7 +144,7 @@ pub async fn run(args: PtrArgs, config: Config) -> Result<()> {
         }
     } else {
         // Single IP address
-        vec![args.input
+        vec![input
             .parse()
             .map_err(|e| anyhow::anyhow!("Invalid IP address: {}", e))?]
     };
@@ -119,6 +158,8 @@ pub async fn run(args: PtrArgs, config: Config) -> Result<()> {
         }
     }
 
+    let scanned = if args.summarize { ips.clone() } else { Vec::new() };
+
     // Use concurrent lookups for better performance
     let concurrency = std::cmp::min(config.core_config.performance.threads, 50); // Cap at 50 concurrent requests
     let semaphore = Arc::new(tokio::sync::Semaphore::new(concurrency));
@@ -135,11 +176,12 @@ pub async fn run(args: PtrArgs, config: Config) -> Result<()> {
         let permit = semaphore.clone().acquire_owned().await.unwrap();
         let ptr_domain = reverse_ip(&ip);
         let options = dns_options.clone(); // Clone the options to create new client
+        let audit_logger = config.audit_logger.clone();
 
         let task = tokio::spawn(async move {
             // Create a new client for this task
             let client_result = match DnsxClient::with_options(options) {
-                Ok(client) => client.query(&ptr_domain, RecordType::Ptr).await,
+                Ok(client) => client.with_audit_logger(audit_logger).query(&ptr_domain, RecordType::Ptr).await,
                 Err(e) => Err(e),
             };
             drop(permit); // Release the permit
@@ -152,11 +194,11 @@ pub async fn run(args: PtrArgs, config: Config) -> Result<()> {
     // Process results as they complete
     for task in tasks {
         match task.await {
-            Ok((_ip, _ptr_domain, result)) => {
+            Ok((ip, _ptr_domain, result)) => {
                 match result {
                     Ok(records) if !records.is_empty() => {
                         success_count += 1;
-                        all_records.extend(records);
+                        all_records.extend(records.into_iter().map(|record| (ip, record)));
                     }
                     Ok(_) => {
                         // No PTR records found - this is normal, don't count as error
@@ -176,8 +218,16 @@ pub async fn run(args: PtrArgs, config: Config) -> Result<()> {
     }
 
     // Write all collected records
-    for record in all_records {
-        output.write_record(&record, false)?;
+    if args.whois_enrich {
+        write_whois_enriched(&mut output, &all_records, &config).await?;
+    } else {
+        for (_, record) in &all_records {
+            output.write_record(record, false)?;
+        }
+    }
+
+    if args.tls_san_expand {
+        write_tls_san_records(&mut output, &all_records, &dns_options, &config).await?;
     }
 
     if !config.silent {
@@ -188,5 +238,201 @@ pub async fn run(args: PtrArgs, config: Config) -> Result<()> {
     }
 
     output.flush()?;
+
+    if args.summarize {
+        let records: Vec<DnsRecord> = all_records.into_iter().map(|(_, record)| record).collect();
+        print_summary(&PtrSummary::group_scan(&scanned, &records, args.summary_prefix), args.top_orgs, &config)?;
+    }
+    Ok(())
+}
+
+/// Print the subnets in address order, then the organizations with the most resolved addresses
+fn print_summary(
+    summaries: &HashMap<IpNetwork, PtrSubnetSummary>,
+    top_orgs: Option<usize>,
+    config: &Config,
+) -> Result<()> {
+    let mut subnets: Vec<(&IpNetwork, &PtrSubnetSummary)> = summaries.iter().collect();
+    subnets.sort_by_key(|(subnet, _)| **subnet);
+    let orgs = top_orgs.map(|n| PtrSummary::top_orgs(summaries, n));
+
+    if config.json_output {
+        let subnets: Vec<serde_json::Value> = subnets
+            .iter()
+            .map(|(subnet, summary)| {
+                let mut value = serde_json::to_value(summary)?;
+                value["subnet"] = serde_json::Value::String(subnet.to_string());
+                Ok(value)
+            })
+            .collect::<serde_json::Result<_>>()?;
+        let mut summary = serde_json::json!({ "subnets": subnets });
+        if let Some(orgs) = &orgs {
+            summary["top_orgs"] = orgs.iter().map(|(org, ips)| serde_json::json!({ "org": org, "ips": ips })).collect();
+        }
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    println!("\n📊 PTR summary ({} subnets)", subnets.len());
+    println!("{:<20} {:>9} {:>9}  {:<20} Sample hostnames", "Subnet", "Resolved", "Scanned", "Likely org");
+    for (subnet, summary) in &subnets {
+        println!(
+            "{:<20} {:>9} {:>9}  {:<20} {}",
+            subnet.to_string(),
+            summary.resolved_count,
+            summary.total_ips_scanned,
+            summary.likely_org.as_deref().unwrap_or("-"),
+            summary.sample_domains.join(", ")
+        );
+    }
+
+    if let Some(orgs) = orgs {
+        println!("\n🏢 Top organizations");
+        for (org, ips) in orgs {
+            println!("{:<30} {:>9} IPs", org, ips);
+        }
+    }
     Ok(())
 }
+
+/// Write each record followed by the RDAP registration of its address
+///
+/// Lookups run one at a time; answers are cached per /24, so a scanned range
+/// costs a handful of RDAP requests rather than one per address.
+/// Resolve the certificate SANs of every address that had a PTR record, skipping the PTR names themselves
+async fn write_tls_san_records(
+    output: &mut OutputWriter,
+    records: &[(IpAddr, DnsRecord)],
+    dns_options: &rdnsx_core::config::DnsxOptions,
+    config: &Config,
+) -> Result<()> {
+    let ips: Vec<IpAddr> = records.iter().map(|(ip, _)| *ip).collect::<BTreeSet<_>>().into_iter().collect();
+    let ptr_names: HashSet<String> = records
+        .iter()
+        .filter_map(|(_, record)| match &record.value {
+            RecordValue::Domain(name) => Some(name.trim_end_matches('.').to_lowercase()),
+            _ => None,
+        })
+        .collect();
+
+    if !config.silent {
+        eprintln!("🔐 Reading TLS certificate SANs from {} addresses", ips.len());
+    }
+    let names: Vec<String> = TlsSanEnumerator::new()?
+        .expand(&ips, 443)
+        .await
+        .into_iter()
+        .filter(|name| !ptr_names.contains(name))
+        .collect();
+    if !config.silent {
+        eprintln!("🔐 {} new names from TLS certificates, resolving them", names.len());
+    }
+
+    let client = DnsxClient::with_options(dns_options.clone())?.with_audit_logger(config.audit_logger.clone());
+    let record_types = [RecordType::A, RecordType::Aaaa];
+    let mut records = std::pin::pin!(client.query_stream(names, &record_types));
+    // Many SAN names have no AAAA record, or no longer resolve at all
+    while let Some(result) = records.next().await {
+        if let Ok(record) = result {
+            output.write_record(&record, false)?;
+        }
+    }
+    Ok(())
+}
+
+async fn write_whois_enriched(output: &mut OutputWriter, records: &[(std::net::IpAddr, DnsRecord)], config: &Config) -> Result<()> {
+    let enricher = WhoisEnricher::new()?;
+
+    for (ip, record) in records {
+        let info = match enricher.enrich(*ip).await {
+            Ok(info) => Some(info),
+            Err(e) => {
+                if !config.silent {
+                    eprintln!("⚠️  Whois lookup for {} failed: {}", ip, e);
+                }
+                None
+            }
+        };
+
+        if config.json_output {
+            let mut value = serde_json::to_value(record)?;
+            value["whois"] = serde_json::to_value(&info)?;
+            output.write_line(&value.to_string())?;
+        } else {
+            match info {
+                Some(info) => output.write_line(&format!(
+                    "{} [{} | {} | {} | {} | abuse: {}]",
+                    record,
+                    info.cidr,
+                    info.name,
+                    info.organization,
+                    info.country,
+                    info.abuse_email.as_deref().unwrap_or("-")
+                ))?,
+                None => output.write_record(record, false)?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolve an ASN to its prefixes and PTR scan every announced IPv4 address
+async fn run_asn_scan(asn: &str, args: &PtrArgs, dns_options: &rdnsx_core::config::DnsxOptions, config: &Config) -> Result<()> {
+    let scanner = PtrScanner::new(dns_options)?;
+
+    let cidrs = scanner.asn_resolver().get_cidrs(asn).await
+        .map_err(|e| anyhow::anyhow!("Failed to resolve prefixes for {}: {}", asn, e))?;
+    let total_ips = PtrScanner::count_ips(&cidrs);
+
+    if total_ips == 0 {
+        anyhow::bail!("No IPv4 prefixes found for ASN {}", asn);
+    }
+
+    if total_ips > ASN_SCAN_CONFIRM_THRESHOLD && !args.force {
+        eprintln!("⚠️  {} announces {} IPv4 addresses (more than {}).", asn, total_ips, ASN_SCAN_CONFIRM_THRESHOLD);
+        anyhow::bail!("Refusing to scan {} addresses without --force", total_ips);
+    }
+
+    if !config.silent {
+        eprintln!("🔍 {} announces {} prefixes covering {} IPv4 addresses", asn, cidrs.len(), total_ips);
+        eprintln!("⏱️  Estimated completion time: {}", format_eta(estimate_scan_seconds(total_ips, dns_options)));
+    }
+
+    let records = scanner.scan_cidrs(&cidrs).await?;
+
+    let mut output = OutputWriter::from_config(config)?;
+    for record in &records {
+        output.write_record(record, false)?;
+    }
+    output.flush()?;
+
+    if !config.silent {
+        eprintln!("✅ PTR scan of {} completed: {} records from {} addresses", asn, records.len(), total_ips);
+    }
+
+    // Whole prefixes are scanned, so every block counts as fully queried
+    if args.summarize {
+        print_summary(&PtrSummary::group_by_subnet(&records, args.summary_prefix), args.top_orgs, config)?;
+    }
+
+    Ok(())
+}
+
+/// Estimate scan duration from the rate limit, or from concurrency and an assumed 50ms lookup when unlimited
+fn estimate_scan_seconds(total_ips: u64, dns_options: &rdnsx_core::config::DnsxOptions) -> u64 {
+    if dns_options.rate_limit > 0 {
+        total_ips.div_ceil(dns_options.rate_limit)
+    } else {
+        let queries_per_second = (dns_options.concurrency.max(1) as u64) * 20;
+        total_ips.div_ceil(queries_per_second)
+    }
+}
+
+fn format_eta(seconds: u64) -> String {
+    match seconds {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+    }
+}
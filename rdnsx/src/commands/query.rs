@@ -1,13 +1,20 @@
 //! Query command implementation
 
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
 use clap::Args;
-use rdnsx_core::{DnsxClient, RecordType, ResponseCode, DnsRecord, CassandraExporter, CassandraConfig, ElasticsearchExporter, MongodbExporter, ResolverPool, WildcardFilter, Exporter, config::DnsxOptions, ConcurrentProcessor, ConcurrencyConfig, ProcessingMetrics, DomainStreamer, DnsCache, CachedDnsClient, AdaptiveBatchSizer};
+use futures::StreamExt;
+use rdnsx_core::config::Config as CoreConfig;
+use rdnsx_core::utils::extract_domain_from_url;
+use rdnsx_core::{DnsxClient, DnsxError, RecordType, RecordValue, ResponseCode, DnsRecord, CassandraExporter, CassandraConfig, ReplicationStrategy, ElasticsearchExporter, ExportOrchestrator, IlmConfig, MongodbExporter, ResolverPool, WildcardFilter, WildcardConfidence, Exporter, config::DnsxOptions, ConcurrentProcessor, ConcurrencyConfig, ProcessingMetrics, QueryOutcome, DomainStreamer, DomainFilter, DnsCache, CachedDnsClient, AdaptiveBatchSizer, SoaTracker, MxAnalyzer, DomainCategorizer};
+use regex::Regex;
 
 use crate::cli::Config;
-use crate::output_writer::OutputWriter;
+use crate::output_writer::{OutputWriter, SortOrder};
 
 #[derive(Args)]
 pub struct QueryArgs {
@@ -19,7 +26,19 @@ pub struct QueryArgs {
     #[arg(short, long)]
     pub list: Option<String>,
 
-    /// DNS record types to query (can be repeated)
+    /// Format of the input list: one domain per line, or JSON objects with a "domain" field (e.g. an earlier -j run)
+    #[arg(long, value_enum, default_value = "text")]
+    pub list_format: ListFormat,
+
+    /// Accept URLs as input (e.g. Burp or browser history exports) and query their hostnames
+    #[arg(long)]
+    pub strip_urls: bool,
+
+    /// With --strip-urls, also drop a leading "www." from hostnames
+    #[arg(long, requires = "strip_urls")]
+    pub strip_www: bool,
+
+    /// DNS record types to query (can be repeated); also accepts aliases such as web, mail, dnssec or full
     #[arg(short = 't', long = "record-type", value_name = "TYPE", action = clap::ArgAction::Append)]
     pub record_type: Vec<String>,
 
@@ -143,14 +162,50 @@ pub struct QueryArgs {
     #[arg(short = 'w', long)]
     pub wildcard_domain: Option<String>,
 
+    /// Only filter wildcard records when random names also get the same web page (sends HTTP requests)
+    #[arg(long, requires = "wildcard_domain")]
+    pub http_wildcard_verify: bool,
+
+    /// Probe agreement required before treating a domain as a wildcard
+    #[arg(long, value_enum, default_value = "medium")]
+    pub wildcard_confidence: WildcardConfidenceArg,
+
     /// Response values only
     #[arg(long)]
     pub resp_only: bool,
 
+    /// Only output records matching this regex (the value with --resp-only, otherwise the whole line)
+    #[arg(long, value_name = "REGEX")]
+    pub filter: Option<String>,
+
+    /// Only output records whose domain matches this regex
+    #[arg(long, value_name = "REGEX")]
+    pub filter_domain: Option<String>,
+
+    /// Only output these record types (comma-separated, like --rcode)
+    #[arg(long, value_name = "TYPE")]
+    pub filter_rtype: Option<String>,
+
     /// Use streaming mode for large files (reduces memory usage)
     #[arg(long)]
     pub stream: bool,
 
+    /// Only process input domains matching this glob, e.g. '*.api.*' (repeatable, OR-combined)
+    #[arg(long = "filter-pattern", value_name = "GLOB")]
+    pub filter_patterns: Vec<String>,
+
+    /// Only process input domains matching this regex (repeatable, OR-combined)
+    #[arg(long = "filter-regex", value_name = "REGEX")]
+    pub filter_regexes: Vec<String>,
+
+    /// Sort output before writing (holds all records in memory)
+    #[arg(long, value_enum)]
+    pub sort_by: Option<SortOrder>,
+
+    /// Print each domain as a header followed by its records
+    #[arg(long)]
+    pub group_by_domain: bool,
+
     /// Enable DNS response caching (reduces redundant queries)
     #[arg(long)]
     pub cache: bool,
@@ -163,6 +218,10 @@ pub struct QueryArgs {
     #[arg(long, default_value = "10000")]
     pub cache_size: usize,
 
+    /// Keep the DNS cache in this file between runs (implies --cache)
+    #[arg(long, value_name = "FILE")]
+    pub cache_file: Option<std::path::PathBuf>,
+
     /// Cassandra batch size (default: 1000)
     #[arg(long, default_value = "1000")]
     pub cassandra_batch_size: usize,
@@ -170,30 +229,109 @@ pub struct QueryArgs {
     /// Number of Cassandra worker threads (default: 4)
     #[arg(long, default_value = "4")]
     pub cassandra_workers: usize,
+
+    /// Delete Elasticsearch indices after this many days via an ILM policy (hot/warm phases from config or defaults)
+    #[arg(long, value_name = "DAYS")]
+    pub es_ilm_delete_after_days: Option<u32>,
+
+    /// Abort before querying if an export backend fails its health check (default: warn and continue)
+    #[arg(long)]
+    pub fail_fast_export: bool,
+
+    /// Cassandra datacenter replication factor as DC=FACTOR (repeatable; enables NetworkTopologyStrategy)
+    #[arg(long = "cassandra-dc-factor", value_name = "DC=FACTOR", value_parser = parse_dc_factor)]
+    pub cassandra_dc_factors: Vec<(String, u32)>,
+
+    /// Report SOA serial changes since the previous run (cached in ~/.rdnsx/soa_cache.json)
+    #[arg(long)]
+    pub track_soa: bool,
+
+    /// Query MX and print each domain's exchanges by priority with the identified mail provider
+    #[arg(long)]
+    pub mx_analyze: bool,
+
+    /// Send non-recursive queries to each zone's authoritative nameservers instead of the resolvers
+    #[arg(long)]
+    pub authoritative: bool,
+
+    /// Tag each domain as web, mail, cdn, cloud_storage, parked or development (also looks up HTTPS and MX)
+    #[arg(long)]
+    pub categorize: bool,
+
+    /// Follow each CNAME record's chain and add the A/AAAA records it ends at
+    #[arg(long)]
+    pub resolve_cname: bool,
+}
+
+/// Input list formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ListFormat {
+    /// One domain per line
+    Text,
+    /// One JSON object per line with a "domain" field
+    Jsonl,
+}
+
+/// Wildcard probe confidence levels
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum WildcardConfidenceArg {
+    /// One of three random probes resolved
+    Low,
+    /// Two of three probes resolved
+    Medium,
+    /// All three probes resolved identically
+    High,
+}
+
+impl From<WildcardConfidenceArg> for WildcardConfidence {
+    fn from(arg: WildcardConfidenceArg) -> Self {
+        match arg {
+            WildcardConfidenceArg::Low => WildcardConfidence::Low,
+            WildcardConfidenceArg::Medium => WildcardConfidence::Medium,
+            WildcardConfidenceArg::High => WildcardConfidence::High,
+        }
+    }
 }
 
 pub async fn run(args: QueryArgs, config: Config) -> Result<()> {
     // Determine record types to query
-    let record_types = determine_record_types(&args);
+    let record_types = determine_record_types(&args, &config.core_config)?;
+
+    // Types --track-soa, --mx-analyze and --categorize need on top of the requested ones;
+    // their records feed the analyses only and are neither output nor exported
+    let mut analysis_types = Vec::new();
+    if args.track_soa {
+        analysis_types.push(RecordType::Soa);
+    }
+    if args.mx_analyze {
+        analysis_types.push(RecordType::Mx);
+    }
+    if args.categorize {
+        analysis_types.extend([RecordType::Https, RecordType::Mx]);
+    }
+    analysis_types.retain(|record_type| !record_types.contains(record_type));
+    analysis_types.dedup();
+    let analysis_records: Arc<Mutex<Vec<DnsRecord>>> = Arc::default();
 
     // Create DNS client
-    let dns_options = DnsxOptions {
-        resolvers: config.core_config.resolvers.servers.clone(),
-        timeout: std::time::Duration::from_secs(config.core_config.resolvers.timeout),
-        retries: config.core_config.resolvers.retries,
-        concurrency: config.core_config.performance.threads,
-        rate_limit: config.core_config.performance.rate_limit,
-    };
-    let client = DnsxClient::with_options(dns_options.clone())?;
+    let dns_options = DnsxOptions { cache_file: args.cache_file.clone(), ..config.dns_options() };
+    let client = DnsxClient::with_options(dns_options.clone())?
+        .with_authoritative(args.authoritative)
+        .with_audit_logger(config.audit_logger.clone());
+    if config.warm_up {
+        client.warm_up().await?;
+    }
 
     // Create wildcard filter if domain specified
     let wildcard_filter: Option<WildcardFilter> = if let Some(ref base_domain) = args.wildcard_domain {
         let resolver_pool = Arc::new(ResolverPool::new(&dns_options)?);
-        Some(WildcardFilter::new(
+        let filter = WildcardFilter::new(
             Some(base_domain.clone()),
             resolver_pool,
             10, // Default threshold: 10 domains pointing to same IP
-        ))
+        )
+        .with_confidence_threshold(args.wildcard_confidence.into());
+        Some(if args.http_wildcard_verify { filter.with_http_verification()? } else { filter })
     } else {
         None
     };
@@ -202,36 +340,61 @@ pub async fn run(args: QueryArgs, config: Config) -> Result<()> {
     let allowed_rcodes = parse_rcodes(&args.rcode)?;
 
     // Create output writer
-    let mut output = OutputWriter::new(config.output_file.clone(), config.json_output, config.silent)?;
+    let mut output = OutputWriter::from_config(&config)?;
+    if let Some(pattern) = &args.filter {
+        let regex = Regex::new(pattern).map_err(|e| anyhow::anyhow!("Invalid --filter regex '{}': {}", pattern, e))?;
+        output = output.with_value_filter(&regex);
+    }
+    if let Some(pattern) = &args.filter_domain {
+        let regex = Regex::new(pattern).map_err(|e| anyhow::anyhow!("Invalid --filter-domain regex '{}': {}", pattern, e))?;
+        output = output.with_domain_filter(&regex);
+    }
+    if let Some(types) = &args.filter_rtype {
+        output = output.with_record_type_filter(types.split(','));
+    }
+    if let Some(sort) = args.sort_by {
+        output.set_sort(sort);
+    }
+    output.set_group_by_domain(args.group_by_domain);
+    if args.stream && (args.sort_by.is_some() || args.group_by_domain) && !config.silent {
+        eprintln!("Warning: --sort-by/--group-by-domain buffer all records in memory, which defeats --stream");
+    }
 
     // Create exporters if configured
-    let mut es_exporter: Option<ElasticsearchExporter> = None;
-    let mut mongo_exporter: Option<MongodbExporter> = None;
-    let mut cassandra_exporter: Option<CassandraExporter> = None;
+    let mut exporters = ExportOrchestrator::new(config.core_config.export.max_concurrent_exports);
+    let mut cassandra_exporter: Option<Arc<CassandraExporter>> = None;
 
     if config.core_config.export.elasticsearch.enabled {
-        es_exporter = Some(
-            ElasticsearchExporter::new(
-                &config.core_config.export.elasticsearch.url,
-                &config.core_config.export.elasticsearch.index,
-                config.core_config.export.batch_size,
-            )
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create Elasticsearch exporter: {}", e))?,
-        );
+        // --es-ilm-delete-after-days overrides (or enables) the configured lifecycle policy
+        let mut ilm_policy = config.core_config.export.elasticsearch.ilm_policy;
+        if let Some(days) = args.es_ilm_delete_after_days {
+            ilm_policy = Some(IlmConfig {
+                delete_after_days: days,
+                ..ilm_policy.unwrap_or_default()
+            });
+        }
+
+        let exporter = ElasticsearchExporter::with_ilm(
+            &config.core_config.export.elasticsearch.url,
+            &config.core_config.export.elasticsearch.index,
+            config.core_config.export.batch_size,
+            ilm_policy.as_ref(),
+        )
+        .await
+        .map_err(|e| anyhow::Error::new(e).context("Failed to create Elasticsearch exporter"))?;
+        exporters = exporters.with_exporter("Elasticsearch", Arc::new(exporter));
     }
 
     if config.core_config.export.mongodb.enabled {
-        mongo_exporter = Some(
-            MongodbExporter::new(
-                &config.core_config.export.mongodb.url,
-                &config.core_config.export.mongodb.database,
-                &config.core_config.export.mongodb.collection,
-                config.core_config.export.batch_size,
-            )
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create MongoDB exporter: {}", e))?,
-        );
+        let exporter = MongodbExporter::new(
+            &config.core_config.export.mongodb.url,
+            &config.core_config.export.mongodb.database,
+            &config.core_config.export.mongodb.collection,
+            config.core_config.export.batch_size,
+        )
+        .await
+        .map_err(|e| anyhow::Error::new(e).context("Failed to create MongoDB exporter"))?;
+        exporters = exporters.with_exporter("MongoDB", Arc::new(exporter));
     }
 
     if config.core_config.export.cassandra.enabled {
@@ -243,16 +406,42 @@ pub async fn run(args: QueryArgs, config: Config) -> Result<()> {
             table: config.core_config.export.cassandra.table.clone(),
             batch_size: args.cassandra_batch_size,
             num_workers: args.cassandra_workers,
+            channel_buffer_size: args.cassandra_batch_size * args.cassandra_workers,
+            replication_strategy: if args.cassandra_dc_factors.is_empty() {
+                ReplicationStrategy::default()
+            } else {
+                ReplicationStrategy::NetworkTopology {
+                    datacenter_factors: args.cassandra_dc_factors.iter().cloned().collect(),
+                }
+            },
             ..Default::default()
         };
 
-        cassandra_exporter = Some(
+        let exporter = Arc::new(
             CassandraExporter::with_config(cassandra_config)
                 .await
-                .map_err(|e| anyhow::anyhow!("Failed to create Cassandra exporter: {}", e))?,
+                .map_err(|e| anyhow::Error::new(e).context("Failed to create Cassandra exporter"))?,
         );
+        exporters = exporters.with_exporter("Cassandra", Arc::clone(&exporter) as Arc<dyn Exporter>);
+        // Kept for its insert metrics
+        cassandra_exporter = Some(exporter);
+    }
+
+    // Find an unreachable backend now rather than at the first write, after the scan has run
+    for (name, exporter) in exporters.exporters() {
+        if let Err(e) = exporter.health_check().await {
+            if args.fail_fast_export {
+                return Err(anyhow::Error::new(e).context(format!("{} health check failed", name)));
+            }
+            if !config.silent {
+                eprintln!("Warning: {} health check failed, continuing without a working export: {}", name, e.report());
+            }
+        }
     }
 
+    let domain_filters = build_domain_filters(&args)?;
+    let url_stripping = args.strip_urls.then_some(args.strip_www);
+
     // Determine if we should use streaming mode
     let use_streaming = args.stream || args.list.is_some(); // Auto-enable for files
 
@@ -264,8 +453,26 @@ pub async fn run(args: QueryArgs, config: Config) -> Result<()> {
         Vec::new() // We'll stream domains directly
     } else {
         // Load all domains into memory for small lists or stdin
-        let mut domains = read_domains(&args.list)?;
+        let mut domains = match args.list_format {
+            ListFormat::Text => read_domains(&args.list)?,
+            ListFormat::Jsonl => read_jsonl_domains(open_list(&args.list)?, Vec::new(), None, config.silent),
+        };
         domains.extend(args.domains.clone());
+        if let Some(strip_www) = url_stripping {
+            domains = domains
+                .iter()
+                .filter_map(|input| {
+                    let domain = extract_domain_from_url(input, strip_www);
+                    if domain.is_none() {
+                        tracing::debug!("Skipping malformed URL input: {}", input);
+                    }
+                    domain
+                })
+                .collect();
+        }
+        if !domain_filters.is_empty() {
+            domains.retain(|domain| domain_filters.iter().any(|f| f.matches(domain.trim())));
+        }
 
         if domains.is_empty() {
             if !config.silent {
@@ -285,15 +492,22 @@ pub async fn run(args: QueryArgs, config: Config) -> Result<()> {
         batch_size: adaptive_batcher.current_size(),
         timeout: std::time::Duration::from_secs(config.core_config.resolvers.timeout),
         rate_limit: config.core_config.performance.rate_limit,
+        rate_limit_burst_multiplier: config.rate_limit_burst_multiplier,
     };
 
     // Create cached client if caching is enabled
-    let (client_clone, cached_client_ref): (Arc<dyn rdnsx_core::DnsQuery + Send + Sync>, Option<Arc<CachedDnsClient<DnsxClient>>>) = if args.cache {
+    let use_cache = args.cache || dns_options.cache_file.is_some();
+    let (client_clone, cached_client_ref): (Arc<dyn rdnsx_core::DnsQuery + Send + Sync>, Option<Arc<CachedDnsClient<DnsxClient>>>) = if use_cache {
         if !config.silent {
             eprintln!("DNS caching enabled (TTL: {}s, max size: {})", args.cache_ttl, args.cache_size);
         }
-        let cache = DnsCache::new(args.cache_size, std::time::Duration::from_secs(args.cache_ttl));
-        let cached_client = Arc::new(CachedDnsClient::new(client, cache));
+        let cache_ttl = std::time::Duration::from_secs(args.cache_ttl);
+        let cache = DnsCache::from_options(&dns_options, args.cache_size, cache_ttl)?;
+        let mut cached_client = CachedDnsClient::new(client, cache);
+        if let Some(path) = &dns_options.cache_file {
+            cached_client = cached_client.with_cache_file(path);
+        }
+        let cached_client = Arc::new(cached_client);
         (cached_client.clone() as Arc<dyn rdnsx_core::DnsQuery + Send + Sync>, Some(cached_client))
     } else {
         (Arc::new(client) as Arc<dyn rdnsx_core::DnsQuery + Send + Sync>, None)
@@ -302,6 +516,8 @@ pub async fn run(args: QueryArgs, config: Config) -> Result<()> {
     // Create the concurrent processor with all record types and domains
     let processor = ConcurrentProcessor::new(concurrency_config, {
         let record_types = record_types.clone();
+        let analysis_types = analysis_types.clone();
+        let analysis_records = Arc::clone(&analysis_records);
         let client = Arc::clone(&client_clone);
         let allowed_rcodes = allowed_rcodes.clone();
         let wildcard_filter = wildcard_filter.clone();
@@ -309,13 +525,15 @@ pub async fn run(args: QueryArgs, config: Config) -> Result<()> {
 
         move |domain: String| {
             let record_types = record_types.clone();
+            let analysis_types = analysis_types.clone();
+            let analysis_records = Arc::clone(&analysis_records);
             let client = Arc::clone(&client);
             let allowed_rcodes = allowed_rcodes.clone();
             let wildcard_filter = wildcard_filter.clone();
             let silent = silent;
 
             Box::pin(async move {
-                let mut all_records = Vec::new();
+                let mut outcome = QueryOutcome::default();
 
                 // Query each record type for this domain
                 for record_type in &record_types {
@@ -339,17 +557,25 @@ pub async fn run(args: QueryArgs, config: Config) -> Result<()> {
                                 records
                             };
 
-                            all_records.extend(filtered_records);
+                            outcome.records.extend(filtered_records);
                         }
                         Err(e) => {
                             if !silent {
-                                eprintln!("Error querying {} ({:?}): {}", domain, record_type, e);
+                                report_query_error(&domain, *record_type, &e);
                             }
+                            outcome.failures.push((*record_type, e));
                         }
                     }
                 }
 
-                Ok(all_records)
+                // Left out of the outcome so they count neither as results nor in the metrics
+                for record_type in &analysis_types {
+                    if let Ok(records) = client.query(&domain, *record_type).await {
+                        analysis_records.lock().unwrap().extend(records);
+                    }
+                }
+
+                Ok(outcome)
             })
         }
     });
@@ -357,21 +583,28 @@ pub async fn run(args: QueryArgs, config: Config) -> Result<()> {
     // Process domains concurrently with adaptive batching
     let (all_records, metrics) = if use_streaming && args.list.is_some() {
         // Streaming mode for large files with adaptive batching
-        let file = std::fs::File::open(args.list.as_ref().unwrap())?;
-        let reader = std::io::BufReader::new(file);
-        let streamer = DomainStreamer::new(reader);
+        let reader = open_list(&args.list)?;
 
-        let domain_iter = streamer.stream_domains().filter_map(|result| match result {
-            Ok(domain) if !domain.is_empty() => Some(domain),
-            Ok(_) => None, // Skip empty lines
-            Err(e) => {
-                eprintln!("Error reading domain: {}", e);
-                None
+        // Collect domains for adaptive batching
+        let domains_vec: Vec<String> = if args.list_format == ListFormat::Jsonl {
+            read_jsonl_domains(reader, domain_filters, url_stripping, config.silent)
+        } else {
+            let mut streamer = domain_filters
+                .into_iter()
+                .fold(DomainStreamer::new(reader), DomainStreamer::with_filter);
+            if let Some(strip_www) = url_stripping {
+                streamer = streamer.with_url_stripping(strip_www);
             }
-        });
 
-        // Collect domains for adaptive batching
-        let domains_vec: Vec<String> = domain_iter.collect();
+            streamer.stream_domains().filter_map(|result| match result {
+                Ok(domain) if !domain.is_empty() => Some(domain),
+                Ok(_) => None, // Skip empty lines
+                Err(e) => {
+                    eprintln!("Error reading domain: {}", e);
+                    None
+                }
+            }).collect()
+        };
 
         // Process with adaptive batching
         process_with_adaptive_batching(
@@ -386,14 +619,21 @@ pub async fn run(args: QueryArgs, config: Config) -> Result<()> {
     };
 
     if !config.silent {
-        eprintln!("Processed {} domains, collected {} records ({:.1} qps)",
-                 metrics.total_domains, all_records.len(), metrics.queries_per_second);
+        eprintln!("Processed {} domains, collected {} records ({:.1} qps) ({} NOERROR, {} NXDOMAIN)",
+                 metrics.total_domains, all_records.len(), metrics.queries_per_second,
+                 metrics.rcode_count(ResponseCode::NoError), metrics.rcode_count(ResponseCode::NxDomain));
+
+        if config.verbose {
+            print_resolver_stats(&metrics);
+            print_rcode_distribution(&metrics);
+        }
 
         // Show cache statistics if caching was enabled
         if let Some(ref cached_client) = cached_client_ref {
             let cache_stats = cached_client.cache_stats();
-            eprintln!("Cache: {} total entries ({} valid, {} expired)",
-                     cache_stats.total_entries, cache_stats.valid_entries, cache_stats.expired_entries);
+            eprintln!("Cache: {} total entries ({} valid, {} expired, {} negative)",
+                     cache_stats.total_entries, cache_stats.valid_entries, cache_stats.expired_entries,
+                     cache_stats.negative_entries);
         }
 
         // Show Cassandra performance metrics if Cassandra export was enabled
@@ -409,64 +649,220 @@ pub async fn run(args: QueryArgs, config: Config) -> Result<()> {
                              metrics.errors,
                              metrics.retries);
                 }
+                if metrics.prepared_stmt_hits + metrics.prepared_stmt_misses > 0 {
+                    eprintln!("Cassandra prepared statements: {} hits, {} misses",
+                             metrics.prepared_stmt_hits, metrics.prepared_stmt_misses);
+                }
             }
         }
     }
 
-    // Output all records
-    for record in all_records {
-        output.write_record(&record, args.resp_only)?;
+    let all_records = if args.resolve_cname {
+        let client = DnsxClient::with_options(dns_options.clone())?;
+        append_cname_addresses(&client, all_records, config.core_config.performance.threads, config.silent).await
+    } else {
+        all_records
+    };
 
-        // Export to Elasticsearch if configured
-        if let Some(ref exporter) = es_exporter {
-            if let Err(e) = exporter.export(record.clone()).await {
-                if !config.silent {
-                    eprintln!("Warning: Failed to export to Elasticsearch: {}", e);
-                }
-            }
+    if args.track_soa || args.mx_analyze || args.categorize {
+        let extra = std::mem::take(&mut *analysis_records.lock().unwrap());
+        let analysis: Vec<DnsRecord> = all_records.iter().cloned().chain(extra).collect();
+
+        if args.track_soa {
+            track_soa_changes(&analysis)?;
         }
 
-        // Export to MongoDB if configured
-        if let Some(ref exporter) = mongo_exporter {
-            if let Err(e) = exporter.export(record.clone()).await {
-                if !config.silent {
-                    eprintln!("Warning: Failed to export to MongoDB: {}", e);
-                }
-            }
+        if args.mx_analyze {
+            print_mx_summary(&analysis);
         }
 
-        // Export to Cassandra if configured
-        if let Some(ref exporter) = cassandra_exporter {
-            if let Err(e) = exporter.export(record.clone()).await {
-                if !config.silent {
-                    eprintln!("Warning: Failed to export to Cassandra: {}", e);
-                }
+        if args.categorize {
+            let domains: HashSet<&str> = all_records.iter().map(|r| r.domain.as_str()).collect();
+            output.set_domain_tags(
+                domains
+                    .into_iter()
+                    .map(|domain| (domain.to_string(), DomainCategorizer::categorize(domain, &analysis)))
+                    .collect(),
+            );
+        }
+    }
+
+    // Records are already collected, so every backend takes them in batches (Cassandra
+    // bypasses its worker channels this way)
+    if !exporters.is_empty() {
+        if let Err(e) = exporters.export_batch(all_records.clone()).await {
+            if !config.silent {
+                eprintln!("Warning: {}", e.report());
             }
         }
     }
 
+    // Output all records
+    for record in all_records {
+        output.write_record(&record, args.resp_only)?;
+    }
+
     // Flush exporters
-    if let Some(ref exporter) = es_exporter {
-        exporter.flush().await.map_err(|e| anyhow::anyhow!("Failed to flush Elasticsearch: {}", e))?;
+    exporters.flush().await.map_err(|e| anyhow::Error::new(e).context("Failed to flush exports"))?;
+
+    output.flush()?;
+    Ok(())
+}
+
+/// Add the final addresses behind every CNAME record, right after it and under the queried name
+async fn append_cname_addresses(
+    client: &DnsxClient,
+    records: Vec<DnsRecord>,
+    concurrency: usize,
+    silent: bool,
+) -> Vec<DnsRecord> {
+    let targets: HashSet<String> =
+        records.iter().filter_map(|record| cname_target(record).map(str::to_string)).collect();
+    let resolved: HashMap<String, Vec<IpAddr>> = futures::stream::iter(targets)
+        .map(|target| async move {
+            let ips = client.resolve_to_ips(&target).await.unwrap_or_else(|e| {
+                if !silent {
+                    eprintln!("Warning: Failed to resolve CNAME target {}: {}", target, e);
+                }
+                Vec::new()
+            });
+            (target, ips)
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut out = Vec::with_capacity(records.len());
+    for record in records {
+        let ips = cname_target(&record).and_then(|target| resolved.get(target)).cloned().unwrap_or_default();
+        let flattened: Vec<DnsRecord> = ips
+            .into_iter()
+            .map(|ip| {
+                let record_type = if ip.is_ipv4() { RecordType::A } else { RecordType::Aaaa };
+                DnsRecord::new(
+                    record.domain.clone(),
+                    record_type,
+                    RecordValue::Ip(ip),
+                    record.ttl,
+                    record.response_code,
+                    record.resolver.clone(),
+                    record.query_time_ms,
+                )
+            })
+            .collect();
+        out.push(record);
+        out.extend(flattened);
+    }
+    out
+}
+
+fn cname_target(record: &DnsRecord) -> Option<&str> {
+    match (&record.value, record.record_type) {
+        (RecordValue::Domain(target), RecordType::Cname) => Some(target),
+        _ => None,
+    }
+}
+
+/// Print a failed query, telling network problems apart from timeouts and bad answers
+fn report_query_error(domain: &str, record_type: RecordType, error: &DnsxError) {
+    match error {
+        DnsxError::Timeout { resolver, duration, .. } => {
+            eprintln!("Timeout querying {} ({}) on {} after {:?}", domain, record_type, resolver, duration);
+        }
+        DnsxError::Network { resolver, message, source } => {
+            let cause = source.as_ref().map(|source| format!(": {}", source)).unwrap_or_default();
+            eprintln!("Network error querying {} ({}) on {}: {}{}", domain, record_type, resolver, message, cause);
+        }
+        DnsxError::Rcode { rcode, .. } => {
+            eprintln!("{} ({}) returned {}", domain, record_type, rcode);
+        }
+        DnsxError::Parse { raw, message, .. } => {
+            eprintln!("Malformed answer for {} ({}): {} ({})", domain, record_type, message, raw);
+        }
+        _ => eprintln!("Error querying {} ({}): {}", domain, record_type, error.report()),
     }
-    if let Some(ref exporter) = mongo_exporter {
-        exporter.flush().await.map_err(|e| anyhow::anyhow!("Failed to flush MongoDB: {}", e))?;
+}
+
+/// Print the per-resolver breakdown, busiest resolver first
+fn print_resolver_stats(metrics: &ProcessingMetrics) {
+    let mut resolvers: Vec<_> = metrics.per_resolver.iter().collect();
+    resolvers.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.queries));
+
+    eprintln!("{:<28} {:>10} {:>8} {:>10} {:>12}", "RESOLVER", "QUERIES", "ERRORS", "NXDOMAIN", "AVG LATENCY");
+    for (resolver, stats) in resolvers {
+        eprintln!("{:<28} {:>10} {:>8} {:>10} {:>10.1}ms",
+                 resolver, stats.queries, stats.errors, stats.nxdomain, stats.avg_latency_ms);
     }
-    if let Some(ref exporter) = cassandra_exporter {
-        exporter.flush().await.map_err(|e| anyhow::anyhow!("Failed to flush Cassandra: {}", e))?;
+}
+
+/// Response codes per record type, e.g. to spot a high NXDOMAIN rate for AAAA
+fn print_rcode_distribution(metrics: &ProcessingMetrics) {
+    let mut by_type: Vec<(RecordType, [u64; 3])> = Vec::new();
+    for (&(record_type, rcode), &count) in &metrics.rcode_distribution {
+        let index = match by_type.iter().position(|(t, _)| *t == record_type) {
+            Some(index) => index,
+            None => {
+                by_type.push((record_type, [0; 3]));
+                by_type.len() - 1
+            }
+        };
+        let column = match rcode {
+            ResponseCode::NoError => 0,
+            ResponseCode::NxDomain => 1,
+            _ => 2,
+        };
+        by_type[index].1[column] += count;
+    }
+    by_type.sort_by_key(|(record_type, _)| record_type.to_string());
+
+    eprintln!("{:<8} {:>10} {:>10} {:>10} {:>8} {:>10}", "TYPE", "QUERIES", "NOERROR", "NXDOMAIN", "OTHER", "NXDOMAIN%");
+    for (record_type, [noerror, nxdomain, other]) in by_type {
+        let total = noerror + nxdomain + other;
+        let nx_rate = if total > 0 { nxdomain as f64 * 100.0 / total as f64 } else { 0.0 };
+        eprintln!("{:<8} {:>10} {:>10} {:>10} {:>8} {:>9.1}%",
+                 record_type.to_string(), total, noerror, nxdomain, other, nx_rate);
     }
+}
+
+/// Compare SOA serials against the previous run and persist the new values
+fn track_soa_changes(records: &[DnsRecord]) -> Result<()> {
+    let home = std::env::var_os("HOME").ok_or_else(|| anyhow::anyhow!("HOME is not set, cannot locate SOA cache"))?;
+    let cache_path = std::path::PathBuf::from(home).join(".rdnsx").join("soa_cache.json");
+
+    let mut tracker = SoaTracker::load(&cache_path)?;
+    for record in records.iter().filter(|r| r.record_type == RecordType::Soa) {
+        tracker.record(record);
+        if let Some(change) = tracker.has_changed(&record.domain) {
+            eprintln!("SOA CHANGED: {} serial {} -> {} ({:+})", record.domain, change.old_serial, change.new_serial, change.delta);
+        }
+    }
+    tracker.save(&cache_path)?;
 
-    output.flush()?;
     Ok(())
 }
 
-fn determine_record_types(args: &QueryArgs) -> Vec<RecordType> {
+/// Print each domain's MX exchanges in priority order with the mail provider
+fn print_mx_summary(records: &[DnsRecord]) {
+    let mut grouped: Vec<_> = MxAnalyzer::group_by_domain(records).into_iter().collect();
+    grouped.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (domain, summary) in grouped {
+        let provider = summary.mail_provider.as_deref().unwrap_or("unknown provider");
+        let backup = if summary.is_backup_configured { "backup MX configured" } else { "no backup MX" };
+        eprintln!("MX {}: {} ({})", domain, provider, backup);
+        for entry in &summary.records_sorted_by_priority {
+            eprintln!("  {:>5} {}", entry.priority, entry.exchange);
+        }
+    }
+}
+
+fn determine_record_types(args: &QueryArgs, core_config: &CoreConfig) -> Result<Vec<RecordType>> {
     let mut types = Vec::new();
 
-    // If --record-type is specified, use those
+    // If --record-type is specified, use those (after expanding aliases such as `web`)
     if !args.record_type.is_empty() {
-        for rt in &args.record_type {
-            match rt.to_uppercase().as_str() {
+        for rt in core_config.expand_record_type_aliases(&args.record_type)? {
+            match rt.as_str() {
                 "A" => types.push(RecordType::A),
                 "AAAA" => types.push(RecordType::Aaaa),
                 "CNAME" => types.push(RecordType::Cname),
@@ -488,6 +884,7 @@ fn determine_record_types(args: &QueryArgs) -> Vec<RecordType> {
                 "NAPTR" => types.push(RecordType::Naptr),
                 "NSEC" => types.push(RecordType::Nsec),
                 "NSEC3" => types.push(RecordType::Nsec3),
+                "NSEC3PARAM" => types.push(RecordType::Nsec3param),
                 "OPT" => types.push(RecordType::Opt),
                 "RRSIG" => types.push(RecordType::Rrsig),
                 "SSHFP" => types.push(RecordType::Sshfp),
@@ -497,7 +894,7 @@ fn determine_record_types(args: &QueryArgs) -> Vec<RecordType> {
                 _ => eprintln!("Warning: Unknown record type '{}', ignoring", rt),
             }
         }
-        return types;
+        return Ok(types);
     }
 
     // Fall back to individual flags
@@ -585,7 +982,7 @@ fn determine_record_types(args: &QueryArgs) -> Vec<RecordType> {
         types.push(RecordType::A);
     }
 
-    types
+    Ok(types)
 }
 
 fn read_domains(input_file: &Option<String>) -> Result<Vec<String>> {
@@ -601,6 +998,74 @@ fn read_domains(input_file: &Option<String>) -> Result<Vec<String>> {
     Ok(lines.into_iter().filter(|s| !s.trim().is_empty()).collect())
 }
 
+/// Open the --list file, or stdin when none is given
+fn open_list(input_file: &Option<String>) -> Result<Box<dyn BufRead>> {
+    Ok(match input_file {
+        Some(file) => Box::new(std::io::BufReader::new(std::fs::File::open(file)?)),
+        None => Box::new(std::io::stdin().lock()),
+    })
+}
+
+/// Domains of JSONL input, each once since an earlier run's output has a line per record
+///
+/// With `url_stripping` set, `domain` fields holding URLs are reduced to their
+/// hostname (dropping `www.` when it is `true`).
+fn read_jsonl_domains(
+    reader: impl BufRead,
+    filters: Vec<DomainFilter>,
+    url_stripping: Option<bool>,
+    silent: bool,
+) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut streamer = filters
+        .into_iter()
+        .fold(DomainStreamer::from_jsonl(reader), DomainStreamer::with_filter);
+    if let Some(strip_www) = url_stripping {
+        streamer = streamer.with_url_stripping(strip_www);
+    }
+    streamer
+        .stream_domains()
+        .filter_map(|result| match result {
+            Ok(domain) => Some(domain),
+            Err(e) => {
+                if !silent {
+                    eprintln!("Warning: Skipping input line: {}", e);
+                }
+                None
+            }
+        })
+        .filter(|domain| seen.insert(domain.clone()))
+        .collect()
+}
+
+/// Combine --filter-pattern globs and --filter-regex expressions into domain filters
+fn build_domain_filters(args: &QueryArgs) -> Result<Vec<DomainFilter>> {
+    let mut filters = Vec::new();
+    if !args.filter_patterns.is_empty() {
+        filters.push(DomainFilter::glob(&args.filter_patterns)?);
+    }
+    for pattern in &args.filter_regexes {
+        filters.push(DomainFilter::regex(pattern)?);
+    }
+    Ok(filters)
+}
+
+/// Parse a `DC=FACTOR` replication setting
+fn parse_dc_factor(value: &str) -> std::result::Result<(String, u32), String> {
+    let (datacenter, factor) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected DC=FACTOR, got '{}'", value))?;
+    let datacenter = datacenter.trim();
+    if datacenter.is_empty() {
+        return Err("datacenter name must not be empty".to_string());
+    }
+    let factor = factor
+        .trim()
+        .parse::<u32>()
+        .map_err(|e| format!("invalid replication factor '{}': {}", factor, e))?;
+    Ok((datacenter.to_string(), factor))
+}
+
 fn parse_rcodes(rcode_str: &Option<String>) -> Result<Vec<ResponseCode>> {
     if let Some(rcodes) = rcode_str {
         let mut result = Vec::new();
@@ -630,7 +1095,7 @@ async fn process_with_adaptive_batching<F>(
     verbose: bool,
 ) -> rdnsx_core::error::Result<(Vec<DnsRecord>, ProcessingMetrics)>
 where
-    F: Fn(String) -> futures::future::BoxFuture<'static, rdnsx_core::error::Result<Vec<DnsRecord>>> + Send + Sync + 'static,
+    F: Fn(String) -> futures::future::BoxFuture<'static, rdnsx_core::error::Result<QueryOutcome>> + Send + Sync + 'static,
 {
     let mut all_records = Vec::new();
     let mut total_metrics = ProcessingMetrics::default();
@@ -656,6 +1121,7 @@ where
                 batch_size: batch_size.min(1000), // Cap internal batch size
                 timeout: processor.config().timeout,
                 rate_limit: processor.config().rate_limit,
+                rate_limit_burst_multiplier: processor.config().rate_limit_burst_multiplier,
             },
             {
                 let query_fn = Arc::clone(processor.query_fn());
@@ -676,6 +1142,9 @@ where
         total_metrics.successful_queries += batch_metrics.successful_queries;
         total_metrics.failed_queries += batch_metrics.failed_queries;
         total_metrics.total_query_time += batch_metrics.total_query_time;
+        total_metrics.processed_items += batch_metrics.processed_items;
+        total_metrics.merge_resolver_stats(&batch_metrics.per_resolver);
+        total_metrics.merge_rcode_distribution(&batch_metrics.rcode_distribution);
 
         // Adjust batch size based on performance
         if batch_metrics.queries_per_second > 0.0 {
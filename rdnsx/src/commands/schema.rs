@@ -0,0 +1,38 @@
+//! Record schema command implementation
+
+use anyhow::Result;
+use clap::Args;
+use rdnsx_core::DnsRecord;
+
+use crate::cli::Config;
+
+/// Schema command arguments
+#[derive(Args)]
+pub struct SchemaArgs {
+    /// Schema language to print
+    #[arg(long, value_enum, default_value = "json-schema")]
+    pub format: SchemaFormat,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum SchemaFormat {
+    /// JSON Schema (draft 2020-12)
+    JsonSchema,
+    /// Avro record schema, e.g. for Kafka Schema Registry
+    Avro,
+}
+
+pub async fn run(args: SchemaArgs, config: Config) -> Result<()> {
+    let schema = match args.format {
+        SchemaFormat::JsonSchema => DnsRecord::json_schema(),
+        SchemaFormat::Avro => DnsRecord::avro_schema(),
+    };
+    let rendered = serde_json::to_string_pretty(&schema)?;
+
+    match &config.output_file {
+        Some(path) => std::fs::write(path, rendered + "\n")?,
+        None => println!("{}", rendered),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! WebSocket server command implementation
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use clap::Args;
+use futures::{SinkExt, StreamExt};
+use rdnsx_core::{DnsRecord, DnsxClient, ProcessingMetrics, RecordType};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc, Mutex};
+
+use crate::cli::Config;
+
+/// WebSocket server command arguments
+#[derive(Args)]
+pub struct ServerArgs {
+    /// Port to accept WebSocket connections on
+    #[arg(long, default_value = "8080")]
+    pub ws_port: u16,
+
+    /// Address to bind to
+    #[arg(long, default_value = "127.0.0.1")]
+    pub bind: IpAddr,
+
+    /// Interval between stats events (seconds)
+    #[arg(long, default_value = "5")]
+    pub stats_interval: u64,
+}
+
+/// Event pushed to connected clients
+///
+/// Records and stats go to every client; errors only to the client whose request failed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "lowercase")]
+pub enum DnsEvent {
+    Record(DnsRecord),
+    Stats(ProcessingMetrics),
+    Error(String),
+}
+
+/// Query request sent by a client
+#[derive(Debug, Deserialize)]
+struct QueryRequest {
+    query: String,
+    #[serde(rename = "type", default = "default_record_type")]
+    record_type: RecordType,
+}
+
+fn default_record_type() -> RecordType {
+    RecordType::A
+}
+
+#[derive(Clone)]
+struct ServerState {
+    client: Arc<DnsxClient>,
+    events: broadcast::Sender<DnsEvent>,
+    metrics: Arc<Mutex<ProcessingMetrics>>,
+}
+
+pub async fn run(args: ServerArgs, config: Config) -> Result<()> {
+    let dns_options = config.dns_options();
+
+    let client = DnsxClient::with_options(dns_options)?.with_audit_logger(config.audit_logger.clone());
+    if config.warm_up {
+        client.warm_up().await?;
+    }
+
+    let (events, _) = broadcast::channel(1024);
+    let state = ServerState {
+        client: Arc::new(client),
+        events,
+        metrics: Arc::new(Mutex::new(ProcessingMetrics::default())),
+    };
+
+    // Periodic stats fan-out
+    tokio::spawn({
+        let state = state.clone();
+        let interval = Duration::from_secs(args.stats_interval.max(1));
+        async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let snapshot = state.metrics.lock().await.clone();
+                // No receivers simply means no clients are connected
+                let _ = state.events.send(DnsEvent::Stats(snapshot));
+            }
+        }
+    });
+
+    let app = Router::new()
+        .route("/ws", get(ws_handler))
+        .with_state(state);
+
+    let addr = SocketAddr::new(args.bind, args.ws_port);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    if !config.silent {
+        eprintln!("🌐 WebSocket server listening on ws://{}/ws", addr);
+    }
+
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn ws_handler(ws: WebSocketUpgrade, State(state): State<ServerState>) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_socket(socket, state))
+}
+
+/// Forward broadcast events and this client's errors to it, and run queries it submits
+async fn handle_socket(socket: WebSocket, state: ServerState) {
+    let (mut sender, mut receiver) = socket.split();
+    let mut events = state.events.subscribe();
+    let (errors_tx, mut errors) = mpsc::unbounded_channel();
+
+    let mut forward = tokio::spawn(async move {
+        loop {
+            let event = tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => event,
+                    // A slow client misses events rather than stalling everyone else
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                Some(error) = errors.recv() => DnsEvent::Error(error),
+            };
+            let Ok(text) = serde_json::to_string(&event) else {
+                continue;
+            };
+            if sender.send(Message::Text(text)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut queries = tokio::spawn(async move {
+        while let Some(Ok(message)) = receiver.next().await {
+            match message {
+                Message::Text(text) => {
+                    let result = match serde_json::from_str::<QueryRequest>(&text) {
+                        Ok(request) => run_query(&state, request).await,
+                        Err(e) => Err(format!("Invalid query request: {}", e)),
+                    };
+                    if let Err(error) = result {
+                        // The forwarding task is gone once the client has
+                        let _ = errors_tx.send(error);
+                    }
+                }
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+    });
+
+    // Whichever side finishes first tears down the connection
+    tokio::select! {
+        _ = &mut forward => queries.abort(),
+        _ = &mut queries => forward.abort(),
+    }
+}
+
+/// Run one query, broadcasting its records; the error message for the requesting client on failure
+async fn run_query(state: &ServerState, request: QueryRequest) -> std::result::Result<(), String> {
+    let started = Instant::now();
+    let result = state.client.query(&request.query, request.record_type).await;
+    let elapsed = started.elapsed();
+
+    let mut metrics = state.metrics.lock().await;
+    metrics.total_domains += 1;
+    metrics.processed_items += 1;
+    metrics.total_query_time += elapsed;
+    metrics.average_query_time = metrics.total_query_time / metrics.total_domains as u32;
+
+    match result {
+        Ok(records) => {
+            metrics.successful_queries += 1;
+            drop(metrics);
+            for record in records {
+                let _ = state.events.send(DnsEvent::Record(record));
+            }
+            Ok(())
+        }
+        Err(e) => {
+            metrics.failed_queries += 1;
+            Err(format!("Query for {} failed: {}", request.query, e))
+        }
+    }
+}
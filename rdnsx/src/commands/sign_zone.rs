@@ -0,0 +1,69 @@
+//! Offline DNSSEC zone signing command
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use clap::Args;
+use rdnsx_core::{read_zone_file, DnskeyPrivate, DnssecSigner};
+
+use crate::cli::Config;
+
+/// Sign-zone command arguments
+#[derive(Args)]
+pub struct SignZoneArgs {
+    /// Zone signing key (PKCS#8 PEM, ECDSA P-256/P-384 or Ed25519)
+    #[arg(long, value_name = "PEM")]
+    pub zsk: PathBuf,
+
+    /// Key signing key, used for the DNSKEY RRset (PKCS#8 PEM)
+    #[arg(long, value_name = "PEM")]
+    pub ksk: PathBuf,
+
+    /// Zone file to sign (master file format)
+    #[arg(long, value_name = "FILE")]
+    pub zone: PathBuf,
+
+    /// Origin for relative names when the zone file has no $ORIGIN
+    #[arg(long)]
+    pub origin: Option<String>,
+
+    /// Days the signatures stay valid, starting one hour ago to allow for clock skew
+    #[arg(long, default_value = "30")]
+    pub validity_days: u32,
+}
+
+pub async fn run(args: SignZoneArgs, config: Config) -> Result<()> {
+    let zsk = load_key(&args.zsk)?;
+    let ksk = load_key(&args.ksk)?;
+    let records = read_zone_file(&args.zone, args.origin.as_deref())?;
+
+    let inception = Utc::now() - Duration::hours(1);
+    let expiration = inception + Duration::days(i64::from(args.validity_days.max(1)));
+    let signed = DnssecSigner::sign_zone(&records, &zsk, &ksk, inception, expiration)?;
+
+    let zone_file: String = signed.iter().map(|record| record.to_zone_file_line() + "\n").collect();
+    match &config.output_file {
+        Some(path) => {
+            std::fs::write(path, zone_file).map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path, e))?
+        }
+        None => print!("{}", zone_file),
+    }
+
+    if !config.silent {
+        eprintln!(
+            "🔏 Signed {} records from {} ({} records written, valid until {})",
+            records.len(),
+            args.zone.display(),
+            signed.len(),
+            expiration.format("%Y-%m-%d %H:%M UTC")
+        );
+    }
+    Ok(())
+}
+
+fn load_key(path: &Path) -> Result<DnskeyPrivate> {
+    let pem = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read key {}: {}", path.display(), e))?;
+    DnskeyPrivate::from_pkcs8_pem(&pem).map_err(|e| anyhow::anyhow!("Invalid key {}: {}", path.display(), e))
+}
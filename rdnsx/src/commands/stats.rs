@@ -0,0 +1,96 @@
+//! Stored-record analytics command implementation
+
+use std::net::IpAddr;
+
+use anyhow::Result;
+use clap::Args;
+use rdnsx_core::MongodbAnalyzer;
+
+use crate::cli::Config;
+
+/// Stats command arguments
+#[derive(Args)]
+pub struct StatsArgs {
+    /// Read analytics from the MongoDB export configured in [export.mongodb]
+    #[arg(long)]
+    pub mongodb: bool,
+
+    /// Number of top domains to show
+    #[arg(long, default_value = "10")]
+    pub limit: u32,
+
+    /// Also list the unique addresses stored for this domain
+    #[arg(short, long)]
+    pub domain: Option<String>,
+
+    /// Also list the domains stored as resolving to this address
+    #[arg(long)]
+    pub ip: Option<IpAddr>,
+}
+
+pub async fn run(args: StatsArgs, config: Config) -> Result<()> {
+    if !args.mongodb {
+        anyhow::bail!("No stats backend selected (use --mongodb)");
+    }
+
+    let mongo = &config.core_config.export.mongodb;
+    if !config.silent {
+        eprintln!("📡 Reading analytics from {}/{}.{}", mongo.url, mongo.database, mongo.collection);
+    }
+    let analyzer = MongodbAnalyzer::new(&mongo.url, &mongo.database, &mongo.collection).await?;
+
+    let top_domains = analyzer.top_domains_by_record_count(args.limit).await?;
+    let mut distribution: Vec<(String, u64)> = analyzer.record_type_distribution().await?.into_iter().collect();
+    distribution.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let domain_ips = match &args.domain {
+        Some(domain) => Some(analyzer.unique_ips_per_domain(domain).await?),
+        None => None,
+    };
+    let ip_domains = match args.ip {
+        Some(ip) => Some(analyzer.domains_resolving_to_ip(ip).await?),
+        None => None,
+    };
+
+    if config.json_output {
+        let output = serde_json::json!({
+            "top_domains": top_domains,
+            "record_types": distribution.iter().cloned().collect::<std::collections::BTreeMap<_, _>>(),
+            "domain_ips": domain_ips,
+            "ip_domains": ip_domains,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("📊 DNS Record Statistics");
+    println!("{}", "=".repeat(50));
+
+    println!("\n🏆 Top {} domains by record count:", args.limit);
+    for (rank, entry) in top_domains.iter().enumerate() {
+        println!("  {:>3}. {:<40} {}", rank + 1, entry.domain, entry.count);
+    }
+
+    let total: u64 = distribution.iter().map(|(_, count)| count).sum();
+    println!("\n📋 Record type distribution ({} records):", total);
+    for (record_type, count) in &distribution {
+        let share = if total > 0 { *count as f64 * 100.0 / total as f64 } else { 0.0 };
+        println!("  {:<8} {:>10} ({:.1}%)", record_type, count, share);
+    }
+
+    if let (Some(domain), Some(ips)) = (&args.domain, &domain_ips) {
+        println!("\n🌐 Unique addresses for {} ({}):", domain, ips.len());
+        for ip in ips {
+            println!("  • {}", ip);
+        }
+    }
+
+    if let (Some(ip), Some(domains)) = (args.ip, &ip_domains) {
+        println!("\n🔗 Domains resolving to {} ({}):", ip, domains.len());
+        for domain in domains {
+            println!("  • {}", domain);
+        }
+    }
+
+    Ok(())
+}
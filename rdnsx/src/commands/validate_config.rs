@@ -0,0 +1,154 @@
+//! Configuration validation command implementation
+
+use std::path::Path;
+
+use anyhow::Result;
+use clap::Args;
+use rdnsx_core::config::Config as CoreConfig;
+use rdnsx_core::{
+    CassandraConfig, CassandraExporter, ConfigError, DnsxError, ElasticsearchExporter, Exporter, MongodbExporter,
+};
+
+/// Validate-config command arguments
+#[derive(Args)]
+pub struct ValidateConfigArgs {
+    /// Only check the file itself, without connecting to enabled export backends
+    #[arg(long)]
+    pub offline: bool,
+}
+
+/// Validate the config file at `path` (or the defaults) and exit 1 if anything is wrong
+///
+/// Runs before the usual config loading, which falls back to defaults on a
+/// broken file and would hide exactly the errors this command is for.
+pub async fn run(args: ValidateConfigArgs, path: Option<&Path>, json: bool, silent: bool) -> Result<()> {
+    let config = match path {
+        Some(path) => match CoreConfig::from_file(path) {
+            Ok(config) => config,
+            Err(e) => {
+                let issues = vec![ConfigError::new(path.display().to_string(), "", e.report())];
+                return report(&issues, json);
+            }
+        },
+        None => CoreConfig::default(),
+    };
+
+    if !silent {
+        match path {
+            Some(path) => eprintln!("🔍 Validating {}", path.display()),
+            None => eprintln!("🔍 No --config given, validating the built-in defaults"),
+        }
+    }
+
+    let mut issues = config.validate();
+    // Connecting with settings already known to be wrong only repeats those errors
+    if !args.offline && issues.iter().all(|issue| !issue.field.starts_with("export.")) {
+        issues.extend(check_backends(&config, silent).await);
+    }
+
+    report(&issues, json)
+}
+
+/// Connect to every enabled export backend and run its health check
+async fn check_backends(config: &CoreConfig, silent: bool) -> Vec<ConfigError> {
+    let export = &config.export;
+    let mut issues = Vec::new();
+
+    if export.elasticsearch.enabled {
+        let es = &export.elasticsearch;
+        if !silent {
+            eprintln!("📡 Checking Elasticsearch at {}", es.url);
+        }
+        let result = match ElasticsearchExporter::new(&es.url, &es.index, export.batch_size).await {
+            Ok(exporter) => exporter.health_check().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            issues.push(backend_issue("export.elasticsearch.url", &es.url, &e));
+        }
+    }
+
+    if export.mongodb.enabled {
+        let mongo = &export.mongodb;
+        if !silent {
+            eprintln!("📡 Checking MongoDB at {}", mongo.url);
+        }
+        let result = MongodbExporter::new(&mongo.url, &mongo.database, &mongo.collection, export.batch_size).await;
+        let result = match result {
+            Ok(exporter) => exporter.health_check().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            issues.push(backend_issue("export.mongodb.url", &mongo.url, &e));
+        }
+    }
+
+    if export.cassandra.enabled {
+        let cassandra = &export.cassandra;
+        if !silent {
+            eprintln!("📡 Checking Cassandra at {}", cassandra.contact_points.join(", "));
+        }
+        let result = CassandraExporter::with_config(CassandraConfig {
+            contact_points: cassandra.contact_points.clone(),
+            username: Some(cassandra.username.clone()),
+            password: Some(cassandra.password.clone()),
+            keyspace: cassandra.keyspace.clone(),
+            table: cassandra.table.clone(),
+            ..Default::default()
+        })
+        .await;
+        let result = match result {
+            Ok(exporter) => exporter.health_check().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            issues.push(backend_issue("export.cassandra.contact_points", cassandra.contact_points.join(","), &e));
+        }
+    }
+
+    issues
+}
+
+fn backend_issue(field: &str, value: impl ToString, error: &DnsxError) -> ConfigError {
+    ConfigError::new(field, value, format!("backend unreachable: {}", error.report()))
+}
+
+fn report(issues: &[ConfigError], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(issues)?);
+    } else if issues.is_empty() {
+        println!("✅ Configuration is valid");
+    } else {
+        print_table(issues);
+    }
+
+    if !issues.is_empty() {
+        anyhow::bail!("{} configuration issue(s) found", issues.len());
+    }
+    Ok(())
+}
+
+fn print_table(issues: &[ConfigError]) {
+    let width = |heading: &str, column: fn(&ConfigError) -> &str| {
+        issues.iter().map(|issue| column(issue).chars().count()).chain([heading.len()]).max().unwrap_or(0)
+    };
+    let field_width = width("Field", |issue| &issue.field);
+    let value_width = width("Value", |issue| &issue.value);
+    let rule = format!("+-{}-+-{}-+--------", "-".repeat(field_width), "-".repeat(value_width));
+
+    println!("\n❌ {} issue(s) found", issues.len());
+    println!("{}", rule);
+    println!("| {:<fw$} | {:<vw$} | Reason", "Field", "Value", fw = field_width, vw = value_width);
+    println!("{}", rule);
+    for issue in issues {
+        println!(
+            "| {:<fw$} | {:<vw$} | {}",
+            issue.field,
+            issue.value,
+            issue.reason,
+            fw = field_width,
+            vw = value_width
+        );
+    }
+    println!("{}", rule);
+}
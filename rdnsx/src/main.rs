@@ -36,9 +36,58 @@ async fn main() -> Result<()> {
     }
 
     // Initialize tracing
+    #[cfg(not(feature = "rdnsx-otel"))]
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
-    cli.run().await
+    #[cfg(feature = "rdnsx-otel")]
+    init_otel_tracing()?;
+
+    let verbose = cli.verbose;
+    let result = cli.run().await;
+
+    #[cfg(feature = "rdnsx-otel")]
+    opentelemetry::global::shutdown_tracer_provider();
+
+    // Causes go on one line, or one per line under "Caused by:" with --verbose
+    if let Err(e) = result {
+        if verbose {
+            eprintln!("Error: {:?}", e);
+        } else {
+            eprintln!("Error: {:#}", e);
+        }
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Log to stderr as usual and export rdnsx spans over OTLP
+#[cfg(feature = "rdnsx-otel")]
+fn init_otel_tracing() -> Result<()> {
+    use opentelemetry::trace::TracerProvider as _;
+    use tracing_subscriber::filter::Targets;
+    use tracing_subscriber::prelude::*;
+
+    // The endpoint is taken from OTEL_EXPORTER_OTLP_ENDPOINT (default http://localhost:4317)
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .build()?;
+    let provider = opentelemetry_sdk::trace::TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .build();
+    let tracer = provider.tracer("rdnsx");
+    opentelemetry::global::set_tracer_provider(provider);
+
+    // Spans are exported regardless of RUST_LOG, which only controls console output
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(EnvFilter::from_default_env()))
+        .with(
+            tracing_opentelemetry::layer()
+                .with_tracer(tracer)
+                .with_filter(Targets::new().with_target("rdnsx_core", tracing::Level::INFO)),
+        )
+        .init();
+
+    Ok(())
 }
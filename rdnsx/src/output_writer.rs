@@ -1,13 +1,52 @@
 //! Output writing utilities
 
 use anyhow::Result;
-use rdnsx_core::DnsRecord;
+use minijinja::{context, Environment};
+use rdnsx_core::{DnsRecord, DomainTag};
+use regex::Regex;
+use std::collections::HashMap;
 use std::io::{self, Write};
 
+use crate::cli::Config;
+
+/// Name the output template is registered under
+const TEMPLATE_NAME: &str = "record";
+
+/// Order in which buffered records are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[allow(clippy::enum_variant_names)] // reads as `SortOrder::ByDomain` at call sites
+pub enum SortOrder {
+    /// Domain name, alphabetically
+    #[value(name = "domain")]
+    ByDomain,
+    /// Record type, then domain
+    #[value(name = "type")]
+    ByRecordType,
+    /// Record value, alphabetically
+    #[value(name = "value")]
+    ByValue,
+    /// Query time, fastest first
+    #[value(name = "time")]
+    ByQueryTime,
+}
+
 pub struct OutputWriter {
     writer: Box<dyn Write>,
     json_output: bool,
     silent: bool,
+    sort: Option<SortOrder>,
+    group_by_domain: bool,
+    /// Records held back until `flush()` when sorting or grouping
+    buffer: Vec<(DnsRecord, bool)>,
+    /// Matched against the value in resp-only mode, otherwise the formatted line
+    value_filter: Option<Regex>,
+    domain_filter: Option<Regex>,
+    /// Upper-case record type names to keep (all when empty)
+    record_types: Vec<String>,
+    /// Text output template; takes precedence over resp-only, not over JSON
+    template: Option<Environment<'static>>,
+    /// Category tags by lower-case domain, attached to every record once set
+    domain_tags: Option<HashMap<String, Vec<DomainTag>>>,
 }
 
 impl OutputWriter {
@@ -22,28 +61,215 @@ impl OutputWriter {
             writer,
             json_output,
             silent,
+            sort: None,
+            group_by_domain: false,
+            buffer: Vec::new(),
+            value_filter: None,
+            domain_filter: None,
+            record_types: Vec::new(),
+            template: None,
+            domain_tags: None,
         })
     }
 
+    /// Writer for the global output options (`-o`, `--json`, `--silent`, `--output-template`)
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let writer = Self::new(config.output_file.clone(), config.json_output, config.silent)?;
+        match &config.output_template {
+            Some(template) => writer.with_template(template),
+            None => Ok(writer),
+        }
+    }
+
+    /// Render text output with `template`
+    ///
+    /// Available fields: `domain`, `record_type`, `value`, `ttl`, `resolver`,
+    /// `response_code`, `query_time_ms`, `timestamp` and `tags`.
+    pub fn with_template(mut self, template: &str) -> Result<Self> {
+        let mut env = Environment::new();
+        env.add_template_owned(TEMPLATE_NAME, template.to_string())
+            .map_err(|e| anyhow::anyhow!("Invalid output template: {}", e))?;
+        self.template = Some(env);
+        Ok(self)
+    }
+
+    /// Only write records whose output matches `pattern`
+    pub fn with_value_filter(mut self, pattern: &Regex) -> Self {
+        self.value_filter = Some(pattern.clone());
+        self
+    }
+
+    /// Only write records whose domain matches `pattern`
+    pub fn with_domain_filter(mut self, pattern: &Regex) -> Self {
+        self.domain_filter = Some(pattern.clone());
+        self
+    }
+
+    /// Only write records of the given types (case-insensitive names such as `A` or `mx`)
+    pub fn with_record_type_filter<I, S>(mut self, record_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.record_types = record_types
+            .into_iter()
+            .map(|t| t.as_ref().trim().to_uppercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+        self
+    }
+
+    /// Sort records before writing; records are buffered until `flush()`
+    pub fn set_sort(&mut self, sort: SortOrder) {
+        self.sort = Some(sort);
+    }
+
+    /// Print each domain once as a header followed by its records; records are buffered until `flush()`
+    pub fn set_group_by_domain(&mut self, group_by_domain: bool) {
+        self.group_by_domain = group_by_domain;
+    }
+
+    /// Attach category tags to each record: a `tags` array in JSON, a `{tag,...}` suffix in text
+    pub fn set_domain_tags(&mut self, tags: HashMap<String, Vec<DomainTag>>) {
+        self.domain_tags = Some(
+            tags.into_iter()
+                .map(|(domain, tags)| (domain.trim_end_matches('.').to_lowercase(), tags))
+                .collect(),
+        );
+    }
+
     pub fn write_record(&mut self, record: &DnsRecord, resp_only: bool) -> Result<()> {
+        if self.silent || !self.passes_filters(record, resp_only)? {
+            return Ok(());
+        }
+
+        if self.sort.is_some() || self.group_by_domain {
+            self.buffer.push((record.clone(), resp_only));
+            return Ok(());
+        }
+
+        self.write_now(record, resp_only, false)
+    }
+
+    /// Write a preformatted line, bypassing filters, sorting and templates
+    pub fn write_line(&mut self, line: &str) -> Result<()> {
         if self.silent {
             return Ok(());
         }
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn passes_filters(&self, record: &DnsRecord, resp_only: bool) -> Result<bool> {
+        if !self.record_types.is_empty() && !self.record_types.contains(&record.record_type.to_string()) {
+            return Ok(false);
+        }
+        if let Some(domain_filter) = &self.domain_filter {
+            if !domain_filter.is_match(&record.domain) {
+                return Ok(false);
+            }
+        }
+        if let Some(value_filter) = &self.value_filter {
+            if !value_filter.is_match(&self.render(record, resp_only)?) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Tag names for the record's domain, `None` unless tagging is enabled
+    fn tags_for(&self, record: &DnsRecord) -> Option<Vec<&'static str>> {
+        let domain_tags = self.domain_tags.as_ref()?;
+        let tags = domain_tags.get(&record.domain.trim_end_matches('.').to_lowercase());
+        Some(tags.map(|tags| tags.iter().map(|t| t.as_str()).collect()).unwrap_or_default())
+    }
+
+    fn render(&self, record: &DnsRecord, resp_only: bool) -> Result<String> {
+        let tags = self.tags_for(record);
+        if let (Some(env), false) = (&self.template, self.json_output) {
+            let line = env.get_template(TEMPLATE_NAME)?.render(context! {
+                domain => record.domain,
+                record_type => record.record_type.to_string(),
+                value => record.value.to_string(),
+                ttl => record.ttl,
+                resolver => record.resolver,
+                response_code => record.response_code.to_string(),
+                query_time_ms => record.query_time_ms,
+                timestamp => record.timestamp.to_rfc3339(),
+                tags => tags.unwrap_or_default(),
+            })?;
+            return Ok(format!("{}\n", line));
+        }
 
-        let output = if resp_only {
+        Ok(if resp_only {
             record.value.to_string()
         } else if self.json_output {
-            serde_json::to_string(record)?
+            match tags {
+                Some(tags) => {
+                    let mut value = serde_json::to_value(record)?;
+                    value["tags"] = serde_json::json!(tags);
+                    serde_json::to_string(&value)?
+                }
+                None => serde_json::to_string(record)?,
+            }
         } else {
-            format!("{}\n", record)
-        };
+            match tags {
+                Some(tags) if !tags.is_empty() => format!("{} {{{}}}\n", record, tags.join(",")),
+                _ => format!("{}\n", record),
+            }
+        })
+    }
+
+    fn write_now(&mut self, record: &DnsRecord, resp_only: bool, indent: bool) -> Result<()> {
+        let output = self.render(record, resp_only)?;
 
-        write!(self.writer, "{}", output)?;
+        if indent {
+            write!(self.writer, "  {}", output)?;
+        } else {
+            write!(self.writer, "{}", output)?;
+        }
         self.writer.flush()?;
         Ok(())
     }
 
     pub fn flush(&mut self) -> Result<()> {
+        let mut records = std::mem::take(&mut self.buffer);
+
+        if let Some(sort) = self.sort {
+            match sort {
+                SortOrder::ByDomain => records.sort_by(|(a, _), (b, _)| a.domain.cmp(&b.domain)),
+                SortOrder::ByRecordType => records.sort_by(|(a, _), (b, _)| {
+                    a.record_type
+                        .to_string()
+                        .cmp(&b.record_type.to_string())
+                        .then_with(|| a.domain.cmp(&b.domain))
+                }),
+                SortOrder::ByValue => records.sort_by_cached_key(|(r, _)| r.value.to_string()),
+                SortOrder::ByQueryTime => records.sort_by(|(a, _), (b, _)| a.query_time_ms.total_cmp(&b.query_time_ms)),
+            }
+        }
+
+        if self.group_by_domain {
+            // Stable sort keeps the requested order within each domain
+            records.sort_by(|(a, _), (b, _)| a.domain.cmp(&b.domain));
+
+            // Headers would break line-delimited JSON, which carries the domain anyway
+            let headers = !self.json_output;
+            let mut current: Option<String> = None;
+            for (record, resp_only) in &records {
+                if headers && current.as_deref() != Some(record.domain.as_str()) {
+                    writeln!(self.writer, "{}:", record.domain)?;
+                    current = Some(record.domain.clone());
+                }
+                self.write_now(record, *resp_only, headers)?;
+            }
+        } else {
+            for (record, resp_only) in &records {
+                self.write_now(record, *resp_only, false)?;
+            }
+        }
+
         self.writer.flush()?;
         Ok(())
     }